@@ -0,0 +1,88 @@
+//! Measures parsing throughput on large inputs.
+//!
+//! Run with `cargo bench`. Each case is parsed several times, and the fastest time is reported.
+
+use serde_derive::Deserialize;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 10;
+
+#[derive(Deserialize)]
+struct Notes {
+    #[serde(rename = "NOTES")]
+    notes: String,
+}
+
+// Returns the fastest of several runs of `parse`.
+fn fastest<F>(mut parse: F) -> Duration
+where
+    F: FnMut(),
+{
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            parse();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn report(name: &str, bytes: usize, duration: Duration) {
+    println!(
+        "{:<32} {:>10.2?} {:>10.1} MB/s",
+        name,
+        duration,
+        bytes as f64 / duration.as_secs_f64() / 1_000_000.0
+    );
+}
+
+fn main() {
+    // A single tag of about 1 MB, like the note data of a long chart.
+    let mut single_tag = b"#NOTES:\n".to_vec();
+    while single_tag.len() < 1_000_000 {
+        single_tag.extend_from_slice(b"0000\n0100\n0010\n1001\n,\n");
+    }
+    single_tag.extend_from_slice(b";\n");
+
+    // Many small tags.
+    let mut small_tags = Vec::new();
+    for index in 0..200_000 {
+        small_tags.extend_from_slice(format!("#TAG{}:value:{};\n", index % 16, index).as_bytes());
+    }
+
+    report(
+        "single tag, slice",
+        single_tag.len(),
+        fastest(|| {
+            assert!(!msd::from_bytes::<Notes>(&single_tag)
+                .unwrap()
+                .notes
+                .is_empty());
+        }),
+    );
+    report(
+        "single tag, reader",
+        single_tag.len(),
+        fastest(|| {
+            assert!(!msd::from_reader::<_, Notes>(single_tag.as_slice())
+                .unwrap()
+                .notes
+                .is_empty());
+        }),
+    );
+    report(
+        "small tags, slice",
+        small_tags.len(),
+        fastest(|| {
+            msd::from_bytes::<Vec<(String, String, u64)>>(&small_tags).unwrap();
+        }),
+    );
+    report(
+        "small tags, reader",
+        small_tags.len(),
+        fastest(|| {
+            msd::from_reader::<_, Vec<(String, String, u64)>>(small_tags.as_slice()).unwrap();
+        }),
+    );
+}
@@ -1,7 +1,7 @@
-use serde::serde_if_integer128;
-
+#[allow(deprecated)]
 fn main() {
-    serde_if_integer128! {
+    println!("cargo:rustc-check-cfg=cfg(has_i128)");
+    serde::serde_if_integer128! {
         println!("cargo:rustc-cfg=has_i128");
     }
 }
@@ -0,0 +1,358 @@
+use super::{list, parameter};
+use crate::de::{
+    parse::{Tag, Values},
+    Error, Result,
+};
+use serde::{
+    de,
+    de::{DeserializeSeed, SeqAccess, Visitor},
+    forward_to_deserialize_any,
+};
+
+// Deserializes the remainder of a tag, starting from its current parameter list, as buffered
+// content.
+//
+// This is requested when serde needs to capture a value before knowing its type, such as for
+// `#[serde(flatten)]` fields and internally tagged or untagged enums. As MSD does not describe its
+// own types, and serde cannot convert a buffered value into another type, the type is guessed:
+//
+// - A tag with no remaining parameters is a unit.
+// - A tag with a single remaining parameter is that parameter, given as a boolean or number if it
+//   reads as one and as a string otherwise.
+// - A tag with multiple remaining parameters is a sequence of them, each given as a string.
+// - A tag with multiple parameter lists is a sequence of those lists, each a sequence of its
+//   parameters. The first list is omitted if it contained nothing but the tag's name.
+pub(in crate::de) struct Deserializer<'a> {
+    tag: Tag<'a>,
+    values: Values<'a>,
+}
+
+impl<'a> Deserializer<'a> {
+    pub(in crate::de) fn new(tag: Tag<'a>, values: Values<'a>) -> Self {
+        Self { tag, values }
+    }
+}
+
+impl<'a, 'de> de::Deserializer<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.tag.assert_exhausted().is_err() {
+            return visitor.visit_seq(Access::new(&mut self.tag, self.values));
+        }
+
+        let mut remaining = self.values.clone();
+        match remaining.next() {
+            Err(_) => visitor.visit_unit(),
+            Ok(value) if remaining.assert_exhausted().is_ok() => {
+                parameter::Deserializer::new(value.parse_string()?, value.position())
+                    .inferring(&value)
+                    .deserialize_any(visitor)
+            }
+            Ok(_) => list::Deserializer::new(self.values, false).deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+struct Access<'a, 'b> {
+    tag: &'a mut Tag<'b>,
+
+    first_values: Option<Values<'b>>,
+}
+
+impl<'a, 'b> Access<'a, 'b> {
+    fn new(tag: &'a mut Tag<'b>, first_values: Values<'b>) -> Self {
+        Self {
+            tag,
+
+            first_values: Some(first_values).filter(|values| values.assert_exhausted().is_err()),
+        }
+    }
+}
+
+impl<'a, 'b, 'de> SeqAccess<'de> for Access<'a, 'b> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let deserializer = match self.first_values.take() {
+            Some(values) => list::Deserializer::new(values, false),
+            None => match self.tag.next() {
+                Ok(values) => list::Deserializer::new(values, true),
+                Err(_) => return Ok(None),
+            },
+        };
+        seed.deserialize(deserializer).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Deserializer;
+    use crate::de::{error, parse::Tag, Error, Position};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use serde::{
+        de,
+        de::{SeqAccess, Visitor},
+        Deserialize,
+    };
+    use std::fmt;
+
+    // A simplified stand-in for the content buffered by serde, recording the shape of the input.
+    #[derive(Debug, PartialEq)]
+    enum Content {
+        Unit,
+        Bool(bool),
+        U64(u64),
+        I64(i64),
+        F64(f64),
+        String(String),
+        Seq(Vec<Content>),
+    }
+
+    impl<'de> Deserialize<'de> for Content {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct ContentVisitor;
+
+            impl<'de> Visitor<'de> for ContentVisitor {
+                type Value = Content;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("content")
+                }
+
+                fn visit_unit<E>(self) -> Result<Self::Value, E> {
+                    Ok(Content::Unit)
+                }
+
+                fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+                    Ok(Content::Bool(value))
+                }
+
+                fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+                    Ok(Content::U64(value))
+                }
+
+                fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+                    Ok(Content::I64(value))
+                }
+
+                fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+                    Ok(Content::F64(value))
+                }
+
+                fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                    Ok(Content::String(value.to_owned()))
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: SeqAccess<'de>,
+                {
+                    let mut elements = Vec::new();
+                    while let Some(element) = seq.next_element()? {
+                        elements.push(element);
+                    }
+                    Ok(Content::Seq(elements))
+                }
+            }
+
+            deserializer.deserialize_any(ContentVisitor)
+        }
+    }
+
+    fn string(s: &str) -> Content {
+        Content::String(s.to_owned())
+    }
+
+    #[test]
+    fn name_only() {
+        let mut tag = Tag::new(b"foo", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            Content::Unit
+        );
+    }
+
+    #[test]
+    fn single_parameter() {
+        let mut tag = Tag::new(b"foo:bar", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            string("bar")
+        );
+    }
+
+    #[test]
+    fn empty_parameter() {
+        let mut tag = Tag::new(b"foo:", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            string("")
+        );
+    }
+
+    #[test]
+    fn multiple_parameters() {
+        let mut tag = Tag::new(b"foo:bar:baz", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            Content::Seq(vec![string("bar"), string("baz")])
+        );
+    }
+
+    #[test]
+    fn multiple_numeric_parameters() {
+        let mut tag = Tag::new(b"foo:1:2", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            Content::Seq(vec![string("1"), string("2")])
+        );
+    }
+
+    #[test]
+    fn multiple_lists() {
+        let mut tag = Tag::new(b"foo:bar;\nbaz:qux;\n", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            Content::Seq(vec![
+                Content::Seq(vec![string("bar")]),
+                Content::Seq(vec![string("baz"), string("qux")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn multiple_lists_after_name() {
+        let mut tag = Tag::new(b"foo;\nbar:baz;\nqux;\n", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            Content::Seq(vec![
+                Content::Seq(vec![string("bar"), string("baz")]),
+                Content::Seq(vec![string("qux")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn escaped_and_commented() {
+        let mut tag = Tag::new(b"foo:b\\:ar// comment\n", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            string("b:ar")
+        );
+    }
+
+    #[test]
+    fn bool() {
+        let mut tag = Tag::new(b"foo:true", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            Content::Bool(true)
+        );
+    }
+
+    #[test]
+    fn unsigned() {
+        let mut tag = Tag::new(b"foo:42", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            Content::U64(42)
+        );
+    }
+
+    #[test]
+    fn signed() {
+        let mut tag = Tag::new(b"foo:-42", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            Content::I64(-42)
+        );
+    }
+
+    #[test]
+    fn float() {
+        let mut tag = Tag::new(b"foo:-0.5", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            Content::F64(-0.5)
+        );
+    }
+
+    #[test]
+    fn invalid_string() {
+        let mut tag = Tag::new(b"foo:\xF0\x9Fbar", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_err_eq!(
+            Content::deserialize(Deserializer::new(tag, values)),
+            Error::new(error::Kind::ExpectedString, Position::new(0, 5))
+        );
+    }
+
+    #[test]
+    fn invalid_type() {
+        let mut tag = Tag::new(b"foo:bar", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_err_eq!(
+            u64::deserialize(Deserializer::new(tag, values)),
+            Error::new(
+                error::Kind::InvalidType("string \"bar\"".to_owned(), "u64".to_owned()),
+                Position::new(0, 5)
+            )
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use super::parameter;
+use crate::de::{parse::Values, Error, Result};
+use serde::{
+    de,
+    de::{DeserializeSeed, SeqAccess, Visitor},
+    forward_to_deserialize_any,
+};
+
+pub(super) struct Deserializer<'a> {
+    values: Values<'a>,
+
+    trim_head: bool,
+}
+
+impl<'a> Deserializer<'a> {
+    /// If `trim_head` is set, leading whitespace is removed from the list's first parameter. This
+    /// is used for lists following the first in a tag, which usually begin on a new line.
+    pub(super) fn new(values: Values<'a>, trim_head: bool) -> Self {
+        Self { values, trim_head }
+    }
+}
+
+impl<'a, 'de> de::Deserializer<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(Access::new(&mut self.values, self.trim_head))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+struct Access<'a, 'b> {
+    values: &'a mut Values<'b>,
+
+    trim_head: bool,
+}
+
+impl<'a, 'b> Access<'a, 'b> {
+    fn new(values: &'a mut Values<'b>, trim_head: bool) -> Self {
+        Self { values, trim_head }
+    }
+}
+
+impl<'a, 'b, 'de> SeqAccess<'de> for Access<'a, 'b> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let value = match self.values.next() {
+            Ok(value) => value,
+            Err(_) => return Ok(None),
+        };
+        let mut parameter = value.parse_string()?;
+        if self.trim_head {
+            self.trim_head = false;
+            parameter = parameter
+                .trim_start_matches(|c: char| c.is_ascii_whitespace())
+                .to_owned();
+        }
+        seed.deserialize(parameter::Deserializer::new(parameter, value.position()))
+            .map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Deserializer;
+    use crate::de::{error, parse::Values, Error, Position};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use serde::Deserialize;
+
+    #[test]
+    fn single_parameter() {
+        assert_ok_eq!(
+            Vec::<String>::deserialize(Deserializer::new(
+                Values::new(b"foo", Position::new(0, 0)),
+                false
+            )),
+            vec!["foo".to_owned()]
+        );
+    }
+
+    #[test]
+    fn multiple_parameters() {
+        assert_ok_eq!(
+            Vec::<String>::deserialize(Deserializer::new(
+                Values::new(b"foo:bar:baz", Position::new(0, 0)),
+                false
+            )),
+            vec!["foo".to_owned(), "bar".to_owned(), "baz".to_owned()]
+        );
+    }
+
+    #[test]
+    fn exhausted() {
+        let mut values = Values::new(b"foo", Position::new(0, 0));
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Vec::<String>::deserialize(Deserializer::new(values, false)),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn escaped_and_commented() {
+        assert_ok_eq!(
+            Vec::<String>::deserialize(Deserializer::new(
+                Values::new(b"f\\:oo// comment\n:bar", Position::new(0, 0)),
+                false
+            )),
+            vec!["f:oo\n".to_owned(), "bar".to_owned()]
+        );
+    }
+
+    #[test]
+    fn trim_head() {
+        assert_ok_eq!(
+            Vec::<String>::deserialize(Deserializer::new(
+                Values::new(b"\n   foo: bar", Position::new(0, 0)),
+                true
+            )),
+            vec!["foo".to_owned(), " bar".to_owned()]
+        );
+    }
+
+    #[test]
+    fn no_trim_head() {
+        assert_ok_eq!(
+            Vec::<String>::deserialize(Deserializer::new(
+                Values::new(b"\n   foo: bar", Position::new(0, 0)),
+                false
+            )),
+            vec!["\n   foo".to_owned(), " bar".to_owned()]
+        );
+    }
+
+    #[test]
+    fn invalid_string() {
+        assert_err_eq!(
+            Vec::<String>::deserialize(Deserializer::new(
+                Values::new(b"foo:\xF0\x9Fbar", Position::new(0, 0)),
+                false
+            )),
+            Error::new(error::Kind::ExpectedString, Position::new(0, 4))
+        );
+    }
+}
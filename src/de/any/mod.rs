@@ -0,0 +1,32 @@
+//! Self-describing deserialization.
+//!
+//! Without any type information, MSD input is interpreted as a map of tag names to sequences of
+//! parameter lists, where each parameter list is a sequence of strings. For example, the input
+//!
+//! ```text
+//! #foo:bar:baz;
+//! #qux:1;
+//! 2:3;
+//! ```
+//!
+//! is interpreted as `{"foo": [["bar", "baz"]], "qux": [["1"], ["2", "3"]]}`.
+//!
+//! Values buffered by serde while deserializing a flattened field, or an enum that is internally
+//! tagged or untagged, are instead interpreted as described in `content`, so that a tag with a
+//! single parameter can be replayed as that parameter.
+
+pub(in crate::de) mod content;
+pub(in crate::de) mod root;
+pub(in crate::de) mod tag;
+
+mod list;
+mod parameter;
+
+// Returns whether `V` is one of the visitors serde uses to buffer input.
+//
+// Serde offers no other way to tell that a `deserialize_any()` request comes from its private
+// buffering machinery, so the visitor is recognized by the module it is defined in.
+pub(in crate::de) fn is_buffering<V>() -> bool {
+    let name = std::any::type_name::<V>();
+    name.starts_with("serde::") && name.contains("::de::content::")
+}
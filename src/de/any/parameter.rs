@@ -0,0 +1,162 @@
+use crate::de::{parse::Value, Error, Position, Result};
+use serde::{de, de::Visitor, forward_to_deserialize_any};
+
+// A parameter that reads as a boolean or a number.
+enum Scalar {
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+}
+
+impl Scalar {
+    fn infer(value: &Value) -> Option<Self> {
+        if let Ok(parsed) = value.parse_bool() {
+            return Some(Scalar::Bool(parsed));
+        }
+        // Words such as `inf` are kept as strings.
+        if !value.bytes().iter().any(u8::is_ascii_digit) {
+            return None;
+        }
+        if let Ok(parsed) = value.parse_u64() {
+            Some(Scalar::U64(parsed))
+        } else if let Ok(parsed) = value.parse_i64() {
+            Some(Scalar::I64(parsed))
+        } else {
+            value.parse_f64().ok().map(Scalar::F64)
+        }
+    }
+}
+
+pub(super) struct Deserializer {
+    parameter: String,
+    position: Position,
+
+    scalar: Option<Scalar>,
+}
+
+impl Deserializer {
+    pub(super) fn new(parameter: String, position: Position) -> Self {
+        Self {
+            parameter,
+            position,
+
+            scalar: None,
+        }
+    }
+
+    // Gives the parameter as a boolean or number instead of a string if `value` reads as one.
+    //
+    // This is used for values buffered by serde, which cannot be converted from strings after
+    // buffering.
+    pub(super) fn inferring(mut self, value: &Value) -> Self {
+        self.scalar = Scalar::infer(value);
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let position = self.position;
+        match self.scalar {
+            Some(Scalar::Bool(parsed)) => visitor.visit_bool(parsed),
+            Some(Scalar::U64(parsed)) => visitor.visit_u64(parsed),
+            Some(Scalar::I64(parsed)) => visitor.visit_i64(parsed),
+            Some(Scalar::F64(parsed)) => visitor.visit_f64(parsed),
+            None => visitor.visit_string(self.parameter),
+        }
+        .map_err(|mut error: Error| {
+            error.set_position(position);
+            error
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Deserializer;
+    use crate::de::{error, parse::Value, Error, Position};
+    use claims::{assert_err_eq, assert_ok_eq};
+    use serde::Deserialize;
+
+    #[test]
+    fn string() {
+        assert_ok_eq!(
+            String::deserialize(Deserializer::new("foo".to_owned(), Position::new(0, 0))),
+            "foo"
+        );
+    }
+
+    #[test]
+    fn invalid_type() {
+        assert_err_eq!(
+            u64::deserialize(Deserializer::new("foo".to_owned(), Position::new(1, 2))),
+            Error::new(
+                error::Kind::InvalidType("string \"foo\"".to_owned(), "u64".to_owned()),
+                Position::new(1, 2)
+            )
+        );
+    }
+
+    fn inferring(parameter: &str) -> Deserializer {
+        Deserializer::new(parameter.to_owned(), Position::new(0, 0))
+            .inferring(&Value::new(parameter.as_bytes(), Position::new(0, 0)))
+    }
+
+    #[test]
+    fn inferred_bool() {
+        assert_ok_eq!(bool::deserialize(inferring("true")), true);
+    }
+
+    #[test]
+    fn inferred_unsigned() {
+        assert_ok_eq!(u8::deserialize(inferring("42")), 42);
+    }
+
+    #[test]
+    fn inferred_signed() {
+        assert_ok_eq!(i32::deserialize(inferring("-42")), -42);
+    }
+
+    #[test]
+    fn inferred_float() {
+        assert_ok_eq!(f64::deserialize(inferring(" 1.5 ")), 1.5);
+    }
+
+    #[test]
+    fn inferred_integer_as_float() {
+        assert_ok_eq!(f32::deserialize(inferring("2")), 2.0);
+    }
+
+    #[test]
+    fn inferred_string() {
+        assert_ok_eq!(String::deserialize(inferring("1.5.0")), "1.5.0");
+    }
+
+    #[test]
+    fn inferred_word_is_string() {
+        assert_ok_eq!(String::deserialize(inferring("inf")), "inf");
+    }
+
+    #[test]
+    fn inferred_number_as_string() {
+        assert_err_eq!(
+            String::deserialize(inferring("42")),
+            Error::new(
+                error::Kind::InvalidType("integer `42`".to_owned(), "a string".to_owned()),
+                Position::new(0, 0)
+            )
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use super::{content, parameter, tag};
+use crate::de::{
+    parse::{StoredTag, StoredValues, Tags},
+    Error, Read, Result,
+};
+use serde::de::{DeserializeSeed, MapAccess};
+
+pub(in crate::de) struct Access<'a, R> {
+    tags: &'a mut Tags<R>,
+
+    // These stored fields contain raw pointers to the internal buffers of the tag and values
+    // respectively. Note that the pointed-to buffers are only guaranteed to be valid until another
+    // call to `self.tags.next()`.
+    tag: Option<StoredTag>,
+    values: Option<StoredValues>,
+
+    // Whether values are given as buffered content rather than as sequences of parameter lists.
+    content: bool,
+}
+
+impl<'a, R> Access<'a, R> {
+    pub(in crate::de) fn new(tags: &'a mut Tags<R>) -> Self {
+        Self {
+            tags,
+
+            tag: None,
+            values: None,
+
+            content: false,
+        }
+    }
+
+    // Gives each tag's value as described in `content`, as serde requests when buffering the input
+    // for internally tagged or untagged enums.
+    pub(in crate::de) fn buffering(mut self) -> Self {
+        self.content = true;
+        self
+    }
+}
+
+impl<'a, 'de, R> MapAccess<'de> for Access<'a, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let mut tag = match self.tags.next_or_end()? {
+            Some(tag) => tag,
+            None => return Ok(None),
+        };
+        let mut values = tag.next()?;
+        let value = values.next()?;
+
+        let key = seed.deserialize(parameter::Deserializer::new(
+            value.parse_identifier()?,
+            value.position(),
+        ))?;
+        // Note that these raw values will only live until the next call to `next_key_seed()`, at
+        // which point they will be overwritten.
+        self.values = Some(values.into_stored());
+        self.tag = Some(tag.into_stored());
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        // SAFETY: `self.tags` is not modified here, so this `Tag` and `Values` will live longer
+        // than the referenced buffer.
+        let tag = unsafe {
+            self.tag
+                .take()
+                .expect("call to `next_value()` not preceeded by successful call to `next_key()`")
+                .into_tag()
+        };
+        let values = unsafe {
+            self.values
+                .take()
+                .expect("call to `next_value()` not preceeded by successful call to `next_key()`")
+                .into_values()
+        };
+
+        if self.content {
+            seed.deserialize(content::Deserializer::new(tag, values))
+        } else {
+            seed.deserialize(tag::Deserializer::new(tag, values))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Access;
+    use crate::de::{error, parse::Tags, DeserializerOptions, Dialect, Error, Position};
+    use claims::{assert_err_eq, assert_none, assert_ok, assert_ok_eq, assert_some_eq};
+    use serde::de::MapAccess;
+
+    #[test]
+    fn next_key_and_value() {
+        let mut tags = Tags::new(b"#foo:bar:baz;\n".as_slice());
+        let mut access = Access::new(&mut tags);
+
+        assert_some_eq!(assert_ok!(access.next_key::<String>()), "foo".to_owned());
+        assert_ok_eq!(
+            access.next_value::<Vec<Vec<String>>>(),
+            vec![vec!["bar".to_owned(), "baz".to_owned()]]
+        );
+        assert_none!(assert_ok!(access.next_key::<String>()));
+    }
+
+    #[test]
+    fn multiple_entries() {
+        let mut tags = Tags::new(b"#foo:bar;\n#baz;\n".as_slice());
+        let mut access = Access::new(&mut tags);
+
+        assert_some_eq!(
+            assert_ok!(access.next_entry::<String, Vec<Vec<String>>>()),
+            ("foo".to_owned(), vec![vec!["bar".to_owned()]])
+        );
+        assert_some_eq!(
+            assert_ok!(access.next_entry::<String, Vec<Vec<String>>>()),
+            ("baz".to_owned(), vec![vec![]])
+        );
+        assert_none!(assert_ok!(access.next_entry::<String, Vec<Vec<String>>>()));
+    }
+
+    #[test]
+    fn key_is_trimmed() {
+        let mut tags = Tags::new(b"#  foo  :bar;\n".as_slice());
+        let mut access = Access::new(&mut tags);
+
+        assert_some_eq!(assert_ok!(access.next_key::<String>()), "foo".to_owned());
+    }
+
+    #[test]
+    fn invalid_key() {
+        let mut tags = Tags::new(b"#\xF0\x9Ffoo:bar;\n".as_slice());
+        let mut access = Access::new(&mut tags);
+
+        assert_err_eq!(
+            access.next_key::<String>(),
+            Error::new(error::Kind::ExpectedIdentifier, Position::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn next_key_error() {
+        let mut tags = Tags::with_options(
+            b"#foo:bar;\n#baz:qux".as_slice(),
+            DeserializerOptions::new().dialect(Dialect::Strict),
+        );
+        let mut access = Access::new(&mut tags);
+
+        assert_some_eq!(
+            assert_ok!(access.next_entry::<String, Vec<Vec<String>>>()),
+            ("foo".to_owned(), vec![vec!["bar".to_owned()]])
+        );
+        assert_err_eq!(
+            access.next_key::<String>(),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(1, 8))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn next_value_without_next_key() {
+        // Should panic if `next_value()` is called before `next_key()`.
+        let mut tags = Tags::new(b"#foo;\n".as_slice());
+        let mut access = Access::new(&mut tags);
+
+        let _ = access.next_value::<Vec<Vec<String>>>();
+    }
+}
@@ -0,0 +1,121 @@
+use super::list;
+use crate::de::{
+    parse::{Tag, Values},
+    Error, Result,
+};
+use serde::{
+    de,
+    de::{DeserializeSeed, SeqAccess, Visitor},
+    forward_to_deserialize_any,
+};
+
+pub(in crate::de) struct Deserializer<'a> {
+    tag: Tag<'a>,
+    values: Values<'a>,
+}
+
+impl<'a> Deserializer<'a> {
+    /// `values` is the remainder of the tag's first parameter list, with the tag's name already
+    /// consumed.
+    pub(in crate::de) fn new(tag: Tag<'a>, values: Values<'a>) -> Self {
+        Self { tag, values }
+    }
+}
+
+impl<'a, 'de> de::Deserializer<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(Access::new(&mut self.tag, self.values))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+struct Access<'a, 'b> {
+    tag: &'a mut Tag<'b>,
+
+    first_values: Option<Values<'b>>,
+}
+
+impl<'a, 'b> Access<'a, 'b> {
+    fn new(tag: &'a mut Tag<'b>, first_values: Values<'b>) -> Self {
+        Self {
+            tag,
+
+            first_values: Some(first_values),
+        }
+    }
+}
+
+impl<'a, 'b, 'de> SeqAccess<'de> for Access<'a, 'b> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let deserializer = match self.first_values.take() {
+            Some(values) => list::Deserializer::new(values, false),
+            None => match self.tag.next() {
+                Ok(values) => list::Deserializer::new(values, true),
+                Err(_) => return Ok(None),
+            },
+        };
+        seed.deserialize(deserializer).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Deserializer;
+    use crate::de::{parse::Tag, Position};
+    use claims::{assert_ok, assert_ok_eq};
+    use serde::Deserialize;
+
+    #[test]
+    fn name_only() {
+        let mut tag = Tag::new(b"foo", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Vec::<Vec<String>>::deserialize(Deserializer::new(tag, values)),
+            vec![Vec::<String>::new()]
+        );
+    }
+
+    #[test]
+    fn single_list() {
+        let mut tag = Tag::new(b"foo:bar:baz", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Vec::<Vec<String>>::deserialize(Deserializer::new(tag, values)),
+            vec![vec!["bar".to_owned(), "baz".to_owned()]]
+        );
+    }
+
+    #[test]
+    fn multiple_lists() {
+        let mut tag = Tag::new(b"foo:bar;\nbaz:qux;\n", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+
+        assert_ok_eq!(
+            Vec::<Vec<String>>::deserialize(Deserializer::new(tag, values)),
+            vec![
+                vec!["bar".to_owned()],
+                vec!["baz".to_owned(), "qux".to_owned()]
+            ]
+        );
+    }
+}
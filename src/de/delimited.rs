@@ -0,0 +1,232 @@
+use crate::de::{
+    parse::{Value, Values},
+    tuple, Error, Result, Segment,
+};
+use serde::{
+    de,
+    de::{DeserializeSeed, SeqAccess, Visitor},
+    forward_to_deserialize_any,
+};
+
+// Deserializes the values separated by `separator` within `value` as a sequence.
+//
+// The adapters in `crate::with` request a delimited list by calling `deserialize_newtype_struct()`
+// with a name identifying the separator, which is then given this module's `Deserializer`. A key
+// and value pair within the list is instead requested by calling `deserialize_tuple_struct()`.
+pub(in crate::de) fn deserialize<'de, V>(
+    value: &Value,
+    separator: u8,
+    visitor: V,
+) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    let mut values = value.split(separator);
+    let result = visitor.visit_seq(Access::new(&mut values))?;
+    // The visitor may stop before a trailing separator.
+    skip_final_blank(&mut values)?;
+    values.assert_exhausted()?;
+    Ok(result)
+}
+
+// Deserializes the contents of the newtype struct wrapping a delimited list.
+pub(in crate::de) struct Deserializer<'a> {
+    value: Value<'a>,
+    separator: u8,
+}
+
+impl<'a> Deserializer<'a> {
+    pub(in crate::de) fn new(value: Value<'a>, separator: u8) -> Self {
+        Self { value, separator }
+    }
+}
+
+impl<'a, 'de> de::Deserializer<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        deserialize(&self.value, self.separator, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+// Skips the next value if it is blank and is the final value, returning whether it was skipped.
+//
+// A final blank value is not an element. This allows both empty sequences and trailing separators.
+fn skip_final_blank(values: &mut Values) -> Result<bool> {
+    if values.assert_exhausted().is_ok() {
+        return Ok(false);
+    }
+    let mut remaining = values.clone();
+    if remaining.next()?.parse_unit().is_ok() && remaining.assert_exhausted().is_ok() {
+        *values = remaining;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+struct Access<'a, 'b> {
+    values: &'a mut Values<'b>,
+    index: usize,
+}
+
+impl<'a, 'b> Access<'a, 'b> {
+    fn new(values: &'a mut Values<'b>) -> Self {
+        Self { values, index: 0 }
+    }
+}
+
+impl<'a, 'b, 'de> SeqAccess<'de> for Access<'a, 'b> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.values.assert_exhausted().is_ok() || skip_final_blank(self.values)? {
+            return Ok(None);
+        }
+
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(tuple::element::Deserializer::new(self.values))
+            .map(Some)
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Value(index));
+                error
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deserialize, Deserializer};
+    use crate::de::{error, parse::Value, Error, Position, Segment};
+    use claims::{assert_err, assert_err_eq, assert_ok_eq};
+    use serde::{
+        de::{SeqAccess, Visitor},
+        Deserialize,
+    };
+    use std::fmt;
+
+    struct Elements;
+
+    impl<'de> Visitor<'de> for Elements {
+        type Value = Vec<u64>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("elements")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut elements = Vec::new();
+            while let Some(element) = seq.next_element()? {
+                elements.push(element);
+            }
+            Ok(elements)
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let value = Value::new(b"", Position::new(0, 0));
+
+        assert_ok_eq!(deserialize(&value, b',', Elements), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn blank() {
+        let value = Value::new(b" \n// comment\n", Position::new(0, 0));
+
+        assert_ok_eq!(deserialize(&value, b',', Elements), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn single() {
+        let value = Value::new(b"1", Position::new(0, 0));
+
+        assert_ok_eq!(deserialize(&value, b',', Elements), vec![1]);
+    }
+
+    #[test]
+    fn multiple() {
+        let value = Value::new(b"1,2,\n3", Position::new(0, 0));
+
+        assert_ok_eq!(deserialize(&value, b',', Elements), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn trailing_separator() {
+        let value = Value::new(b"1,2,\n", Position::new(0, 0));
+
+        assert_ok_eq!(deserialize(&value, b',', Elements), vec![1, 2]);
+    }
+
+    #[test]
+    fn other_separator() {
+        let value = Value::new(b"1|2", Position::new(0, 0));
+
+        assert_ok_eq!(deserialize(&value, b'|', Elements), vec![1, 2]);
+    }
+
+    #[test]
+    fn deserializer() {
+        let value = Value::new(b"1,2", Position::new(0, 0));
+
+        assert_ok_eq!(
+            Vec::<u64>::deserialize(Deserializer::new(value, b',')),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn invalid_element() {
+        let value = Value::new(b"1,foo", Position::new(0, 0));
+
+        let error = assert_err!(deserialize(&value, b',', Elements));
+        assert_eq!(error.kind(), &error::Kind::ExpectedU64);
+        assert_eq!(error.position(), Position::new(0, 2));
+        assert_eq!(error.path(), &[Segment::Value(1)]);
+    }
+
+    #[test]
+    fn unexpected_value() {
+        struct Pair;
+
+        impl<'de> Visitor<'de> for Pair {
+            type Value = (u64, u64);
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a pair")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                Ok((
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                ))
+            }
+        }
+        let value = Value::new(b"1=2=3", Position::new(0, 0));
+
+        assert_err_eq!(
+            deserialize(&value, b'=', Pair),
+            Error::new(error::Kind::UnexpectedValue, Position::new(0, 4))
+        );
+    }
+}
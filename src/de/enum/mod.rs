@@ -5,11 +5,15 @@ use serde::de::{DeserializeSeed, EnumAccess};
 
 pub(in crate::de) struct Access<'a, 'b> {
     values: &'a mut Values<'b>,
+    variants: &'static [&'static str],
 }
 
 impl<'a, 'b> Access<'a, 'b> {
-    pub(in crate::de) fn new(values: &'a mut Values<'b>) -> Self {
-        Self { values }
+    pub(in crate::de) fn new(
+        values: &'a mut Values<'b>,
+        variants: &'static [&'static str],
+    ) -> Self {
+        Self { values, variants }
     }
 }
 
@@ -21,9 +25,13 @@ impl<'a, 'b, 'de> EnumAccess<'de> for Access<'a, 'b> {
     where
         V: DeserializeSeed<'de>,
     {
+        let value = self.values.next()?;
+        // The name is only used to give context to later errors. If it can't be parsed, the seed
+        // will report the error itself.
+        let name = value.parse_identifier_in(self.variants).unwrap_or_default();
         Ok((
-            seed.deserialize(variant::Deserializer::new(self.values.next()?))?,
-            variant::Access::new(self.values),
+            seed.deserialize(variant::Deserializer::new(value, self.variants))?,
+            variant::Access::new(name, self.values),
         ))
     }
 }
@@ -71,7 +79,44 @@ mod tests {
         }
 
         let mut values = Values::new(b"foo", Position::new(0, 0));
-        let access = Access::new(&mut values);
+        let access = Access::new(&mut values, &["foo"]);
+
+        let (variant, _variant_access) = assert_ok!(access.variant::<Variant>());
+        assert_eq!(variant, Variant("foo".to_string()));
+    }
+
+    #[test]
+    fn variant_ignoring_case() {
+        #[derive(Debug, PartialEq)]
+        struct Variant(String);
+        impl<'de> Deserialize<'de> for Variant {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                struct VariantVisitor;
+
+                impl<'de> Visitor<'de> for VariantVisitor {
+                    type Value = Variant;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str("identifier")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+                    where
+                        E: de::Error,
+                    {
+                        Ok(Variant(value.to_owned()))
+                    }
+                }
+
+                deserializer.deserialize_identifier(VariantVisitor)
+            }
+        }
+
+        let mut values = Values::new(b"FOO", Position::new(0, 0)).ignoring_case();
+        let access = Access::new(&mut values, &["foo"]);
 
         let (variant, _variant_access) = assert_ok!(access.variant::<Variant>());
         assert_eq!(variant, Variant("foo".to_string()));
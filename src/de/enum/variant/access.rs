@@ -1,13 +1,14 @@
-use crate::de::{parse::Values, tuple, Error, Result};
+use crate::de::{parse::Values, tuple, Error, Result, Segment};
 use serde::de::{DeserializeSeed, VariantAccess, Visitor};
 
 pub(in crate::de) struct Access<'a, 'b> {
+    name: String,
     values: &'a mut Values<'b>,
 }
 
 impl<'a, 'b> Access<'a, 'b> {
-    pub(in super::super) fn new(values: &'a mut Values<'b>) -> Self {
-        Self { values }
+    pub(in super::super) fn new(name: String, values: &'a mut Values<'b>) -> Self {
+        Self { name, values }
     }
 }
 
@@ -24,14 +25,25 @@ impl<'a, 'b, 'de> VariantAccess<'de> for Access<'a, 'b> {
     where
         T: DeserializeSeed<'de>,
     {
+        let name = self.name;
         seed.deserialize(tuple::element::Deserializer::new(self.values))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Variant(name));
+                error
+            })
     }
 
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_seq(tuple::Access::new(self.values, len))
+        let name = self.name;
+        visitor
+            .visit_seq(tuple::Access::new(self.values, len))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Variant(name));
+                error
+            })
     }
 
     fn struct_variant<V>(self, _fields: &'static [&'static str], _visitor: V) -> Result<V::Value>
@@ -53,7 +65,7 @@ mod tests {
     #[test]
     fn unit_variant() {
         let mut values = Values::new(b"", Position::new(0, 0));
-        let access = Access::new(&mut values);
+        let access = Access::new("foo".to_owned(), &mut values);
 
         assert_ok!(access.unit_variant());
     }
@@ -61,7 +73,7 @@ mod tests {
     #[test]
     fn newtype_variant() {
         let mut values = Values::new(b"42", Position::new(0, 0));
-        let access = Access::new(&mut values);
+        let access = Access::new("foo".to_owned(), &mut values);
 
         assert_ok_eq!(access.newtype_variant::<u64>(), 42);
     }
@@ -95,7 +107,7 @@ mod tests {
         }
 
         let mut values = Values::new(b"42:foo:1.2", Position::new(0, 0));
-        let access = Access::new(&mut values);
+        let access = Access::new("foo".to_owned(), &mut values);
 
         assert_ok_eq!(
             access.tuple_variant(4, TupleVisitor),
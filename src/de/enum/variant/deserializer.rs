@@ -3,11 +3,12 @@ use serde::{de, de::Visitor};
 
 pub(in super::super) struct Deserializer<'a> {
     value: Value<'a>,
+    variants: &'static [&'static str],
 }
 
 impl<'a> Deserializer<'a> {
-    pub(in super::super) fn new(value: Value<'a>) -> Self {
-        Self { value }
+    pub(in super::super) fn new(value: Value<'a>, variants: &'static [&'static str]) -> Self {
+        Self { value, variants }
     }
 }
 
@@ -326,7 +327,7 @@ impl<'a, 'de> de::Deserializer<'de> for Deserializer<'a> {
         V: Visitor<'de>,
     {
         visitor
-            .visit_str(&self.value.parse_identifier()?)
+            .visit_str(&self.value.parse_identifier_in(self.variants)?)
             .map_err(|mut error: Error| {
                 error.set_position(self.value.position());
                 error
@@ -385,7 +386,7 @@ mod tests {
 
     #[test]
     fn identifier() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(0, 0)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(0, 0)), &[]);
 
         assert_ok_eq!(
             Identifier::deserialize(deserializer),
@@ -395,7 +396,7 @@ mod tests {
 
     #[test]
     fn identifier_invalid() {
-        let deserializer = Deserializer::new(Value::new(b"\xF0\x9Ffoo", Position::new(0, 0)));
+        let deserializer = Deserializer::new(Value::new(b"\xF0\x9Ffoo", Position::new(0, 0)), &[]);
 
         assert_err_eq!(
             Identifier::deserialize(deserializer),
@@ -434,7 +435,7 @@ mod tests {
             }
         }
 
-        let deserializer = Deserializer::new(Value::new(b"a", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"a", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             CustomIdentifier::deserialize(deserializer),
@@ -466,7 +467,7 @@ mod tests {
             }
         }
 
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             Any::deserialize(deserializer),
@@ -501,7 +502,7 @@ mod tests {
             }
         }
 
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             IgnoredAny::deserialize(deserializer),
@@ -514,7 +515,7 @@ mod tests {
 
     #[test]
     fn bool() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             bool::deserialize(deserializer),
@@ -527,7 +528,7 @@ mod tests {
 
     #[test]
     fn i8() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             i8::deserialize(deserializer),
@@ -540,7 +541,7 @@ mod tests {
 
     #[test]
     fn i16() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             i16::deserialize(deserializer),
@@ -553,7 +554,7 @@ mod tests {
 
     #[test]
     fn i32() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             i32::deserialize(deserializer),
@@ -566,7 +567,7 @@ mod tests {
 
     #[test]
     fn i64() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             i64::deserialize(deserializer),
@@ -579,7 +580,7 @@ mod tests {
 
     #[test]
     fn i128() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             i128::deserialize(deserializer),
@@ -592,7 +593,7 @@ mod tests {
 
     #[test]
     fn u8() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             u8::deserialize(deserializer),
@@ -605,7 +606,7 @@ mod tests {
 
     #[test]
     fn u16() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             u16::deserialize(deserializer),
@@ -618,7 +619,7 @@ mod tests {
 
     #[test]
     fn u32() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             u32::deserialize(deserializer),
@@ -631,7 +632,7 @@ mod tests {
 
     #[test]
     fn u64() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             u64::deserialize(deserializer),
@@ -644,7 +645,7 @@ mod tests {
 
     #[test]
     fn u128() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             u128::deserialize(deserializer),
@@ -657,7 +658,7 @@ mod tests {
 
     #[test]
     fn f32() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             f32::deserialize(deserializer),
@@ -670,7 +671,7 @@ mod tests {
 
     #[test]
     fn f64() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             f64::deserialize(deserializer),
@@ -683,7 +684,7 @@ mod tests {
 
     #[test]
     fn char() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             char::deserialize(deserializer),
@@ -696,7 +697,7 @@ mod tests {
 
     #[test]
     fn str() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             <&str>::deserialize(deserializer),
@@ -709,7 +710,7 @@ mod tests {
 
     #[test]
     fn string() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             String::deserialize(deserializer),
@@ -722,7 +723,7 @@ mod tests {
 
     #[test]
     fn bytes() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             <&Bytes>::deserialize(deserializer),
@@ -735,7 +736,7 @@ mod tests {
 
     #[test]
     fn byte_buf() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             ByteBuf::deserialize(deserializer),
@@ -748,7 +749,7 @@ mod tests {
 
     #[test]
     fn option() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             Option::<()>::deserialize(deserializer),
@@ -761,7 +762,7 @@ mod tests {
 
     #[test]
     fn unit() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             <()>::deserialize(deserializer),
@@ -777,7 +778,7 @@ mod tests {
         #[derive(Debug, Deserialize, PartialEq)]
         struct Unit;
 
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             Unit::deserialize(deserializer),
@@ -793,7 +794,7 @@ mod tests {
         #[derive(Debug, Deserialize, PartialEq)]
         struct Newtype(u64);
 
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             Newtype::deserialize(deserializer),
@@ -806,7 +807,7 @@ mod tests {
 
     #[test]
     fn seq() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             Vec::<()>::deserialize(deserializer),
@@ -819,7 +820,7 @@ mod tests {
 
     #[test]
     fn tuple() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             <((),)>::deserialize(deserializer),
@@ -833,9 +834,10 @@ mod tests {
     #[test]
     fn tuple_struct() {
         #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
         struct TupleStruct(String, u64, (), f64);
 
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             TupleStruct::deserialize(deserializer),
@@ -848,7 +850,7 @@ mod tests {
 
     #[test]
     fn map() {
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             HashMap::<(), ()>::deserialize(deserializer),
@@ -869,7 +871,7 @@ mod tests {
             _qux: f64,
         }
 
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             Struct::deserialize(deserializer),
@@ -885,7 +887,7 @@ mod tests {
         #[derive(Debug, Deserialize)]
         enum Enum {}
 
-        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)));
+        let deserializer = Deserializer::new(Value::new(b"foo", Position::new(1, 2)), &[]);
 
         assert_err_eq!(
             Enum::deserialize(deserializer),
@@ -3,64 +3,183 @@ use serde::{
     de,
     de::{Expected, Unexpected},
 };
-use std::{fmt, fmt::Display};
+use std::{fmt, fmt::Display, io, sync::Arc};
 
+/// The specific cause of an [`Error`].
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Kind {
     // Formatting errors.
+    /// The input ended before a tag was found.
     EndOfFile,
+    /// A tag was expected, but something else was found.
     ExpectedTag,
+    /// A tag was found where none was expected.
     UnexpectedTag,
+    /// A tag ended before all expected parameter lists were found.
     EndOfTag,
+    /// A parameter list was found where none was expected.
     UnexpectedValues,
+    /// A parameter was found where none was expected.
     UnexpectedValue,
+    /// A parameter list ended before all expected parameters were found.
     EndOfValues,
+    /// A new tag began before the previous tag was terminated by a `;`.
+    MissingTagTerminator,
+    /// A `#` within a tag was not escaped.
+    UnescapedNumberSign,
 
     // Value errors.
+    /// A parameter could not be parsed as a `bool`.
     ExpectedBool,
+    /// A parameter could not be parsed as an `i8`.
     ExpectedI8,
+    /// A parameter could not be parsed as an `i16`.
     ExpectedI16,
+    /// A parameter could not be parsed as an `i32`.
     ExpectedI32,
+    /// A parameter could not be parsed as an `i64`.
     ExpectedI64,
+    /// A parameter could not be parsed as an `i128`.
     ExpectedI128,
+    /// A parameter could not be parsed as a `u8`.
     ExpectedU8,
+    /// A parameter could not be parsed as a `u16`.
     ExpectedU16,
+    /// A parameter could not be parsed as a `u32`.
     ExpectedU32,
+    /// A parameter could not be parsed as a `u64`.
     ExpectedU64,
+    /// A parameter could not be parsed as a `u128`.
     ExpectedU128,
+    /// A parameter could not be parsed as an `f32`.
     ExpectedF32,
+    /// A parameter could not be parsed as an `f64`.
     ExpectedF64,
+    /// A parameter could not be parsed as a `char`.
     ExpectedChar,
+    /// A parameter was not valid UTF-8.
     ExpectedString,
+    /// A parameter was not empty when a unit value was expected.
     ExpectedUnit,
+    /// A parameter could not be parsed as an identifier.
     ExpectedIdentifier,
 
     // IO-related errors.
-    Io,
+    /// The underlying reader failed with an error of the contained kind.
+    ///
+    /// The original error is available through [`Error::source()`].
+    ///
+    /// [`Error::source()`]: std::error::Error::source()
+    Io(io::ErrorKind),
 
     // User-provided errors (provided through `serde::de::Error` trait methods).
+    /// A custom error message.
     Custom(String),
+    /// A value of the wrong type was found. Contains the unexpected and expected types.
     InvalidType(String, String),
+    /// A value of the right type but wrong value was found. Contains the unexpected and expected
+    /// values.
     InvalidValue(String, String),
+    /// A sequence or map had the wrong number of elements.
     InvalidLength(usize, String),
+    /// An enum variant was not recognized.
     UnknownVariant(String, &'static [&'static str]),
+    /// A struct field was not recognized.
     UnknownField(String, &'static [&'static str]),
+    /// A struct field was missing.
     MissingField(&'static str),
+    /// A struct field was found more than once.
     DuplicateField(&'static str),
+    /// A tag was found more than once. Contains the tag's name and the position of its first
+    /// occurrence.
+    ///
+    /// Only returned when using [`DuplicateTags`](crate::de::DuplicateTags).
+    DuplicateTag(String, Position),
+
+    // Limit errors.
+    /// The input exceeded one of the configured [`Limits`](crate::de::Limits).
+    LimitExceeded(Limit),
 
     // Unrepresentable type errors.
+    /// The type requested a self-describing value in a position where MSD has none.
     CannotDeserializeAsSelfDescribing,
+    /// The type requested an option within a tuple.
     CannotDeserializeAsOptionInTuple,
+    /// The type requested a sequence within a tuple.
     CannotDeserializeAsSeqInTuple,
+    /// The type requested a map within a tuple.
     CannotDeserializeAsMapInTuple,
+    /// The type requested a struct within a tuple.
     CannotDeserializeAsStructInTuple,
+    /// The type requested a struct nested within another struct's field.
     CannotDeserializeNestedStruct,
+    /// A struct field name was requested as something other than an identifier.
     MustDeserializeStructFieldAsIdentifier,
+    /// The type requested an option within a sequence.
     CannotDeserializeAsOptionInSeq,
+    /// The type requested a sequence nested within another sequence, whose elements are not
+    /// themselves sequences or tuples.
     CannotDeserializeNestedSeq,
+    /// An enum variant was requested as something other than an identifier.
     MustDeserializeEnumVariantAsIdentifier,
 }
 
+impl Kind {
+    /// Returns the coarse [`Category`] this kind of error belongs to.
+    pub fn category(&self) -> Category {
+        match self {
+            Kind::EndOfFile => Category::Eof,
+            Kind::ExpectedTag
+            | Kind::UnexpectedTag
+            | Kind::EndOfTag
+            | Kind::UnexpectedValues
+            | Kind::UnexpectedValue
+            | Kind::EndOfValues
+            | Kind::MissingTagTerminator
+            | Kind::UnescapedNumberSign => Category::Syntax,
+            Kind::ExpectedBool
+            | Kind::ExpectedI8
+            | Kind::ExpectedI16
+            | Kind::ExpectedI32
+            | Kind::ExpectedI64
+            | Kind::ExpectedI128
+            | Kind::ExpectedU8
+            | Kind::ExpectedU16
+            | Kind::ExpectedU32
+            | Kind::ExpectedU64
+            | Kind::ExpectedU128
+            | Kind::ExpectedF32
+            | Kind::ExpectedF64
+            | Kind::ExpectedChar
+            | Kind::ExpectedString
+            | Kind::ExpectedUnit
+            | Kind::ExpectedIdentifier
+            | Kind::Custom(_)
+            | Kind::InvalidType(..)
+            | Kind::InvalidValue(..)
+            | Kind::InvalidLength(..)
+            | Kind::UnknownVariant(..)
+            | Kind::UnknownField(..)
+            | Kind::MissingField(_)
+            | Kind::DuplicateField(_)
+            | Kind::DuplicateTag(..)
+            | Kind::LimitExceeded(_) => Category::Data,
+            Kind::Io(_) => Category::Io,
+            Kind::CannotDeserializeAsSelfDescribing
+            | Kind::CannotDeserializeAsOptionInTuple
+            | Kind::CannotDeserializeAsSeqInTuple
+            | Kind::CannotDeserializeAsMapInTuple
+            | Kind::CannotDeserializeAsStructInTuple
+            | Kind::CannotDeserializeNestedStruct
+            | Kind::MustDeserializeStructFieldAsIdentifier
+            | Kind::CannotDeserializeAsOptionInSeq
+            | Kind::CannotDeserializeNestedSeq
+            | Kind::MustDeserializeEnumVariantAsIdentifier => Category::Unrepresentable,
+        }
+    }
+}
+
 impl Display for Kind {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -71,6 +190,8 @@ impl Display for Kind {
             Kind::UnexpectedValues => formatter.write_str("unexpected values"),
             Kind::UnexpectedValue => formatter.write_str("unexpected value"),
             Kind::EndOfValues => formatter.write_str("unexpected end of values"),
+            Kind::MissingTagTerminator => formatter.write_str("missing `;` at end of tag"),
+            Kind::UnescapedNumberSign => formatter.write_str("unescaped `#` within tag"),
             Kind::ExpectedBool => formatter.write_str("expected bool"),
             Kind::ExpectedI8 => formatter.write_str("expected i8"),
             Kind::ExpectedI16 => formatter.write_str("expected i16"),
@@ -88,7 +209,7 @@ impl Display for Kind {
             Kind::ExpectedString => formatter.write_str("expected string"),
             Kind::ExpectedUnit => formatter.write_str("expected unit value"),
             Kind::ExpectedIdentifier => formatter.write_str("expected identifier"),
-            Kind::Io => formatter.write_str("io error"),
+            Kind::Io(_) => formatter.write_str("io error"),
             Kind::Custom(msg) => formatter.write_str(msg),
             Kind::InvalidType(unexpected, expected) => {
                 write!(
@@ -133,6 +254,16 @@ impl Display for Kind {
             Kind::DuplicateField(field) => {
                 write!(formatter, "duplicate field {}", field)
             }
+            Kind::DuplicateTag(name, first) => {
+                write!(
+                    formatter,
+                    "duplicate tag #{}, first found at line {} column {}",
+                    name,
+                    first.line(),
+                    first.column()
+                )
+            }
+            Kind::LimitExceeded(limit) => write!(formatter, "exceeded maximum {}", limit),
             Kind::CannotDeserializeAsSelfDescribing => {
                 formatter.write_str("cannot deserialize as self-describing")
             }
@@ -167,21 +298,159 @@ impl Display for Kind {
     }
 }
 
+/// A limit on the input that can be exceeded, as configured by [`Limits`].
+///
+/// [`Limits`]: crate::de::Limits
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// The size of a single tag in bytes.
+    TagSize,
+    /// The number of tags.
+    Tags,
+    /// The number of parameters within a single tag.
+    Parameters,
+    /// The total size of the input in bytes.
+    TotalBytes,
+    /// The length of a single parameter in bytes.
+    StringLength,
+}
+
+impl Display for Limit {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match self {
+            Limit::TagSize => "tag size",
+            Limit::Tags => "number of tags",
+            Limit::Parameters => "number of parameters in a tag",
+            Limit::TotalBytes => "input size",
+            Limit::StringLength => "parameter length",
+        })
+    }
+}
+
+/// A coarse classification of an [`Error`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Category {
+    /// The input is not valid MSD, or does not have the structure the type expects.
+    Syntax,
+    /// The input is structurally valid, but a value could not be converted to the type expected.
+    Data,
+    /// The input ended unexpectedly.
+    ///
+    /// When reading from a stream, this may indicate that more input is yet to arrive.
+    Eof,
+    /// The underlying reader failed.
+    Io,
+    /// The type being deserialized cannot be represented in MSD.
+    Unrepresentable,
+}
+
+/// A single step along the path to the value being deserialized when an [`Error`] occurred.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Segment {
+    /// A tag, identified by its name. This is also the name of the struct field being
+    /// deserialized from it.
+    Tag(String),
+    /// An element within a sequence or map, identified by its index.
+    Element(usize),
+    /// A parameter within a parameter list, identified by its index.
+    Value(usize),
+    /// An enum variant, identified by its name.
+    Variant(String),
+}
+
+impl Display for Segment {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Segment::Tag(name) => write!(formatter, "tag #{}", name),
+            Segment::Element(index) => write!(formatter, "element {}", index),
+            Segment::Value(index) => write!(formatter, "value {}", index),
+            Segment::Variant(name) => write!(formatter, "variant {}", name),
+        }
+    }
+}
+
 /// An error that may occur during deserialization.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// Two errors are considered equal if they have the same kind, position and path. For I/O
+/// errors, only the [`io::ErrorKind`] of the underlying error is compared.
+#[derive(Clone, Debug)]
 pub struct Error {
     position: Position,
     kind: Kind,
+    path: Vec<Segment>,
+    // Shared so that errors can be cloned when they need to be returned repeatedly.
+    source: Option<Arc<io::Error>>,
 }
 
 impl Error {
     pub(super) fn new(kind: Kind, position: Position) -> Self {
-        Self { position, kind }
+        Self {
+            position,
+            kind,
+            path: Vec::new(),
+            source: None,
+        }
+    }
+
+    pub(in crate::de) fn io(error: io::Error, position: Position) -> Self {
+        Self {
+            position,
+            kind: Kind::Io(error.kind()),
+            path: Vec::new(),
+            source: Some(Arc::new(error)),
+        }
+    }
+
+    /// Returns the position in the input at which the error occurred.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Returns the zero-indexed line at which the error occurred.
+    pub fn line(&self) -> usize {
+        self.position.line()
+    }
+
+    /// Returns the zero-indexed column at which the error occurred, measured in bytes from the
+    /// start of the line.
+    pub fn column(&self) -> usize {
+        self.position.column()
+    }
+
+    /// Returns the zero-indexed byte offset from the start of the input at which the error
+    /// occurred.
+    pub fn offset(&self) -> usize {
+        self.position.offset()
+    }
+
+    /// Returns the specific cause of the error.
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// Returns the coarse [`Category`] of the error.
+    pub fn category(&self) -> Category {
+        self.kind.category()
+    }
+
+    /// Returns the path to the value being deserialized when the error occurred, starting from
+    /// the outermost tag.
+    ///
+    /// The path is empty if the error did not occur within a tag, such as when the input is not
+    /// valid MSD.
+    pub fn path(&self) -> &[Segment] {
+        &self.path
     }
 
     pub(in crate::de) fn set_position(&mut self, position: Position) {
         self.position = position;
     }
+
+    // Segments are added as the error propagates outward, so each new segment is the outermost.
+    pub(in crate::de) fn push_segment(&mut self, segment: Segment) {
+        self.path.insert(0, segment);
+    }
 }
 
 impl de::Error for Error {
@@ -238,6 +507,13 @@ impl de::Error for Error {
 
 impl Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((first, rest)) = self.path.split_first() {
+            write!(formatter, "in {}", first)?;
+            for segment in rest {
+                write!(formatter, ", {}", segment)?;
+            }
+            formatter.write_str(": ")?;
+        }
         write!(
             formatter,
             "{} at line {} column {}",
@@ -248,7 +524,21 @@ impl Display for Error {
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|error| error as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position && self.kind == other.kind && self.path == other.path
+    }
+}
+
+impl Eq for Error {}
 
 /// An alias for a [`Result`] with the error type [`Error`].
 ///
@@ -257,10 +547,11 @@ pub type Result<T> = core::result::Result<T, Error>;
 
 #[cfg(test)]
 mod tests {
-    use super::{Error, Kind};
+    use super::{Category, Error, Kind, Limit, Segment};
     use crate::de::Position;
     use serde::de::Error as SerdeError;
     use serde::de::Unexpected;
+    use std::{error::Error as _, io};
 
     #[test]
     fn end_of_file() {
@@ -321,6 +612,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn missing_tag_terminator() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(Kind::MissingTagTerminator, Position::new(7, 8))
+            ),
+            "missing `;` at end of tag at line 7 column 8"
+        );
+    }
+
+    #[test]
+    fn unescaped_number_sign() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(Kind::UnescapedNumberSign, Position::new(7, 8))
+            ),
+            "unescaped `#` within tag at line 7 column 8"
+        );
+    }
+
     #[test]
     fn expected_bool() {
         assert_eq!(
@@ -466,7 +779,10 @@ mod tests {
     #[test]
     fn io() {
         assert_eq!(
-            format!("{}", Error::new(Kind::Io, Position::new(25, 26))),
+            format!(
+                "{}",
+                Error::new(Kind::Io(io::ErrorKind::Other), Position::new(25, 26))
+            ),
             "io error at line 25 column 26"
         );
     }
@@ -514,7 +830,7 @@ mod tests {
 
     #[test]
     fn unknown_variant() {
-        static EXPECTED: &'static [&'static str] = &["foo", "bar"];
+        static EXPECTED: &[&str] = &["foo", "bar"];
         let mut error = Error::unknown_variant("baz", EXPECTED);
         error.set_position(Position::new(30, 31));
 
@@ -557,6 +873,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn duplicate_tag() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(
+                    Kind::DuplicateTag("FOO".to_owned(), Position::new(1, 0)),
+                    Position::new(34, 35)
+                )
+            ),
+            "duplicate tag #FOO, first found at line 1 column 0 at line 34 column 35"
+        );
+    }
+
+    #[test]
+    fn limit_exceeded() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::new(Kind::LimitExceeded(Limit::TagSize), Position::new(35, 36))
+            ),
+            "exceeded maximum tag size at line 35 column 36"
+        );
+    }
+
     #[test]
     fn cannot_deserialize_as_self_describing() {
         assert_eq!(
@@ -690,4 +1031,151 @@ mod tests {
 
         assert_eq!(error.position, Position::new(1, 2));
     }
+
+    #[test]
+    fn accessors() {
+        let error = Error::new(
+            Kind::ExpectedU8,
+            Position::new(0, 0).increment_line().advance_columns(3),
+        );
+
+        assert_eq!(error.position(), Position::new(1, 3));
+        assert_eq!(error.line(), 1);
+        assert_eq!(error.column(), 3);
+        assert_eq!(error.offset(), 4);
+        assert_eq!(error.kind(), &Kind::ExpectedU8);
+    }
+
+    #[test]
+    fn category_syntax() {
+        assert_eq!(
+            Error::new(Kind::UnexpectedTag, Position::new(0, 0)).category(),
+            Category::Syntax
+        );
+    }
+
+    #[test]
+    fn category_data() {
+        assert_eq!(
+            Error::new(Kind::ExpectedF64, Position::new(0, 0)).category(),
+            Category::Data
+        );
+        assert_eq!(Error::missing_field("foo").category(), Category::Data);
+        assert_eq!(
+            Error::new(Kind::LimitExceeded(Limit::Tags), Position::new(0, 0)).category(),
+            Category::Data
+        );
+    }
+
+    #[test]
+    fn category_eof() {
+        assert_eq!(
+            Error::new(Kind::EndOfFile, Position::new(0, 0)).category(),
+            Category::Eof
+        );
+    }
+
+    #[test]
+    fn category_io() {
+        assert_eq!(
+            Error::new(Kind::Io(io::ErrorKind::Other), Position::new(0, 0)).category(),
+            Category::Io
+        );
+    }
+
+    #[test]
+    fn category_unrepresentable() {
+        assert_eq!(
+            Error::new(Kind::CannotDeserializeNestedSeq, Position::new(0, 0)).category(),
+            Category::Unrepresentable
+        );
+    }
+
+    #[test]
+    fn path_empty() {
+        let error = Error::new(Kind::ExpectedF64, Position::new(1, 2));
+
+        assert_eq!(error.path(), &[]);
+    }
+
+    #[test]
+    fn path() {
+        let mut error = Error::new(Kind::ExpectedF64, Position::new(1, 2));
+        error.push_segment(Segment::Value(1));
+        error.push_segment(Segment::Element(3));
+        error.push_segment(Segment::Tag("BPMS".to_owned()));
+
+        assert_eq!(
+            error.path(),
+            &[
+                Segment::Tag("BPMS".to_owned()),
+                Segment::Element(3),
+                Segment::Value(1)
+            ]
+        );
+    }
+
+    #[test]
+    fn display_path_single() {
+        let mut error = Error::new(Kind::ExpectedF64, Position::new(12, 7));
+        error.push_segment(Segment::Tag("OFFSET".to_owned()));
+
+        assert_eq!(
+            format!("{}", error),
+            "in tag #OFFSET: expected f64 at line 12 column 7"
+        );
+    }
+
+    #[test]
+    fn display_path_multiple() {
+        let mut error = Error::new(Kind::ExpectedF64, Position::new(12, 7));
+        error.push_segment(Segment::Value(1));
+        error.push_segment(Segment::Variant("Foo".to_owned()));
+        error.push_segment(Segment::Element(3));
+        error.push_segment(Segment::Tag("BPMS".to_owned()));
+
+        assert_eq!(
+            format!("{}", error),
+            "in tag #BPMS, element 3, variant Foo, value 1: expected f64 at line 12 column 7"
+        );
+    }
+
+    #[test]
+    fn io_source() {
+        let error = Error::io(
+            io::Error::new(io::ErrorKind::PermissionDenied, "foo"),
+            Position::new(0, 0),
+        );
+
+        assert_eq!(error.kind(), &Kind::Io(io::ErrorKind::PermissionDenied));
+        assert_eq!(format!("{}", error.source().unwrap()), "foo");
+    }
+
+    #[test]
+    fn io_source_cloned() {
+        let error = Error::io(
+            io::Error::new(io::ErrorKind::BrokenPipe, "foo"),
+            Position::new(0, 0),
+        );
+
+        assert_eq!(format!("{}", error.clone().source().unwrap()), "foo");
+    }
+
+    #[test]
+    fn no_source() {
+        let error = Error::new(Kind::ExpectedF64, Position::new(0, 0));
+
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn eq_ignores_io_source() {
+        assert_eq!(
+            Error::io(
+                io::Error::new(io::ErrorKind::Other, "foo"),
+                Position::new(0, 0)
+            ),
+            Error::new(Kind::Io(io::ErrorKind::Other), Position::new(0, 0))
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use crate::de::{
     parse::{Tag, Values},
-    tuple, Error, Result,
+    tuple, Error, Result, Segment,
 };
 use serde::de::{DeserializeSeed, MapAccess};
 
@@ -8,11 +8,16 @@ pub(in crate::de) struct Access<'a, 'b> {
     tag: &'a mut Tag<'b>,
 
     values: Option<Values<'a>>,
+    index: usize,
 }
 
 impl<'a, 'b> Access<'a, 'b> {
     pub(in crate::de) fn new(tag: &'a mut Tag<'b>) -> Self {
-        Self { tag, values: None }
+        Self {
+            tag,
+            values: None,
+            index: 0,
+        }
     }
 }
 
@@ -27,7 +32,13 @@ impl<'a, 'b, 'de> MapAccess<'de> for Access<'a, 'b> {
             Ok(values) => values,
             Err(_) => return Ok(None),
         };
-        let key = seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
+        let index = self.index;
+        let key = seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Element(index));
+                error
+            })?;
         self.values = Some(values);
 
         Ok(Some(key))
@@ -42,7 +53,14 @@ impl<'a, 'b, 'de> MapAccess<'de> for Access<'a, 'b> {
             .take()
             .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
 
-        let value = seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
+        let index = self.index;
+        self.index += 1;
+        let value = seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Element(index));
+                error
+            })?;
         values.assert_exhausted()?;
 
         Ok(value)
@@ -61,9 +79,21 @@ impl<'a, 'b, 'de> MapAccess<'de> for Access<'a, 'b> {
             Ok(values) => values,
             Err(_) => return Ok(None),
         };
-        let key = key_seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
-
-        let value = value_seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
+        let index = self.index;
+        self.index += 1;
+        let key = key_seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Element(index));
+                error
+            })?;
+
+        let value = value_seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Element(index));
+                error
+            })?;
         values.assert_exhausted()?;
 
         Ok(Some((key, value)))
@@ -1,2 +1,4 @@
 pub(in crate::de) mod field;
 pub(in crate::de) mod root;
+
+mod value;
@@ -1,25 +1,106 @@
+use super::value;
 use crate::de::{
-    parse::{StoredValues, Tags},
-    tuple, Error, Result,
+    error,
+    parse::{StoredTag, StoredValues, Tags, Values},
+    tuple, DuplicateTags, Error, Position, Read, Result, Segment,
 };
 use serde::de::{DeserializeSeed, MapAccess};
-use std::io::Read;
+use std::collections::HashMap;
 
 pub(in crate::de) struct Access<'a, R> {
     tags: &'a mut Tags<R>,
 
+    // These stored fields contain raw pointers to the internal buffers of the tag and values
+    // respectively. Note that the pointed-to buffers are only guaranteed to be valid until another
+    // call to `self.tags.next()`.
+    tag: Option<StoredTag>,
     values: Option<StoredValues>,
+    key: String,
+    index: usize,
+
+    // The position of the first occurrence of each key, tracked only when handling duplicate
+    // tags.
+    seen: HashMap<String, Position>,
+    arranged: bool,
 }
 
 impl<'a, R> Access<'a, R> {
     pub(in crate::de) fn new(tags: &'a mut Tags<R>) -> Self {
-        Self { tags, values: None }
+        Self {
+            tags,
+            tag: None,
+            values: None,
+            key: String::new(),
+            index: 0,
+
+            seen: HashMap::new(),
+            arranged: false,
+        }
+    }
+}
+
+impl<'a, 'de, R> Access<'a, R>
+where
+    R: Read<'de>,
+{
+    // Removes all but the last occurrence of each key if required by the duplicate tag policy.
+    fn arrange(&mut self) -> Result<Option<DuplicateTags>> {
+        let policy = self.tags.duplicate_tags();
+        if self.arranged || policy != Some(DuplicateTags::LastWins) {
+            return Ok(policy);
+        }
+        self.arranged = true;
+
+        let buffered = self
+            .tags
+            .read_ahead(|mut tag| key_name(&tag.next().ok()?))?;
+        let last: HashMap<String, usize> = buffered
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (_, key))| key.clone().map(|key| (key, index)))
+            .collect();
+        self.tags.replay(
+            buffered
+                .into_iter()
+                .enumerate()
+                .filter(|(index, (_, key))| key.as_ref().map_or(true, |key| last[key] == *index))
+                .map(|(_, (tag, _))| tag)
+                .collect(),
+        );
+        Ok(policy)
+    }
+}
+
+// The name of the key within `values`, used to identify duplicate tags and the tags of a sequence
+// value. It is normalized the same way as the field name of a sequence is matched in `seq::field`.
+fn key_name(values: &Values) -> Option<String> {
+    values.clone().next().ok()?.parse_identifier().ok()
+}
+
+// Returns whether the tag at `position` should be skipped as a duplicate.
+fn check_duplicate(
+    seen: &mut HashMap<String, Position>,
+    policy: Option<DuplicateTags>,
+    key: Option<String>,
+    position: Position,
+) -> Result<bool> {
+    let key = match (policy, key) {
+        (Some(DuplicateTags::Collect) | None, _) | (_, None) => return Ok(false),
+        (_, Some(key)) => key,
+    };
+    match seen.get(&key) {
+        Some(_) if policy == Some(DuplicateTags::FirstWins) => Ok(true),
+        Some(&first) => Err(Error::new(error::Kind::DuplicateTag(key, first), position)),
+        None => {
+            seen.insert(key, position);
+            Ok(false)
+        }
     }
 }
 
 impl<'a, 'de, R> MapAccess<'de> for Access<'a, R>
 where
-    R: Read,
+    R: Read<'de>,
 {
     type Error = Error;
 
@@ -27,14 +108,28 @@ where
     where
         K: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
+        let policy = self.arrange()?;
+        let (tag, mut values, key) = loop {
+            let mut tag = match self.tags.next_or_end()? {
+                Some(tag) => tag,
+                None => return Ok(None),
+            };
+            let values = tag.next()?;
+            let key = key_name(&values);
+            if !check_duplicate(&mut self.seen, policy, key.clone(), tag.origin_position())? {
+                break (tag, values, key);
+            }
         };
-        let mut values = tag.next()?;
-        tag.assert_exhausted()?;
-        let key = seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
+        self.key = key.unwrap_or_default();
+        let index = self.index;
+        let key = seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Element(index));
+                error
+            })?;
         self.values = Some(values.into_stored());
+        self.tag = Some(tag.into_stored());
 
         Ok(Some(key))
     }
@@ -43,19 +138,22 @@ where
     where
         V: DeserializeSeed<'de>,
     {
-        // SAFETY: `self.tags` is not modified here, so this `Values` will live longer than the
-        // referenced buffer.
-        let mut values = unsafe {
-            self.values
-                .take()
-                .expect("call to `next_value()` not preceeded by successful call to `next_key()`")
-                .into_values()
-        };
-
-        let value = seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
-        values.assert_exhausted()?;
-
-        Ok(value)
+        let tag = self
+            .tag
+            .take()
+            .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
+        let values = self
+            .values
+            .take()
+            .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
+
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(value::Deserializer::new(&self.key, self.tags, tag, values))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Element(index));
+                error
+            })
     }
 
     fn next_entry_seed<K, V>(
@@ -67,16 +165,36 @@ where
         K: DeserializeSeed<'de>,
         V: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
+        let policy = self.arrange()?;
+        let (tag, mut values, key) = loop {
+            let mut tag = match self.tags.next_or_end()? {
+                Some(tag) => tag,
+                None => return Ok(None),
+            };
+            let values = tag.next()?;
+            let key = key_name(&values);
+            if !check_duplicate(&mut self.seen, policy, key.clone(), tag.origin_position())? {
+                break (tag, values, key);
+            }
         };
-        let mut values = tag.next()?;
-        tag.assert_exhausted()?;
-        let key = key_seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
-
-        let value = value_seed.deserialize(tuple::element::Deserializer::new(&mut values))?;
-        values.assert_exhausted()?;
+        self.key = key.unwrap_or_default();
+        let index = self.index;
+        self.index += 1;
+        let key = key_seed
+            .deserialize(tuple::element::Deserializer::new(&mut values))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Element(index));
+                error
+            })?;
+        let tag = tag.into_stored();
+        let values = values.into_stored();
+
+        let value = value_seed
+            .deserialize(value::Deserializer::new(&self.key, self.tags, tag, values))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Element(index));
+                error
+            })?;
 
         Ok(Some((key, value)))
     }
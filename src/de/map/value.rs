@@ -0,0 +1,504 @@
+use crate::{
+    de::{
+        any,
+        parse::{StoredTag, StoredValues, Tags},
+        seq, tuple, Error, Read, Result,
+    },
+    with,
+};
+use serde::{de, de::Visitor};
+
+// Deserializes the value of a tag within a map, whose key has already been read.
+//
+// Most values are read from the remainder of the tag's only parameter list. Sequences are instead
+// read from the tag's parameter lists, as they are serialized, or from consecutive tags sharing
+// the same key, just as a sequence within a struct is.
+pub(super) struct Deserializer<'a, R> {
+    key: &'a str,
+    tags: &'a mut Tags<R>,
+
+    tag: StoredTag,
+    values: StoredValues,
+}
+
+impl<'a, R> Deserializer<'a, R> {
+    pub(super) fn new(
+        key: &'a str,
+        tags: &'a mut Tags<R>,
+        tag: StoredTag,
+        values: StoredValues,
+    ) -> Self {
+        Self {
+            key,
+            tags,
+            tag,
+            values,
+        }
+    }
+
+    // Deserializes the remaining values as a single element, requiring that nothing follows it.
+    fn deserialize_element<T, F>(self, deserialize: F) -> Result<T>
+    where
+        F: FnOnce(tuple::element::Deserializer) -> Result<T>,
+    {
+        // SAFETY: `self.tags` has not been advanced since these were stored.
+        let mut values = unsafe { self.values.into_values() };
+        let result = deserialize(tuple::element::Deserializer::new(&mut values))?;
+        values.assert_exhausted()?;
+        unsafe { self.tag.into_tag() }.assert_exhausted()?;
+        Ok(result)
+    }
+}
+
+impl<'a, 'de, R> de::Deserializer<'de> for Deserializer<'a, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+
+    // Requested for keys that a struct containing a flattened field does not recognize itself, in
+    // which case the value is buffered as described in `any::content`. Otherwise every parameter
+    // list is given, as at the root.
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let tag = unsafe { self.tag.into_tag() };
+        let values = unsafe { self.values.into_values() };
+        if any::is_buffering::<V>() {
+            any::content::Deserializer::new(tag, values).deserialize_any(visitor)
+        } else {
+            any::tag::Deserializer::new(tag, values).deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_bool(visitor))
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_i8(visitor))
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_i16(visitor))
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_i32(visitor))
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_i64(visitor))
+    }
+
+    #[cfg(has_i128)]
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_i128(visitor))
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_u8(visitor))
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_u16(visitor))
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_u32(visitor))
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_u64(visitor))
+    }
+
+    #[cfg(has_i128)]
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_u128(visitor))
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_f32(visitor))
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_f64(visitor))
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_char(visitor))
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_str(visitor))
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_string(visitor))
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_bytes(visitor))
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_byte_buf(visitor))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_option(visitor))
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_unit(visitor))
+    }
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_unit_struct(name, visitor))
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if with::separator(name).is_some() {
+            return self.deserialize_element(|deserializer| {
+                deserializer.deserialize_newtype_struct(name, visitor)
+            });
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut tag = unsafe { self.tag.into_tag() };
+        let values = unsafe { self.values.into_values() };
+        if tag.assert_exhausted().is_err() || values.assert_exhausted().is_ok() {
+            // SAFETY: `values` was created by a call to `tag.next()`.
+            unsafe { tag.revisit(values) };
+            let mut access = seq::lists::Access::new(&mut tag);
+            let result = visitor.visit_seq(&mut access)?;
+            if access.read_parameters() {
+                // There is no enclosing sequence to read any further parameter lists.
+                tag.assert_exhausted()?;
+            }
+            return Ok(result);
+        }
+
+        // The first element is this tag, so it must be read again.
+        tag.reset();
+        let stored_tag = tag.into_stored();
+        // SAFETY: `stored_tag` references the buffer still active in `self.tags`.
+        unsafe { self.tags.revisit(stored_tag) };
+        visitor.visit_seq(seq::field::Access::new(self.key, self.tags))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_tuple(len, visitor))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| {
+            deserializer.deserialize_tuple_struct(name, len, visitor)
+        })
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_map(visitor))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| {
+            deserializer.deserialize_struct(name, fields, visitor)
+        })
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| {
+            deserializer.deserialize_enum(name, variants, visitor)
+        })
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_identifier(visitor))
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_element(|deserializer| deserializer.deserialize_ignored_any(visitor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Deserializer;
+    use crate::de::{error, parse::Tags, Error, Position};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use serde::{
+        de,
+        de::{SeqAccess, Visitor},
+        Deserialize,
+    };
+    use serde_derive::Deserialize;
+    use std::fmt;
+
+    #[test]
+    fn u64() {
+        let mut tags = Tags::new(b"#foo:42;\n".as_slice());
+        let mut tag = assert_ok!(tags.next());
+        let mut values = assert_ok!(tag.next());
+        let _key = assert_ok!(values.next());
+        let stored_tag = tag.into_stored();
+        let stored_values = values.into_stored();
+        let deserializer = Deserializer::new("foo", &mut tags, stored_tag, stored_values);
+
+        assert_ok_eq!(u64::deserialize(deserializer), 42);
+    }
+
+    #[test]
+    fn u64_unexpected_value() {
+        let mut tags = Tags::new(b"#foo:42:43;\n".as_slice());
+        let mut tag = assert_ok!(tags.next());
+        let mut values = assert_ok!(tag.next());
+        let _key = assert_ok!(values.next());
+        let stored_tag = tag.into_stored();
+        let stored_values = values.into_stored();
+        let deserializer = Deserializer::new("foo", &mut tags, stored_tag, stored_values);
+
+        assert_err_eq!(
+            u64::deserialize(deserializer),
+            Error::new(error::Kind::UnexpectedValue, Position::new(0, 8))
+        );
+    }
+
+    #[test]
+    fn u64_unexpected_values() {
+        let mut tags = Tags::new(b"#foo:42;\n43;\n".as_slice());
+        let mut tag = assert_ok!(tags.next());
+        let mut values = assert_ok!(tag.next());
+        let _key = assert_ok!(values.next());
+        let stored_tag = tag.into_stored();
+        let stored_values = values.into_stored();
+        let deserializer = Deserializer::new("foo", &mut tags, stored_tag, stored_values);
+
+        assert_err_eq!(
+            u64::deserialize(deserializer),
+            Error::new(error::Kind::UnexpectedValues, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn seq() {
+        let mut tags = Tags::new(b"#foo:1;\n#foo:2;\n#bar:3;\n".as_slice());
+        let mut tag = assert_ok!(tags.next());
+        let mut values = assert_ok!(tag.next());
+        let _key = assert_ok!(values.next());
+        let stored_tag = tag.into_stored();
+        let stored_values = values.into_stored();
+        let deserializer = Deserializer::new("foo", &mut tags, stored_tag, stored_values);
+
+        assert_ok_eq!(Vec::<u64>::deserialize(deserializer), vec![1, 2]);
+        // The following tag is left for the next key.
+        let mut tag = assert_ok!(tags.next());
+        let mut values = assert_ok!(tag.next());
+        assert_ok_eq!(assert_ok!(values.next()).parse_string(), "bar");
+    }
+
+    #[test]
+    fn seq_of_lists() {
+        let mut tags = Tags::new(b"#foo:1:2;\n3:4;\n#foo:5:6;\n".as_slice());
+        let mut tag = assert_ok!(tags.next());
+        let mut values = assert_ok!(tag.next());
+        let _key = assert_ok!(values.next());
+        let stored_tag = tag.into_stored();
+        let stored_values = values.into_stored();
+        let deserializer = Deserializer::new("foo", &mut tags, stored_tag, stored_values);
+
+        assert_ok_eq!(
+            Vec::<(u64, u64)>::deserialize(deserializer),
+            vec![(1, 2), (3, 4)]
+        );
+    }
+
+    #[test]
+    fn seq_empty() {
+        let mut tags = Tags::new(b"#foo;\n#foo:1:2;\n".as_slice());
+        let mut tag = assert_ok!(tags.next());
+        let mut values = assert_ok!(tag.next());
+        let _key = assert_ok!(values.next());
+        let stored_tag = tag.into_stored();
+        let stored_values = values.into_stored();
+        let deserializer = Deserializer::new("foo", &mut tags, stored_tag, stored_values);
+
+        assert_ok_eq!(
+            Vec::<(u64, u64)>::deserialize(deserializer),
+            Vec::<(u64, u64)>::new()
+        );
+    }
+
+    #[test]
+    fn newtype_seq() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Newtype(Vec<u64>);
+
+        let mut tags = Tags::new(b"#foo:1;\n#foo:2;\n".as_slice());
+        let mut tag = assert_ok!(tags.next());
+        let mut values = assert_ok!(tag.next());
+        let _key = assert_ok!(values.next());
+        let stored_tag = tag.into_stored();
+        let stored_values = values.into_stored();
+        let deserializer = Deserializer::new("foo", &mut tags, stored_tag, stored_values);
+
+        assert_ok_eq!(Newtype::deserialize(deserializer), Newtype(vec![1, 2]));
+    }
+
+    #[test]
+    fn any() {
+        #[derive(Debug, PartialEq)]
+        struct Any(Vec<Vec<String>>);
+
+        impl<'de> Deserialize<'de> for Any {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                struct AnyVisitor;
+
+                impl<'de> Visitor<'de> for AnyVisitor {
+                    type Value = Any;
+
+                    fn expecting(&self, _f: &mut fmt::Formatter) -> fmt::Result {
+                        unimplemented!()
+                    }
+
+                    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: SeqAccess<'de>,
+                    {
+                        let mut elements = Vec::new();
+                        while let Some(element) = seq.next_element()? {
+                            elements.push(element);
+                        }
+                        Ok(Any(elements))
+                    }
+                }
+
+                deserializer.deserialize_any(AnyVisitor)
+            }
+        }
+
+        let mut tags = Tags::new(b"#foo:bar:baz;\n".as_slice());
+        let mut tag = assert_ok!(tags.next());
+        let mut values = assert_ok!(tag.next());
+        let _key = assert_ok!(values.next());
+        let stored_tag = tag.into_stored();
+        let stored_values = values.into_stored();
+        let deserializer = Deserializer::new("foo", &mut tags, stored_tag, stored_values);
+
+        assert_ok_eq!(
+            Any::deserialize(deserializer),
+            Any(vec![vec!["bar".to_owned(), "baz".to_owned()]])
+        );
+    }
+}
@@ -19,29 +19,42 @@
 //!
 //! [`Deserialize`]: serde::Deserialize
 
+pub mod raw;
+
+mod any;
+mod delimited;
 mod r#enum;
 mod error;
 mod map;
+mod options;
 mod parse;
 mod position;
+mod read;
 mod seq;
+mod spanned;
 mod r#struct;
 mod tuple;
 
-pub use error::{Error, Result};
+pub use error::{Category, Error, Kind, Limit, Result, Segment};
+pub use options::{DeserializerOptions, Dialect, DuplicateTags, Limits, UnknownTags};
+pub use position::Position;
+pub use read::{Read, SliceRead};
+pub use spanned::Spanned;
 
-use position::Position;
+use crate::Encoding;
 use serde::{
     de,
     de::{DeserializeOwned, Visitor},
     Deserialize,
 };
-use std::io::Read;
+use std::{borrow::Cow, io, ops::Range};
 
 /// Deserializes data from MSD format.
 ///
-/// `Deserializer` can be used to read from any value that implements the [`Read`] trait. The bytes
-/// will be interpreted as MSD and deserialized into a given type.
+/// `Deserializer` can be used to read from any value that implements the [`std::io::Read`] trait.
+/// The bytes will be interpreted as MSD and deserialized into a given type. When reading from a
+/// slice of bytes, [`Deserializer::from_slice()`] allows deserialized strings and bytes to borrow
+/// from the input instead of being copied.
 ///
 /// # Example
 /// ```
@@ -63,29 +76,71 @@ pub struct Deserializer<R> {
 }
 
 impl<R> Deserializer<R> {
-    pub fn new(reader: R) -> Self
+    pub fn new<'de>(reader: R) -> Self
     where
-        R: Read,
+        R: Read<'de>,
     {
         Self {
             tags: parse::Tags::new(reader),
         }
     }
+
+    /// Creates a `Deserializer` interpreting its input according to the given `options`.
+    pub fn with_options<'de>(reader: R, options: DeserializerOptions) -> Self
+    where
+        R: Read<'de>,
+    {
+        Self {
+            tags: parse::Tags::with_options(reader, options),
+        }
+    }
+}
+
+impl<'de> Deserializer<SliceRead<'de>> {
+    /// Creates a `Deserializer` reading from a slice of bytes.
+    ///
+    /// Strings and bytes that contain no escape sequences or comments are borrowed directly from
+    /// `bytes`.
+    ///
+    /// # Example
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Song<'a> {
+    ///     #[serde(rename = "TITLE")]
+    ///     title: &'a str,
+    /// }
+    ///
+    /// let mut deserializer = msd::Deserializer::from_slice(b"#TITLE:foo;\n");
+    /// let song = Song::deserialize(&mut deserializer).unwrap();
+    ///
+    /// assert_eq!(song.title, "foo");
+    /// ```
+    pub fn from_slice(bytes: &'de [u8]) -> Self {
+        Self::new(SliceRead::new(bytes))
+    }
 }
 
-impl<'de, 'a, R> de::Deserializer<'de> for &'a mut Deserializer<R>
+impl<'de, R> de::Deserializer<'de> for &mut Deserializer<R>
 where
-    R: Read,
+    R: Read<'de>,
 {
     type Error = Error;
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(self
-            .tags
-            .error_at_current_tag(error::Kind::CannotDeserializeAsSelfDescribing))
+        let access = any::root::Access::new(&mut self.tags);
+        let result = if any::is_buffering::<V>() {
+            visitor.visit_map(access.buffering())
+        } else {
+            visitor.visit_map(access)
+        }?;
+        self.tags.assert_exhausted()?;
+        Ok(result)
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
@@ -349,13 +404,17 @@ where
         let mut tag = self.tags.next()?;
         let mut values = tag.next()?;
         let value = values.next()?;
-        // Parsed string must be owned, since it removes escaping and comments.
-        let parsed = value.parse_string()?;
+        // SAFETY: `'de` is the lifetime of the input.
+        let parsed = unsafe { value.parse_str() }?;
         let value_position = value.position();
         values.assert_exhausted()?;
         tag.assert_exhausted()?;
         self.tags.assert_exhausted()?;
-        visitor.visit_str(&parsed).map_err(|mut error: Error| {
+        match parsed {
+            Cow::Borrowed(borrowed) => visitor.visit_borrowed_str(borrowed),
+            Cow::Owned(owned) => visitor.visit_str(&owned),
+        }
+        .map_err(|mut error: Error| {
             error.set_position(value_position);
             error
         })
@@ -386,13 +445,17 @@ where
         let mut tag = self.tags.next()?;
         let mut values = tag.next()?;
         let value = values.next()?;
-        // Parsed bytes must be owned, since it removes escaping and comments.
-        let parsed = value.parse_byte_buf();
+        // SAFETY: `'de` is the lifetime of the input.
+        let parsed = unsafe { value.parse_bytes() };
         let value_position = value.position();
         values.assert_exhausted()?;
         tag.assert_exhausted()?;
         self.tags.assert_exhausted()?;
-        visitor.visit_bytes(&parsed).map_err(|mut error: Error| {
+        match parsed {
+            Cow::Borrowed(borrowed) => visitor.visit_borrowed_bytes(borrowed),
+            Cow::Owned(owned) => visitor.visit_bytes(&owned),
+        }
+        .map_err(|mut error: Error| {
             error.set_position(value_position);
             error
         })
@@ -522,13 +585,13 @@ where
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let result = visitor.visit_map(r#struct::root::Access::new(&mut self.tags))?;
+        let result = visitor.visit_map(r#struct::root::Access::new(&mut self.tags, fields))?;
         self.tags.assert_exhausted()?;
         Ok(result)
     }
@@ -536,7 +599,7 @@ where
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
@@ -544,7 +607,7 @@ where
     {
         let mut tag = self.tags.next()?;
         let mut values = tag.next()?;
-        let result = visitor.visit_enum(r#enum::Access::new(&mut values))?;
+        let result = visitor.visit_enum(r#enum::Access::new(&mut values, variants))?;
         values.assert_exhausted()?;
         tag.assert_exhausted()?;
         self.tags.assert_exhausted()?;
@@ -587,7 +650,7 @@ unsafe impl<R> Sync for Deserializer<R> {}
 /// Deserialize a value of type `T` from the given `reader`.
 pub fn from_reader<R, T>(reader: R) -> Result<T>
 where
-    R: Read,
+    R: io::Read,
     T: DeserializeOwned,
 {
     let mut deserializer = Deserializer::new(reader);
@@ -595,22 +658,117 @@ where
 }
 
 /// Deserialize a value of type `T` from a slice of bytes.
+///
+/// Strings and bytes that contain no escape sequences or comments are borrowed directly from
+/// `bytes`.
 pub fn from_bytes<'a, T>(bytes: &'a [u8]) -> Result<T>
 where
     T: Deserialize<'a>,
 {
-    let mut deserializer = Deserializer::new(bytes);
+    let mut deserializer = Deserializer::from_slice(bytes);
+    T::deserialize(&mut deserializer)
+}
+
+// Deserializes a value from input that was read in its entirety ahead of time, such as from an
+// asynchronous reader.
+//
+// If reading was cut short by an `error`, it is reported exactly as if it had been encountered by
+// a blocking reader directly after the last byte read.
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub(crate) fn from_read_ahead<T>(
+    bytes: &[u8],
+    error: Option<io::Error>,
+    options: DeserializerOptions,
+) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    struct Failed(Option<io::Error>);
+
+    impl io::Read for Failed {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            self.0.take().map_or(Ok(0), Err)
+        }
+    }
+
+    let mut deserializer =
+        Deserializer::with_options(io::Read::chain(bytes, Failed(error)), options);
     T::deserialize(&mut deserializer)
 }
 
+// Returns how many bytes need to be read ahead of time to deserialize according to `options`.
+//
+// Two bytes beyond the total bytes limit are needed, since a `#` directly following the limit is
+// not counted against it, in which case the byte after it is the first to exceed the limit.
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub(crate) fn read_ahead_limit(options: &DeserializerOptions) -> Option<usize> {
+    options
+        .get_limits()
+        .get_max_total_bytes()
+        .map(|max| max.saturating_add(2))
+}
+
+// Removes comments and interprets escape sequences, exactly as is done when deserializing a
+// value's bytes in the given `encoding` and `dialect`.
+pub(crate) fn clean(bytes: &[u8], encoding: Encoding, dialect: Dialect) -> Vec<u8> {
+    let value = parse::Value::new(bytes, Position::new(0, 0)).with_encoding(encoding);
+    if dialect.recognizes_comments() {
+        value.parse_byte_buf()
+    } else {
+        value.without_comments().parse_byte_buf()
+    }
+}
+
+// Locates the part of a raw value that `clean()` would keep, leaving out the whitespace and
+// comments surrounding it. An empty range at the start is returned if there is no such part.
+pub(crate) fn content_range(bytes: &[u8], encoding: Encoding, dialect: Dialect) -> Range<usize> {
+    let comments = dialect.recognizes_comments();
+    let mut content: Option<Range<usize>> = None;
+    let mut index = 0;
+    while let Some(&byte) = bytes.get(index) {
+        let width = match byte {
+            b'/' if comments && bytes.get(index + 1) == Some(&b'/') => {
+                // The comment lasts until the end of the line.
+                index = bytes[index..]
+                    .iter()
+                    .position(|&byte| byte == b'\n')
+                    .map_or(bytes.len(), |offset| index + offset);
+                continue;
+            }
+            b'\\' => match bytes.get(index + 1) {
+                Some(&next) if encoding.is_lead_byte(next) => 3,
+                _ => 2,
+            },
+            byte if encoding.is_lead_byte(byte) => 2,
+            _ => 1,
+        };
+        let end = (index + width).min(bytes.len());
+        if !byte.is_ascii_whitespace() {
+            content = Some(content.map_or(index, |content| content.start)..end);
+        }
+        index = end;
+    }
+    content.unwrap_or(0..0)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{error, Deserializer, Error, Position};
-    use claims::{assert_err_eq, assert_ok_eq};
+    use super::{
+        error, from_bytes, Category, Deserializer, DeserializerOptions, Dialect, DuplicateTags,
+        Error, Limit, Limits, Position, Segment, SliceRead, Spanned, UnknownTags,
+    };
+    use crate::{
+        value::{Tag, Value},
+        Encoding,
+    };
+    use claims::{assert_err, assert_err_eq, assert_ok, assert_ok_eq};
     use serde::{de, de::Visitor, Deserialize};
     use serde_bytes::ByteBuf;
     use serde_derive::Deserialize;
-    use std::{collections::HashMap, fmt};
+    use std::{
+        collections::{BTreeMap, HashMap},
+        fmt,
+    };
 
     #[test]
     fn bool_true() {
@@ -1872,6 +2030,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn str_borrowed() {
+        let mut deserializer = Deserializer::from_slice(b"#foo;");
+
+        assert_ok_eq!(<&str>::deserialize(&mut deserializer), "foo");
+    }
+
+    #[test]
+    fn str_borrowed_escaped() {
+        let mut deserializer = Deserializer::from_slice(b"#f\\:oo;");
+
+        // Escaped strings cannot be borrowed.
+        assert_err_eq!(
+            <&str>::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::InvalidType(
+                    "string \"f:oo\"".to_owned(),
+                    "a borrowed string".to_owned()
+                ),
+                Position::new(0, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn str_borrowed_comment() {
+        let mut deserializer = Deserializer::from_slice(b"#foo// comment\n;");
+
+        // Strings containing comments cannot be borrowed.
+        assert_err_eq!(
+            <&str>::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::InvalidType(
+                    "string \"foo\\n\"".to_owned(),
+                    "a borrowed string".to_owned()
+                ),
+                Position::new(0, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn str_borrowed_invalid() {
+        let mut deserializer = Deserializer::from_slice(b"#\xF0\x9Ffoo;\n");
+
+        assert_err_eq!(
+            <&str>::deserialize(&mut deserializer),
+            Error::new(error::Kind::ExpectedString, Position::new(0, 1)),
+        );
+    }
+
+    #[test]
+    fn str_from_reader_not_borrowed() {
+        let mut deserializer = Deserializer::new(b"#foo;".as_slice());
+
+        assert_err_eq!(
+            <&str>::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::InvalidType(
+                    "string \"foo\"".to_owned(),
+                    "a borrowed string".to_owned()
+                ),
+                Position::new(0, 1)
+            )
+        );
+    }
+
     #[test]
     fn string() {
         let mut deserializer = Deserializer::new(b"#foo;".as_slice());
@@ -2076,6 +2301,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bytes_borrowed() {
+        let mut deserializer = Deserializer::from_slice(b"#foo;");
+
+        assert_ok_eq!(<&[u8]>::deserialize(&mut deserializer), b"foo");
+    }
+
     #[test]
     fn byte_buf() {
         let mut deserializer = Deserializer::new(b"#foo;".as_slice());
@@ -2937,40 +3169,47 @@ mod tests {
     }
 
     #[test]
-    fn any() {
-        #[derive(Debug)]
-        struct Any;
-
-        impl<'de> Deserialize<'de> for Any {
-            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-            where
-                D: de::Deserializer<'de>,
-            {
-                struct AnyVisitor;
-
-                impl<'de> Visitor<'de> for AnyVisitor {
-                    type Value = Any;
+    fn struct_borrowed() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Struct<'a> {
+            foo: &'a str,
+            bar: Vec<&'a str>,
+            baz: (&'a str, u64),
+        }
 
-                    fn expecting(&self, _f: &mut fmt::Formatter) -> fmt::Result {
-                        unimplemented!()
-                    }
-                }
+        let mut deserializer =
+            Deserializer::from_slice(b"#foo:qux;\n#bar:a;\n#bar:b;\n#baz:c:1;\n");
 
-                deserializer.deserialize_any(AnyVisitor)
+        assert_ok_eq!(
+            Struct::deserialize(&mut deserializer),
+            Struct {
+                foo: "qux",
+                bar: vec!["a", "b"],
+                baz: ("c", 1),
             }
-        }
+        );
+    }
 
-        let mut deserializer = Deserializer::new(b"#foo;".as_slice());
+    #[test]
+    fn any() {
+        let mut deserializer = Deserializer::new(b"#foo:bar;\n#baz;\n1:2;\n".as_slice());
 
-        assert_err_eq!(
-            Any::deserialize(&mut deserializer),
-            Error::new(
-                error::Kind::CannotDeserializeAsSelfDescribing,
-                Position::new(0, 1)
-            )
+        assert_ok_eq!(
+            Value::deserialize(&mut deserializer),
+            Value::from(vec![
+                Tag::new("foo", vec![vec!["bar".to_owned()]]),
+                Tag::new("baz", vec![vec![], vec!["1".to_owned(), "2".to_owned()]]),
+            ])
         );
     }
 
+    #[test]
+    fn any_empty() {
+        let mut deserializer = Deserializer::new(b"".as_slice());
+
+        assert_ok_eq!(Value::deserialize(&mut deserializer), Value::new());
+    }
+
     #[test]
     fn ignored_any() {
         #[derive(Debug)]
@@ -3005,4 +3244,2185 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn error_location() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Chart {
+            title: String,
+            offset: f64,
+        }
+
+        let error = assert_err!(crate::from_bytes::<Chart>(
+            b"#title:foo;\n// comment\n#offset:bar;\n"
+        ));
+
+        assert_eq!(error.line(), 2);
+        assert_eq!(error.column(), 8);
+        assert_eq!(error.offset(), 31);
+        assert_eq!(error.kind(), &error::Kind::ExpectedF64);
+        assert_eq!(error.category(), Category::Data);
+    }
+
+    #[test]
+    fn error_path_struct_field() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Chart {
+            title: String,
+            offset: f64,
+        }
+
+        let error = assert_err!(crate::from_bytes::<Chart>(b"#title:foo;\n#offset:bar;\n"));
+
+        assert_eq!(error.path(), &[Segment::Tag("offset".to_owned())]);
+        assert_eq!(
+            format!("{}", error),
+            "in tag #offset: expected f64 at line 1 column 8"
+        );
+    }
+
+    #[test]
+    fn error_path_struct_seq_field() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Chart {
+            bpms: Vec<(f64, f64)>,
+        }
+
+        let error = assert_err!(crate::from_bytes::<Chart>(
+            b"#bpms:0.000:120.000;\n#bpms:1.000:foo;\n"
+        ));
+
+        assert_eq!(
+            error.path(),
+            &[
+                Segment::Tag("bpms".to_owned()),
+                Segment::Element(1),
+                Segment::Value(1)
+            ]
+        );
+    }
+
+    #[test]
+    fn error_path_seq() {
+        let error = assert_err!(crate::from_bytes::<Vec<u8>>(b"#1;\n#2;\n#foo;\n"));
+
+        assert_eq!(error.path(), &[Segment::Element(2)]);
+    }
+
+    #[test]
+    fn error_path_map() {
+        let error = assert_err!(crate::from_bytes::<HashMap<String, u8>>(
+            b"#foo:1;\n#bar:baz;\n"
+        ));
+
+        assert_eq!(error.path(), &[Segment::Element(1)]);
+    }
+
+    #[test]
+    fn error_path_enum() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        enum Kind {
+            Foo(u8, u8),
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Chart {
+            kind: Kind,
+        }
+
+        let error = assert_err!(crate::from_bytes::<Chart>(b"#kind:Foo:1:bar;\n"));
+
+        assert_eq!(
+            error.path(),
+            &[
+                Segment::Tag("kind".to_owned()),
+                Segment::Variant("Foo".to_owned()),
+                Segment::Value(1)
+            ]
+        );
+    }
+
+    #[test]
+    fn error_path_syntax() {
+        let error = assert_err!(crate::from_bytes::<Vec<u8>>(b"foo#1;\n"));
+
+        assert_eq!(error.path(), &[]);
+    }
+
+    #[test]
+    fn io_error_source() {
+        struct FailingReader;
+
+        impl std::io::Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    "denied",
+                ))
+            }
+        }
+
+        let error = assert_err!(crate::from_reader::<_, u8>(FailingReader));
+
+        assert_eq!(
+            error.kind(),
+            &error::Kind::Io(std::io::ErrorKind::PermissionDenied)
+        );
+        assert_eq!(error.category(), Category::Io);
+        assert_eq!(
+            std::error::Error::source(&error).unwrap().to_string(),
+            "denied"
+        );
+    }
+
+    #[test]
+    fn dialect_strict() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:bar#;\n".as_slice(),
+            DeserializerOptions::new().dialect(Dialect::Strict),
+        );
+
+        assert_err_eq!(
+            <(String, String)>::deserialize(&mut deserializer),
+            Error::new(error::Kind::UnescapedNumberSign, Position::new(0, 8))
+        );
+    }
+
+    #[test]
+    fn dialect_strict_missing_final_terminator() {
+        let input = b"#A:b;\n#C:d";
+        let options = DeserializerOptions::new().dialect(Dialect::Strict);
+        let expected = Error::new(error::Kind::MissingTagTerminator, Position::new(1, 4));
+
+        assert_err_eq!(
+            HashMap::<String, String>::deserialize(&mut Deserializer::with_options(
+                input.as_slice(),
+                options.clone()
+            )),
+            expected.clone()
+        );
+        assert_err_eq!(
+            Vec::<(String, String)>::deserialize(&mut Deserializer::with_options(
+                input.as_slice(),
+                options.clone()
+            )),
+            expected.clone()
+        );
+        assert_err_eq!(
+            Value::deserialize(&mut Deserializer::with_options(input.as_slice(), options)),
+            expected
+        );
+    }
+
+    #[test]
+    fn dialect_strict_missing_final_terminator_struct() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Song {
+            #[serde(rename = "A")]
+            a: String,
+            #[serde(rename = "C")]
+            c: Option<String>,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#A:b;\n#C:d".as_slice(),
+            DeserializerOptions::new().dialect(Dialect::Strict),
+        );
+
+        assert_err_eq!(
+            Song::deserialize(&mut deserializer),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(1, 4))
+        );
+    }
+
+    // Returns the given input, then fails with a broken pipe on every following read.
+    struct BrokenPipeReader(&'static [u8]);
+
+    impl std::io::Read for BrokenPipeReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "broken pipe",
+                ))
+            } else {
+                std::io::Read::read(&mut self.0, buf)
+            }
+        }
+    }
+
+    #[test]
+    fn io_error_after_first_read_map() {
+        let error = assert_err!(crate::from_reader::<_, HashMap<String, String>>(
+            BrokenPipeReader(b"#A:b;\n#C:d;\n")
+        ));
+
+        assert_eq!(
+            error.kind(),
+            &error::Kind::Io(std::io::ErrorKind::BrokenPipe)
+        );
+    }
+
+    #[test]
+    fn io_error_after_first_read_seq() {
+        let error = assert_err!(crate::from_reader::<_, Vec<(String, String)>>(
+            BrokenPipeReader(b"#A:b;\n#C:d;\n")
+        ));
+
+        assert_eq!(
+            error.kind(),
+            &error::Kind::Io(std::io::ErrorKind::BrokenPipe)
+        );
+    }
+
+    #[test]
+    fn io_error_after_first_read_struct() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Song {
+            #[serde(rename = "A")]
+            a: String,
+            #[serde(rename = "C")]
+            c: Option<String>,
+        }
+
+        let error = assert_err!(crate::from_reader::<_, Song>(BrokenPipeReader(
+            b"#A:b;\n#C:d;\n"
+        )));
+
+        assert_eq!(
+            error.kind(),
+            &error::Kind::Io(std::io::ErrorKind::BrokenPipe)
+        );
+    }
+
+    #[test]
+    fn io_error_after_first_read_value() {
+        let error = assert_err!(crate::from_reader::<_, Value>(BrokenPipeReader(
+            b"#A:b;\n#C:d;\n"
+        )));
+
+        assert_eq!(
+            error.kind(),
+            &error::Kind::Io(std::io::ErrorKind::BrokenPipe)
+        );
+    }
+
+    #[test]
+    fn empty_as_none_marker_outside_option() {
+        let mut deserializer = Deserializer::with_options(
+            b"#a:\\0b:\\0;\n".as_slice(),
+            DeserializerOptions::new().empty_as_none(true),
+        );
+
+        assert_ok_eq!(
+            <(String, String, Option<String>)>::deserialize(&mut deserializer),
+            ("a".to_owned(), "\\0b".to_owned(), Some(String::new()))
+        );
+    }
+
+    #[test]
+    fn trim_strings() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Chart {
+            #[serde(rename = "TITLE")]
+            title: String,
+            #[serde(rename = "NOTES")]
+            notes: (String, Vec<String>),
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#TITLE: foo ;\n#NOTES:\n   dance-single:\n0000\n1000\n,\n0100\n;\n".as_slice(),
+            DeserializerOptions::new().trim_strings(true),
+        );
+
+        assert_ok_eq!(
+            Chart::deserialize(&mut deserializer),
+            Chart {
+                title: "foo".to_owned(),
+                notes: (
+                    "dance-single".to_owned(),
+                    vec!["0000\n1000".to_owned(), "0100".to_owned()]
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn dialect_stepmania() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:bar#// comment\n".as_slice(),
+            DeserializerOptions::new().dialect(Dialect::StepMania),
+        );
+
+        assert_ok_eq!(
+            <(String, String)>::deserialize(&mut deserializer),
+            ("foo".to_owned(), "bar#".to_owned())
+        );
+    }
+
+    #[test]
+    fn dialect_dwi() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            #[serde(rename = "TITLE")]
+            title: String,
+            #[serde(rename = "FILE")]
+            file: String,
+        }
+
+        let mut deserializer = Deserializer::with_options(
+            b"garbage\n#TITLE:foo;\n#FILE:songs//bar.mp3\n".as_slice(),
+            DeserializerOptions::new().dialect(Dialect::Dwi),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "foo".to_owned(),
+                file: "songs//bar.mp3".to_owned(),
+            }
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Song {
+        #[serde(rename = "TITLE")]
+        title: String,
+        #[serde(rename = "ARTIST")]
+        artist: String,
+    }
+
+    #[test]
+    fn encoding_shift_jis() {
+        let mut deserializer = Deserializer::with_options(
+            b"#TITLE:\x93\x8c\x95\xfb;\n#ARTIST:\x83\x5c\x83\x93;\n".as_slice(),
+            DeserializerOptions::new().encoding(Encoding::ShiftJis),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "東方".to_owned(),
+                artist: "ソン".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn encoding_shift_jis_trailing_backslash() {
+        let mut deserializer = Deserializer::with_options(
+            b"#TITLE:\x83\x5c;\n#ARTIST:\x95\x5c\\;;\n".as_slice(),
+            DeserializerOptions::new().encoding(Encoding::ShiftJis),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "ソ".to_owned(),
+                artist: "表;".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn encoding_detected_shift_jis_trailing_backslash() {
+        let mut deserializer = Deserializer::with_options(
+            b"#TITLE:\x93\x8c\x95\xfb\x97\x64\x81\x58\x96\xb2\x83\x5c;\n#ARTIST:ZUN;\n".as_slice(),
+            DeserializerOptions::new().detect_encoding(),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "東方妖々夢ソ".to_owned(),
+                artist: "ZUN".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn encoding_utf8_rejects_shift_jis() {
+        let mut deserializer = Deserializer::new(b"#TITLE:\x93\x8c\x95\xfb;\n".as_slice());
+
+        assert_eq!(
+            assert_err!(Song::deserialize(&mut deserializer)).kind(),
+            &error::Kind::ExpectedString
+        );
+    }
+
+    #[test]
+    fn encoding_detected_shift_jis() {
+        let mut deserializer = Deserializer::with_options(
+            b"#TITLE:\x93\x8c\x95\xfb\x97\x64\x81\x58\x96\xb2;\n#ARTIST:ZUN;\n".as_slice(),
+            DeserializerOptions::new().detect_encoding(),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "東方妖々夢".to_owned(),
+                artist: "ZUN".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn encoding_detected_windows_1252() {
+        let mut deserializer = Deserializer::with_options(
+            b"#TITLE:Caf\xe9 del Mar;\n#ARTIST:Ni\xf1o;\n".as_slice(),
+            DeserializerOptions::new().detect_encoding(),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "Café del Mar".to_owned(),
+                artist: "Niño".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn encoding_detected_utf8() {
+        let mut deserializer = Deserializer::with_options(
+            "#TITLE:東方妖々夢;\n#ARTIST:ZUN;\n".as_bytes(),
+            DeserializerOptions::new().detect_encoding(),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "東方妖々夢".to_owned(),
+                artist: "ZUN".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn case_sensitive_by_default() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            title: String,
+        }
+        let mut deserializer = Deserializer::new(b"#TITLE:foo;\n".as_slice());
+
+        assert_err!(Song::deserialize(&mut deserializer));
+    }
+
+    #[test]
+    fn case_insensitive_struct() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            title: String,
+            sample_start: f64,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#TITLE:foo;\n#SAMPLE_START:12.5;\n".as_slice(),
+            DeserializerOptions::new().case_insensitive(true),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "foo".to_owned(),
+                sample_start: 12.5,
+            }
+        );
+    }
+
+    #[test]
+    fn case_insensitive_prefers_exact_match() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Fields {
+            foo: u64,
+            #[serde(rename = "FOO")]
+            upper_foo: u64,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#FOO:1;\n#foo:2;\n".as_slice(),
+            DeserializerOptions::new().case_insensitive(true),
+        );
+
+        assert_ok_eq!(
+            Fields::deserialize(&mut deserializer),
+            Fields {
+                foo: 2,
+                upper_foo: 1
+            }
+        );
+    }
+
+    #[test]
+    fn case_insensitive_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Difficulty {
+            Beginner,
+            Challenge,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Chart {
+            difficulty: Difficulty,
+            notes: Vec<(Difficulty, u64)>,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#DIFFICULTY:BEGINNER;\n#NOTES:challenge:10;\n#NOTES:beginner:1;\n".as_slice(),
+            DeserializerOptions::new().case_insensitive(true),
+        );
+
+        assert_ok_eq!(
+            Chart::deserialize(&mut deserializer),
+            Chart {
+                difficulty: Difficulty::Beginner,
+                notes: vec![(Difficulty::Challenge, 10), (Difficulty::Beginner, 1)],
+            }
+        );
+    }
+
+    #[test]
+    fn case_insensitive_root_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum Unit {
+            Variant,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#VARIANT;\n".as_slice(),
+            DeserializerOptions::new().case_insensitive(true),
+        );
+
+        assert_ok_eq!(Unit::deserialize(&mut deserializer), Unit::Variant);
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Duplicates {
+        foo: u64,
+        bar: Vec<u64>,
+    }
+
+    #[test]
+    fn duplicate_tags_default() {
+        let mut deserializer = Deserializer::new(b"#foo:1;\n#bar:2;\n#foo:3;\n".as_slice());
+
+        assert_err!(Duplicates::deserialize(&mut deserializer));
+    }
+
+    #[test]
+    fn duplicate_tags_error() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#bar:2;\n#foo:3;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::Error),
+        );
+
+        assert_err_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::DuplicateTag("foo".to_owned(), Position::new(0, 0)),
+                Position::new(2, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_error_sequence_field() {
+        let mut deserializer = Deserializer::with_options(
+            b"#bar:1;\n#bar:2;\n#foo:3;\n#bar:4;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::Error),
+        );
+
+        assert_err_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::DuplicateTag("bar".to_owned(), Position::new(0, 0)),
+                Position::new(3, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_first_wins() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#bar:2;\n#bar:3;\n#foo:4;\n#bar:5;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::FirstWins),
+        );
+
+        assert_ok_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Duplicates {
+                foo: 1,
+                bar: vec![2, 3],
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_last_wins() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#bar:2;\n#bar:3;\n#foo:4;\n#bar:5;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::LastWins),
+        );
+
+        assert_ok_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Duplicates {
+                foo: 4,
+                bar: vec![5],
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_collect() {
+        let mut deserializer = Deserializer::with_options(
+            b"#bar:1;\n#foo:2;\n#bar:3;\n#bar:4;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::Collect),
+        );
+
+        assert_ok_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Duplicates {
+                foo: 2,
+                bar: vec![1, 3, 4],
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_collect_non_sequence_field() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#bar:2;\n#foo:3;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::Collect),
+        );
+
+        assert_err_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::DuplicateTag("foo".to_owned(), Position::new(0, 0)),
+                Position::new(2, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_default_adjacent() {
+        let mut deserializer = Deserializer::new(b"#foo:1;\n#foo:2;\n#bar:3;\n".as_slice());
+
+        assert_err!(Duplicates::deserialize(&mut deserializer));
+    }
+
+    #[test]
+    fn duplicate_tags_error_adjacent() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#foo:2;\n#bar:3;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::Error),
+        );
+
+        assert_err_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::DuplicateTag("foo".to_owned(), Position::new(0, 0)),
+                Position::new(1, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_first_wins_adjacent() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#foo:2;\n#bar:3;\n#bar:4;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::FirstWins),
+        );
+
+        assert_ok_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Duplicates {
+                foo: 1,
+                bar: vec![3, 4],
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_last_wins_adjacent() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#foo:2;\n#bar:3;\n#bar:4;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::LastWins),
+        );
+
+        assert_ok_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Duplicates {
+                foo: 2,
+                bar: vec![3, 4],
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_last_wins_adjacent_and_separate() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#foo:2;\n#bar:3;\n#foo:4;\n#foo:5;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::LastWins),
+        );
+
+        assert_ok_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Duplicates {
+                foo: 5,
+                bar: vec![3],
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_last_wins_adjacent_case_insensitive() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            title: String,
+            offset: f64,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#title:a;\n#TITLE:b;\n#offset:1;\n".as_slice(),
+            DeserializerOptions::new()
+                .duplicate_tags(DuplicateTags::LastWins)
+                .case_insensitive(true),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "b".to_owned(),
+                offset: 1.0,
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_collect_adjacent_non_sequence_field() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#foo:2;\n#bar:3;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::Collect),
+        );
+
+        assert_err_eq!(
+            Duplicates::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::DuplicateTag("foo".to_owned(), Position::new(0, 0)),
+                Position::new(1, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_last_wins_borrowed() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#bar:2;\n#foo:4;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::LastWins),
+        );
+        let mut slice_deserializer = Deserializer::with_options(
+            SliceRead::new(b"#foo:1;\n#bar:2;\n#foo:4;\n"),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::LastWins),
+        );
+
+        let expected = Duplicates {
+            foo: 4,
+            bar: vec![2],
+        };
+        assert_ok_eq!(Duplicates::deserialize(&mut deserializer), expected);
+        assert_ok_eq!(Duplicates::deserialize(&mut slice_deserializer), expected);
+    }
+
+    // Preserves the order in which map entries are visited.
+    #[derive(Debug, PartialEq)]
+    struct Entries(Vec<(String, u64)>);
+
+    impl<'de> Deserialize<'de> for Entries {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct EntriesVisitor;
+
+            impl<'de> Visitor<'de> for EntriesVisitor {
+                type Value = Entries;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("map")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: de::MapAccess<'de>,
+                {
+                    let mut entries = Vec::new();
+                    while let Some(entry) = map.next_entry()? {
+                        entries.push(entry);
+                    }
+                    Ok(Entries(entries))
+                }
+            }
+
+            deserializer.deserialize_map(EntriesVisitor)
+        }
+    }
+
+    #[test]
+    fn duplicate_tags_map_error() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#bar:2;\n#foo:3;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::Error),
+        );
+
+        assert_err_eq!(
+            HashMap::<String, u64>::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::DuplicateTag("foo".to_owned(), Position::new(0, 0)),
+                Position::new(2, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_map_first_wins() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#bar:2;\n#foo:3;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::FirstWins),
+        );
+
+        assert_ok_eq!(
+            Entries::deserialize(&mut deserializer),
+            Entries(vec![("foo".to_owned(), 1), ("bar".to_owned(), 2)])
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_map_last_wins() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#bar:2;\n#foo:3;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::LastWins),
+        );
+
+        assert_ok_eq!(
+            Entries::deserialize(&mut deserializer),
+            Entries(vec![("bar".to_owned(), 2), ("foo".to_owned(), 3)])
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_map_last_wins_adjacent() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#foo:3;\n#bar:2;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::LastWins),
+        );
+
+        assert_ok_eq!(
+            Entries::deserialize(&mut deserializer),
+            Entries(vec![("foo".to_owned(), 3), ("bar".to_owned(), 2)])
+        );
+    }
+
+    #[test]
+    fn duplicate_tags_map_collect() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#bar:2;\n#foo:3;\n".as_slice(),
+            DeserializerOptions::new().duplicate_tags(DuplicateTags::Collect),
+        );
+
+        assert_ok_eq!(
+            Entries::deserialize(&mut deserializer),
+            Entries(vec![
+                ("foo".to_owned(), 1),
+                ("bar".to_owned(), 2),
+                ("foo".to_owned(), 3)
+            ])
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Known {
+        foo: u64,
+        bar: u64,
+    }
+
+    #[test]
+    fn unknown_tags_default() {
+        let mut deserializer = Deserializer::new(b"#foo:1;\n#baz:2;\n#bar:3;\n".as_slice());
+
+        assert_err!(Known::deserialize(&mut deserializer));
+    }
+
+    #[test]
+    fn unknown_tags_skip() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#baz:2;\n#bar:3;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Skip),
+        );
+
+        assert_ok_eq!(
+            Known::deserialize(&mut deserializer),
+            Known { foo: 1, bar: 3 }
+        );
+    }
+
+    #[test]
+    fn unknown_tags_error() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n#baz:2;\n#bar:3;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Error),
+        );
+
+        assert_err_eq!(
+            Known::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::UnknownField("baz".to_owned(), &["foo", "bar"]),
+                Position::new(1, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn unknown_tags_capture() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Captured {
+            foo: u64,
+            extras: BTreeMap<String, Vec<String>>,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#baz:2:3;\n#foo:1;\n#qux;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Capture("extras")),
+        );
+
+        let mut extras = BTreeMap::new();
+        extras.insert("baz".to_owned(), vec!["2".to_owned(), "3".to_owned()]);
+        extras.insert("qux".to_owned(), vec![]);
+        assert_ok_eq!(
+            Captured::deserialize(&mut deserializer),
+            Captured { foo: 1, extras }
+        );
+    }
+
+    #[test]
+    fn unknown_tags_capture_every_occurrence() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Captured {
+            foo: u64,
+            extras: Vec<(String, Vec<String>)>,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#baz:2;\n#foo:1;\n#baz:3;4;\n#extras:5;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Capture("extras")),
+        );
+
+        assert_ok_eq!(
+            Captured::deserialize(&mut deserializer),
+            Captured {
+                foo: 1,
+                extras: vec![
+                    ("baz".to_owned(), vec!["2".to_owned()]),
+                    ("baz".to_owned(), vec!["3".to_owned(), "4".to_owned()]),
+                    ("extras".to_owned(), vec!["5".to_owned()]),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_tags_capture_none() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Captured {
+            foo: u64,
+            extras: BTreeMap<String, Vec<String>>,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Capture("extras")),
+        );
+
+        assert_ok_eq!(
+            Captured::deserialize(&mut deserializer),
+            Captured {
+                foo: 1,
+                extras: BTreeMap::new()
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_tags_default_in_sequence() {
+        let mut deserializer =
+            Deserializer::new(b"#;\n#foo:1;\n#bar:3;\n#baz:2;\n#;\n#foo:4;\n#bar:5;\n".as_slice());
+
+        let error = assert_err!(Vec::<Known>::deserialize(&mut deserializer));
+        assert_eq!(error.kind(), &error::Kind::ExpectedUnit);
+        assert_eq!(error.path(), &[Segment::Element(1)]);
+    }
+
+    #[test]
+    fn unknown_tags_skip_in_sequence() {
+        let mut deserializer = Deserializer::with_options(
+            b"#;\n#foo:1;\n#baz:2;\n#bar:3;\n#;\n#foo:4;\n#bar:5;\n#baz:6;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Skip),
+        );
+
+        assert_ok_eq!(
+            Vec::<Known>::deserialize(&mut deserializer),
+            vec![Known { foo: 1, bar: 3 }, Known { foo: 4, bar: 5 }]
+        );
+    }
+
+    #[test]
+    fn unknown_tags_error_in_sequence() {
+        let mut deserializer = Deserializer::with_options(
+            b"#;\n#foo:1;\n#bar:3;\n#;\n#foo:4;\n#baz:2;\n#bar:5;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Error),
+        );
+
+        let error = assert_err!(Vec::<Known>::deserialize(&mut deserializer));
+        assert_eq!(
+            error.kind(),
+            &error::Kind::UnknownField("baz".to_owned(), &["foo", "bar"])
+        );
+        assert_eq!(error.position(), Position::new(5, 0));
+        assert_eq!(error.path(), &[Segment::Element(1)]);
+    }
+
+    #[test]
+    fn unknown_tags_capture_in_sequence() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Captured {
+            foo: u64,
+            extras: BTreeMap<String, Vec<String>>,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#;\n#foo:1;\n#baz:2;\n#;\n#foo:3;\n#qux:4;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Capture("extras")),
+        );
+
+        let mut first = BTreeMap::new();
+        first.insert("baz".to_owned(), vec!["2".to_owned()]);
+        let mut second = BTreeMap::new();
+        second.insert("qux".to_owned(), vec!["4".to_owned()]);
+        assert_ok_eq!(
+            Vec::<Captured>::deserialize(&mut deserializer),
+            vec![
+                Captured {
+                    foo: 1,
+                    extras: first
+                },
+                Captured {
+                    foo: 3,
+                    extras: second
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn limits_within() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:bar;\n#baz:qux;\n".as_slice(),
+            DeserializerOptions::new().limits(
+                Limits::new()
+                    .max_tag_size(9)
+                    .max_tags(2)
+                    .max_parameters(2)
+                    .max_total_bytes(20)
+                    .max_string_length(3),
+            ),
+        );
+
+        assert_ok_eq!(
+            Vec::<(String, String)>::deserialize(&mut deserializer),
+            vec![
+                ("foo".to_owned(), "bar".to_owned()),
+                ("baz".to_owned(), "qux".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn limits_max_parameters() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:1:2:3:4;\n".as_slice(),
+            DeserializerOptions::new().limits(Limits::new().max_parameters(3)),
+        );
+
+        assert_err_eq!(
+            HashMap::<String, Vec<u64>>::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::Parameters),
+                Position::new(0, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn limits_max_string_length() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Song {
+            title: String,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#title:a very long title;\n".as_slice(),
+            DeserializerOptions::new().limits(Limits::new().max_string_length(8)),
+        );
+
+        let error = assert_err!(Song::deserialize(&mut deserializer));
+        assert_eq!(
+            error.kind(),
+            &error::Kind::LimitExceeded(Limit::StringLength)
+        );
+        assert_eq!(error.position(), Position::new(0, 7));
+    }
+
+    #[test]
+    fn limits_max_total_bytes() {
+        let mut deserializer = Deserializer::with_options(
+            b"#foo:bar;\n#baz:qux;\n".as_slice(),
+            DeserializerOptions::new().limits(Limits::new().max_total_bytes(15)),
+        );
+
+        assert_err_eq!(
+            Vec::<(String, String)>::deserialize(&mut deserializer),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TotalBytes),
+                Position::new(1, 5)
+            )
+        );
+    }
+
+    #[test]
+    fn spanned_struct_field() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            title: Spanned<String>,
+            bpm: Spanned<u64>,
+        }
+        let mut deserializer = Deserializer::new(b"#title:foo;\n#bpm:120;\n".as_slice());
+
+        let song = assert_ok!(Song::deserialize(&mut deserializer));
+        assert_eq!(song.title.get_ref(), "foo");
+        assert_eq!(song.title.start(), Position::new(0, 0));
+        assert_eq!(song.title.end(), Position::new(0, 11));
+        assert_eq!(song.bpm.get_ref(), &120);
+        assert_eq!(song.bpm.start(), Position::new(1, 0));
+        assert_eq!(song.bpm.start().offset(), 12);
+        assert_eq!(song.bpm.end(), Position::new(1, 9));
+        assert_eq!(song.bpm.end().offset(), 21);
+    }
+
+    #[test]
+    fn spanned_struct_field_trailing_comment() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            offsets: Spanned<(u64, u64)>,
+        }
+        let mut deserializer = Deserializer::new(b"#offsets:1:\n2; // end\n".as_slice());
+
+        let song = assert_ok!(Song::deserialize(&mut deserializer));
+        assert_eq!(song.offsets.get_ref(), &(1, 2));
+        assert_eq!(song.offsets.start(), Position::new(0, 0));
+        assert_eq!(song.offsets.end(), Position::new(1, 2));
+    }
+
+    #[test]
+    fn spanned_struct_field_repeated_tags() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            bpms: Spanned<Vec<(f64, f64)>>,
+            title: String,
+        }
+        let mut deserializer =
+            Deserializer::new(b"#bpms:0:120;\n#bpms:32:140;\n#title:foo;\n".as_slice());
+
+        let song = assert_ok!(Song::deserialize(&mut deserializer));
+        assert_eq!(song.bpms.get_ref(), &vec![(0.0, 120.0), (32.0, 140.0)]);
+        assert_eq!(song.bpms.start(), Position::new(0, 0));
+        assert_eq!(song.bpms.end(), Position::new(1, 13));
+        assert_eq!(song.title, "foo");
+    }
+
+    #[test]
+    fn spanned_struct_field_repeated_tags_at_end_of_input() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            bpms: Spanned<Vec<Spanned<(f64, f64)>>>,
+        }
+        let mut deserializer =
+            Deserializer::new(b"#bpms:0:120;\n#bpms:32:140;\n#bpms:64:160;\n".as_slice());
+
+        let song = assert_ok!(Song::deserialize(&mut deserializer));
+        assert_eq!(song.bpms.get_ref().len(), 3);
+        assert_eq!(song.bpms.start(), Position::new(0, 0));
+        assert_eq!(song.bpms.end(), Position::new(2, 13));
+        assert_eq!(song.bpms.get_ref()[1].start(), Position::new(1, 0));
+        assert_eq!(song.bpms.get_ref()[1].end(), Position::new(1, 13));
+    }
+
+    #[test]
+    fn spanned_nested_struct_field() {
+        #[derive(Debug, Deserialize)]
+        struct Chart {
+            difficulty: Spanned<String>,
+        }
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            chart: Vec<Chart>,
+        }
+        let mut deserializer = Deserializer::new(
+            b"#chart:;\n#difficulty:easy;\n#chart:;\n#difficulty:hard;\n".as_slice(),
+        );
+
+        let song = assert_ok!(Song::deserialize(&mut deserializer));
+        assert_eq!(song.chart[0].difficulty.get_ref(), "easy");
+        assert_eq!(song.chart[0].difficulty.start(), Position::new(1, 0));
+        assert_eq!(song.chart[1].difficulty.get_ref(), "hard");
+        assert_eq!(song.chart[1].difficulty.start(), Position::new(3, 0));
+        assert_eq!(song.chart[1].difficulty.end(), Position::new(3, 17));
+    }
+
+    #[test]
+    fn spanned_sequence_elements() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            bpm: Vec<Spanned<u64>>,
+        }
+        let mut deserializer = Deserializer::new(b"#bpm:120;\n#bpm:150;\n".as_slice());
+
+        let song = assert_ok!(Song::deserialize(&mut deserializer));
+        assert_eq!(song.bpm.len(), 2);
+        assert_eq!(song.bpm[0].get_ref(), &120);
+        assert_eq!(song.bpm[0].start(), Position::new(0, 0));
+        assert_eq!(song.bpm[0].end(), Position::new(0, 9));
+        assert_eq!(song.bpm[1].get_ref(), &150);
+        assert_eq!(song.bpm[1].start(), Position::new(1, 0));
+        assert_eq!(song.bpm[1].end(), Position::new(1, 9));
+    }
+
+    #[test]
+    fn spanned_tuple_elements() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            offsets: (Spanned<u64>, Spanned<String>),
+        }
+        let mut deserializer = Deserializer::new(b"#offsets:12:foo;\n".as_slice());
+
+        let song = assert_ok!(Song::deserialize(&mut deserializer));
+        assert_eq!(song.offsets.0.get_ref(), &12);
+        assert_eq!(song.offsets.0.start(), Position::new(0, 9));
+        assert_eq!(song.offsets.0.end(), Position::new(0, 11));
+        assert_eq!(song.offsets.1.get_ref(), "foo");
+        assert_eq!(song.offsets.1.start(), Position::new(0, 12));
+        assert_eq!(song.offsets.1.end(), Position::new(0, 15));
+    }
+
+    #[test]
+    fn spanned_map_values() {
+        let mut deserializer = Deserializer::new(b"#foo:1;\n#bar:23;\n".as_slice());
+
+        let map = assert_ok!(BTreeMap::<String, Spanned<u64>>::deserialize(
+            &mut deserializer
+        ));
+        let bar = &map["bar"];
+        assert_eq!(bar.get_ref(), &23);
+        assert_eq!(bar.start(), Position::new(1, 5));
+        assert_eq!(bar.end(), Position::new(1, 7));
+    }
+
+    #[test]
+    fn spanned_invalid_value() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            #[allow(dead_code)]
+            bpm: Spanned<u64>,
+        }
+        let mut deserializer = Deserializer::new(b"#bpm:foo;\n".as_slice());
+
+        let error = assert_err!(Song::deserialize(&mut deserializer));
+        assert_eq!(error.kind(), &error::Kind::ExpectedU64);
+        assert_eq!(error.position(), Position::new(0, 5));
+    }
+
+    #[test]
+    fn struct_containing_nested_seq() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            title: String,
+            bgchanges: Vec<Vec<(f64, String)>>,
+        }
+        let mut deserializer = Deserializer::new(
+            b"#title:foo;\n#bgchanges:0.0:bar;\n4.0:baz;\n#bgchanges:8.0:qux;\n".as_slice(),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "foo".to_owned(),
+                bgchanges: vec![
+                    vec![(0.0, "bar".to_owned()), (4.0, "baz".to_owned())],
+                    vec![(8.0, "qux".to_owned())],
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn struct_containing_seq_of_parameter_lists() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Struct {
+            x: Vec<Vec<String>>,
+            y: String,
+        }
+        let mut deserializer = Deserializer::new(b"#x:a:b;\nc;\n#y:d;\n".as_slice());
+
+        assert_ok_eq!(
+            Struct::deserialize(&mut deserializer),
+            Struct {
+                x: vec![vec!["a".to_owned(), "b".to_owned()], vec!["c".to_owned()]],
+                y: "d".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn struct_containing_seq_of_parameter_lists_across_tags() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Struct {
+            x: Vec<Vec<String>>,
+        }
+        let mut deserializer = Deserializer::new(b"#x:a:b;\nc;\n#x:d;\n".as_slice());
+
+        assert_ok_eq!(
+            Struct::deserialize(&mut deserializer),
+            Struct {
+                x: vec![
+                    vec!["a".to_owned(), "b".to_owned()],
+                    vec!["c".to_owned()],
+                    vec!["d".to_owned()]
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn seq_of_parameter_lists() {
+        let mut deserializer = Deserializer::new(b"#1:2;\n3;\n".as_slice());
+
+        assert_ok_eq!(
+            Vec::<Vec<u32>>::deserialize(&mut deserializer),
+            vec![vec![1, 2], vec![3]]
+        );
+    }
+
+    #[test]
+    fn seq_of_parameter_lists_invalid_parameter() {
+        let mut deserializer = Deserializer::new(b"#1:2;\n3:foo;\n".as_slice());
+
+        let error = assert_err!(Vec::<Vec<u32>>::deserialize(&mut deserializer));
+        assert_eq!(error.kind(), &error::Kind::ExpectedU32);
+        assert_eq!(error.path(), &[Segment::Element(1), Segment::Value(1)]);
+    }
+
+    #[test]
+    fn map_containing_parameters_followed_by_parameter_lists() {
+        let mut deserializer = Deserializer::new(b"#x:a:b;\nc;\n".as_slice());
+
+        assert_eq!(
+            assert_err!(HashMap::<String, Vec<String>>::deserialize(
+                &mut deserializer
+            ))
+            .kind(),
+            &error::Kind::UnexpectedValues
+        );
+    }
+
+    #[test]
+    fn struct_containing_nested_seq_error_path() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            #[allow(dead_code)]
+            bgchanges: Vec<Vec<(f64, String)>>,
+        }
+        let mut deserializer = Deserializer::new(b"#bgchanges:0.0:bar;\nfoo:baz;\n".as_slice());
+
+        let error = assert_err!(Song::deserialize(&mut deserializer));
+        assert_eq!(error.kind(), &error::Kind::ExpectedF64);
+        assert_eq!(
+            error.path(),
+            &[
+                Segment::Tag("bgchanges".to_owned()),
+                Segment::Element(0),
+                Segment::Element(1),
+                Segment::Value(0)
+            ]
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Timing {
+        offset: f64,
+        bpms: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TimedSong {
+        title: String,
+        timing: Timing,
+        artist: String,
+    }
+
+    #[test]
+    fn nested_structs() {
+        let mut deserializer = Deserializer::with_options(
+            b"#TITLE:foo;\n#TIMING.OFFSET:-0.5;\n#TIMING.BPMS:0=120;\n#TIMING.BPMS:4=60;\n#ARTIST:bar;\n"
+                .as_slice(),
+            DeserializerOptions::new()
+                .case_insensitive(true)
+                .nested_structs('.'),
+        );
+
+        assert_ok_eq!(
+            TimedSong::deserialize(&mut deserializer),
+            TimedSong {
+                title: "foo".to_owned(),
+                timing: Timing {
+                    offset: -0.5,
+                    bpms: vec!["0=120".to_owned(), "4=60".to_owned()],
+                },
+                artist: "bar".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn nested_structs_disabled() {
+        let mut deserializer =
+            Deserializer::new(b"#title:foo;\n#timing:-0.5;\n#artist:bar;\n".as_slice());
+
+        let error = assert_err!(TimedSong::deserialize(&mut deserializer));
+        assert_eq!(error.kind(), &error::Kind::CannotDeserializeNestedStruct);
+        assert_eq!(error.path(), &[Segment::Tag("timing".to_owned())]);
+    }
+
+    #[test]
+    fn nested_structs_separator() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            timing_mode: String,
+            timing: Timing,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#timing_mode:foo;\n#timing_offset:1;\n#timing_bpms:0=120;\n".as_slice(),
+            DeserializerOptions::new().nested_structs('_'),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                timing_mode: "foo".to_owned(),
+                timing: Timing {
+                    offset: 1.0,
+                    bpms: vec!["0=120".to_owned()],
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn nested_structs_deeply() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inner {
+            foo: u64,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Middle {
+            inner: Inner,
+            bar: u64,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Outer {
+            middle: Middle,
+            baz: u64,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#middle.inner.foo:1;\n#middle.bar:2;\n#baz:3;\n".as_slice(),
+            DeserializerOptions::new().nested_structs('.'),
+        );
+
+        assert_ok_eq!(
+            Outer::deserialize(&mut deserializer),
+            Outer {
+                middle: Middle {
+                    inner: Inner { foo: 1 },
+                    bar: 2,
+                },
+                baz: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn nested_structs_in_sequence() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Chart {
+            name: String,
+            timing: Timing,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#;\n#name:foo;\n#timing.offset:1;\n#timing.bpms:0=120;\n#;\n#name:bar;\n#timing.offset:2;\n#timing.bpms:0=60;\n"
+                .as_slice(),
+            DeserializerOptions::new().nested_structs('.'),
+        );
+
+        assert_ok_eq!(
+            Vec::<Chart>::deserialize(&mut deserializer),
+            vec![
+                Chart {
+                    name: "foo".to_owned(),
+                    timing: Timing {
+                        offset: 1.0,
+                        bpms: vec!["0=120".to_owned()],
+                    },
+                },
+                Chart {
+                    name: "bar".to_owned(),
+                    timing: Timing {
+                        offset: 2.0,
+                        bpms: vec!["0=60".to_owned()],
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_structs_unknown_tags_skip() {
+        let mut deserializer = Deserializer::with_options(
+            b"#title:foo;\n#timing.offset:1;\n#timing.stops:;\n#timing.bpms:0=120;\n#artist:bar;\n"
+                .as_slice(),
+            DeserializerOptions::new()
+                .unknown_tags(UnknownTags::Skip)
+                .nested_structs('.'),
+        );
+
+        assert_ok_eq!(
+            TimedSong::deserialize(&mut deserializer),
+            TimedSong {
+                title: "foo".to_owned(),
+                timing: Timing {
+                    offset: 1.0,
+                    bpms: vec!["0=120".to_owned()],
+                },
+                artist: "bar".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn nested_structs_unknown_tags_error() {
+        let mut deserializer = Deserializer::with_options(
+            b"#title:foo;\n#timing.offset:1;\n#timing.stops:;\n".as_slice(),
+            DeserializerOptions::new()
+                .unknown_tags(UnknownTags::Error)
+                .nested_structs('.'),
+        );
+
+        let error = assert_err!(TimedSong::deserialize(&mut deserializer));
+        assert_eq!(
+            error.kind(),
+            &error::Kind::UnknownField("stops".to_owned(), &["offset", "bpms"])
+        );
+        assert_eq!(error.path(), &[Segment::Tag("timing".to_owned())]);
+    }
+
+    #[test]
+    fn nested_structs_error_path() {
+        let mut deserializer = Deserializer::with_options(
+            b"#title:foo;\n#timing.offset:bar;\n".as_slice(),
+            DeserializerOptions::new().nested_structs('.'),
+        );
+
+        let error = assert_err!(TimedSong::deserialize(&mut deserializer));
+        assert_eq!(
+            error.path(),
+            &[
+                Segment::Tag("timing".to_owned()),
+                Segment::Tag("offset".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_structs_not_contiguous() {
+        let mut deserializer = Deserializer::with_options(
+            b"#timing.offset:1;\n#title:foo;\n#timing.bpms:0=120;\n#artist:bar;\n".as_slice(),
+            DeserializerOptions::new().nested_structs('.'),
+        );
+
+        assert_ok_eq!(
+            TimedSong::deserialize(&mut deserializer),
+            TimedSong {
+                title: "foo".to_owned(),
+                timing: Timing {
+                    offset: 1.0,
+                    bpms: vec!["0=120".to_owned()],
+                },
+                artist: "bar".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn nested_structs_not_contiguous_seq() {
+        let mut deserializer = Deserializer::with_options(
+            b"#timing.bpms:0=120;\n#title:foo;\n#timing.offset:1;\n#artist:bar;\n#timing.bpms:4=60;\n"
+                .as_slice(),
+            DeserializerOptions::new().nested_structs('.'),
+        );
+
+        assert_ok_eq!(
+            TimedSong::deserialize(&mut deserializer),
+            TimedSong {
+                title: "foo".to_owned(),
+                timing: Timing {
+                    offset: 1.0,
+                    bpms: vec!["0=120".to_owned(), "4=60".to_owned()],
+                },
+                artist: "bar".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn nested_structs_not_contiguous_deeply() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inner {
+            foo: u64,
+            qux: u64,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Middle {
+            inner: Inner,
+            bar: u64,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Outer {
+            middle: Middle,
+            baz: u64,
+        }
+        let mut deserializer = Deserializer::with_options(
+            b"#MIDDLE.INNER.FOO:1;\n#baz:3;\n#middle.bar:2;\n#middle.inner.qux:4;\n".as_slice(),
+            DeserializerOptions::new()
+                .case_insensitive(true)
+                .nested_structs('.'),
+        );
+
+        assert_ok_eq!(
+            Outer::deserialize(&mut deserializer),
+            Outer {
+                middle: Middle {
+                    inner: Inner { foo: 1, qux: 4 },
+                    bar: 2,
+                },
+                baz: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn nested_structs_not_contiguous_last_wins() {
+        let mut deserializer = Deserializer::with_options(
+            b"#timing.offset:1;\n#title:foo;\n#timing.bpms:0=120;\n#artist:bar;\n".as_slice(),
+            DeserializerOptions::new()
+                .duplicate_tags(DuplicateTags::LastWins)
+                .nested_structs('.'),
+        );
+
+        assert_ok_eq!(
+            TimedSong::deserialize(&mut deserializer),
+            TimedSong {
+                title: "foo".to_owned(),
+                timing: Timing {
+                    offset: 1.0,
+                    bpms: vec!["0=120".to_owned()],
+                },
+                artist: "bar".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn nested_structs_missing_field_position() {
+        let mut deserializer = Deserializer::with_options(
+            b"#title:foo;\n#artist:bar;\n#timing.offset:1;\n".as_slice(),
+            DeserializerOptions::new().nested_structs('.'),
+        );
+
+        let error = assert_err!(TimedSong::deserialize(&mut deserializer));
+        assert_eq!(error.kind(), &error::Kind::MissingField("bpms"));
+        assert_eq!(error.position(), Position::from_parts(2, 0, 24));
+        assert_eq!(error.path(), &[Segment::Tag("timing".to_owned())]);
+    }
+
+    #[test]
+    fn nested_structs_not_contiguous_collect() {
+        let mut deserializer = Deserializer::with_options(
+            b"#timing.offset:1;\n#title:foo;\n#timing.bpms:0=120;\n#artist:bar;\n".as_slice(),
+            DeserializerOptions::new()
+                .duplicate_tags(DuplicateTags::Collect)
+                .nested_structs('.'),
+        );
+
+        assert_ok_eq!(
+            TimedSong::deserialize(&mut deserializer),
+            TimedSong {
+                title: "foo".to_owned(),
+                timing: Timing {
+                    offset: 1.0,
+                    bpms: vec!["0=120".to_owned()],
+                },
+                artist: "bar".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn map_seq_value() {
+        let mut deserializer = Deserializer::new(b"#foo:1;\n#foo:2;\n#bar:3;\n".as_slice());
+
+        let mut expected = HashMap::new();
+        expected.insert("foo".to_owned(), vec![1, 2]);
+        expected.insert("bar".to_owned(), vec![3]);
+        assert_ok_eq!(
+            HashMap::<String, Vec<u64>>::deserialize(&mut deserializer),
+            expected
+        );
+    }
+
+    #[test]
+    fn map_seq_value_key_whitespace() {
+        let mut expected = BTreeMap::new();
+        expected.insert("a ".to_owned(), vec!["x".to_owned(), "y".to_owned()]);
+        assert_ok_eq!(
+            from_bytes::<BTreeMap<String, Vec<String>>>(b"#a :x;\n#a:y;\n"),
+            expected
+        );
+
+        let mut expected = BTreeMap::new();
+        expected.insert(" a".to_owned(), vec!["x".to_owned()]);
+        assert_ok_eq!(
+            from_bytes::<BTreeMap<String, Vec<String>>>(b"# a:x;"),
+            expected
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "UPPERCASE")]
+    struct Header {
+        title: String,
+        subtitle: Option<String>,
+        artist: String,
+    }
+
+    #[test]
+    fn flatten() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "UPPERCASE")]
+        struct Song {
+            #[serde(flatten)]
+            header: Header,
+            offset: f64,
+            bpms: Vec<(f64, f64)>,
+        }
+        let mut deserializer = Deserializer::new(
+            b"#TITLE:foo;\n#OFFSET:-0.5;\n#ARTIST:bar;\n#BPMS:0:120;\n#BPMS:4:60;\n".as_slice(),
+        );
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                header: Header {
+                    title: "foo".to_owned(),
+                    subtitle: None,
+                    artist: "bar".to_owned(),
+                },
+                offset: -0.5,
+                bpms: vec![(0.0, 120.0), (4.0, 60.0)],
+            }
+        );
+    }
+
+    #[test]
+    fn flatten_shared_header() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Sm {
+            #[serde(flatten)]
+            header: Header,
+            #[serde(rename = "SELECTABLE")]
+            selectable: String,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Dwi {
+            #[serde(flatten)]
+            header: Header,
+            #[serde(rename = "GAP")]
+            gap: i64,
+        }
+
+        assert_ok_eq!(
+            Sm::deserialize(&mut Deserializer::new(
+                b"#TITLE:foo;\n#SUBTITLE:baz;\n#ARTIST:bar;\n#SELECTABLE:YES;\n".as_slice()
+            )),
+            Sm {
+                header: Header {
+                    title: "foo".to_owned(),
+                    subtitle: Some("baz".to_owned()),
+                    artist: "bar".to_owned(),
+                },
+                selectable: "YES".to_owned(),
+            }
+        );
+        assert_ok_eq!(
+            Dwi::deserialize(&mut Deserializer::new(
+                b"#TITLE:foo;\n#SUBTITLE:baz;\n#ARTIST:bar;\n#GAP:-100;\n".as_slice()
+            )),
+            Dwi {
+                header: Header {
+                    title: "foo".to_owned(),
+                    subtitle: Some("baz".to_owned()),
+                    artist: "bar".to_owned(),
+                },
+                gap: -100,
+            }
+        );
+    }
+
+    #[test]
+    fn flatten_empty_tag_as_none() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            #[serde(flatten)]
+            header: Header,
+        }
+        let mut deserializer =
+            Deserializer::new(b"#TITLE:foo;\n#SUBTITLE;\n#ARTIST:bar;\n".as_slice());
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                header: Header {
+                    title: "foo".to_owned(),
+                    subtitle: None,
+                    artist: "bar".to_owned(),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn flatten_multiple_parameters() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inner {
+            range: (String, String),
+            lists: Vec<Vec<String>>,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Outer {
+            #[serde(flatten)]
+            inner: Inner,
+        }
+        let mut deserializer = Deserializer::new(b"#range:1:2;\n#lists:a:b;\nc;\n".as_slice());
+
+        assert_ok_eq!(
+            Outer::deserialize(&mut deserializer),
+            Outer {
+                inner: Inner {
+                    range: ("1".to_owned(), "2".to_owned()),
+                    lists: vec![vec!["a".to_owned(), "b".to_owned()], vec!["c".to_owned()]],
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn flatten_map() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            #[serde(rename = "TITLE")]
+            title: String,
+            #[serde(flatten)]
+            other: BTreeMap<String, String>,
+        }
+        let mut deserializer =
+            Deserializer::new(b"#TITLE:foo;\n#ARTIST:bar;\n#GENRE:baz;\n".as_slice());
+
+        let mut other = BTreeMap::new();
+        other.insert("ARTIST".to_owned(), "bar".to_owned());
+        other.insert("GENRE".to_owned(), "baz".to_owned());
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "foo".to_owned(),
+                other,
+            }
+        );
+    }
+
+    #[test]
+    fn flatten_map_multiple_parameters() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            #[serde(flatten)]
+            other: BTreeMap<String, serde_json::Value>,
+        }
+        let mut deserializer = Deserializer::new(b"#a:b:c;\n#d:e\\:f;\n#g:1;\n".as_slice());
+
+        let mut other = BTreeMap::new();
+        other.insert("a".to_owned(), serde_json::json!(["b", "c"]));
+        other.insert("d".to_owned(), serde_json::json!("e:f"));
+        other.insert("g".to_owned(), serde_json::json!(1));
+        assert_ok_eq!(Song::deserialize(&mut deserializer), Song { other });
+    }
+
+    #[test]
+    fn flatten_map_multiple_parameters_as_string() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            #[serde(flatten)]
+            #[allow(dead_code)]
+            other: BTreeMap<String, String>,
+        }
+        let mut deserializer = Deserializer::new(b"#a:b:c;\n".as_slice());
+
+        let error = assert_err!(Song::deserialize(&mut deserializer));
+        assert_eq!(
+            error.kind(),
+            &error::Kind::InvalidType("sequence".to_owned(), "a string".to_owned())
+        );
+    }
+
+    #[test]
+    fn flatten_non_string_fields() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inner {
+            offset: f64,
+            gap: i64,
+            selectable: bool,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Outer {
+            #[serde(flatten)]
+            inner: Inner,
+        }
+        let mut deserializer =
+            Deserializer::new(b"#offset:1.5;\n#gap:-100;\n#selectable:true;\n".as_slice());
+
+        assert_ok_eq!(
+            Outer::deserialize(&mut deserializer),
+            Outer {
+                inner: Inner {
+                    offset: 1.5,
+                    gap: -100,
+                    selectable: true,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn flatten_integer_as_float() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Inner {
+            offset: f64,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Outer {
+            #[serde(flatten)]
+            inner: Inner,
+        }
+        let mut deserializer = Deserializer::new(b"#offset:2;\n".as_slice());
+
+        assert_ok_eq!(
+            Outer::deserialize(&mut deserializer),
+            Outer {
+                inner: Inner { offset: 2.0 },
+            }
+        );
+    }
+
+    #[test]
+    fn flatten_number_as_string() {
+        #[derive(Debug, Deserialize)]
+        struct Inner {
+            #[allow(dead_code)]
+            version: String,
+        }
+        #[derive(Debug, Deserialize)]
+        struct Outer {
+            #[serde(flatten)]
+            #[allow(dead_code)]
+            inner: Inner,
+        }
+        let mut deserializer = Deserializer::new(b"#version:0.83;\n".as_slice());
+
+        let error = assert_err!(Outer::deserialize(&mut deserializer));
+        assert_eq!(
+            error.kind(),
+            &error::Kind::InvalidType("floating point `0.83`".to_owned(), "a string".to_owned())
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(tag = "TYPE")]
+    enum Chart {
+        #[serde(rename = "single")]
+        Single { meter: u32 },
+        #[serde(rename = "double")]
+        Double { meter: u32, style: String },
+    }
+
+    #[test]
+    fn flatten_internally_tagged_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            title: String,
+            #[serde(flatten)]
+            chart: Chart,
+        }
+        let mut deserializer =
+            Deserializer::new(b"#title:foo;\n#style:pad;\n#TYPE:double;\n#meter:9;\n".as_slice());
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "foo".to_owned(),
+                chart: Chart::Double {
+                    meter: 9,
+                    style: "pad".to_owned(),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn flatten_internally_tagged_enum_unknown_variant() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            #[serde(flatten)]
+            #[allow(dead_code)]
+            chart: Chart,
+        }
+        let mut deserializer = Deserializer::new(b"#TYPE:triple;\n#meter:9;\n".as_slice());
+
+        let error = assert_err!(Song::deserialize(&mut deserializer));
+        assert_eq!(
+            error.kind(),
+            &error::Kind::UnknownVariant("triple".to_owned(), &["single", "double"])
+        );
+    }
+
+    #[test]
+    fn flatten_untagged_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(untagged)]
+        enum Timing {
+            Bpms { bpms: String },
+            Display { displaybpm: u32 },
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            title: String,
+            #[serde(flatten)]
+            timing: Timing,
+        }
+        let mut deserializer = Deserializer::new(b"#title:foo;\n#displaybpm:120;\n".as_slice());
+
+        assert_ok_eq!(
+            Song::deserialize(&mut deserializer),
+            Song {
+                title: "foo".to_owned(),
+                timing: Timing::Display { displaybpm: 120 },
+            }
+        );
+    }
+
+    #[test]
+    fn internally_tagged_enum_field() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            #[allow(dead_code)]
+            chart: Chart,
+        }
+        let mut deserializer = Deserializer::new(b"#chart:single;\n".as_slice());
+
+        let error = assert_err!(Song::deserialize(&mut deserializer));
+        assert_eq!(error.path(), &[Segment::Tag("chart".to_owned())]);
+    }
+
+    #[test]
+    fn root_internally_tagged_enum() {
+        let mut deserializer =
+            Deserializer::new(b"#TYPE:double;\n#meter:9;\n#style:pad;\n".as_slice());
+
+        assert_ok_eq!(
+            Chart::deserialize(&mut deserializer),
+            Chart::Double {
+                meter: 9,
+                style: "pad".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn root_untagged_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(untagged)]
+        enum Timing {
+            Bpms { bpms: String },
+            Display { displaybpm: u32 },
+        }
+        let mut deserializer = Deserializer::new(b"#bpms:0=120,4=60;\n".as_slice());
+
+        assert_ok_eq!(
+            Timing::deserialize(&mut deserializer),
+            Timing::Bpms {
+                bpms: "0=120,4=60".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn any_keeps_parameter_lists() {
+        let mut deserializer = Deserializer::new(b"#A:1:2;\n#A:3;\n#B;\n".as_slice());
+
+        assert_ok_eq!(
+            serde_json::Value::deserialize(&mut deserializer),
+            serde_json::json!({"A": [["3"]], "B": [[]]})
+        );
+    }
+
+    #[test]
+    fn seq_of_untagged_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(untagged)]
+        enum Parameter {
+            Number(u32),
+            Text(String),
+            List(Vec<String>),
+        }
+        let mut deserializer = Deserializer::new(b"#5;\n#foo;\n#bar:baz;\n".as_slice());
+
+        assert_ok_eq!(
+            Vec::<Parameter>::deserialize(&mut deserializer),
+            vec![
+                Parameter::Number(5),
+                Parameter::Text("foo".to_owned()),
+                Parameter::List(vec!["bar".to_owned(), "baz".to_owned()]),
+            ]
+        );
+    }
 }
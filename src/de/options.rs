@@ -0,0 +1,763 @@
+use crate::Encoding;
+
+/// The variant of the MSD format to accept when deserializing.
+///
+/// MSD has never been formally specified, and the programs that read it disagree on how to handle
+/// malformed input. Each dialect matches the behavior of a family of readers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Dialect {
+    /// Only well-formed MSD is accepted.
+    ///
+    /// Every tag must be terminated by a `;`, and every `#` within a tag must be escaped. Only
+    /// whitespace and `//` comments are allowed before the first tag.
+    Strict,
+    /// Matches the leniency of StepMania's reader. This is the default.
+    ///
+    /// A `#` at the start of a line begins a new tag, even if the previous tag was not terminated
+    /// by a `;`. A `#` anywhere else within a tag is treated as part of the tag's contents. Only
+    /// whitespace and `//` comments are allowed before the first tag.
+    StepMania,
+    /// Matches the leniency of Dance With Intensity's reader.
+    ///
+    /// Missing `;` terminators and unescaped `#` are handled the same way as
+    /// [`StepMania`](Dialect::StepMania). Anything before the first tag is ignored, and `//` does
+    /// not begin a comment.
+    Dwi,
+}
+
+impl Dialect {
+    // Whether a `#` at the start of a line ends a tag that is missing its terminating `;`.
+    pub(in crate::de) fn recovers_missing_terminator(self) -> bool {
+        !matches!(self, Dialect::Strict)
+    }
+
+    // Whether a `#` that does not begin a new tag is treated as part of the current tag.
+    pub(in crate::de) fn allows_unescaped_number_sign(self) -> bool {
+        !matches!(self, Dialect::Strict)
+    }
+
+    // Whether bytes other than whitespace and comments are ignored before the first tag.
+    pub(in crate::de) fn skips_leading_garbage(self) -> bool {
+        matches!(self, Dialect::Dwi)
+    }
+
+    // Whether `//` begins a comment that lasts until the end of the line.
+    pub(in crate::de) fn recognizes_comments(self) -> bool {
+        !matches!(self, Dialect::Dwi)
+    }
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Dialect::StepMania
+    }
+}
+
+/// How tags that occur more than once within a struct or map are handled.
+///
+/// Consecutive tags with the same name are considered a single occurrence, since that is how a
+/// struct field containing a sequence is written. A struct field that is not a sequence is read from
+/// the first of them under [`FirstWins`](DuplicateTags::FirstWins) and from the last of them under
+/// [`LastWins`](DuplicateTags::LastWins), and otherwise results in an error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DuplicateTags {
+    /// Return a [`DuplicateTag`] error reporting the positions of both occurrences.
+    ///
+    /// [`DuplicateTag`]: crate::de::Kind::DuplicateTag
+    Error,
+    /// Keep the first occurrence, ignoring the rest.
+    FirstWins,
+    /// Keep the last occurrence, ignoring the rest.
+    ///
+    /// The remaining input must be read ahead of time to find the last occurrence.
+    LastWins,
+    /// Combine every occurrence of a struct field into one, so that a field containing a sequence
+    /// receives all of them. Every occurrence of a map key is passed to the map.
+    ///
+    /// The remaining input must be read ahead of time to find every occurrence.
+    Collect,
+}
+
+/// How tags that do not correspond to a field of the struct being deserialized are handled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UnknownTags {
+    /// Ignore unknown tags.
+    Skip,
+    /// Return an [`UnknownField`] error containing the tag's name.
+    ///
+    /// [`UnknownField`]: crate::de::Kind::UnknownField
+    Error,
+    /// Capture unknown tags into the struct field with the given name.
+    ///
+    /// The field is deserialized as a map from each unknown tag's name to its values, such as a
+    /// `BTreeMap<String, Vec<String>>`. Each occurrence of an unknown tag is a separate entry, so
+    /// a type such as `Vec<(String, Vec<String>)>` can be used to keep every occurrence in order.
+    ///
+    /// A tag with the same name as the field is also captured.
+    Capture(&'static str),
+}
+
+/// Limits on the input accepted by a [`Deserializer`], for use with untrusted input.
+///
+/// Every limit is unset by default. Exceeding a limit results in a
+/// [`LimitExceeded`](crate::de::Kind::LimitExceeded) error at the position where it was exceeded.
+///
+/// # Example
+/// ```
+/// use msd::de::{DeserializerOptions, Kind, Limit, Limits};
+/// use serde::Deserialize;
+///
+/// let mut deserializer = msd::Deserializer::with_options(
+///     b"#foo:bar;\n#baz:qux;\n".as_slice(),
+///     DeserializerOptions::new().limits(Limits::new().max_tags(1)),
+/// );
+///
+/// let error = Vec::<(String, String)>::deserialize(&mut deserializer).unwrap_err();
+/// assert_eq!(error.kind(), &Kind::LimitExceeded(Limit::Tags));
+/// ```
+///
+/// [`Deserializer`]: crate::Deserializer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Limits {
+    tag_size: Option<usize>,
+    tags: Option<usize>,
+    parameters: Option<usize>,
+    total_bytes: Option<usize>,
+    string_length: Option<usize>,
+}
+
+impl Limits {
+    /// Creates a new set of limits, none of which are set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum size of a single tag in bytes, not including its leading `#`.
+    ///
+    /// This bounds the memory used to buffer a tag when reading from an [`io::Read`].
+    ///
+    /// [`io::Read`]: std::io::Read
+    pub fn max_tag_size(mut self, max: usize) -> Self {
+        self.tag_size = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of tags.
+    pub fn max_tags(mut self, max: usize) -> Self {
+        self.tags = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of parameters within a single tag, across all of its parameter
+    /// lists. The tag's name counts as its first parameter.
+    pub fn max_parameters(mut self, max: usize) -> Self {
+        self.parameters = Some(max);
+        self
+    }
+
+    /// Sets the maximum total size of the input in bytes.
+    pub fn max_total_bytes(mut self, max: usize) -> Self {
+        self.total_bytes = Some(max);
+        self
+    }
+
+    /// Sets the maximum length of a single parameter in bytes, as it appears in the input.
+    pub fn max_string_length(mut self, max: usize) -> Self {
+        self.string_length = Some(max);
+        self
+    }
+
+    pub(in crate::de) fn get_max_tag_size(&self) -> Option<usize> {
+        self.tag_size
+    }
+
+    pub(in crate::de) fn get_max_tags(&self) -> Option<usize> {
+        self.tags
+    }
+
+    pub(in crate::de) fn get_max_parameters(&self) -> Option<usize> {
+        self.parameters
+    }
+
+    pub(in crate::de) fn get_max_total_bytes(&self) -> Option<usize> {
+        self.total_bytes
+    }
+
+    pub(in crate::de) fn get_max_string_length(&self) -> Option<usize> {
+        self.string_length
+    }
+}
+
+/// Options controlling how a [`Deserializer`] interprets its input.
+///
+/// # Example
+/// ```
+/// use msd::de::{Dialect, DeserializerOptions};
+/// use serde::Deserialize;
+///
+/// let options = DeserializerOptions::new().dialect(Dialect::Strict);
+/// let mut deserializer =
+///     msd::Deserializer::with_options(b"#foo:bar\n#baz;\n".as_slice(), options);
+///
+/// assert!(Vec::<(String, String)>::deserialize(&mut deserializer).is_err());
+/// ```
+///
+/// [`Deserializer`]: crate::Deserializer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeserializerOptions {
+    dialect: Dialect,
+    // `None` indicates the encoding should be detected from the input.
+    encoding: Option<Encoding>,
+    case_insensitive: bool,
+    // `None` indicates duplicates are passed to the type being deserialized.
+    duplicate_tags: Option<DuplicateTags>,
+    // `None` indicates unknown tags are passed to the type being deserialized, or end a struct
+    // nested within a sequence.
+    unknown_tags: Option<UnknownTags>,
+    limits: Limits,
+    empty_as_none: bool,
+    // `None` indicates nested structs are not supported.
+    nested_structs: Option<char>,
+    trim_strings: bool,
+}
+
+impl DeserializerOptions {
+    /// Creates a new set of options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`Dialect`] of MSD to accept.
+    ///
+    /// Defaults to [`Dialect::StepMania`].
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Sets the [`Encoding`] of the text within the input.
+    ///
+    /// Defaults to [`Encoding::Utf8`].
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Detects the [`Encoding`] of the text within the input.
+    ///
+    /// The encoding is guessed from the first tag containing non-ASCII bytes, and is then used for
+    /// the rest of the input. This overrides any encoding set using
+    /// [`encoding()`](DeserializerOptions::encoding()).
+    pub fn detect_encoding(mut self) -> Self {
+        self.encoding = None;
+        self
+    }
+
+    /// Sets whether struct fields and enum variants are matched regardless of ASCII case.
+    ///
+    /// This allows tags such as `#TITLE` to be deserialized into a field named `title` without
+    /// renaming it. A name exactly matching a field or variant is always preferred. Defaults to
+    /// `false`.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::de::DeserializerOptions;
+    /// use serde::Deserialize;
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Song {
+    ///     title: String,
+    ///     sample_start: f64,
+    /// }
+    ///
+    /// let mut deserializer = msd::Deserializer::with_options(
+    ///     b"#TITLE:foo;\n#SAMPLE_START:12.5;\n".as_slice(),
+    ///     DeserializerOptions::new().case_insensitive(true),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     Song::deserialize(&mut deserializer).unwrap(),
+    ///     Song {
+    ///         title: "foo".to_owned(),
+    ///         sample_start: 12.5,
+    ///     }
+    /// );
+    /// ```
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Sets how [`DuplicateTags`] within a struct or map are handled.
+    ///
+    /// By default, every tag is passed to the type being deserialized. Structs deriving
+    /// `Deserialize` return an error on a duplicate field, while maps keep the last occurrence of
+    /// each key.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::de::{DeserializerOptions, DuplicateTags};
+    /// use serde::Deserialize;
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Song {
+    ///     #[serde(rename = "TITLE")]
+    ///     title: String,
+    ///     #[serde(rename = "ARTIST")]
+    ///     artist: String,
+    /// }
+    ///
+    /// let mut deserializer = msd::Deserializer::with_options(
+    ///     b"#TITLE:foo;\n#ARTIST:bar;\n#TITLE:baz;\n".as_slice(),
+    ///     DeserializerOptions::new().duplicate_tags(DuplicateTags::LastWins),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     Song::deserialize(&mut deserializer).unwrap(),
+    ///     Song {
+    ///         title: "baz".to_owned(),
+    ///         artist: "bar".to_owned(),
+    ///     }
+    /// );
+    /// ```
+    pub fn duplicate_tags(mut self, duplicate_tags: DuplicateTags) -> Self {
+        self.duplicate_tags = Some(duplicate_tags);
+        self
+    }
+
+    /// Sets how [`UnknownTags`] within a struct are handled.
+    ///
+    /// By default, an unknown tag within a struct is passed to the struct, which results in an
+    /// error for structs deriving `Deserialize`.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::de::{DeserializerOptions, UnknownTags};
+    /// use serde::Deserialize;
+    /// use serde_derive::Deserialize;
+    /// use std::collections::BTreeMap;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Song {
+    ///     #[serde(rename = "TITLE")]
+    ///     title: String,
+    ///     extras: BTreeMap<String, Vec<String>>,
+    /// }
+    ///
+    /// let mut deserializer = msd::Deserializer::with_options(
+    ///     b"#TITLE:foo;\n#VENDOR:bar:baz;\n".as_slice(),
+    ///     DeserializerOptions::new().unknown_tags(UnknownTags::Capture("extras")),
+    /// );
+    ///
+    /// let mut extras = BTreeMap::new();
+    /// extras.insert("VENDOR".to_owned(), vec!["bar".to_owned(), "baz".to_owned()]);
+    /// assert_eq!(
+    ///     Song::deserialize(&mut deserializer).unwrap(),
+    ///     Song {
+    ///         title: "foo".to_owned(),
+    ///         extras,
+    ///     }
+    /// );
+    /// ```
+    pub fn unknown_tags(mut self, unknown_tags: UnknownTags) -> Self {
+        self.unknown_tags = Some(unknown_tags);
+        self
+    }
+
+    /// Sets the [`Limits`] enforced on the input.
+    ///
+    /// By default, no limits are enforced.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets whether an empty parameter within a tuple or sequence is deserialized as `None`.
+    ///
+    /// Normally, an `Option` cannot be deserialized as an element of a tuple or a sequence, since
+    /// there is no way to tell whether it is present. When enabled, a parameter containing only
+    /// whitespace and comments is `None`, and any other parameter is `Some`. A `Some` containing
+    /// an empty value is written with the escape sequence `\0` preceding it, which is removed when
+    /// deserializing. See [`SerializerOptions::empty_as_none()`] for the matching serializer
+    /// option.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::de::DeserializerOptions;
+    /// use serde::Deserialize;
+    ///
+    /// let mut deserializer = msd::Deserializer::with_options(
+    ///     b"#NOTES:dance-single::Beginner:\\0;\n".as_slice(),
+    ///     DeserializerOptions::new().empty_as_none(true),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     <(String, String, Option<String>, Option<String>, Option<String>)>::deserialize(
+    ///         &mut deserializer
+    ///     )
+    ///     .unwrap(),
+    ///     (
+    ///         "NOTES".to_owned(),
+    ///         "dance-single".to_owned(),
+    ///         None,
+    ///         Some("Beginner".to_owned()),
+    ///         Some(String::new())
+    ///     )
+    /// );
+    /// ```
+    ///
+    /// [`SerializerOptions::empty_as_none()`]: crate::ser::SerializerOptions::empty_as_none()
+    pub fn empty_as_none(mut self, empty_as_none: bool) -> Self {
+        self.empty_as_none = empty_as_none;
+        self
+    }
+
+    /// Allows structs nested within struct fields, read from tags named by the path to each of
+    /// their fields joined by `separator`.
+    ///
+    /// Normally, a struct cannot be the value of a struct field. When enabled, the fields of such
+    /// a struct are read from tags such as `#timing.offset:...;` (with a `separator` of `.`).
+    /// Structs may be nested to any depth. Usually combined with
+    /// [`case_insensitive()`](Self::case_insensitive()) to read uppercase tag names. See
+    /// [`SerializerOptions::nested_structs()`] for the matching serializer option.
+    ///
+    /// Within the top-level struct, the tags of a nested struct may appear anywhere, so the
+    /// remaining input is read ahead of time to collect them. Within a struct that is an element
+    /// of a sequence, they must directly follow each other.
+    ///
+    /// By default, nested structs are not supported.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::de::DeserializerOptions;
+    /// use serde::Deserialize;
+    /// use serde_derive::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Timing {
+    ///     offset: f64,
+    ///     bpms: String,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize, PartialEq)]
+    /// struct Song {
+    ///     title: String,
+    ///     timing: Timing,
+    /// }
+    ///
+    /// let mut deserializer = msd::Deserializer::with_options(
+    ///     b"#TITLE:foo;\n#TIMING.OFFSET:-0.5;\n#TIMING.BPMS:0.000=120.000;\n".as_slice(),
+    ///     DeserializerOptions::new()
+    ///         .case_insensitive(true)
+    ///         .nested_structs('.'),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     Song::deserialize(&mut deserializer).unwrap(),
+    ///     Song {
+    ///         title: "foo".to_owned(),
+    ///         timing: Timing {
+    ///             offset: -0.5,
+    ///             bpms: "0.000=120.000".to_owned(),
+    ///         },
+    ///     }
+    /// );
+    /// ```
+    ///
+    /// [`SerializerOptions::nested_structs()`]: crate::ser::SerializerOptions::nested_structs()
+    pub fn nested_structs(mut self, separator: char) -> Self {
+        self.nested_structs = Some(separator);
+        self
+    }
+
+    /// Sets whether leading and trailing whitespace is removed from strings.
+    ///
+    /// StepMania ignores the whitespace surrounding each parameter of its `#NOTES` tags, which are
+    /// usually laid out with each parameter on its own indented line. When enabled, strings are
+    /// read without this whitespace, just as numbers, `bool`s and enum variants always are. This
+    /// allows reading the output of [`SerializerOptions::pretty()`], although whitespace
+    /// surrounding the original strings is lost.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::de::DeserializerOptions;
+    /// use serde::Deserialize;
+    ///
+    /// let mut deserializer = msd::Deserializer::with_options(
+    ///     b"#NOTES:\n     dance-single:\n     Beginner:\n0000\n1000\n;\n".as_slice(),
+    ///     DeserializerOptions::new().trim_strings(true),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     <(String, String, String, String)>::deserialize(&mut deserializer).unwrap(),
+    ///     (
+    ///         "NOTES".to_owned(),
+    ///         "dance-single".to_owned(),
+    ///         "Beginner".to_owned(),
+    ///         "0000\n1000".to_owned()
+    ///     )
+    /// );
+    /// ```
+    ///
+    /// [`SerializerOptions::pretty()`]: crate::ser::SerializerOptions::pretty()
+    pub fn trim_strings(mut self, trim_strings: bool) -> Self {
+        self.trim_strings = trim_strings;
+        self
+    }
+
+    pub(crate) fn get_dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    pub(crate) fn get_encoding(&self) -> Option<Encoding> {
+        self.encoding
+    }
+
+    pub(in crate::de) fn get_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    pub(in crate::de) fn get_duplicate_tags(&self) -> Option<DuplicateTags> {
+        self.duplicate_tags
+    }
+
+    pub(in crate::de) fn get_unknown_tags(&self) -> Option<UnknownTags> {
+        self.unknown_tags
+    }
+
+    pub(in crate::de) fn get_limits(&self) -> Limits {
+        self.limits
+    }
+
+    pub(in crate::de) fn get_empty_as_none(&self) -> bool {
+        self.empty_as_none
+    }
+
+    pub(in crate::de) fn get_nested_structs(&self) -> Option<char> {
+        self.nested_structs
+    }
+
+    pub(in crate::de) fn get_trim_strings(&self) -> bool {
+        self.trim_strings
+    }
+}
+
+impl Default for DeserializerOptions {
+    fn default() -> Self {
+        Self {
+            dialect: Dialect::default(),
+            encoding: Some(Encoding::default()),
+            case_insensitive: false,
+            duplicate_tags: None,
+            unknown_tags: None,
+            limits: Limits::default(),
+            empty_as_none: false,
+            nested_structs: None,
+            trim_strings: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeserializerOptions, Dialect, DuplicateTags, Limits, UnknownTags};
+    use crate::Encoding;
+
+    #[test]
+    fn default_dialect() {
+        assert_eq!(DeserializerOptions::new().get_dialect(), Dialect::StepMania);
+    }
+
+    #[test]
+    fn set_dialect() {
+        assert_eq!(
+            DeserializerOptions::new()
+                .dialect(Dialect::Dwi)
+                .get_dialect(),
+            Dialect::Dwi
+        );
+    }
+
+    #[test]
+    fn default_encoding() {
+        assert_eq!(
+            DeserializerOptions::new().get_encoding(),
+            Some(Encoding::Utf8)
+        );
+    }
+
+    #[test]
+    fn set_encoding() {
+        assert_eq!(
+            DeserializerOptions::new()
+                .encoding(Encoding::ShiftJis)
+                .get_encoding(),
+            Some(Encoding::ShiftJis)
+        );
+    }
+
+    #[test]
+    fn detect_encoding() {
+        assert_eq!(
+            DeserializerOptions::new()
+                .encoding(Encoding::ShiftJis)
+                .detect_encoding()
+                .get_encoding(),
+            None
+        );
+    }
+
+    #[test]
+    fn default_case_insensitive() {
+        assert!(!DeserializerOptions::new().get_case_insensitive());
+    }
+
+    #[test]
+    fn set_case_insensitive() {
+        assert!(DeserializerOptions::new()
+            .case_insensitive(true)
+            .get_case_insensitive());
+    }
+
+    #[test]
+    fn default_duplicate_tags() {
+        assert_eq!(DeserializerOptions::new().get_duplicate_tags(), None);
+    }
+
+    #[test]
+    fn set_duplicate_tags() {
+        assert_eq!(
+            DeserializerOptions::new()
+                .duplicate_tags(DuplicateTags::Collect)
+                .get_duplicate_tags(),
+            Some(DuplicateTags::Collect)
+        );
+    }
+
+    #[test]
+    fn default_unknown_tags() {
+        assert_eq!(DeserializerOptions::new().get_unknown_tags(), None);
+    }
+
+    #[test]
+    fn set_unknown_tags() {
+        assert_eq!(
+            DeserializerOptions::new()
+                .unknown_tags(UnknownTags::Capture("extras"))
+                .get_unknown_tags(),
+            Some(UnknownTags::Capture("extras"))
+        );
+    }
+
+    #[test]
+    fn default_limits() {
+        assert_eq!(DeserializerOptions::new().get_limits(), Limits::new());
+    }
+
+    #[test]
+    fn set_limits() {
+        let limits = Limits::new()
+            .max_tag_size(1)
+            .max_tags(2)
+            .max_parameters(3)
+            .max_total_bytes(4)
+            .max_string_length(5);
+
+        let options = DeserializerOptions::new().limits(limits);
+
+        assert_eq!(options.get_limits().get_max_tag_size(), Some(1));
+        assert_eq!(options.get_limits().get_max_tags(), Some(2));
+        assert_eq!(options.get_limits().get_max_parameters(), Some(3));
+        assert_eq!(options.get_limits().get_max_total_bytes(), Some(4));
+        assert_eq!(options.get_limits().get_max_string_length(), Some(5));
+    }
+
+    #[test]
+    fn default_empty_as_none() {
+        assert!(!DeserializerOptions::new().get_empty_as_none());
+    }
+
+    #[test]
+    fn set_empty_as_none() {
+        assert!(DeserializerOptions::new()
+            .empty_as_none(true)
+            .get_empty_as_none());
+    }
+
+    #[test]
+    fn default_nested_structs() {
+        assert_eq!(DeserializerOptions::new().get_nested_structs(), None);
+    }
+
+    #[test]
+    fn set_nested_structs() {
+        assert_eq!(
+            DeserializerOptions::new()
+                .nested_structs('.')
+                .get_nested_structs(),
+            Some('.')
+        );
+    }
+
+    #[test]
+    fn default_trim_strings() {
+        assert!(!DeserializerOptions::new().get_trim_strings());
+    }
+
+    #[test]
+    fn set_trim_strings() {
+        assert!(DeserializerOptions::new()
+            .trim_strings(true)
+            .get_trim_strings());
+    }
+
+    #[test]
+    fn limits_unset_by_default() {
+        let limits = Limits::new();
+
+        assert_eq!(limits.get_max_tag_size(), None);
+        assert_eq!(limits.get_max_tags(), None);
+        assert_eq!(limits.get_max_parameters(), None);
+        assert_eq!(limits.get_max_total_bytes(), None);
+        assert_eq!(limits.get_max_string_length(), None);
+    }
+
+    #[test]
+    fn strict() {
+        let dialect = Dialect::Strict;
+
+        assert!(!dialect.recovers_missing_terminator());
+        assert!(!dialect.allows_unescaped_number_sign());
+        assert!(!dialect.skips_leading_garbage());
+        assert!(dialect.recognizes_comments());
+    }
+
+    #[test]
+    fn stepmania() {
+        let dialect = Dialect::StepMania;
+
+        assert!(dialect.recovers_missing_terminator());
+        assert!(dialect.allows_unescaped_number_sign());
+        assert!(!dialect.skips_leading_garbage());
+        assert!(dialect.recognizes_comments());
+    }
+
+    #[test]
+    fn dwi() {
+        let dialect = Dialect::Dwi;
+
+        assert!(dialect.recovers_missing_terminator());
+        assert!(dialect.allows_unescaped_number_sign());
+        assert!(dialect.skips_leading_garbage());
+        assert!(!dialect.recognizes_comments());
+    }
+}
@@ -1,10 +1,14 @@
+mod push;
+mod separators;
 mod tag;
 mod tags;
 mod utf8_char_width;
 mod value;
 mod values;
 
+pub(super) use push::{Push, Step};
+pub(super) use separators::Separators;
 pub(super) use tag::{StoredTag, Tag};
-pub(super) use tags::Tags;
+pub(super) use tags::{BufferedTag, Tags};
 pub(super) use value::Value;
 pub(super) use values::{StoredValues, Values};
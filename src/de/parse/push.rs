@@ -0,0 +1,868 @@
+use super::Tag;
+use crate::{
+    de::{error, DeserializerOptions, Error, Limit, Position, Result},
+    Encoding,
+};
+use std::collections::VecDeque;
+
+#[derive(Debug)]
+enum State {
+    None,
+    MaybeEnteringComment,
+    InComment,
+    Escaping,
+    // Within a two-byte character, whose second byte is never special.
+    Trailing,
+}
+
+// The outcome of attempting to parse the next tag from the input fed so far.
+#[derive(Debug, PartialEq)]
+pub(in crate::de) enum Step<'a> {
+    Tag(Tag<'a>),
+    // More input must be fed before the next tag is known to be complete.
+    Incomplete,
+    // The input has finished and every tag has been returned.
+    End,
+}
+
+// How many bytes, starting from the first byte that could be in another encoding, are buffered
+// before detecting the encoding of the input, unless the input finishes first.
+const DETECTION_LEN: usize = 1024;
+
+// A tag known to be complete that has not been returned yet.
+#[derive(Debug)]
+struct Complete {
+    // The number of bytes following the tag's `#`.
+    len: usize,
+    position: Position,
+    // The encoding of the input, as known when the tag was completed.
+    encoding: Option<Encoding>,
+}
+
+// A push-based equivalent of `Tags`.
+//
+// Rather than pulling from a reader, input is fed in chunks of any size. Each chunk is scanned as
+// soon as it is fed, and all scanning state is kept between chunks, so a chunk boundary may fall
+// anywhere, including within an escape sequence or a comment. A tag is only returned once it is
+// known to be complete, meaning the start of the next tag has been seen or the input has
+// finished.
+//
+// Since input is scanned as it arrives, the configured limits also bound how much of it is kept.
+// Once an error is found, no further input is kept at all.
+#[derive(Debug)]
+pub(in crate::de) struct Push {
+    // The bytes of each complete tag that has not been returned yet, followed by the bytes of the
+    // current tag. Each tag's bytes follow its `#`, and are followed by the `#` of the next tag.
+    // Bytes before the first tag are only kept until the first tag is found.
+    buffer: Vec<u8>,
+    // How many bytes of `buffer` have been scanned.
+    scanned: usize,
+    // The index within `buffer` of the current tag's bytes.
+    tag_start: usize,
+    // How many bytes at the start of `buffer` belong to the previously returned tag. These are
+    // released on the next call to `next()`.
+    returned: usize,
+    complete: VecDeque<Complete>,
+
+    options: DeserializerOptions,
+    // The encoding of the input, or `None` if it has not been detected yet.
+    encoding: Option<Encoding>,
+
+    state: State,
+    end_of_values: bool,
+    starting_new_line: bool,
+
+    // The position of the current tag's `#`, or `None` if the first tag has not been found.
+    tag_position: Option<Position>,
+    current_position: Position,
+
+    // The number of tags found, counted against the configured limits.
+    tags_found: usize,
+    // The number of bytes fed, counted against the configured limits.
+    bytes_fed: usize,
+    // Whether input beyond the limit on its total size was discarded when fed.
+    truncated: bool,
+
+    encountered_error: Option<Error>,
+    finished: bool,
+    exhausted: bool,
+}
+
+impl Push {
+    pub(in crate::de) fn new(options: DeserializerOptions) -> Self {
+        Self {
+            buffer: Vec::new(),
+            scanned: 0,
+            tag_start: 0,
+            returned: 0,
+            complete: VecDeque::new(),
+
+            encoding: options.get_encoding(),
+            options,
+
+            state: State::None,
+            end_of_values: false,
+            starting_new_line: false,
+
+            tag_position: None,
+            current_position: Position::new(0, 0),
+
+            tags_found: 0,
+            bytes_fed: 0,
+            truncated: false,
+
+            encountered_error: None,
+            finished: false,
+            exhausted: false,
+        }
+    }
+
+    pub(in crate::de) fn feed(&mut self, mut chunk: &[u8]) {
+        assert!(!self.finished, "input fed after being finished");
+        if self.encountered_error.is_some() {
+            // Nothing after an error can be returned, so there is no need to keep it.
+            return;
+        }
+
+        if let Some(max) = self.options.get_limits().get_max_total_bytes() {
+            // The byte following the limit is kept, as it may begin a tag that is not counted
+            // against the limit. Anything beyond it is only known to exceed the limit.
+            let available = max.saturating_add(1).saturating_sub(self.bytes_fed);
+            if chunk.len() > available {
+                chunk = &chunk[..available];
+                self.truncated = true;
+            }
+        }
+        self.bytes_fed += chunk.len();
+        self.buffer.extend_from_slice(chunk);
+        self.resume();
+    }
+
+    pub(in crate::de) fn finish(&mut self) {
+        self.finished = true;
+        self.resume();
+    }
+
+    // Continues scanning the input.
+    fn resume(&mut self) {
+        if self.encountered_error.is_none() && self.scan().is_err() {
+            // Only the complete tags before the error are still needed.
+            let kept = self.returned
+                + self
+                    .complete
+                    .iter()
+                    .map(|complete| complete.len + 1)
+                    .sum::<usize>();
+            self.buffer.truncate(kept);
+            self.scanned = kept;
+            self.tag_start = kept;
+        }
+    }
+
+    fn error(&mut self, kind: error::Kind, position: Position) -> Error {
+        let error = Error::new(kind, position);
+        self.encountered_error = Some(error.clone());
+        error
+    }
+
+    // Advances the current position past `byte`.
+    fn advance(&mut self, byte: u8) {
+        if matches!(byte, b'\n') {
+            self.current_position = self.current_position.increment_line();
+            self.starting_new_line = true;
+        } else {
+            self.current_position = self.current_position.increment_column();
+            self.starting_new_line = false;
+        }
+        self.scanned += 1;
+    }
+
+    // Returns the limit exceeded by the byte at the current position, assuming it belongs to the
+    // current tag.
+    fn exceeded_limit(&self) -> Option<Limit> {
+        let limits = self.options.get_limits();
+        let offset = self.current_position.offset();
+        match (self.tag_position, limits.get_max_tag_size()) {
+            // Skip the tag's leading `#`.
+            (Some(position), Some(max)) if offset - position.offset() > max => Some(Limit::TagSize),
+            _ => limits
+                .get_max_total_bytes()
+                .filter(|&max| offset >= max)
+                .map(|_| Limit::TotalBytes),
+        }
+    }
+
+    // Begins a new tag whose `#` is at the current position and scanned index.
+    fn start_tag(&mut self) -> Result<()> {
+        self.tags_found += 1;
+        if matches!(self.options.get_limits().get_max_tags(), Some(max) if self.tags_found > max) {
+            return Err(self.error(
+                error::Kind::LimitExceeded(Limit::Tags),
+                self.current_position,
+            ));
+        }
+
+        self.tag_position = Some(self.current_position);
+        self.current_position = self.current_position.increment_column();
+        self.scanned += 1;
+        self.tag_start = self.scanned;
+
+        self.state = State::None;
+        self.end_of_values = false;
+        self.starting_new_line = false;
+        Ok(())
+    }
+
+    // Scans for the first tag, returning whether it was found.
+    fn find_first_tag(&mut self) -> Result<bool> {
+        let dialect = self.options.get_dialect();
+
+        while self.scanned < self.buffer.len() {
+            if self.exceeded_limit().is_some() {
+                return Err(self.error(
+                    error::Kind::LimitExceeded(Limit::TotalBytes),
+                    self.current_position,
+                ));
+            }
+
+            let byte = self.buffer[self.scanned];
+            match self.state {
+                State::None => match byte {
+                    b'#' => {
+                        self.start_tag()?;
+                        self.buffer.drain(..self.scanned);
+                        self.scanned = 0;
+                        self.tag_start = 0;
+                        return Ok(true);
+                    }
+                    b'/' if dialect.recognizes_comments() => {
+                        self.state = State::MaybeEnteringComment;
+                    }
+                    _ => {
+                        // Non-whitespace bytes are not allowed before the first tag, unless the
+                        // dialect ignores them.
+                        if !byte.is_ascii_whitespace() && !dialect.skips_leading_garbage() {
+                            return Err(self.error(error::Kind::ExpectedTag, self.current_position));
+                        }
+                    }
+                },
+                State::MaybeEnteringComment => match byte {
+                    b'/' => {
+                        self.state = State::InComment;
+                    }
+                    _ => {
+                        return Err(self.error(
+                            error::Kind::ExpectedTag,
+                            self.current_position.decrement_column(),
+                        ));
+                    }
+                },
+                State::InComment => {
+                    // Consume bytes until we are on a new line.
+                    if matches!(byte, b'\n') {
+                        self.state = State::None;
+                    }
+                }
+                State::Escaping | State::Trailing => unreachable!(),
+            }
+            self.advance(byte);
+        }
+
+        // Nothing before the first tag needs to be kept.
+        self.buffer.clear();
+        self.scanned = 0;
+        Ok(false)
+    }
+
+    // Scans the input fed so far, recording each tag found to be complete.
+    fn scan(&mut self) -> Result<()> {
+        if self.tag_position.is_none() && !self.find_first_tag()? {
+            return Ok(());
+        }
+
+        let dialect = self.options.get_dialect();
+        while self.scanned < self.buffer.len() {
+            // A limit is only exceeded if the next byte belongs to this tag, rather than beginning
+            // the next one.
+            let exceeded = self.exceeded_limit();
+
+            let byte = self.buffer[self.scanned];
+            if self.encoding.is_none() && !byte.is_ascii() {
+                // See `Tags::next()` for why the encoding must be detected here.
+                let bytes = &self.buffer[self.scanned..];
+                if bytes.len() < DETECTION_LEN && !self.finished {
+                    // Wait for enough input to detect the encoding reliably.
+                    return Ok(());
+                }
+                self.encoding = Some(Encoding::detect(bytes));
+            }
+            let lead = self
+                .encoding
+                .map_or(false, |encoding| encoding.is_lead_byte(byte));
+            match self.state {
+                State::None => {
+                    match byte {
+                        b'#' => {
+                            // See `Tags::next()` for how a `#` is interpreted by each dialect.
+                            if self.end_of_values
+                                || (self.starting_new_line && dialect.recovers_missing_terminator())
+                            {
+                                // Entering a new tag, which completes the previous one.
+                                self.complete.push_back(Complete {
+                                    len: self.scanned - self.tag_start,
+                                    // `tag_position` is always set once the first tag is found.
+                                    position: self.tag_position.unwrap_or(self.current_position),
+                                    encoding: self.encoding,
+                                });
+                                self.start_tag()?;
+                                continue;
+                            }
+                            let kind = if self.starting_new_line {
+                                Some(error::Kind::MissingTagTerminator)
+                            } else if !dialect.allows_unescaped_number_sign() {
+                                Some(error::Kind::UnescapedNumberSign)
+                            } else {
+                                None
+                            };
+                            if let Some(kind) = kind {
+                                return Err(self.error(kind, self.current_position));
+                            }
+                            self.end_of_values = false;
+                        }
+                        b';' => {
+                            self.end_of_values = true;
+                        }
+                        b'\\' => {
+                            self.state = State::Escaping;
+                            self.end_of_values = false;
+                        }
+                        b'/' if dialect.recognizes_comments() => {
+                            self.state = State::MaybeEnteringComment;
+                        }
+                        _ if lead => {
+                            self.state = State::Trailing;
+                            self.end_of_values = false;
+                        }
+                        _ => {
+                            if !byte.is_ascii_whitespace() {
+                                self.end_of_values = false;
+                            }
+                        }
+                    }
+                }
+                State::MaybeEnteringComment => match byte {
+                    b';' => {
+                        self.end_of_values = true;
+                        self.state = State::None;
+                    }
+                    b'\\' => {
+                        self.state = State::Escaping;
+                        self.end_of_values = false;
+                    }
+                    b'/' => {
+                        self.state = State::InComment;
+                    }
+                    _ if lead => {
+                        self.state = State::Trailing;
+                        self.end_of_values = false;
+                    }
+                    _ => {
+                        self.state = State::None;
+                        self.end_of_values = false;
+                    }
+                },
+                State::InComment => {
+                    // Consume bytes until we are on a new line.
+                    if matches!(byte, b'\n') {
+                        self.state = State::None;
+                    }
+                }
+                State::Escaping => {
+                    // The second byte of a two-byte character cannot be escaped by itself.
+                    self.state = if lead { State::Trailing } else { State::None };
+                    self.end_of_values = false;
+                }
+                State::Trailing => {
+                    self.state = State::None;
+                    self.end_of_values = false;
+                }
+            }
+            if let Some(limit) = exceeded {
+                return Err(self.error(error::Kind::LimitExceeded(limit), self.current_position));
+            }
+            self.advance(byte);
+        }
+
+        if self.truncated {
+            // The discarded input would have continued the current tag.
+            return Err(self.error(
+                error::Kind::LimitExceeded(Limit::TotalBytes),
+                self.current_position,
+            ));
+        }
+        Ok(())
+    }
+
+    pub(in crate::de) fn next(&mut self) -> Result<Step<'_>> {
+        // Release the previously returned tag.
+        self.buffer.drain(..self.returned);
+        self.scanned -= self.returned;
+        self.tag_start -= self.returned;
+        self.returned = 0;
+
+        if let Some(complete) = self.complete.pop_front() {
+            // Release the `#` beginning the next tag along with this tag.
+            self.returned = complete.len + 1;
+            return Ok(Step::Tag(self.tag(
+                complete.len,
+                complete.position,
+                complete.encoding,
+            )));
+        }
+        if let Some(error) = &self.encountered_error {
+            return Err(error.clone());
+        }
+        if self.exhausted {
+            return Ok(Step::End);
+        }
+        if !self.finished {
+            return Ok(Step::Incomplete);
+        }
+
+        // The input has finished, so the current tag is the last one.
+        let position = match self.tag_position {
+            Some(position) => position,
+            None => {
+                self.exhausted = true;
+                return Ok(Step::End);
+            }
+        };
+        if self.buffer.is_empty() {
+            return Err(self.error(error::Kind::EndOfFile, self.current_position));
+        }
+        if !self.end_of_values && !self.options.get_dialect().recovers_missing_terminator() {
+            return Err(self.error(error::Kind::MissingTagTerminator, self.current_position));
+        }
+        self.exhausted = true;
+        let len = self.buffer.len();
+        Ok(Step::Tag(self.tag(len, position, self.encoding)))
+    }
+
+    fn tag(&mut self, len: usize, position: Position, encoding: Option<Encoding>) -> Tag<'_> {
+        let bytes = &self.buffer[..len];
+        // Detection is deferred until a tag contains bytes that could be in another encoding.
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None if bytes.is_ascii() => Encoding::Utf8,
+            None => *self.encoding.insert(Encoding::detect(bytes)),
+        };
+        let mut tag = Tag::new(bytes, position).with_encoding(encoding);
+        if !self.options.get_dialect().recognizes_comments() {
+            tag = tag.without_comments();
+        }
+        if self.options.get_case_insensitive() {
+            tag = tag.ignoring_case();
+        }
+        if self.options.get_empty_as_none() {
+            tag = tag.treating_empty_as_none();
+        }
+        if self.options.get_trim_strings() {
+            tag = tag.trimming_strings();
+        }
+        tag.with_limits(self.options.get_limits())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Push, Step};
+    use crate::{
+        de::{error, parse::Tag, DeserializerOptions, Dialect, Error, Limit, Limits, Position},
+        Encoding,
+    };
+    use claims::{assert_err_eq, assert_le, assert_ok_eq};
+
+    #[test]
+    fn empty() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        assert_ok_eq!(push.next(), Step::Incomplete);
+        push.finish();
+        assert_ok_eq!(push.next(), Step::End);
+        assert_ok_eq!(push.next(), Step::End);
+    }
+
+    #[test]
+    fn single_tag() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        push.feed(b"#foo:bar;\n");
+        assert_ok_eq!(push.next(), Step::Incomplete);
+        push.finish();
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo:bar;\n", Position::new(0, 0)))
+        );
+        assert_ok_eq!(push.next(), Step::End);
+    }
+
+    #[test]
+    fn tag_completed_by_next_tag() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        push.feed(b"#foo:bar;\n#");
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo:bar;\n", Position::new(0, 0)))
+        );
+        assert_ok_eq!(push.next(), Step::Incomplete);
+        push.feed(b"baz;\n");
+        push.finish();
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"baz;\n", Position::new(1, 0)))
+        );
+        assert_ok_eq!(push.next(), Step::End);
+    }
+
+    #[test]
+    fn byte_by_byte() {
+        let input = b"// comment\n#foo:b\\;ar;\n#baz:// #qux;\n;\n#a;#b;";
+        let mut push = Push::new(DeserializerOptions::new());
+
+        let mut tags = Vec::new();
+        for byte in input {
+            push.feed(&[*byte]);
+            while let Step::Tag(tag) = push.next().unwrap() {
+                tags.push(tag.into_stored().origin_position());
+            }
+        }
+        push.finish();
+        while let Step::Tag(tag) = push.next().unwrap() {
+            tags.push(tag.into_stored().origin_position());
+        }
+
+        assert_eq!(
+            tags,
+            vec![
+                Position::new(1, 0),
+                Position::new(2, 0),
+                Position::new(4, 0),
+                Position::new(4, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn escape_across_chunks() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        push.feed(b"#foo:bar\\");
+        assert_ok_eq!(push.next(), Step::Incomplete);
+        push.feed(b";#baz;");
+        push.finish();
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo:bar\\;#baz;", Position::new(0, 0)))
+        );
+    }
+
+    #[test]
+    fn comment_across_chunks() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        push.feed(b"#foo:bar;/");
+        assert_ok_eq!(push.next(), Step::Incomplete);
+        push.feed(b"/ #baz;");
+        assert_ok_eq!(push.next(), Step::Incomplete);
+        push.feed(b"\n#qux;");
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo:bar;// #baz;\n", Position::new(0, 0)))
+        );
+    }
+
+    #[test]
+    fn comment_before_first_tag_across_chunks() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        push.feed(b"/");
+        assert_ok_eq!(push.next(), Step::Incomplete);
+        push.feed(b"/ #foo;");
+        assert_ok_eq!(push.next(), Step::Incomplete);
+        push.feed(b"\n#bar;");
+        push.finish();
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"bar;", Position::new(1, 0)))
+        );
+    }
+
+    #[test]
+    fn character_before_first_tag() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        push.feed(b"\n  foo#bar;\n");
+
+        assert_err_eq!(
+            push.next(),
+            Error::new(error::Kind::ExpectedTag, Position::new(1, 2))
+        );
+        // The error is repeated.
+        assert_err_eq!(
+            push.next(),
+            Error::new(error::Kind::ExpectedTag, Position::new(1, 2))
+        );
+    }
+
+    #[test]
+    fn single_slash_before_first_tag() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        push.feed(b"/");
+        assert_ok_eq!(push.next(), Step::Incomplete);
+        push.feed(b"#foo;");
+
+        assert_err_eq!(
+            push.next(),
+            Error::new(error::Kind::ExpectedTag, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn only_number_sign() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        push.feed(b"#");
+        push.finish();
+
+        assert_err_eq!(
+            push.next(),
+            Error::new(error::Kind::EndOfFile, Position::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn missing_terminator_at_end() {
+        let mut push = Push::new(DeserializerOptions::new().dialect(Dialect::Strict));
+
+        push.feed(b"#foo:bar");
+        push.finish();
+
+        assert_err_eq!(
+            push.next(),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(0, 8))
+        );
+    }
+
+    #[test]
+    fn missing_terminator_recovered() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        push.feed(b"#foo:bar\n#baz");
+        push.finish();
+
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo:bar\n", Position::new(0, 0)))
+        );
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"baz", Position::new(1, 0)))
+        );
+    }
+
+    #[test]
+    fn missing_terminator_strict() {
+        let mut push = Push::new(DeserializerOptions::new().dialect(Dialect::Strict));
+
+        push.feed(b"#foo:bar\n#baz;");
+
+        assert_err_eq!(
+            push.next(),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn unescaped_number_sign_strict() {
+        let mut push = Push::new(DeserializerOptions::new().dialect(Dialect::Strict));
+
+        push.feed(b"#foo:b#ar;");
+
+        assert_err_eq!(
+            push.next(),
+            Error::new(error::Kind::UnescapedNumberSign, Position::new(0, 6))
+        );
+    }
+
+    #[test]
+    fn detect_encoding() {
+        let mut push = Push::new(DeserializerOptions::new().detect_encoding());
+
+        push.feed(b"#foo:bar;\n#TITLE:\x93\x8c\x95\xfb\x97");
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo:bar;\n", Position::new(0, 0)))
+        );
+        push.feed(b"\x64\x81\x58\x96\xb2;\n#baz;\n");
+        push.finish();
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(
+                Tag::new(
+                    b"TITLE:\x93\x8c\x95\xfb\x97\x64\x81\x58\x96\xb2;\n",
+                    Position::new(1, 0)
+                )
+                .with_encoding(Encoding::ShiftJis)
+            )
+        );
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"baz;\n", Position::new(2, 0)).with_encoding(Encoding::ShiftJis))
+        );
+    }
+
+    #[test]
+    fn shift_jis_trailing_backslash() {
+        let mut push = Push::new(DeserializerOptions::new().encoding(Encoding::ShiftJis));
+
+        push.feed(b"#\x83");
+        assert_ok_eq!(push.next(), Step::Incomplete);
+        push.feed(b"\x5c;\n#foo;\n");
+        push.finish();
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(
+                Tag::new(b"\x83\x5c;\n", Position::new(0, 0)).with_encoding(Encoding::ShiftJis)
+            )
+        );
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo;\n", Position::new(1, 0)).with_encoding(Encoding::ShiftJis))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "input fed after being finished")]
+    fn feed_after_finish() {
+        let mut push = Push::new(DeserializerOptions::new());
+
+        push.finish();
+        push.feed(b"#foo;");
+    }
+
+    #[test]
+    fn max_tag_size() {
+        let limits = Limits::new().max_tag_size(5);
+        let mut push = Push::new(DeserializerOptions::new().limits(limits));
+
+        push.feed(b"#foo;\n#barbaz;\n");
+        push.finish();
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo;\n", Position::new(0, 0)).with_limits(limits))
+        );
+        assert_err_eq!(
+            push.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TagSize),
+                Position::new(1, 6)
+            )
+        );
+    }
+
+    #[test]
+    fn max_tag_size_byte_by_byte() {
+        let mut push = Push::new(DeserializerOptions::new().limits(Limits::new().max_tag_size(4)));
+
+        for byte in b"#foo:bar;\n" {
+            push.feed(&[*byte]);
+        }
+        assert_err_eq!(
+            push.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TagSize),
+                Position::new(0, 5)
+            )
+        );
+    }
+
+    #[test]
+    fn max_tags() {
+        let limits = Limits::new().max_tags(1);
+        let mut push = Push::new(DeserializerOptions::new().limits(limits));
+
+        push.feed(b"#foo;\n#bar;\n");
+        push.finish();
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo;\n", Position::new(0, 0)).with_limits(limits))
+        );
+        assert_err_eq!(
+            push.next(),
+            Error::new(error::Kind::LimitExceeded(Limit::Tags), Position::new(1, 0))
+        );
+        assert_err_eq!(
+            push.next(),
+            Error::new(error::Kind::LimitExceeded(Limit::Tags), Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn max_total_bytes() {
+        let limits = Limits::new().max_total_bytes(8);
+        let mut push = Push::new(DeserializerOptions::new().limits(limits));
+
+        push.feed(b"#foo;\n#bar;\n");
+        push.finish();
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo;\n", Position::new(0, 0)).with_limits(limits))
+        );
+        assert_err_eq!(
+            push.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TotalBytes),
+                Position::new(1, 2)
+            )
+        );
+    }
+
+    #[test]
+    fn max_total_bytes_exact() {
+        let limits = Limits::new().max_total_bytes(6);
+        let mut push = Push::new(DeserializerOptions::new().limits(limits));
+
+        push.feed(b"#foo;\n");
+        push.finish();
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo;\n", Position::new(0, 0)).with_limits(limits))
+        );
+        assert_ok_eq!(push.next(), Step::End);
+    }
+
+    #[test]
+    fn max_total_bytes_large_chunk() {
+        let limits = Limits::new().max_total_bytes(8);
+        let mut push = Push::new(DeserializerOptions::new().limits(limits));
+
+        let mut input = b"#foo;\n#b".to_vec();
+        input.extend_from_slice(&[b'a'; 1024]);
+        push.feed(&input);
+        push.feed(&input);
+        assert_le!(push.buffer.len(), 9);
+        assert_ok_eq!(
+            push.next(),
+            Step::Tag(Tag::new(b"foo;\n", Position::new(0, 0)).with_limits(limits))
+        );
+        assert_err_eq!(
+            push.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TotalBytes),
+                Position::new(1, 2)
+            )
+        );
+    }
+}
@@ -0,0 +1,74 @@
+// A set of ASCII punctuation bytes that separate values, any of which may be escaped within a value.
+//
+// There are exactly 32 ASCII punctuation characters, so the set is stored as a single `u32`. This
+// keeps the parsing types that carry it small.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(in crate::de) struct Separators(u32);
+
+impl Separators {
+    // Returns the bit representing `byte`, if it is ASCII punctuation.
+    fn bit(byte: u8) -> Option<u32> {
+        let index = match byte {
+            b'!'..=b'/' => byte - b'!',
+            b':'..=b'@' => byte - b':' + 15,
+            b'['..=b'`' => byte - b'[' + 22,
+            b'{'..=b'~' => byte - b'{' + 28,
+            _ => return None,
+        };
+        Some(1 << index)
+    }
+
+    // Adds `byte` to the set. Bytes that are not ASCII punctuation are ignored.
+    pub(in crate::de) fn insert(&mut self, byte: u8) {
+        if let Some(bit) = Self::bit(byte) {
+            self.0 |= bit;
+        }
+    }
+
+    pub(in crate::de) fn contains(&self, byte: u8) -> bool {
+        Self::bit(byte).map_or(false, |bit| self.0 & bit != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Separators;
+
+    #[test]
+    fn empty() {
+        assert!(!Separators::default().contains(b','));
+    }
+
+    #[test]
+    fn insert() {
+        let mut separators = Separators::default();
+        separators.insert(b',');
+        separators.insert(b'|');
+
+        assert!(separators.contains(b','));
+        assert!(separators.contains(b'|'));
+        assert!(!separators.contains(b'='));
+    }
+
+    #[test]
+    fn all_punctuation() {
+        let mut separators = Separators::default();
+        for byte in 0..=u8::MAX {
+            separators.insert(byte);
+        }
+
+        for byte in 0..=u8::MAX {
+            assert_eq!(separators.contains(byte), byte.is_ascii_punctuation());
+        }
+    }
+
+    #[test]
+    fn not_punctuation() {
+        let mut separators = Separators::default();
+        separators.insert(b'a');
+        separators.insert(0xe9);
+
+        assert!(!separators.contains(b'a'));
+        assert!(!separators.contains(0xe9));
+    }
+}
@@ -1,5 +1,8 @@
 use super::{StoredValues, Values};
-use crate::de::{error, Error, Position, Result};
+use crate::{
+    de::{error, Error, Limit, Limits, Position, Result},
+    Encoding,
+};
 use std::slice;
 
 enum State {
@@ -7,6 +10,8 @@ enum State {
     MaybeEnteringComment,
     InComment,
     Escaping,
+    // Within a two-byte character, whose second byte is never special.
+    Trailing,
 }
 
 // Tag without the lifetime. Used when storing within an Access.
@@ -22,6 +27,16 @@ pub(in crate::de) struct StoredTag {
 
     origin_position: Position,
 
+    borrowed: bool,
+    comments: bool,
+    ignore_case: bool,
+    empty_as_none: bool,
+    trim_strings: bool,
+    encoding: Encoding,
+    max_parameters: Option<usize>,
+    max_string_length: Option<usize>,
+    parameters: usize,
+
     revisit: Option<StoredValues>,
 }
 
@@ -42,6 +57,16 @@ impl StoredTag {
 
             origin_position: self.origin_position,
 
+            borrowed: self.borrowed,
+            comments: self.comments,
+            ignore_case: self.ignore_case,
+            empty_as_none: self.empty_as_none,
+            trim_strings: self.trim_strings,
+            encoding: self.encoding,
+            max_parameters: self.max_parameters,
+            max_string_length: self.max_string_length,
+            parameters: self.parameters,
+
             // SAFETY: The revisit is guaranteed to have the same lifetime as the containing `Tag`.
             revisit: unsafe { self.revisit.map(|stored| stored.into_values()) },
         }
@@ -52,7 +77,7 @@ impl StoredTag {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub(in crate::de) struct Tag<'a> {
     // Should contain all bytes except the leading `#`.
     bytes: &'a [u8],
@@ -64,6 +89,23 @@ pub(in crate::de) struct Tag<'a> {
 
     origin_position: Position,
 
+    // Whether `bytes` is borrowed directly from the input, rather than from a reused buffer.
+    borrowed: bool,
+    // Whether `//` begins a comment.
+    comments: bool,
+    // Whether identifiers match expected names regardless of ASCII case.
+    ignore_case: bool,
+    // Whether an empty parameter within a tuple or sequence is `None`.
+    empty_as_none: bool,
+    // Whether leading and trailing whitespace is removed from strings.
+    trim_strings: bool,
+    // The encoding used to decode text.
+    encoding: Encoding,
+    max_parameters: Option<usize>,
+    max_string_length: Option<usize>,
+    // The number of parameters found so far, counted against `max_parameters`.
+    parameters: usize,
+
     revisit: Option<Values<'a>>,
 }
 
@@ -79,10 +121,94 @@ impl<'a> Tag<'a> {
 
             origin_position: position,
 
+            borrowed: false,
+            comments: true,
+            ignore_case: false,
+            empty_as_none: false,
+            trim_strings: false,
+            encoding: Encoding::Utf8,
+            max_parameters: None,
+            max_string_length: None,
+            parameters: 0,
+
             revisit: None,
         }
     }
 
+    // # Safety
+    // The caller must guarantee that `bytes` lives for the entire lifetime `'de` of the input
+    // being deserialized.
+    pub(in crate::de) unsafe fn assume_borrowed(mut self) -> Self {
+        self.borrowed = true;
+        self
+    }
+
+    pub(in crate::de) fn without_comments(mut self) -> Self {
+        self.comments = false;
+        self
+    }
+
+    pub(in crate::de) fn ignoring_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    pub(in crate::de) fn treating_empty_as_none(mut self) -> Self {
+        self.empty_as_none = true;
+        self
+    }
+
+    pub(in crate::de) fn trimming_strings(mut self) -> Self {
+        self.trim_strings = true;
+        self
+    }
+
+    pub(in crate::de) fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub(in crate::de) fn with_limits(mut self, limits: Limits) -> Self {
+        self.max_parameters = limits.get_max_parameters();
+        self.max_string_length = limits.get_max_string_length();
+        self
+    }
+
+    fn values(&self, bytes: &'a [u8], position: Position) -> Values<'a> {
+        let mut values = Values::new(bytes, position);
+        if self.borrowed {
+            // SAFETY: `bytes` is a subslice of this tag's bytes, which are borrowed from the
+            // input.
+            values = unsafe { values.assume_borrowed() };
+        }
+        if !self.comments {
+            values = values.without_comments();
+        }
+        if self.ignore_case {
+            values = values.ignoring_case();
+        }
+        if self.empty_as_none {
+            values = values.treating_empty_as_none();
+        }
+        if self.trim_strings {
+            values = values.trimming_strings();
+        }
+        values
+            .with_encoding(self.encoding)
+            .with_max_string_length(self.max_string_length)
+    }
+
+    fn count_parameters(&mut self, parameters: usize, position: Position) -> Result<()> {
+        self.parameters += parameters;
+        match self.max_parameters {
+            Some(max) if self.parameters > max => Err(Error::new(
+                error::Kind::LimitExceeded(Limit::Parameters),
+                position,
+            )),
+            _ => Ok(()),
+        }
+    }
+
     pub(in crate::de) fn next(&mut self) -> Result<Values<'a>> {
         if let Some(revisit) = self.revisit.take() {
             return Ok(revisit);
@@ -94,7 +220,33 @@ impl<'a> Tag<'a> {
         let mut state = State::None;
         let mut encountered_non_whitespace = false;
         let mut last_byte_newline = false;
+        let mut parameters = 1;
         loop {
+            if matches!(state, State::None) {
+                // Skip over bytes that can't change the state in bulk.
+                let encoding = self.encoding;
+                let remaining = self
+                    .bytes
+                    .get(self.current_byte_index..)
+                    .unwrap_or_default();
+                let skipped = remaining
+                    .iter()
+                    .position(|&byte| {
+                        matches!(byte, b';' | b':' | b'\\' | b'/' | b'\n')
+                            || encoding.is_lead_byte(byte)
+                    })
+                    .unwrap_or(remaining.len());
+                if skipped > 0 {
+                    if !encountered_non_whitespace {
+                        encountered_non_whitespace = remaining[..skipped]
+                            .iter()
+                            .any(|byte| !byte.is_ascii_whitespace());
+                    }
+                    last_byte_newline = false;
+                    self.current_position = self.current_position.advance_columns(skipped);
+                    self.current_byte_index += skipped;
+                }
+            }
             if let Some(byte) = self.bytes.get(self.current_byte_index) {
                 // Process byte.
                 match state {
@@ -102,7 +254,7 @@ impl<'a> Tag<'a> {
                         match byte {
                             b';' => {
                                 // This is the end of a `Values`.
-                                values = Some(Values::new(
+                                values = Some(self.values(
                                     // SAFETY: Both ends of the range used here have already been
                                     // determined to be within the bounds of self.bytes.
                                     unsafe {
@@ -113,13 +265,19 @@ impl<'a> Tag<'a> {
                                     started_position,
                                 ));
                             }
+                            b':' => {
+                                parameters += 1;
+                            }
                             b'\\' => {
                                 // Enter an escaping state.
                                 state = State::Escaping;
                             }
-                            b'/' => {
+                            b'/' if self.comments => {
                                 state = State::MaybeEnteringComment;
                             }
+                            byte if self.encoding.is_lead_byte(*byte) => {
+                                state = State::Trailing;
+                            }
                             _ => {}
                         }
                     }
@@ -127,7 +285,7 @@ impl<'a> Tag<'a> {
                         match byte {
                             b';' => {
                                 // This is the end of a `Values`.
-                                values = Some(Values::new(
+                                values = Some(self.values(
                                     // SAFETY: Both ends of the range used here have already been
                                     // determined to be within the bounds of self.bytes.
                                     unsafe {
@@ -142,9 +300,16 @@ impl<'a> Tag<'a> {
                                 // Enter an escaping state.
                                 state = State::Escaping;
                             }
+                            b':' => {
+                                parameters += 1;
+                                state = State::None;
+                            }
                             b'/' => {
                                 state = State::InComment;
                             }
+                            byte if self.encoding.is_lead_byte(*byte) => {
+                                state = State::Trailing;
+                            }
                             _ => {
                                 state = State::None;
                             }
@@ -157,6 +322,14 @@ impl<'a> Tag<'a> {
                         }
                     }
                     State::Escaping => {
+                        // The second byte of a two-byte character cannot be escaped by itself.
+                        if self.encoding.is_lead_byte(*byte) {
+                            state = State::Trailing;
+                        } else {
+                            state = State::None;
+                        }
+                    }
+                    State::Trailing => {
                         state = State::None;
                     }
                 }
@@ -173,17 +346,19 @@ impl<'a> Tag<'a> {
 
                 if let Some(values) = values {
                     self.first_values = false;
+                    self.count_parameters(parameters, started_position)?;
                     return Ok(values);
                 }
             } else {
                 if self.first_values || encountered_non_whitespace {
                     self.first_values = false;
+                    self.count_parameters(parameters, started_position)?;
                     let ending_byte_index = if last_byte_newline {
                         self.current_byte_index - 1
                     } else {
                         self.current_byte_index
                     };
-                    return Ok(Values::new(
+                    return Ok(self.values(
                         // SAFETY: self.current_byte_index is guaranteed to only be one past the
                         // last value in the slice.
                         unsafe {
@@ -198,8 +373,42 @@ impl<'a> Tag<'a> {
         }
     }
 
+    pub(in crate::de) fn origin_position(&self) -> Position {
+        self.origin_position
+    }
+
+    // Returns the position just past the final values of this tag, without consuming them.
+    pub(in crate::de) fn end_position(&self) -> Position {
+        let mut tag = self.clone();
+        let mut end = tag.current_position;
+        // Trailing whitespace and comments are not part of any values.
+        while tag.revisit.is_some() || tag.assert_exhausted().is_err() {
+            if tag.next().is_err() {
+                break;
+            }
+            end = tag.current_position;
+        }
+        end
+    }
+
+    // Whether no parameter list has been read from this tag yet.
+    pub(in crate::de) fn is_unread(&self) -> bool {
+        self.first_values
+    }
+
+    // Whether the next parameter list follows another within this tag, rather than being the
+    // first or the remainder of the first.
+    pub(in crate::de) fn continues(&self) -> bool {
+        !self.first_values && self.revisit.is_none()
+    }
+
+    pub(in crate::de) fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
     pub(in crate::de) fn reset(&mut self) {
         self.first_values = true;
+        self.parameters = 0;
         self.current_byte_index = 0;
         self.current_position = self.origin_position.increment_column();
     }
@@ -215,7 +424,7 @@ impl<'a> Tag<'a> {
         // SAFETY: self.current_byte_index is guaranteed to be within the bounds of self.bytes.
         let mut in_comment = 0;
         for byte in unsafe { self.bytes.get_unchecked(self.current_byte_index..) } {
-            if *byte == b'/' {
+            if self.comments && *byte == b'/' {
                 in_comment += 1;
             } else if !byte.is_ascii_whitespace() {
                 if in_comment == 0 {
@@ -243,6 +452,16 @@ impl<'a> Tag<'a> {
 
             origin_position: self.origin_position,
 
+            borrowed: self.borrowed,
+            comments: self.comments,
+            ignore_case: self.ignore_case,
+            empty_as_none: self.empty_as_none,
+            trim_strings: self.trim_strings,
+            encoding: self.encoding,
+            max_parameters: self.max_parameters,
+            max_string_length: self.max_string_length,
+            parameters: self.parameters,
+
             revisit: self.revisit.map(|values| values.into_stored()),
         }
     }
@@ -251,7 +470,7 @@ impl<'a> Tag<'a> {
 #[cfg(test)]
 mod tests {
     use super::Tag;
-    use crate::de::{error, parse::Values, Error, Position};
+    use crate::de::{error, parse::Values, Error, Limit, Limits, Position};
     use claims::{assert_err_eq, assert_ok, assert_ok_eq};
 
     #[test]
@@ -460,6 +679,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn origin_position() {
+        let mut tag = Tag::new(b"foo;bar;", Position::new(1, 2));
+        assert_ok!(tag.next());
+
+        assert_eq!(tag.origin_position(), Position::new(1, 2));
+    }
+
+    #[test]
+    fn end_position() {
+        let tag = Tag::new(b"foo:bar;\nbaz;\n", Position::new(1, 2));
+
+        assert_eq!(tag.end_position(), Position::new(2, 4));
+    }
+
+    #[test]
+    fn end_position_ignores_trailing_comment() {
+        let tag = Tag::new(b"foo; // bar\n", Position::new(0, 0));
+
+        assert_eq!(tag.end_position(), Position::new(0, 5));
+    }
+
+    #[test]
+    fn end_position_does_not_consume() {
+        let mut tag = Tag::new(b"foo;bar;", Position::new(0, 0));
+        assert_ok!(tag.next());
+
+        assert_eq!(tag.end_position(), Position::new(0, 9));
+        assert_ok_eq!(tag.next(), Values::new(b"bar", Position::new(0, 5)));
+    }
+
     #[test]
     fn stored_origin_position() {
         let buffer = b"foo;bar;";
@@ -468,4 +718,104 @@ mod tests {
 
         assert_eq!(tag.into_stored().origin_position(), Position::new(1, 2));
     }
+
+    #[test]
+    fn borrowed_values() {
+        let mut tag = unsafe { Tag::new(b"foo:bar;baz;", Position::new(0, 0)).assume_borrowed() };
+
+        assert_ok_eq!(tag.next(), unsafe {
+            Values::new(b"foo:bar", Position::new(0, 1)).assume_borrowed()
+        });
+        assert_ok_eq!(tag.next(), unsafe {
+            Values::new(b"baz", Position::new(0, 9)).assume_borrowed()
+        });
+    }
+
+    #[test]
+    fn stored_borrowed() {
+        let buffer = b"foo;";
+        let tag = unsafe { Tag::new(buffer, Position::new(0, 0)).assume_borrowed() };
+
+        let mut unstored_tag = unsafe { tag.into_stored().into_tag() };
+
+        assert_ok_eq!(unstored_tag.next(), unsafe {
+            Values::new(b"foo", Position::new(0, 1)).assume_borrowed()
+        });
+    }
+
+    #[test]
+    fn without_comments() {
+        let mut tag = Tag::new(b"foo//;bar;", Position::new(0, 0)).without_comments();
+
+        assert_ok_eq!(
+            tag.next(),
+            Values::new(b"foo//", Position::new(0, 1)).without_comments()
+        );
+        assert_ok_eq!(
+            tag.next(),
+            Values::new(b"bar", Position::new(0, 7)).without_comments()
+        );
+    }
+
+    #[test]
+    fn without_comments_assert_exhausted() {
+        let mut tag = Tag::new(b"foo;//", Position::new(0, 0)).without_comments();
+
+        assert_ok!(tag.next());
+        assert_err_eq!(
+            tag.assert_exhausted(),
+            Error::new(error::Kind::UnexpectedValues, Position::new(0, 5))
+        );
+    }
+
+    #[test]
+    fn max_parameters() {
+        let limits = Limits::new().max_parameters(2);
+        let mut tag = Tag::new(b"foo:bar:baz;", Position::new(0, 0)).with_limits(limits);
+
+        assert_err_eq!(
+            tag.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::Parameters),
+                Position::new(0, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn max_parameters_across_parameter_lists() {
+        let limits = Limits::new().max_parameters(3);
+        let mut tag = Tag::new(b"foo:bar;baz:qux;", Position::new(0, 0)).with_limits(limits);
+
+        assert_ok_eq!(tag.next(), Values::new(b"foo:bar", Position::new(0, 1)));
+        assert_err_eq!(
+            tag.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::Parameters),
+                Position::new(0, 9)
+            )
+        );
+    }
+
+    #[test]
+    fn max_parameters_ignores_escaped_colon() {
+        let limits = Limits::new().max_parameters(2);
+        let mut tag = Tag::new(b"foo:bar\\:baz;", Position::new(0, 0)).with_limits(limits);
+
+        assert_ok_eq!(
+            tag.next(),
+            Values::new(b"foo:bar\\:baz", Position::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn max_parameters_reset() {
+        let limits = Limits::new().max_parameters(2);
+        let mut tag = Tag::new(b"foo:bar;", Position::new(0, 0)).with_limits(limits);
+
+        assert_ok!(tag.next());
+        tag.reset();
+
+        assert_ok!(tag.next());
+    }
 }
@@ -1,21 +1,54 @@
 use super::Tag;
-use crate::de::{error, parse::StoredTag, Error, Position, Result};
-use std::io::{Bytes, Read};
+use crate::{
+    de::{
+        error, parse::StoredTag, read::private::Scratch, DeserializerOptions, DuplicateTags, Error,
+        Limit, Position, Read, Result, UnknownTags,
+    },
+    Encoding,
+};
+use std::{collections::VecDeque, slice};
 
 enum State {
     None,
     MaybeEnteringComment,
     InComment,
     Escaping,
+    // Within a two-byte character, whose second byte is never special.
+    Trailing,
+}
+
+// A tag read ahead of time by `Tags::read_ahead()`.
+//
+// The referenced bytes are either borrowed from the input or owned by the `Tags` that read them.
+#[derive(Debug)]
+pub(in crate::de) struct BufferedTag {
+    byte_ptr: *const u8,
+    byte_len: usize,
+
+    position: Position,
+}
+
+// Tags read ahead of time, returned in place of the remaining input.
+#[derive(Debug)]
+struct Replay {
+    tags: VecDeque<BufferedTag>,
+    // The position at the end of the input.
+    end_position: Position,
 }
 
 #[derive(Debug)]
 pub(in crate::de) struct Tags<R> {
-    reader: Bytes<R>,
+    reader: R,
+
+    scratch: Scratch,
 
-    buffer: Vec<u8>,
+    options: DeserializerOptions,
+    // The encoding of the input, or `None` if it has not been detected yet.
+    encoding: Option<Encoding>,
 
     first_tag: bool,
+    // The number of tags read from the input, counted against the configured limits.
+    tags_read: usize,
 
     current_position: Position,
 
@@ -23,19 +56,37 @@ pub(in crate::de) struct Tags<R> {
     exhausted: bool,
 
     revisit: Option<StoredTag>,
+
+    // The number of spans being recorded. While nonzero, the end positions of the last tag
+    // returned and of the one returned before it are tracked.
+    spans: usize,
+    consumed_end: Position,
+    previous_end: Position,
+
+    // Copies of tags read ahead of time from input that cannot be borrowed.
+    owned: Vec<Vec<u8>>,
+    replay: Option<Replay>,
 }
 
-impl<R> Tags<R>
+impl<'de, R> Tags<R>
 where
-    R: Read,
+    R: Read<'de>,
 {
     pub(in crate::de) fn new(reader: R) -> Self {
+        Self::with_options(reader, DeserializerOptions::default())
+    }
+
+    pub(in crate::de) fn with_options(reader: R, options: DeserializerOptions) -> Self {
         Self {
-            reader: reader.bytes(),
+            reader,
 
-            buffer: Vec::with_capacity(1024),
+            scratch: Scratch::default(),
+
+            encoding: options.get_encoding(),
+            options,
 
             first_tag: true,
+            tags_read: 0,
 
             current_position: Position::new(0, 0),
 
@@ -43,6 +94,13 @@ where
             exhausted: false,
 
             revisit: None,
+
+            spans: 0,
+            consumed_end: Position::new(0, 0),
+            previous_end: Position::new(0, 0),
+
+            owned: Vec::new(),
+            replay: None,
         }
     }
 
@@ -54,72 +112,114 @@ where
         }
 
         let mut state = State::None;
+        let dialect = self.options.get_dialect();
+        let byte_limit = self.byte_limit(None);
 
         if self.first_tag {
             self.first_tag = false;
             loop {
-                let byte = match self.reader.next() {
-                    Some(byte) => match byte {
-                        Ok(byte) => byte,
-                        Err(_error) => {
-                            let error = Error::new(error::Kind::Io, self.current_position);
-                            self.encountered_error = Some(error.clone());
-                            self.exhausted = true;
-                            break Err(error);
-                        }
-                    },
-                    None => {
+                let bytes = match self.reader.fill(&mut self.scratch) {
+                    Ok(bytes) => bytes,
+                    Err(error) => {
+                        let error = Error::io(error, self.current_position);
+                        self.encountered_error = Some(error.clone());
                         self.exhausted = true;
-                        break Ok(());
+                        break Err(error);
                     }
                 };
+                if bytes.is_empty() {
+                    self.exhausted = true;
+                    break Ok(());
+                }
 
-                match state {
-                    State::None => {
-                        match byte {
-                            b'#' => {
-                                break Ok(());
+                let mut index = 0;
+                while index < bytes.len() {
+                    let available = match byte_limit {
+                        Some((end, limit)) => match end.checked_sub(self.current_position.offset())
+                        {
+                            Some(0) | None => {
+                                let error = Error::new(
+                                    error::Kind::LimitExceeded(limit),
+                                    self.current_position,
+                                );
+                                self.encountered_error = Some(error.clone());
+                                return Err(error);
                             }
+                            Some(available) => available,
+                        },
+                        None => usize::MAX,
+                    };
+                    if let State::InComment = state {
+                        // Skip to the end of the comment in bulk.
+                        let skipped = bytes[index..]
+                            .iter()
+                            .position(|&byte| byte == b'\n')
+                            .unwrap_or(bytes.len() - index)
+                            .min(available);
+                        if skipped > 0 {
+                            self.current_position = self.current_position.advance_columns(skipped);
+                            index += skipped;
+                            continue;
+                        }
+                    }
+
+                    let byte = bytes[index];
+                    match state {
+                        State::None => {
+                            match byte {
+                                b'#' => {
+                                    // Consume the `#`, leaving the current position on it.
+                                    self.reader.consume(&mut self.scratch, index + 1);
+                                    return Ok(());
+                                }
+                                b'/' if dialect.recognizes_comments() => {
+                                    state = State::MaybeEnteringComment;
+                                }
+                                _ => {
+                                    // Non-whitespace bytes are not allowed before the first tag,
+                                    // unless the dialect ignores them.
+                                    if !byte.is_ascii_whitespace()
+                                        && !dialect.skips_leading_garbage()
+                                    {
+                                        let error = Error::new(
+                                            error::Kind::ExpectedTag,
+                                            self.current_position,
+                                        );
+                                        self.encountered_error = Some(error.clone());
+                                        return Err(error);
+                                    }
+                                }
+                            }
+                        }
+                        State::MaybeEnteringComment => match byte {
                             b'/' => {
-                                state = State::MaybeEnteringComment;
+                                state = State::InComment;
                             }
                             _ => {
-                                // Non-whitespace bytes are not allowed before the first tag.
-                                if !byte.is_ascii_whitespace() {
-                                    let error =
-                                        Error::new(error::Kind::ExpectedTag, self.current_position);
-                                    self.encountered_error = Some(error.clone());
-                                    break Err(error);
-                                }
+                                let error = Error::new(
+                                    error::Kind::ExpectedTag,
+                                    self.current_position.decrement_column(),
+                                );
+                                self.encountered_error = Some(error.clone());
+                                return Err(error);
+                            }
+                        },
+                        State::InComment => {
+                            // Consume bytes until we are on a new line.
+                            if matches!(byte, b'\n') {
+                                state = State::None;
                             }
                         }
                     }
-                    State::MaybeEnteringComment => match byte {
-                        b'/' => {
-                            state = State::InComment;
-                        }
-                        _ => {
-                            let error = Error::new(
-                                error::Kind::ExpectedTag,
-                                self.current_position.decrement_column(),
-                            );
-                            self.encountered_error = Some(error.clone());
-                            break Err(error);
-                        }
-                    },
-                    State::InComment => {
-                        // Consume bytes until we are on a new line.
-                        if matches!(byte, b'\n') {
-                            state = State::None;
-                        }
-                    }
-                }
 
-                if matches!(byte, b'\n') {
-                    self.current_position = self.current_position.increment_line();
-                } else {
-                    self.current_position = self.current_position.increment_column();
+                    if matches!(byte, b'\n') {
+                        self.current_position = self.current_position.increment_line();
+                    } else {
+                        self.current_position = self.current_position.increment_column();
+                    }
+                    index += 1;
                 }
+                self.reader.consume(&mut self.scratch, index);
             }
         } else {
             // Already found the first tag.
@@ -133,7 +233,22 @@ where
     /// return items that have a shorter lifetime than the iterator itself. Each `Tag` returned
     /// here only lives until the next call to `next()` because it borrows from a reused internal
     /// buffer.
-    pub(in crate::de) fn next(&mut self) -> Result<Tag> {
+    pub(in crate::de) fn next(&mut self) -> Result<Tag<'_>> {
+        if self.spans > 0 {
+            // The tag's end must be found now, as its bytes may be overwritten by the time the
+            // span is complete.
+            let tag = self.read_next()?;
+            let end = tag.end_position();
+            let stored = tag.into_stored();
+            self.previous_end = self.consumed_end;
+            self.consumed_end = end;
+            // Return the same tag by revisiting it.
+            self.revisit = Some(stored);
+        }
+        self.read_next()
+    }
+
+    fn read_next(&mut self) -> Result<Tag<'_>> {
         if let Some(error) = &self.encountered_error {
             return Err(error.clone());
         }
@@ -146,9 +261,34 @@ where
             );
         }
 
+        if let Some(replay) = &mut self.replay {
+            return match replay.tags.pop_front() {
+                Some(buffered) => {
+                    self.exhausted = replay.tags.is_empty();
+                    // Leave the current position on the next tag's `#`, as when reading normally.
+                    self.current_position = replay
+                        .tags
+                        .front()
+                        .map_or(replay.end_position, |next| next.position);
+                    // SAFETY: `buffered` references either the input or `self.owned`, both of
+                    // which outlive the returned `Tag`.
+                    let bytes =
+                        unsafe { slice::from_raw_parts(buffered.byte_ptr, buffered.byte_len) };
+                    let encoding = self.encoding.unwrap_or_default();
+                    Ok(self.configure(Tag::new(bytes, buffered.position).with_encoding(encoding)))
+                }
+                None => {
+                    let error = Error::new(error::Kind::EndOfFile, self.current_position);
+                    self.encountered_error = Some(error.clone());
+                    Err(error)
+                }
+            };
+        }
+
         let mut state = State::None;
         let mut end_of_values = false;
         let mut starting_new_line = false;
+        let dialect = self.options.get_dialect();
 
         // Find the first tag, if necessary.
         if self.first_tag {
@@ -158,112 +298,329 @@ where
                 self.encountered_error = Some(error.clone());
                 return Err(error);
             }
+        } else if self.exhausted {
+            // The end of the input is not another tag, so it must not count towards the limit
+            // below. The position is reported just as if the end had been found while reading.
+            let error = Error::new(
+                error::Kind::EndOfFile,
+                self.current_position.increment_column(),
+            );
+            self.encountered_error = Some(error.clone());
+            return Err(error);
+        }
+
+        self.tags_read += 1;
+        if matches!(self.options.get_limits().get_max_tags(), Some(max) if self.tags_read > max) {
+            let error = Error::new(
+                error::Kind::LimitExceeded(Limit::Tags),
+                self.current_position,
+            );
+            self.encountered_error = Some(error.clone());
+            return Err(error);
         }
 
         // Reuse the same buffer.
-        self.buffer.clear();
+        self.reader.start_tag(&mut self.scratch);
+        let mut empty = true;
 
         let started_position = self.current_position;
+        let byte_limit = self.byte_limit(Some(started_position));
         // Consume the `#` that has already been encountered.
         self.current_position = self.current_position.increment_column();
 
         loop {
-            let byte = match self.reader.next() {
-                Some(byte) => match byte {
-                    Ok(byte) => byte,
-                    Err(_error) => {
-                        let error = Error::new(error::Kind::Io, self.current_position);
-                        self.encountered_error = Some(error.clone());
-                        self.exhausted = true;
-                        return Err(error);
-                    }
-                },
-                None => {
+            let bytes = match self.reader.fill(&mut self.scratch) {
+                Ok(bytes) => bytes,
+                Err(error) => {
+                    let error = Error::io(error, self.current_position);
+                    self.encountered_error = Some(error.clone());
                     self.exhausted = true;
-                    if self.buffer.is_empty() {
-                        let error = Error::new(error::Kind::EndOfFile, self.current_position);
-                        self.encountered_error = Some(error.clone());
-                        return Err(error);
-                    } else {
-                        return Ok(Tag::new(&self.buffer, started_position));
-                    }
+                    return Err(error);
                 }
             };
+            if bytes.is_empty() {
+                self.exhausted = true;
+                if empty {
+                    let error = Error::new(error::Kind::EndOfFile, self.current_position);
+                    self.encountered_error = Some(error.clone());
+                    return Err(error);
+                } else if !end_of_values && !dialect.recovers_missing_terminator() {
+                    let error =
+                        Error::new(error::Kind::MissingTagTerminator, self.current_position);
+                    self.encountered_error = Some(error.clone());
+                    return Err(error);
+                } else {
+                    return Ok(self.tag(started_position));
+                }
+            }
 
-            // Process byte.
-            match state {
-                State::None => {
-                    match byte {
-                        b'#' => {
-                            // We are lenient on the formatting here. If a `#` is at the
-                            // start of a newline we begin a new tag and assume the
-                            // previous tag was missing the closing `;` (some old
-                            // implementations of MSD didn't explicitly require the `;`).
-                            // If we are in the middle of a line, we assume it was meant to
-                            // be escaped.
-                            if starting_new_line || end_of_values {
-                                // Entering a new tag. Return the previous one.
-                                return Ok(Tag::new(&self.buffer, started_position));
-                            }
+            let mut index = 0;
+            let mut found_next_tag = false;
+            while index < bytes.len() {
+                // A limit is only exceeded if the next byte belongs to this tag, rather than
+                // beginning the next one.
+                let (available, exceeded) = match byte_limit {
+                    Some((end, limit)) => match end.checked_sub(self.current_position.offset()) {
+                        Some(0) | None => (0, Some(limit)),
+                        Some(available) => (available, None),
+                    },
+                    None => (usize::MAX, None),
+                };
+
+                // Skip over bytes that can't change the state in bulk.
+                let encoding = self.encoding;
+                let skipped = match state {
+                    State::None => {
+                        let skipped = bytes[index..]
+                            .iter()
+                            .position(|&byte| {
+                                matches!(byte, b'#' | b';' | b'\\' | b'/' | b'\n')
+                                    || match encoding {
+                                        Some(encoding) => encoding.is_lead_byte(byte),
+                                        None => !byte.is_ascii(),
+                                    }
+                            })
+                            .unwrap_or(bytes.len() - index);
+                        if bytes[index..index + skipped]
+                            .iter()
+                            .any(|byte| !byte.is_ascii_whitespace())
+                        {
                             end_of_values = false;
                         }
+                        skipped
+                    }
+                    State::InComment => bytes[index..]
+                        .iter()
+                        .position(|&byte| byte == b'\n')
+                        .unwrap_or(bytes.len() - index),
+                    State::MaybeEnteringComment | State::Escaping | State::Trailing => 0,
+                }
+                .min(available);
+                if skipped > 0 {
+                    self.current_position = self.current_position.advance_columns(skipped);
+                    starting_new_line = false;
+                    index += skipped;
+                    continue;
+                }
+
+                // Process byte.
+                let byte = bytes[index];
+                if self.encoding.is_none() && !byte.is_ascii() {
+                    // Detection is deferred until the input contains bytes that could be in
+                    // another encoding. It must happen before scanning any further, since the
+                    // encoding determines which bytes end this tag.
+                    self.encoding = Some(Encoding::detect(&bytes[index..]));
+                }
+                let lead = self
+                    .encoding
+                    .map_or(false, |encoding| encoding.is_lead_byte(byte));
+                match state {
+                    State::None => {
+                        match byte {
+                            b'#' => {
+                                // Depending on the dialect, we may be lenient on the formatting
+                                // here. If a `#` is at the start of a newline we begin a new tag
+                                // and assume the previous tag was missing the closing `;` (some
+                                // old implementations of MSD didn't explicitly require the `;`).
+                                // If we are in the middle of a line, we assume it was meant to
+                                // be escaped.
+                                if end_of_values
+                                    || (starting_new_line && dialect.recovers_missing_terminator())
+                                {
+                                    // Entering a new tag. Return the previous one.
+                                    found_next_tag = true;
+                                    break;
+                                }
+                                let kind = if starting_new_line {
+                                    Some(error::Kind::MissingTagTerminator)
+                                } else if !dialect.allows_unescaped_number_sign() {
+                                    Some(error::Kind::UnescapedNumberSign)
+                                } else {
+                                    None
+                                };
+                                if let Some(kind) = kind {
+                                    let error = Error::new(kind, self.current_position);
+                                    self.encountered_error = Some(error.clone());
+                                    return Err(error);
+                                }
+                                end_of_values = false;
+                            }
+                            b';' => {
+                                end_of_values = true;
+                            }
+                            b'\\' => {
+                                state = State::Escaping;
+                                end_of_values = false;
+                            }
+                            b'/' if dialect.recognizes_comments() => {
+                                state = State::MaybeEnteringComment;
+                            }
+                            _ if lead => {
+                                state = State::Trailing;
+                                end_of_values = false;
+                            }
+                            _ => {
+                                if !byte.is_ascii_whitespace() {
+                                    end_of_values = false;
+                                }
+                            }
+                        }
+                    }
+                    State::MaybeEnteringComment => match byte {
                         b';' => {
                             end_of_values = true;
+                            state = State::None;
                         }
                         b'\\' => {
                             state = State::Escaping;
                             end_of_values = false;
                         }
                         b'/' => {
-                            state = State::MaybeEnteringComment;
+                            state = State::InComment;
+                        }
+                        _ if lead => {
+                            state = State::Trailing;
+                            end_of_values = false;
                         }
                         _ => {
-                            if !byte.is_ascii_whitespace() {
-                                end_of_values = false;
-                            }
+                            state = State::None;
+                            end_of_values = false;
+                        }
+                    },
+                    State::InComment => {
+                        // Consume bytes until we are on a new line.
+                        if matches!(byte, b'\n') {
+                            state = State::None;
                         }
                     }
-                }
-                State::MaybeEnteringComment => match byte {
-                    b';' => {
-                        end_of_values = true;
-                        state = State::None;
-                    }
-                    b'\\' => {
-                        state = State::Escaping;
+                    State::Escaping => {
+                        // The second byte of a two-byte character cannot be escaped by itself.
+                        state = if lead { State::Trailing } else { State::None };
                         end_of_values = false;
                     }
-                    b'/' => {
-                        state = State::InComment;
-                    }
-                    _ => {
+                    State::Trailing => {
                         state = State::None;
                         end_of_values = false;
                     }
-                },
-                State::InComment => {
-                    // Consume bytes until we are on a new line.
-                    if matches!(byte, b'\n') {
-                        state = State::None;
-                    }
                 }
-                State::Escaping => {
-                    state = State::None;
-                    end_of_values = false;
+                if let Some(limit) = exceeded {
+                    let error =
+                        Error::new(error::Kind::LimitExceeded(limit), self.current_position);
+                    self.encountered_error = Some(error.clone());
+                    return Err(error);
+                }
+
+                if matches!(byte, b'\n') {
+                    self.current_position = self.current_position.increment_line();
+                    starting_new_line = true;
+                } else {
+                    self.current_position = self.current_position.increment_column();
+                    starting_new_line = false;
                 }
+                index += 1;
             }
-            self.buffer.push(byte);
 
-            if matches!(byte, b'\n') {
-                self.current_position = self.current_position.increment_line();
-                starting_new_line = true;
-            } else {
-                self.current_position = self.current_position.increment_column();
-                starting_new_line = false;
+            self.reader.push_tag(&mut self.scratch, index);
+            if index > 0 {
+                empty = false;
             }
+            if found_next_tag {
+                // Consume the `#` beginning the next tag, leaving the current position on it.
+                self.reader.consume(&mut self.scratch, 1);
+                return Ok(self.tag(started_position));
+            }
+        }
+    }
+
+    // Returns the next tag, or `None` at the end of the input.
+    //
+    // Unlike `next()`, only reaching the end of the input ends iteration. Any other error, such as
+    // a syntax error, an exceeded limit or a failing reader, is returned.
+    pub(in crate::de) fn next_or_end(&mut self) -> Result<Option<Tag<'_>>> {
+        match self.next() {
+            Ok(tag) => Ok(Some(tag)),
+            Err(error) if matches!(error.kind(), error::Kind::EndOfFile) => Ok(None),
+            Err(error) => Err(error),
         }
     }
 
+    fn tag(&mut self, position: Position) -> Tag<'_> {
+        let bytes = self.reader.tag(&self.scratch);
+        // Detection is deferred until a tag contains bytes that could be in another encoding.
+        let encoding = match self.encoding {
+            Some(encoding) => encoding,
+            None if bytes.is_ascii() => Encoding::Utf8,
+            None => *self.encoding.insert(Encoding::detect(bytes)),
+        };
+        self.configure(Tag::new(bytes, position).with_encoding(encoding))
+    }
+
+    fn configure<'a>(&self, mut tag: Tag<'a>) -> Tag<'a> {
+        if R::BORROWED {
+            // SAFETY: The tag's bytes are borrowed from the input, which lives for `'de`.
+            tag = unsafe { tag.assume_borrowed() };
+        }
+        if !self.options.get_dialect().recognizes_comments() {
+            tag = tag.without_comments();
+        }
+        if self.options.get_case_insensitive() {
+            tag = tag.ignoring_case();
+        }
+        if self.options.get_empty_as_none() {
+            tag = tag.treating_empty_as_none();
+        }
+        if self.options.get_trim_strings() {
+            tag = tag.trimming_strings();
+        }
+        tag.with_limits(self.options.get_limits())
+    }
+
+    // Returns the offset of the first byte exceeding a limit on the size of the input, along with
+    // the limit it exceeds. If `tag_position` is given, the size of the tag starting there is
+    // limited as well.
+    fn byte_limit(&self, tag_position: Option<Position>) -> Option<(usize, Limit)> {
+        let limits = self.options.get_limits();
+        let tag = tag_position.and_then(|position| {
+            limits
+                .get_max_tag_size()
+                // Skip the tag's leading `#`.
+                .map(|max| {
+                    (
+                        position.offset().saturating_add(1).saturating_add(max),
+                        Limit::TagSize,
+                    )
+                })
+        });
+        let total = limits
+            .get_max_total_bytes()
+            .map(|max| (max, Limit::TotalBytes));
+        match (tag, total) {
+            (Some(tag), Some(total)) if total.0 < tag.0 => Some(total),
+            (Some(tag), _) => Some(tag),
+            (None, total) => total,
+        }
+    }
+
+    pub(in crate::de) fn duplicate_tags(&self) -> Option<DuplicateTags> {
+        self.options.get_duplicate_tags()
+    }
+
+    pub(in crate::de) fn unknown_tags(&self) -> Option<UnknownTags> {
+        self.options.get_unknown_tags()
+    }
+
+    pub(in crate::de) fn empty_as_none(&self) -> bool {
+        self.options.get_empty_as_none()
+    }
+
+    pub(in crate::de) fn nested_structs(&self) -> Option<char> {
+        self.options.get_nested_structs()
+    }
+
+    pub(in crate::de) fn current_position(&self) -> Position {
+        self.current_position
+    }
+
     /// Returns whether there will be another tag.
     ///
     /// If this returns `true`, then a call to `next()` will return `Some(tag)`.
@@ -279,17 +636,98 @@ where
         }
     }
 
+    // Reads every remaining tag ahead of time, along with a name for each as determined by `name`.
+    //
+    // The tags will not be returned by `next()` until they are passed to `replay()`, which allows
+    // them to be reordered first.
+    pub(in crate::de) fn read_ahead<F, T>(&mut self, mut name: F) -> Result<Vec<(BufferedTag, T)>>
+    where
+        F: FnMut(Tag<'_>) -> T,
+    {
+        let mut buffered = Vec::new();
+        while self.revisit.is_some() || self.has_next()? {
+            let mut tag = self.next()?;
+            tag.reset();
+            let position = tag.origin_position();
+            let bytes = tag.bytes();
+            // Bytes borrowed from the input remain valid, but bytes in the reused buffer must be
+            // copied.
+            let owned = if R::BORROWED {
+                None
+            } else {
+                Some(bytes.to_vec())
+            };
+            let (byte_ptr, byte_len) = owned
+                .as_ref()
+                .map_or((bytes.as_ptr(), bytes.len()), |owned| {
+                    (owned.as_ptr(), owned.len())
+                });
+            let name = name(tag);
+            if let Some(owned) = owned {
+                // Moving the `Vec` does not move its contents, so `byte_ptr` remains valid.
+                self.owned.push(owned);
+            }
+            buffered.push((
+                BufferedTag {
+                    byte_ptr,
+                    byte_len,
+                    position,
+                },
+                name,
+            ));
+        }
+        Ok(buffered)
+    }
+
+    // Returns `tags` from `next()` in place of the remaining input.
+    //
+    // `tags` must have been returned by `read_ahead()` on this struct.
+    pub(in crate::de) fn replay(&mut self, tags: Vec<BufferedTag>) {
+        let end_position = self.current_position;
+        self.exhausted = tags.is_empty();
+        if let Some(first) = tags.first() {
+            self.current_position = first.position;
+        }
+        self.replay = Some(Replay {
+            tags: tags.into(),
+            end_position,
+        });
+    }
+
     // SAFETY: `tag` must reference this struct's buffer.
     pub(in crate::de) unsafe fn revisit(&mut self, tag: StoredTag) {
+        // The revisited tag has not been consumed after all.
+        self.consumed_end = self.previous_end;
         self.revisit = Some(tag)
     }
 
+    // Begins recording the span of a value whose source so far ends at `end`.
+    pub(in crate::de) fn begin_span(&mut self, end: Position) {
+        self.spans += 1;
+        self.consumed_end = end;
+        self.previous_end = end;
+    }
+
+    // Completes the span begun by the last call to `begin_span()`, returning the position just
+    // past the last tag consumed since.
+    pub(in crate::de) fn end_span(&mut self) -> Position {
+        self.spans -= 1;
+        self.consumed_end
+    }
+
     pub(in crate::de) fn assert_exhausted(&self) -> Result<()> {
         if let Some(revisit) = self.revisit.as_ref() {
             Err(Error::new(
                 error::Kind::UnexpectedTag,
                 revisit.origin_position(),
             ))
+        } else if let Some(error) = self
+            .encountered_error
+            .as_ref()
+            .filter(|error| !matches!(error.kind(), error::Kind::EndOfFile))
+        {
+            // Reading stopped early, so report why, even if the input was consumed entirely.
+            Err(error.clone())
         } else if self.exhausted {
             Ok(())
         } else {
@@ -318,8 +756,15 @@ where
 #[cfg(test)]
 mod tests {
     use super::Tags;
-    use crate::de::{error, parse::Tag, Error, Position};
-    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use crate::{
+        de::{
+            error, parse::Tag, DeserializerOptions, Dialect, Error, Limit, Limits, Position,
+            SliceRead,
+        },
+        Encoding,
+    };
+    use claims::{assert_err, assert_err_eq, assert_none, assert_ok, assert_ok_eq, assert_some_eq};
+    use std::{io, io::Read};
 
     #[test]
     fn empty_reader() {
@@ -589,6 +1034,86 @@ mod tests {
         assert_ok_eq!(tags.next(), Tag::new(b"foo;\n", Position::new(0, 0)));
     }
 
+    #[test]
+    fn span() {
+        let input = b"#foo;\n#bar:baz;\n#qux;\n";
+        let mut tags = Tags::new(input.as_slice());
+
+        assert_ok!(tags.next());
+        tags.begin_span(Position::new(0, 4));
+        assert_ok!(tags.next());
+        let tag = assert_ok!(tags.next()).into_stored();
+        unsafe { tags.revisit(tag) };
+        assert_eq!(tags.end_span(), Position::from_parts(1, 9, 15));
+        assert_ok_eq!(tags.next(), Tag::new(b"qux;\n", Position::new(2, 0)));
+    }
+
+    #[test]
+    fn read_ahead() {
+        let input = b"#foo;\n#bar;\n";
+        let mut tags = Tags::new(input.as_slice());
+
+        let buffered = assert_ok!(tags.read_ahead(|tag| Some(format!("{:?}", tag.bytes()))));
+
+        assert_eq!(
+            buffered
+                .into_iter()
+                .map(|(_, name)| name)
+                .collect::<Vec<_>>(),
+            vec![
+                Some(format!("{:?}", b"foo;\n")),
+                Some(format!("{:?}", b"bar;\n"))
+            ]
+        );
+        assert_ok!(tags.assert_exhausted());
+    }
+
+    #[test]
+    fn read_ahead_includes_revisit() {
+        let input = b"#foo;\n#bar;\n";
+        let mut tags = Tags::new(input.as_slice());
+
+        let tag = assert_ok!(tags.next()).into_stored();
+        unsafe { tags.revisit(tag) };
+        let buffered = assert_ok!(tags.read_ahead(|_| ()));
+
+        assert_eq!(buffered.len(), 2);
+    }
+
+    #[test]
+    fn replay_reordered() {
+        let input = b"#foo;\n#bar;\n#baz;\n";
+        let mut tags = Tags::new(input.as_slice());
+
+        let mut buffered = assert_ok!(tags.read_ahead(|_| ()))
+            .into_iter()
+            .map(|(tag, _)| tag)
+            .collect::<Vec<_>>();
+        buffered.swap(0, 2);
+        buffered.remove(1);
+        tags.replay(buffered);
+
+        assert_ok_eq!(tags.next(), Tag::new(b"baz;\n", Position::new(2, 0)));
+        assert_ok_eq!(tags.next(), Tag::new(b"foo;\n", Position::new(0, 0)));
+        assert_ok!(tags.assert_exhausted());
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::EndOfFile, Position::new(3, 0))
+        );
+    }
+
+    #[test]
+    fn replay_empty() {
+        let input = b"#foo;\n";
+        let mut tags = Tags::new(input.as_slice());
+
+        assert_ok!(tags.read_ahead(|_| ()));
+        tags.replay(Vec::new());
+
+        assert_ok!(tags.assert_exhausted());
+        assert!(!assert_ok!(tags.has_next()));
+    }
+
     #[test]
     fn exhausted() {
         let input = b"#foo;\n";
@@ -625,4 +1150,465 @@ mod tests {
             Error::new(error::Kind::UnexpectedTag, Position::new(0, 0))
         );
     }
+
+    #[test]
+    fn slice_borrowed() {
+        let mut tags = Tags::new(SliceRead::new(b"#foo;\n#bar;\n"));
+
+        assert_ok_eq!(tags.next(), unsafe {
+            Tag::new(b"foo;\n", Position::new(0, 0)).assume_borrowed()
+        });
+        assert_ok_eq!(tags.next(), unsafe {
+            Tag::new(b"bar;\n", Position::new(1, 0)).assume_borrowed()
+        });
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::EndOfFile, Position::new(2, 1))
+        );
+    }
+
+    #[test]
+    fn slice_missing_semicolon() {
+        let mut tags = Tags::new(SliceRead::new(b"#foo\n#bar"));
+
+        assert_ok_eq!(tags.next(), unsafe {
+            Tag::new(b"foo\n", Position::new(0, 0)).assume_borrowed()
+        });
+        assert_ok_eq!(tags.next(), unsafe {
+            Tag::new(b"bar", Position::new(1, 0)).assume_borrowed()
+        });
+    }
+
+    /// Returns at most one byte from each call to `read()`, splitting the input across as many
+    /// chunks as possible.
+    struct ByteAtATime<'a>(&'a [u8]);
+
+    impl Read for ByteAtATime<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(&mut buf[..1])
+        }
+    }
+
+    #[test]
+    fn split_across_reads() {
+        let mut tags = Tags::new(ByteAtATime(
+            b"// comment\n#foo:b\\;ar// baz;\n;\n#qux\n#quux;",
+        ));
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo:b\\;ar// baz;\n;\n", Position::new(1, 0))
+        );
+        assert_ok_eq!(tags.next(), Tag::new(b"qux\n", Position::new(3, 0)));
+        assert_ok_eq!(tags.next(), Tag::new(b"quux;", Position::new(4, 0)));
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::EndOfFile, Position::new(4, 7))
+        );
+    }
+
+    #[test]
+    fn split_across_reads_character_before_first_tag() {
+        let mut tags = Tags::new(ByteAtATime(b"// comment\n  /foo"));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::ExpectedTag, Position::new(1, 2))
+        );
+    }
+
+    #[test]
+    fn larger_than_read_buffer() {
+        let mut input = b"#foo:".to_vec();
+        input.extend(std::iter::repeat(b'a').take(20000));
+        input.extend(b";\n#bar;\n");
+        let mut tags = Tags::new(input.as_slice());
+
+        let mut expected = b"foo:".to_vec();
+        expected.extend(std::iter::repeat(b'a').take(20000));
+        expected.extend(b";\n");
+        assert_ok_eq!(tags.next(), Tag::new(&expected, Position::new(0, 0)));
+        assert_ok_eq!(tags.next(), Tag::new(b"bar;\n", Position::new(1, 0)));
+    }
+
+    #[test]
+    fn io_error() {
+        struct FailingReader;
+
+        impl Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::from(io::ErrorKind::Other))
+            }
+        }
+
+        let mut tags = Tags::new(FailingReader);
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::Io(io::ErrorKind::Other), Position::new(0, 0))
+        );
+    }
+
+    fn options(dialect: Dialect) -> DeserializerOptions {
+        DeserializerOptions::new().dialect(dialect)
+    }
+
+    #[test]
+    fn strict_well_formed() {
+        let mut tags = Tags::with_options(
+            b"// comment\n#foo:bar;#baz\\#;\n".as_slice(),
+            options(Dialect::Strict),
+        );
+
+        assert_ok_eq!(tags.next(), Tag::new(b"foo:bar;", Position::new(1, 0)));
+        assert_ok_eq!(tags.next(), Tag::new(b"baz\\#;\n", Position::new(1, 9)));
+    }
+
+    #[test]
+    fn strict_missing_terminator() {
+        let mut tags =
+            Tags::with_options(b"#foo:bar\n#baz;\n".as_slice(), options(Dialect::Strict));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(1, 0))
+        );
+        // The error is repeated.
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn strict_missing_terminator_at_end_of_file() {
+        let mut tags = Tags::with_options(b"#foo:bar\n".as_slice(), options(Dialect::Strict));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn strict_missing_terminator_at_end_of_file_not_exhausted() {
+        let mut tags =
+            Tags::with_options(b"#foo:bar;\n#baz:qux".as_slice(), options(Dialect::Strict));
+
+        assert_ok!(tags.next());
+        assert_err!(tags.next());
+        assert_err_eq!(
+            tags.assert_exhausted(),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(1, 8))
+        );
+    }
+
+    #[test]
+    fn next_or_end() {
+        let mut tags = Tags::new(b"#foo;\n".as_slice());
+
+        assert_some_eq!(
+            assert_ok!(tags.next_or_end()),
+            Tag::new(b"foo;\n", Position::new(0, 0))
+        );
+        assert_none!(assert_ok!(tags.next_or_end()));
+    }
+
+    #[test]
+    fn next_or_end_error() {
+        let mut tags =
+            Tags::with_options(b"#foo:bar;\n#baz:qux".as_slice(), options(Dialect::Strict));
+
+        assert_ok!(tags.next_or_end());
+        assert_err_eq!(
+            tags.next_or_end(),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(1, 8))
+        );
+    }
+
+    #[test]
+    fn strict_terminator_followed_by_comment() {
+        let mut tags = Tags::with_options(
+            b"#foo:bar; // comment\n".as_slice(),
+            options(Dialect::Strict),
+        );
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo:bar; // comment\n", Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn strict_unescaped_number_sign() {
+        let mut tags = Tags::with_options(b"#foo:bar#baz;\n".as_slice(), options(Dialect::Strict));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::UnescapedNumberSign, Position::new(0, 8))
+        );
+    }
+
+    #[test]
+    fn strict_character_before_first_tag() {
+        let mut tags = Tags::with_options(b"foo#bar;\n".as_slice(), options(Dialect::Strict));
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::ExpectedTag, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn dwi_character_before_first_tag() {
+        let mut tags = Tags::with_options(b"foo\nbar#baz;\n".as_slice(), options(Dialect::Dwi));
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"baz;\n", Position::new(1, 3)).without_comments()
+        );
+    }
+
+    #[test]
+    fn dwi_ignores_comments() {
+        let mut tags = Tags::with_options(
+            b"#foo:bar//baz;\n#qux// ;\n".as_slice(),
+            options(Dialect::Dwi),
+        );
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo:bar//baz;\n", Position::new(0, 0)).without_comments()
+        );
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"qux// ;\n", Position::new(1, 0)).without_comments()
+        );
+    }
+
+    #[test]
+    fn dwi_missing_terminator() {
+        let mut tags = Tags::with_options(b"#foo:bar\n#baz;\n".as_slice(), options(Dialect::Dwi));
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo:bar\n", Position::new(0, 0)).without_comments()
+        );
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"baz;\n", Position::new(1, 0)).without_comments()
+        );
+    }
+
+    #[test]
+    fn explicit_encoding() {
+        let mut tags = Tags::with_options(
+            b"#foo:bar;\n".as_slice(),
+            DeserializerOptions::new().encoding(Encoding::Windows1252),
+        );
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo:bar;\n", Position::new(0, 0)).with_encoding(Encoding::Windows1252)
+        );
+    }
+
+    #[test]
+    fn detect_encoding() {
+        let mut tags = Tags::with_options(
+            b"#foo:bar;\n#TITLE:\x93\x8c\x95\xfb\x97\x64\x81\x58\x96\xb2;\n#baz;\n".as_slice(),
+            DeserializerOptions::new().detect_encoding(),
+        );
+
+        assert_ok_eq!(tags.next(), Tag::new(b"foo:bar;\n", Position::new(0, 0)));
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(
+                b"TITLE:\x93\x8c\x95\xfb\x97\x64\x81\x58\x96\xb2;\n",
+                Position::new(1, 0)
+            )
+            .with_encoding(Encoding::ShiftJis)
+        );
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"baz;\n", Position::new(2, 0)).with_encoding(Encoding::ShiftJis)
+        );
+    }
+
+    #[test]
+    fn shift_jis_trailing_backslash() {
+        let mut tags = Tags::with_options(
+            b"#\x83\x5c;\n#foo;\n".as_slice(),
+            DeserializerOptions::new().encoding(Encoding::ShiftJis),
+        );
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"\x83\x5c;\n", Position::new(0, 0)).with_encoding(Encoding::ShiftJis)
+        );
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo;\n", Position::new(1, 0)).with_encoding(Encoding::ShiftJis)
+        );
+    }
+
+    #[test]
+    fn max_tag_size() {
+        let limits = Limits::new().max_tag_size(5);
+        let mut tags = Tags::with_options(
+            b"#foo;\n#barbaz;\n".as_slice(),
+            DeserializerOptions::new().limits(limits),
+        );
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo;\n", Position::new(0, 0)).with_limits(limits)
+        );
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TagSize),
+                Position::new(1, 6)
+            )
+        );
+    }
+
+    #[test]
+    fn max_tag_size_byte_at_a_time() {
+        let mut tags = Tags::with_options(
+            ByteAtATime(b"#foo:bar;\n"),
+            DeserializerOptions::new().limits(Limits::new().max_tag_size(4)),
+        );
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TagSize),
+                Position::new(0, 5)
+            )
+        );
+    }
+
+    #[test]
+    fn max_tag_size_borrowed() {
+        let mut tags = Tags::with_options(
+            SliceRead::new(b"#foo:bar;\n"),
+            DeserializerOptions::new().limits(Limits::new().max_tag_size(4)),
+        );
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TagSize),
+                Position::new(0, 5)
+            )
+        );
+    }
+
+    #[test]
+    fn max_tags() {
+        let limits = Limits::new().max_tags(1);
+        let mut tags = Tags::with_options(
+            b"#foo;\n#bar;\n".as_slice(),
+            DeserializerOptions::new().limits(limits),
+        );
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo;\n", Position::new(0, 0)).with_limits(limits)
+        );
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::LimitExceeded(Limit::Tags), Position::new(1, 0))
+        );
+        // The error is repeated.
+        assert_err_eq!(
+            tags.next(),
+            Error::new(error::Kind::LimitExceeded(Limit::Tags), Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn not_exhausted_after_error() {
+        let mut tags = Tags::with_options(
+            b"#foo;\n#bar;\n".as_slice(),
+            DeserializerOptions::new().limits(Limits::new().max_tags(1)),
+        );
+
+        assert_ok!(tags.next());
+        assert_err!(tags.next());
+
+        assert_err_eq!(
+            tags.assert_exhausted(),
+            Error::new(error::Kind::LimitExceeded(Limit::Tags), Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn max_tags_revisit_not_counted() {
+        let limits = Limits::new().max_tags(1);
+        let mut tags = Tags::with_options(
+            b"#foo;\n".as_slice(),
+            DeserializerOptions::new().limits(limits),
+        );
+
+        let tag = assert_ok!(tags.next()).into_stored();
+        unsafe { tags.revisit(tag) };
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo;\n", Position::new(0, 0)).with_limits(limits)
+        );
+    }
+
+    #[test]
+    fn max_total_bytes() {
+        let limits = Limits::new().max_total_bytes(8);
+        let mut tags = Tags::with_options(
+            b"#foo;\n#bar;\n".as_slice(),
+            DeserializerOptions::new().limits(limits),
+        );
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo;\n", Position::new(0, 0)).with_limits(limits)
+        );
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TotalBytes),
+                Position::new(1, 2)
+            )
+        );
+    }
+
+    #[test]
+    fn max_total_bytes_before_first_tag() {
+        let mut tags = Tags::with_options(
+            b"// comment\n#foo;\n".as_slice(),
+            DeserializerOptions::new().limits(Limits::new().max_total_bytes(4)),
+        );
+
+        assert_err_eq!(
+            tags.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::TotalBytes),
+                Position::new(0, 4)
+            )
+        );
+    }
+
+    #[test]
+    fn max_total_bytes_exact() {
+        let limits = Limits::new().max_total_bytes(6);
+        let mut tags = Tags::with_options(
+            b"#foo;\n".as_slice(),
+            DeserializerOptions::new().limits(limits),
+        );
+
+        assert_ok_eq!(
+            tags.next(),
+            Tag::new(b"foo;\n", Position::new(0, 0)).with_limits(limits)
+        );
+    }
 }
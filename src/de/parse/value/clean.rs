@@ -1,13 +1,38 @@
+use crate::{de::parse::Separators, Encoding};
+
 /// Cleans values, removing comments and interpreting escape sequences.
 
 #[derive(Clone)]
 pub(super) struct Clean<'a> {
     bytes: &'a [u8],
+    comments: bool,
+    // Additional bytes that may be escaped.
+    separators: Separators,
+    // The encoding of `bytes`, determining which bytes belong to two-byte characters.
+    encoding: Encoding,
+    // Whether the next byte is the second byte of a two-byte character.
+    trailing: bool,
 }
 
 impl<'a> Clean<'a> {
-    pub(super) fn new(bytes: &'a [u8]) -> Self {
-        Self { bytes }
+    pub(super) fn new(bytes: &'a [u8], comments: bool) -> Self {
+        Self {
+            bytes,
+            comments,
+            separators: Separators::default(),
+            encoding: Encoding::Utf8,
+            trailing: false,
+        }
+    }
+
+    pub(super) fn with_separators(mut self, separators: Separators) -> Self {
+        self.separators = separators;
+        self
+    }
+
+    pub(super) fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
     }
 }
 
@@ -19,6 +44,11 @@ impl<'a> Iterator for Clean<'a> {
         // SAFETY: If `self.bytes` is empty, it will have returned in the previous statement.
         self.bytes = unsafe { self.bytes.get_unchecked(1..) };
 
+        if self.trailing {
+            self.trailing = false;
+            return Some(byte);
+        }
+
         match byte {
             b'\\' => {
                 // Possibly escaping the next byte.
@@ -30,12 +60,17 @@ impl<'a> Iterator for Clean<'a> {
                             self.bytes = unsafe { self.bytes.get_unchecked(1..) };
                             Some(next_byte)
                         }
+                        _ if self.separators.contains(next_byte) => {
+                            // SAFETY: We can only be in this branch is self.bytes is nonempty.
+                            self.bytes = unsafe { self.bytes.get_unchecked(1..) };
+                            Some(next_byte)
+                        }
                         _ => Some(byte),
                     },
                     None => Some(byte),
                 }
             }
-            b'/' => {
+            b'/' if self.comments => {
                 // Possibly entering a comment.
                 match self.bytes.first().copied() {
                     Some(b'/') => {
@@ -53,6 +88,10 @@ impl<'a> Iterator for Clean<'a> {
                     _ => Some(byte),
                 }
             }
+            _ if self.encoding.is_lead_byte(byte) => {
+                self.trailing = true;
+                Some(byte)
+            }
             _ => Some(byte),
         }
     }
@@ -60,40 +99,44 @@ impl<'a> Iterator for Clean<'a> {
 
 #[cfg(test)]
 mod tests {
-    use super::Clean;
+    use super::{Clean, Separators};
+    use crate::Encoding;
 
     #[test]
     fn empty() {
-        assert_eq!(Clean::new(b"").collect::<Vec<_>>(), b"");
+        assert_eq!(Clean::new(b"", true).collect::<Vec<_>>(), b"");
     }
 
     #[test]
     fn plain() {
-        assert_eq!(Clean::new(b"foo").collect::<Vec<_>>(), b"foo");
+        assert_eq!(Clean::new(b"foo", true).collect::<Vec<_>>(), b"foo");
     }
 
     #[test]
     fn escaped() {
         assert_eq!(
-            Clean::new(b"\\/foo\\\\bar\\#baz\\;qux\\:").collect::<Vec<_>>(),
+            Clean::new(b"\\/foo\\\\bar\\#baz\\;qux\\:", true).collect::<Vec<_>>(),
             b"/foo\\bar#baz;qux:"
         );
     }
 
     #[test]
     fn not_escaped() {
-        assert_eq!(Clean::new(b"foo\\bar").collect::<Vec<_>>(), b"foo\\bar");
+        assert_eq!(
+            Clean::new(b"foo\\bar", true).collect::<Vec<_>>(),
+            b"foo\\bar"
+        );
     }
 
     #[test]
     fn backslash_at_end() {
-        assert_eq!(Clean::new(b"foo\\").collect::<Vec<_>>(), b"foo\\");
+        assert_eq!(Clean::new(b"foo\\", true).collect::<Vec<_>>(), b"foo\\");
     }
 
     #[test]
     fn comment() {
         assert_eq!(
-            Clean::new(b"foo // bar\nbaz").collect::<Vec<_>>(),
+            Clean::new(b"foo // bar\nbaz", true).collect::<Vec<_>>(),
             b"foo \nbaz"
         );
     }
@@ -101,18 +144,60 @@ mod tests {
     #[test]
     fn escaped_comment() {
         assert_eq!(
-            Clean::new(b"foo /\\/ bar\nbaz").collect::<Vec<_>>(),
+            Clean::new(b"foo /\\/ bar\nbaz", true).collect::<Vec<_>>(),
             b"foo // bar\nbaz"
         );
     }
 
     #[test]
     fn forward_slash_by_itself() {
-        assert_eq!(Clean::new(b"foo/bar").collect::<Vec<_>>(), b"foo/bar");
+        assert_eq!(Clean::new(b"foo/bar", true).collect::<Vec<_>>(), b"foo/bar");
     }
 
     #[test]
     fn forward_slash_at_end() {
-        assert_eq!(Clean::new(b"foo/").collect::<Vec<_>>(), b"foo/");
+        assert_eq!(Clean::new(b"foo/", true).collect::<Vec<_>>(), b"foo/");
+    }
+
+    #[test]
+    fn comments_disabled() {
+        assert_eq!(
+            Clean::new(b"foo//bar\\//baz", false).collect::<Vec<_>>(),
+            b"foo//bar//baz"
+        );
+    }
+
+    #[test]
+    fn escaped_separators() {
+        let mut separators = Separators::default();
+        separators.insert(b',');
+        separators.insert(b'=');
+
+        assert_eq!(
+            Clean::new(b"foo\\,bar\\=baz\\|", true)
+                .with_separators(separators)
+                .collect::<Vec<_>>(),
+            b"foo,bar=baz\\|"
+        );
+    }
+
+    #[test]
+    fn shift_jis_trailing_backslash() {
+        assert_eq!(
+            Clean::new(b"\x83\\;\\\\", true)
+                .with_encoding(Encoding::ShiftJis)
+                .collect::<Vec<_>>(),
+            b"\x83\\;\\"
+        );
+    }
+
+    #[test]
+    fn shift_jis_escaped_lead_byte() {
+        assert_eq!(
+            Clean::new(b"\\\x83\\", true)
+                .with_encoding(Encoding::ShiftJis)
+                .collect::<Vec<_>>(),
+            b"\\\x83\\"
+        );
     }
 }
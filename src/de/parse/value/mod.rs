@@ -1,13 +1,16 @@
 mod clean;
 mod trim;
 
-use super::utf8_char_width::utf8_char_width;
-use crate::de::{error, Error, Position, Result};
+use super::{utf8_char_width::utf8_char_width, Separators, Values};
+use crate::{
+    de::{error, Error, Position, Result},
+    Encoding,
+};
 use arrayvec::ArrayVec;
 use clean::Clean;
 use either::Either;
 use num_traits::{Float, PrimInt, Signed};
-use std::str;
+use std::{borrow::Cow, str};
 use trim::Trim;
 
 fn parse_ident<I>(mut value_bytes: I, ident: &[u8]) -> bool
@@ -300,23 +303,172 @@ where
     Some(value)
 }
 
+// Removes leading and trailing ASCII whitespace from `bytes`.
+fn trim_whitespace(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    let end = bytes
+        .iter()
+        .rposition(|byte| !byte.is_ascii_whitespace())
+        .map_or(start, |index| index + 1);
+    &bytes[start..end]
+}
+
 #[derive(Debug, PartialEq)]
 pub(in crate::de) struct Value<'a> {
     bytes: &'a [u8],
     position: Position,
+
+    // Whether `bytes` is borrowed directly from the input, rather than from a reused buffer.
+    borrowed: bool,
+    // Whether `//` begins a comment.
+    comments: bool,
+    // Whether identifiers match expected names regardless of ASCII case.
+    ignore_case: bool,
+    // Whether an empty parameter within a tuple or sequence is `None`.
+    empty_as_none: bool,
+    // Whether this value is `Some`, in which case a leading `\0` marks it as not `None`.
+    some_marker: bool,
+    // Whether leading and trailing whitespace is removed from strings.
+    trim_strings: bool,
+    // The encoding used to decode text.
+    encoding: Encoding,
+    // The separators this value was split by, which may be escaped within it.
+    separators: Separators,
 }
 
 impl<'a> Value<'a> {
     pub(in crate::de) fn new(bytes: &'a [u8], position: Position) -> Self {
-        Self { bytes, position }
+        Self {
+            bytes,
+            position,
+
+            borrowed: false,
+            comments: true,
+            ignore_case: false,
+            empty_as_none: false,
+            some_marker: false,
+            trim_strings: false,
+            encoding: Encoding::Utf8,
+            separators: Separators::default(),
+        }
+    }
+
+    pub(in crate::de) fn without_comments(mut self) -> Self {
+        self.comments = false;
+        self
+    }
+
+    pub(in crate::de) fn ignoring_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    pub(in crate::de) fn treating_empty_as_none(mut self) -> Self {
+        self.empty_as_none = true;
+        self
+    }
+
+    pub(in crate::de) fn marked_as_some(mut self) -> Self {
+        self.some_marker = true;
+        self
+    }
+
+    pub(in crate::de) fn trimming_strings(mut self) -> Self {
+        self.trim_strings = true;
+        self
+    }
+
+    pub(in crate::de) fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub(in crate::de) fn with_separators(mut self, separators: Separators) -> Self {
+        self.separators = separators;
+        self
+    }
+
+    fn clean(&self) -> Clean<'a> {
+        let mut bytes = self.bytes;
+        if self.some_marker {
+            // Skip the `\0` marking a value that would otherwise be empty.
+            let start = bytes
+                .iter()
+                .position(|byte| !byte.is_ascii_whitespace())
+                .unwrap_or(bytes.len());
+            if let Some(remaining) = bytes[start..].strip_prefix(b"\\0") {
+                bytes = remaining;
+            }
+        }
+        Clean::new(bytes, self.comments)
+            .with_separators(self.separators)
+            .with_encoding(self.encoding)
+    }
+
+    // Whether this value contains only whitespace and comments, and is therefore `None` when
+    // empty values are treated as `None`.
+    pub(in crate::de) fn is_empty(&self) -> bool {
+        Clean::new(self.bytes, self.comments)
+            .with_encoding(self.encoding)
+            .all(|byte| byte.is_ascii_whitespace())
+    }
+
+    // # Safety
+    // The caller must guarantee that `bytes` lives for the entire lifetime `'de` of the input
+    // being deserialized.
+    pub(in crate::de) unsafe fn assume_borrowed(mut self) -> Self {
+        self.borrowed = true;
+        self
     }
 
     pub(in crate::de) fn position(&self) -> Position {
         self.position
     }
 
+    // Splits this value into the values separated by `separator` within it.
+    pub(in crate::de) fn split(&self, separator: u8) -> Values<'a> {
+        let mut values = Values::new(self.bytes, self.position)
+            .with_separators(self.separators)
+            .with_separator(separator);
+        if self.borrowed {
+            // SAFETY: `self.bytes` is borrowed from the input.
+            values = unsafe { values.assume_borrowed() };
+        }
+        if !self.comments {
+            values = values.without_comments();
+        }
+        if self.ignore_case {
+            values = values.ignoring_case();
+        }
+        if self.empty_as_none {
+            values = values.treating_empty_as_none();
+        }
+        if self.trim_strings {
+            values = values.trimming_strings();
+        }
+        values.with_encoding(self.encoding)
+    }
+
+    // Returns the position just past the last byte of this value.
+    pub(in crate::de) fn end_position(&self) -> Position {
+        self.bytes.iter().fold(self.position, |position, byte| {
+            if matches!(byte, b'\n') {
+                position.increment_line()
+            } else {
+                position.increment_column()
+            }
+        })
+    }
+
+    pub(in crate::de) fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
     pub(in crate::de) fn parse_bool(&self) -> Result<bool> {
-        let mut value = Trim::new(Clean::new(self.bytes));
+        let mut value = Trim::new(self.clean());
         match value
             .next()
             .ok_or_else(|| Error::new(error::Kind::ExpectedBool, self.position))?
@@ -340,69 +492,73 @@ impl<'a> Value<'a> {
     }
 
     pub(in crate::de) fn parse_i8(&self) -> Result<i8> {
-        parse_signed_integer(Trim::new(Clean::new(self.bytes)))
+        parse_signed_integer(Trim::new(self.clean()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedI8, self.position))
     }
 
     pub(in crate::de) fn parse_i16(&self) -> Result<i16> {
-        parse_signed_integer(Trim::new(Clean::new(self.bytes)))
+        parse_signed_integer(Trim::new(self.clean()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedI16, self.position))
     }
 
     pub(in crate::de) fn parse_i32(&self) -> Result<i32> {
-        parse_signed_integer(Trim::new(Clean::new(self.bytes)))
+        parse_signed_integer(Trim::new(self.clean()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedI32, self.position))
     }
 
     pub(in crate::de) fn parse_i64(&self) -> Result<i64> {
-        parse_signed_integer(Trim::new(Clean::new(self.bytes)))
+        parse_signed_integer(Trim::new(self.clean()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedI64, self.position))
     }
 
     #[cfg(has_i128)]
     pub(in crate::de) fn parse_i128(&self) -> Result<i128> {
-        parse_signed_integer(Trim::new(Clean::new(self.bytes)))
+        parse_signed_integer(Trim::new(self.clean()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedI128, self.position))
     }
 
     pub(in crate::de) fn parse_u8(&self) -> Result<u8> {
-        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)))
+        parse_unsigned_integer(Trim::new(self.clean()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedU8, self.position))
     }
 
     pub(in crate::de) fn parse_u16(&self) -> Result<u16> {
-        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)))
+        parse_unsigned_integer(Trim::new(self.clean()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedU16, self.position))
     }
 
     pub(in crate::de) fn parse_u32(&self) -> Result<u32> {
-        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)))
+        parse_unsigned_integer(Trim::new(self.clean()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedU32, self.position))
     }
 
     pub(in crate::de) fn parse_u64(&self) -> Result<u64> {
-        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)))
+        parse_unsigned_integer(Trim::new(self.clean()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedU64, self.position))
     }
 
     #[cfg(has_i128)]
     pub(in crate::de) fn parse_u128(&self) -> Result<u128> {
-        parse_unsigned_integer(Trim::new(Clean::new(self.bytes)))
+        parse_unsigned_integer(Trim::new(self.clean()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedU128, self.position))
     }
 
     pub(in crate::de) fn parse_f32(&self) -> Result<f32> {
-        parse_float(Trim::new(Clean::new(self.bytes)).map(|b| b.to_ascii_lowercase()))
+        parse_float(Trim::new(self.clean()).map(|b| b.to_ascii_lowercase()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedF32, self.position))
     }
 
     pub(in crate::de) fn parse_f64(&self) -> Result<f64> {
-        parse_float(Trim::new(Clean::new(self.bytes)).map(|b| b.to_ascii_lowercase()))
+        parse_float(Trim::new(self.clean()).map(|b| b.to_ascii_lowercase()))
             .ok_or_else(|| Error::new(error::Kind::ExpectedF64, self.position))
     }
 
     pub(in crate::de) fn parse_char(&self) -> Result<char> {
-        let cleaned = Clean::new(self.bytes);
+        if self.encoding != Encoding::Utf8 {
+            return self.parse_encoded_char();
+        }
+
+        let cleaned = self.clean();
         // Try to trim.
         let mut value = Either::Left(Trim::new(cleaned.clone()));
         let first_byte = if let Some(byte) = value.next() {
@@ -453,18 +609,111 @@ impl<'a> Value<'a> {
         }
     }
 
+    // Parses a char that is not encoded as UTF-8.
+    //
+    // The whole value must be decoded before its characters can be counted.
+    fn parse_encoded_char(&self) -> Result<char> {
+        let decoded = self
+            .encoding
+            .decode_owned(self.clean().collect())
+            .ok_or_else(|| Error::new(error::Kind::ExpectedChar, self.position))?;
+        let trimmed = decoded.trim_matches(|c: char| c.is_ascii_whitespace());
+        let mut chars = if trimmed.is_empty() {
+            decoded.chars()
+        } else {
+            trimmed.chars()
+        };
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(Error::new(error::Kind::ExpectedChar, self.position)),
+        }
+    }
+
     pub(in crate::de) fn parse_string(&self) -> Result<String> {
-        String::from_utf8(Clean::new(self.bytes).collect::<Vec<u8>>())
-            .map_err(|_| Error::new(error::Kind::ExpectedString, self.position))
+        let bytes = if self.needs_cleaning() {
+            if self.trim_strings {
+                Trim::new(self.clean()).collect()
+            } else {
+                self.clean().collect()
+            }
+        } else if self.trim_strings {
+            // Copy the bytes in bulk, as there is nothing to clean.
+            trim_whitespace(self.bytes).to_vec()
+        } else {
+            self.bytes.to_vec()
+        };
+        self.encoding
+            .decode_owned(bytes)
+            .ok_or_else(|| Error::new(error::Kind::ExpectedString, self.position))
     }
 
     pub(in crate::de) fn parse_byte_buf(&self) -> Vec<u8> {
-        Clean::new(self.bytes).collect()
+        if self.needs_cleaning() {
+            self.clean().collect()
+        } else {
+            self.bytes.to_vec()
+        }
+    }
+
+    // Whether the value contains anything that would need to be cleaned, meaning escape sequences
+    // or comments.
+    fn needs_cleaning(&self) -> bool {
+        self.bytes.contains(&b'\\')
+            || (self.comments && self.bytes.windows(2).any(|window| window == b"//"))
+    }
+
+    // Returns the value's bytes for the lifetime of the input, if they can be used as-is.
+    //
+    // This is only possible if the value is borrowed from the input and needs no cleaning.
+    //
+    // # Safety
+    // `'de` must be the lifetime of the input being deserialized.
+    unsafe fn borrowed_bytes<'de>(&self) -> Option<&'de [u8]> {
+        if self.borrowed && !self.needs_cleaning() {
+            // SAFETY: Since `bytes` is borrowed from the input, it lives for `'de`, as
+            // guaranteed by the caller.
+            Some(unsafe { &*(self.bytes as *const [u8]) })
+        } else {
+            None
+        }
+    }
+
+    // Parses a string, borrowing from the input if possible.
+    //
+    // # Safety
+    // `'de` must be the lifetime of the input being deserialized.
+    pub(in crate::de) unsafe fn parse_str<'de>(&self) -> Result<Cow<'de, str>> {
+        // SAFETY: The lifetime is guaranteed by the caller.
+        match unsafe { self.borrowed_bytes() } {
+            Some(bytes) => {
+                let bytes = if self.trim_strings {
+                    trim_whitespace(bytes)
+                } else {
+                    bytes
+                };
+                self.encoding
+                    .decode(bytes)
+                    .ok_or_else(|| Error::new(error::Kind::ExpectedString, self.position))
+            }
+            None => self.parse_string().map(Cow::Owned),
+        }
+    }
+
+    // Parses bytes, borrowing from the input if possible.
+    //
+    // # Safety
+    // `'de` must be the lifetime of the input being deserialized.
+    pub(in crate::de) unsafe fn parse_bytes<'de>(&self) -> Cow<'de, [u8]> {
+        // SAFETY: The lifetime is guaranteed by the caller.
+        match unsafe { self.borrowed_bytes() } {
+            Some(bytes) => Cow::Borrowed(bytes),
+            None => Cow::Owned(self.parse_byte_buf()),
+        }
     }
 
     pub(in crate::de) fn parse_unit(&self) -> Result<()> {
         // A unit must contain only whitespace and comments.
-        if Clean::new(self.bytes).all(|b| b.is_ascii_whitespace()) {
+        if self.clean().all(|b| b.is_ascii_whitespace()) {
             Ok(())
         } else {
             Err(Error::new(error::Kind::ExpectedUnit, self.position))
@@ -472,16 +721,101 @@ impl<'a> Value<'a> {
     }
 
     pub(in crate::de) fn parse_identifier(&self) -> Result<String> {
-        String::from_utf8(Trim::new(Clean::new(self.bytes)).collect::<Vec<u8>>())
-            .map_err(|_| Error::new(error::Kind::ExpectedIdentifier, self.position))
+        self.encoding
+            .decode_owned(Trim::new(self.clean()).collect())
+            .ok_or_else(|| Error::new(error::Kind::ExpectedIdentifier, self.position))
+    }
+
+    // Parses an identifier that is expected to be one of `names`.
+    //
+    // When ignoring case, an identifier matching one of `names` except for ASCII case is returned
+    // as that name. Exact matches are always preferred.
+    pub(in crate::de) fn parse_identifier_in(&self, names: &[&str]) -> Result<String> {
+        let identifier = self.parse_identifier()?;
+        Ok(self
+            .find_name(&identifier, names)
+            .map_or(identifier, ToOwned::to_owned))
+    }
+
+    // Parses an identifier naming a field, which is expected to be one of `names`.
+    //
+    // When `separator` is given, an identifier naming a field of a struct nested within one of
+    // `names`, such as `timing.offset`, is returned as that name.
+    pub(in crate::de) fn parse_field_in(
+        &self,
+        names: &[&str],
+        separator: Option<char>,
+    ) -> Result<String> {
+        let identifier = self.parse_identifier()?;
+        Ok(self
+            .find_field(&identifier, names, separator)
+            .map_or(identifier, ToOwned::to_owned))
+    }
+
+    // Parses an identifier naming a field of a nested struct, whose tag names all begin with
+    // `prefix`.
+    //
+    // The identifier is interpreted as in `parse_field_in()` after `prefix` is removed. Returns
+    // `None` if the identifier does not begin with `prefix`.
+    pub(in crate::de) fn parse_nested_field_in(
+        &self,
+        prefix: &str,
+        names: &[&str],
+        separator: Option<char>,
+    ) -> Result<Option<String>> {
+        let identifier = self.parse_identifier()?;
+        let rest = match identifier.get(..prefix.len()) {
+            Some(start)
+                if start == prefix || (self.ignore_case && start.eq_ignore_ascii_case(prefix)) =>
+            {
+                &identifier[prefix.len()..]
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(
+            self.find_field(rest, names, separator)
+                .unwrap_or(rest)
+                .to_owned(),
+        ))
+    }
+
+    // Finds the name within `names` matching `identifier`, preferring exact matches.
+    fn find_name<'b>(&self, identifier: &str, names: &[&'b str]) -> Option<&'b str> {
+        names
+            .iter()
+            .find(|name| **name == identifier)
+            .or_else(|| {
+                names
+                    .iter()
+                    .find(|name| self.ignore_case && name.eq_ignore_ascii_case(identifier))
+            })
+            .copied()
+    }
+
+    // Finds the field within `names` named by `identifier`, either directly or by the portion
+    // before `separator`.
+    fn find_field<'b>(
+        &self,
+        identifier: &str,
+        names: &[&'b str],
+        separator: Option<char>,
+    ) -> Option<&'b str> {
+        self.find_name(identifier, names).or_else(|| {
+            let (head, _) = identifier.split_once(separator?)?;
+            self.find_name(head, names)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Value;
-    use crate::de::{error, Error, Position};
+    use crate::{
+        de::{error, Error, Position},
+        Encoding,
+    };
     use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use std::borrow::Cow;
 
     #[test]
     fn get_position() {
@@ -490,6 +824,27 @@ mod tests {
         assert_eq!(value.position(), Position::new(1, 2));
     }
 
+    #[test]
+    fn end_position() {
+        let value = Value::new(b"foo", Position::new(1, 2));
+
+        assert_eq!(value.end_position(), Position::new(1, 5));
+    }
+
+    #[test]
+    fn end_position_multiple_lines() {
+        let value = Value::new(b"foo\nbar", Position::new(1, 2));
+
+        assert_eq!(value.end_position(), Position::new(2, 3));
+    }
+
+    #[test]
+    fn get_bytes() {
+        let value = Value::new(b"foo\\:bar", Position::new(0, 0));
+
+        assert_eq!(value.bytes(), b"foo\\:bar");
+    }
+
     #[test]
     fn parse_bool_true() {
         let value = Value::new(b"true", Position::new(0, 0));
@@ -1272,6 +1627,20 @@ mod tests {
         assert_ok_eq!(value.parse_string(), "foo\n\nbar".to_owned(),);
     }
 
+    #[test]
+    fn parse_string_trimmed() {
+        let value = Value::new(b"\n   foo\nbar \n", Position::new(0, 0)).trimming_strings();
+
+        assert_ok_eq!(value.parse_string(), "foo\nbar".to_owned());
+    }
+
+    #[test]
+    fn parse_string_untrimmed() {
+        let value = Value::new(b"\n   foo\nbar \n", Position::new(0, 0));
+
+        assert_ok_eq!(value.parse_string(), "\n   foo\nbar \n".to_owned());
+    }
+
     #[test]
     fn parse_string_fails() {
         let value = Value::new(b"\xF0\x9Ffoo", Position::new(0, 0));
@@ -1303,6 +1672,99 @@ mod tests {
         assert_eq!(value.parse_byte_buf(), b"foo\n\nbar",);
     }
 
+    #[test]
+    fn parse_str_borrowed() {
+        let value = unsafe { Value::new(b"foo", Position::new(0, 0)).assume_borrowed() };
+
+        assert_ok_eq!(unsafe { value.parse_str() }, Cow::Borrowed("foo"));
+    }
+
+    #[test]
+    fn parse_str_borrowed_trimmed() {
+        let value = unsafe {
+            Value::new(b"\n   foo \n", Position::new(0, 0))
+                .trimming_strings()
+                .assume_borrowed()
+        };
+
+        assert_ok_eq!(unsafe { value.parse_str() }, Cow::Borrowed("foo"));
+    }
+
+    #[test]
+    fn parse_str_borrowed_trimmed_blank() {
+        let value = unsafe {
+            Value::new(b" \n ", Position::new(0, 0))
+                .trimming_strings()
+                .assume_borrowed()
+        };
+
+        assert_ok_eq!(unsafe { value.parse_str() }, Cow::Borrowed(""));
+    }
+
+    #[test]
+    fn parse_str_not_borrowed() {
+        let value = Value::new(b"foo", Position::new(0, 0));
+
+        assert_ok_eq!(
+            unsafe { value.parse_str() },
+            Cow::<str>::Owned("foo".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_str_borrowed_escaped() {
+        let value = unsafe { Value::new(b"f\\:oo", Position::new(0, 0)).assume_borrowed() };
+
+        assert_ok_eq!(
+            unsafe { value.parse_str() },
+            Cow::<str>::Owned("f:oo".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_str_borrowed_comment() {
+        let value = unsafe { Value::new(b"foo// bar\n", Position::new(0, 0)).assume_borrowed() };
+
+        assert_ok_eq!(
+            unsafe { value.parse_str() },
+            Cow::<str>::Owned("foo\n".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_str_borrowed_single_slash() {
+        let value = unsafe { Value::new(b"foo/bar", Position::new(0, 0)).assume_borrowed() };
+
+        assert_ok_eq!(unsafe { value.parse_str() }, Cow::Borrowed("foo/bar"));
+    }
+
+    #[test]
+    fn parse_str_borrowed_invalid() {
+        let value = unsafe { Value::new(b"\xF0\x9Ffoo", Position::new(1, 2)).assume_borrowed() };
+
+        assert_err_eq!(
+            unsafe { value.parse_str() },
+            Error::new(error::Kind::ExpectedString, Position::new(1, 2))
+        );
+    }
+
+    #[test]
+    fn parse_bytes_borrowed() {
+        let value = unsafe { Value::new(b"foo", Position::new(0, 0)).assume_borrowed() };
+
+        assert_eq!(unsafe { value.parse_bytes() }, Cow::Borrowed(b"foo"));
+    }
+
+    #[test]
+    fn parse_bytes_borrowed_escaped() {
+        let value = unsafe { Value::new(b"\\#foo", Position::new(0, 0)).assume_borrowed() };
+
+        assert_eq!(
+            unsafe { value.parse_bytes() },
+            Cow::<[u8]>::Owned(b"#foo".to_vec())
+        );
+    }
+
     #[test]
     fn parse_byte_buf_non_ascii() {
         let value = Value::new(b"\xF0\x9Ffoo", Position::new(0, 0));
@@ -1341,6 +1803,58 @@ mod tests {
         assert_ok!(value.parse_unit());
     }
 
+    #[test]
+    fn is_empty() {
+        assert!(Value::new(b"", Position::new(0, 0)).is_empty());
+        assert!(Value::new(b" \n// comment\n", Position::new(0, 0)).is_empty());
+        assert!(!Value::new(b"foo", Position::new(0, 0)).is_empty());
+        assert!(!Value::new(b"\\0", Position::new(0, 0))
+            .treating_empty_as_none()
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_string_empty_marker() {
+        let value = Value::new(b"\\0", Position::new(0, 0)).marked_as_some();
+
+        assert_ok_eq!(value.parse_string(), String::new());
+    }
+
+    #[test]
+    fn parse_string_empty_marker_leading_whitespace() {
+        let value = Value::new(b"\n   \\0  ", Position::new(0, 0)).marked_as_some();
+
+        assert_ok_eq!(value.parse_string(), "  ".to_owned());
+    }
+
+    #[test]
+    fn parse_string_empty_marker_not_marked_as_some() {
+        let value = Value::new(b"\\0", Position::new(0, 0));
+
+        assert_ok_eq!(value.parse_string(), "\\0".to_owned());
+    }
+
+    #[test]
+    fn parse_string_empty_marker_treating_empty_as_none_not_marked_as_some() {
+        let value = Value::new(b"\\0", Position::new(0, 0)).treating_empty_as_none();
+
+        assert_ok_eq!(value.parse_string(), "\\0".to_owned());
+    }
+
+    #[test]
+    fn parse_string_escaped_backslash_not_empty_marker() {
+        let value = Value::new(b"\\\\0", Position::new(0, 0)).marked_as_some();
+
+        assert_ok_eq!(value.parse_string(), "\\0".to_owned());
+    }
+
+    #[test]
+    fn parse_unit_empty_marker() {
+        let value = Value::new(b"\\0", Position::new(0, 0)).marked_as_some();
+
+        assert_ok!(value.parse_unit());
+    }
+
     #[test]
     fn parse_identifier() {
         let value = Value::new(b"foo", Position::new(0, 0));
@@ -1385,4 +1899,271 @@ mod tests {
             Error::new(error::Kind::ExpectedIdentifier, Position::new(0, 0))
         );
     }
+
+    #[test]
+    fn parse_string_without_comments() {
+        let value = Value::new(b"foo//bar", Position::new(0, 0)).without_comments();
+
+        assert_ok_eq!(value.parse_string(), "foo//bar");
+    }
+
+    #[test]
+    fn parse_str_borrowed_without_comments() {
+        let value = unsafe {
+            Value::new(b"foo//bar", Position::new(0, 0))
+                .without_comments()
+                .assume_borrowed()
+        };
+
+        assert_ok_eq!(unsafe { value.parse_str() }, Cow::Borrowed("foo//bar"));
+    }
+
+    #[test]
+    fn parse_string_shift_jis() {
+        let value =
+            Value::new(b"\x93\x8c\x95\xfb", Position::new(0, 0)).with_encoding(Encoding::ShiftJis);
+
+        assert_ok_eq!(value.parse_string(), "東方");
+    }
+
+    #[test]
+    fn parse_string_shift_jis_trailing_backslash() {
+        let value = Value::new(b"\x95\\\\;", Position::new(0, 0)).with_encoding(Encoding::ShiftJis);
+
+        assert_ok_eq!(value.parse_string(), "表;");
+    }
+
+    #[test]
+    fn parse_string_shift_jis_invalid() {
+        let value = Value::new(b"\x93", Position::new(0, 0)).with_encoding(Encoding::ShiftJis);
+
+        assert_err_eq!(
+            value.parse_string(),
+            Error::new(error::Kind::ExpectedString, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_string_windows_1252() {
+        let value =
+            Value::new(b"Caf\xe9", Position::new(0, 0)).with_encoding(Encoding::Windows1252);
+
+        assert_ok_eq!(value.parse_string(), "Café");
+    }
+
+    #[test]
+    fn parse_str_borrowed_shift_jis_ascii() {
+        let value = unsafe {
+            Value::new(b"foo", Position::new(0, 0))
+                .with_encoding(Encoding::ShiftJis)
+                .assume_borrowed()
+        };
+
+        assert_ok_eq!(unsafe { value.parse_str() }, Cow::Borrowed("foo"));
+    }
+
+    #[test]
+    fn parse_str_borrowed_shift_jis() {
+        let value = unsafe {
+            Value::new(b"\x93\x8c\x95\xfb", Position::new(0, 0))
+                .with_encoding(Encoding::ShiftJis)
+                .assume_borrowed()
+        };
+
+        assert_ok_eq!(
+            unsafe { value.parse_str() },
+            Cow::<str>::Owned("東方".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_char_shift_jis() {
+        let value =
+            Value::new(b" \x93\x8c\n", Position::new(0, 0)).with_encoding(Encoding::ShiftJis);
+
+        assert_ok_eq!(value.parse_char(), '東');
+    }
+
+    #[test]
+    fn parse_char_shift_jis_whitespace() {
+        let value = Value::new(b" ", Position::new(0, 0)).with_encoding(Encoding::ShiftJis);
+
+        assert_ok_eq!(value.parse_char(), ' ');
+    }
+
+    #[test]
+    fn parse_char_shift_jis_multiple_chars() {
+        let value =
+            Value::new(b"\x93\x8c\x95\xfb", Position::new(0, 0)).with_encoding(Encoding::ShiftJis);
+
+        assert_err_eq!(
+            value.parse_char(),
+            Error::new(error::Kind::ExpectedChar, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_identifier_windows_1252() {
+        let value =
+            Value::new(b" Caf\xe9 ", Position::new(0, 0)).with_encoding(Encoding::Windows1252);
+
+        assert_ok_eq!(value.parse_identifier(), "Café");
+    }
+
+    #[test]
+    fn parse_identifier_in() {
+        let value = Value::new(b"foo", Position::new(0, 0));
+
+        assert_ok_eq!(value.parse_identifier_in(&["foo", "bar"]), "foo");
+    }
+
+    #[test]
+    fn parse_identifier_in_case_sensitive() {
+        let value = Value::new(b"FOO", Position::new(0, 0));
+
+        assert_ok_eq!(value.parse_identifier_in(&["foo", "bar"]), "FOO");
+    }
+
+    #[test]
+    fn parse_identifier_in_ignoring_case() {
+        let value = Value::new(b" FOO ", Position::new(0, 0)).ignoring_case();
+
+        assert_ok_eq!(value.parse_identifier_in(&["foo", "bar"]), "foo");
+    }
+
+    #[test]
+    fn parse_identifier_in_ignoring_case_prefers_exact_match() {
+        let value = Value::new(b"Foo", Position::new(0, 0)).ignoring_case();
+
+        assert_ok_eq!(value.parse_identifier_in(&["foo", "Foo"]), "Foo");
+    }
+
+    #[test]
+    fn parse_identifier_in_ignoring_case_no_match() {
+        let value = Value::new(b"BAZ", Position::new(0, 0)).ignoring_case();
+
+        assert_ok_eq!(value.parse_identifier_in(&["foo", "bar"]), "BAZ");
+    }
+
+    #[test]
+    fn parse_identifier_in_ignoring_case_invalid() {
+        let value = Value::new(b"\xF0\x9Ffoo", Position::new(0, 0)).ignoring_case();
+
+        assert_err_eq!(
+            value.parse_identifier_in(&["foo"]),
+            Error::new(error::Kind::ExpectedIdentifier, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parse_field_in() {
+        let value = Value::new(b"foo", Position::new(0, 0));
+
+        assert_ok_eq!(value.parse_field_in(&["foo", "bar"], None), "foo");
+    }
+
+    #[test]
+    fn parse_field_in_nested() {
+        let value = Value::new(b"foo.baz", Position::new(0, 0));
+
+        assert_ok_eq!(value.parse_field_in(&["foo", "bar"], Some('.')), "foo");
+    }
+
+    #[test]
+    fn parse_field_in_nested_disabled() {
+        let value = Value::new(b"foo.baz", Position::new(0, 0));
+
+        assert_ok_eq!(value.parse_field_in(&["foo", "bar"], None), "foo.baz");
+    }
+
+    #[test]
+    fn parse_field_in_nested_prefers_field() {
+        let value = Value::new(b"foo_bar", Position::new(0, 0));
+
+        assert_ok_eq!(
+            value.parse_field_in(&["foo", "foo_bar"], Some('_')),
+            "foo_bar"
+        );
+    }
+
+    #[test]
+    fn parse_field_in_nested_ignoring_case() {
+        let value = Value::new(b"FOO.BAZ", Position::new(0, 0)).ignoring_case();
+
+        assert_ok_eq!(value.parse_field_in(&["foo", "bar"], Some('.')), "foo");
+    }
+
+    #[test]
+    fn parse_field_in_nested_unknown() {
+        let value = Value::new(b"baz.foo", Position::new(0, 0));
+
+        assert_ok_eq!(value.parse_field_in(&["foo", "bar"], Some('.')), "baz.foo");
+    }
+
+    #[test]
+    fn parse_nested_field_in() {
+        let value = Value::new(b"baz.foo", Position::new(0, 0));
+
+        assert_ok_eq!(
+            value.parse_nested_field_in("baz.", &["foo", "bar"], Some('.')),
+            Some("foo".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_nested_field_in_nested() {
+        let value = Value::new(b"baz.foo.qux", Position::new(0, 0));
+
+        assert_ok_eq!(
+            value.parse_nested_field_in("baz.", &["foo", "bar"], Some('.')),
+            Some("foo".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_nested_field_in_unknown() {
+        let value = Value::new(b"baz.qux", Position::new(0, 0));
+
+        assert_ok_eq!(
+            value.parse_nested_field_in("baz.", &["foo", "bar"], Some('.')),
+            Some("qux".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_nested_field_in_without_prefix() {
+        let value = Value::new(b"foo", Position::new(0, 0));
+
+        assert_ok_eq!(
+            value.parse_nested_field_in("baz.", &["foo", "bar"], Some('.')),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_nested_field_in_case_sensitive() {
+        let value = Value::new(b"BAZ.FOO", Position::new(0, 0));
+
+        assert_ok_eq!(
+            value.parse_nested_field_in("baz.", &["foo", "bar"], Some('.')),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_nested_field_in_ignoring_case() {
+        let value = Value::new(b"BAZ.FOO", Position::new(0, 0)).ignoring_case();
+
+        assert_ok_eq!(
+            value.parse_nested_field_in("baz.", &["foo", "bar"], Some('.')),
+            Some("foo".to_owned())
+        );
+    }
+
+    #[test]
+    fn parse_nested_field_in_not_char_boundary() {
+        let value = Value::new("bé".as_bytes(), Position::new(0, 0));
+
+        assert_ok_eq!(value.parse_nested_field_in("ba", &["foo"], Some('.')), None);
+    }
 }
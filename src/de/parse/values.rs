@@ -1,5 +1,8 @@
-use super::Value;
-use crate::de::{error, Error, Position, Result};
+use super::{Separators, Value};
+use crate::{
+    de::{error, Error, Limit, Position, Result},
+    Encoding,
+};
 use std::slice;
 
 enum State {
@@ -7,6 +10,8 @@ enum State {
     MaybeEnteringComment,
     InComment,
     Escaping,
+    // Within a two-byte character, whose second byte is never special.
+    Trailing,
 }
 
 #[derive(Debug)]
@@ -18,6 +23,17 @@ pub(in crate::de) struct StoredValues {
 
     current_byte_index: usize,
     current_position: Position,
+
+    borrowed: bool,
+    comments: bool,
+    ignore_case: bool,
+    empty_as_none: bool,
+    some_marker: bool,
+    trim_strings: bool,
+    encoding: Encoding,
+    max_string_length: Option<usize>,
+    separator: u8,
+    separators: Separators,
 }
 
 impl StoredValues {
@@ -34,11 +50,22 @@ impl StoredValues {
 
             current_byte_index: self.current_byte_index,
             current_position: self.current_position,
+
+            borrowed: self.borrowed,
+            comments: self.comments,
+            ignore_case: self.ignore_case,
+            empty_as_none: self.empty_as_none,
+            some_marker: self.some_marker,
+            trim_strings: self.trim_strings,
+            encoding: self.encoding,
+            max_string_length: self.max_string_length,
+            separator: self.separator,
+            separators: self.separators,
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub(in crate::de) struct Values<'a> {
     bytes: &'a [u8],
 
@@ -46,6 +73,27 @@ pub(in crate::de) struct Values<'a> {
 
     current_byte_index: usize,
     current_position: Position,
+
+    // Whether `bytes` is borrowed directly from the input, rather than from a reused buffer.
+    borrowed: bool,
+    // Whether `//` begins a comment.
+    comments: bool,
+    // Whether identifiers match expected names regardless of ASCII case.
+    ignore_case: bool,
+    // Whether an empty parameter within a tuple or sequence is `None`.
+    empty_as_none: bool,
+    // Whether the next value is known to be `Some`, and may therefore be marked with `\0`.
+    some_marker: bool,
+    // Whether leading and trailing whitespace is removed from strings.
+    trim_strings: bool,
+    // The encoding used to decode text.
+    encoding: Encoding,
+    max_string_length: Option<usize>,
+    // The byte separating each value. This is `:`, except when splitting a single value.
+    separator: u8,
+    // A set of the ASCII separators splitting these values and any enclosing values, which may be
+    // escaped within each value.
+    separators: Separators,
 }
 
 impl<'a> Values<'a> {
@@ -57,7 +105,102 @@ impl<'a> Values<'a> {
 
             current_byte_index: 0,
             current_position: position,
+
+            borrowed: false,
+            comments: true,
+            ignore_case: false,
+            empty_as_none: false,
+            some_marker: false,
+            trim_strings: false,
+            encoding: Encoding::Utf8,
+            max_string_length: None,
+            separator: b':',
+            separators: Separators::default(),
+        }
+    }
+
+    // # Safety
+    // The caller must guarantee that `bytes` lives for the entire lifetime `'de` of the input
+    // being deserialized.
+    pub(in crate::de) unsafe fn assume_borrowed(mut self) -> Self {
+        self.borrowed = true;
+        self
+    }
+
+    pub(in crate::de) fn without_comments(mut self) -> Self {
+        self.comments = false;
+        self
+    }
+
+    pub(in crate::de) fn ignoring_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    pub(in crate::de) fn treating_empty_as_none(mut self) -> Self {
+        self.empty_as_none = true;
+        self
+    }
+
+    pub(in crate::de) fn trimming_strings(mut self) -> Self {
+        self.trim_strings = true;
+        self
+    }
+
+    pub(in crate::de) fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub(in crate::de) fn with_max_string_length(mut self, max: Option<usize>) -> Self {
+        self.max_string_length = max;
+        self
+    }
+
+    pub(in crate::de) fn with_separator(mut self, separator: u8) -> Self {
+        self.separator = separator;
+        self.separators.insert(separator);
+        self
+    }
+
+    pub(in crate::de) fn with_separators(mut self, separators: Separators) -> Self {
+        self.separators = separators;
+        self
+    }
+
+    fn value(&self, bytes: &'a [u8], position: Position) -> Value<'a> {
+        let mut value = Value::new(bytes, position);
+        if self.borrowed {
+            // SAFETY: `bytes` is a subslice of these values bytes, which are borrowed from the
+            // input.
+            value = unsafe { value.assume_borrowed() };
+        }
+        if !self.comments {
+            value = value.without_comments();
+        }
+        if self.ignore_case {
+            value = value.ignoring_case();
+        }
+        if self.empty_as_none {
+            value = value.treating_empty_as_none();
+        }
+        if self.trim_strings {
+            value = value.trimming_strings();
         }
+        value
+            .with_encoding(self.encoding)
+            .with_separators(self.separators)
+    }
+
+    // Whether an empty parameter within a tuple or sequence is `None`.
+    pub(in crate::de) fn empty_as_none(&self) -> bool {
+        self.empty_as_none
+    }
+
+    // Marks the next value as `Some`, so that the `\0` distinguishing an empty value from `None`
+    // is removed from it.
+    pub(in crate::de) fn mark_next_as_some(&mut self) {
+        self.some_marker = true;
     }
 
     pub(in crate::de) fn current_position(&self) -> Position {
@@ -65,19 +208,52 @@ impl<'a> Values<'a> {
     }
 
     pub(in crate::de) fn next(&mut self) -> Result<Value<'a>> {
+        let mut value = self.parse_next()?;
+        if self.some_marker {
+            self.some_marker = false;
+            value = value.marked_as_some();
+        }
+        match self.max_string_length {
+            Some(max) if value.bytes().len() > max => Err(Error::new(
+                error::Kind::LimitExceeded(Limit::StringLength),
+                value.position(),
+            )),
+            _ => Ok(value),
+        }
+    }
+
+    fn parse_next(&mut self) -> Result<Value<'a>> {
         let mut value = None;
         let started_byte_index = self.current_byte_index;
         let started_position = self.current_position;
         let mut state = State::None;
         loop {
+            if matches!(state, State::None) {
+                // Skip over bytes that can't change the state in bulk.
+                let encoding = self.encoding;
+                let remaining = self
+                    .bytes
+                    .get(self.current_byte_index..)
+                    .unwrap_or_default();
+                let skipped = remaining
+                    .iter()
+                    .position(|&byte| {
+                        byte == self.separator
+                            || matches!(byte, b'\\' | b'/' | b'\n')
+                            || encoding.is_lead_byte(byte)
+                    })
+                    .unwrap_or(remaining.len());
+                self.current_position = self.current_position.advance_columns(skipped);
+                self.current_byte_index += skipped;
+            }
             if let Some(byte) = self.bytes.get(self.current_byte_index) {
                 // TODO: Put the parsing logic in here instead.
                 match state {
                     State::None => {
                         match byte {
-                            b':' => {
+                            byte if *byte == self.separator => {
                                 // This is the end of a `Value`.
-                                value = Some(Value::new(
+                                value = Some(self.value(
                                     // SAFETY: Both ends of the range used here have already been
                                     // determined to be within the bounds of self.bytes.
                                     unsafe {
@@ -92,17 +268,20 @@ impl<'a> Values<'a> {
                                 // Enter an escaping state.
                                 state = State::Escaping;
                             }
-                            b'/' => {
+                            b'/' if self.comments => {
                                 state = State::MaybeEnteringComment;
                             }
+                            byte if self.encoding.is_lead_byte(*byte) => {
+                                state = State::Trailing;
+                            }
                             _ => {}
                         }
                     }
                     State::MaybeEnteringComment => {
                         match byte {
-                            b':' => {
+                            byte if *byte == self.separator => {
                                 // This is the end of a `Value`.
-                                value = Some(Value::new(
+                                value = Some(self.value(
                                     // SAFETY: Both ends of the range used here have already been
                                     // determined to be within the bounds of self.bytes.
                                     unsafe {
@@ -121,6 +300,9 @@ impl<'a> Values<'a> {
                                 // Handle comment state.
                                 state = State::InComment;
                             }
+                            byte if self.encoding.is_lead_byte(*byte) => {
+                                state = State::Trailing;
+                            }
                             _ => {
                                 state = State::None;
                             }
@@ -133,6 +315,14 @@ impl<'a> Values<'a> {
                         }
                     }
                     State::Escaping => {
+                        // The second byte of a two-byte character cannot be escaped by itself.
+                        if self.encoding.is_lead_byte(*byte) {
+                            state = State::Trailing;
+                        } else {
+                            state = State::None;
+                        }
+                    }
+                    State::Trailing => {
                         state = State::None;
                     }
                 }
@@ -149,7 +339,7 @@ impl<'a> Values<'a> {
                 }
             } else if !self.exhausted {
                 self.exhausted = true;
-                return Ok(Value::new(
+                return Ok(self.value(
                     // SAFETY: self.current_byte_index is guaranteed to only be one past the
                     // last value in the slice.
                     unsafe {
@@ -164,6 +354,23 @@ impl<'a> Values<'a> {
         }
     }
 
+    // Skips any ASCII whitespace at the start of the next value, such as the line ending that
+    // precedes each parameter list after the first within a tag.
+    pub(in crate::de) fn skip_whitespace(&mut self) {
+        while let Some(byte) = self
+            .bytes
+            .get(self.current_byte_index)
+            .filter(|byte| byte.is_ascii_whitespace())
+        {
+            if matches!(byte, b'\n') {
+                self.current_position = self.current_position.increment_line();
+            } else {
+                self.current_position = self.current_position.increment_column();
+            }
+            self.current_byte_index += 1;
+        }
+    }
+
     pub(in crate::de) fn assert_exhausted(&self) -> Result<()> {
         if self.exhausted {
             Ok(())
@@ -184,6 +391,17 @@ impl<'a> Values<'a> {
 
             current_byte_index: self.current_byte_index,
             current_position: self.current_position,
+
+            borrowed: self.borrowed,
+            comments: self.comments,
+            ignore_case: self.ignore_case,
+            empty_as_none: self.empty_as_none,
+            some_marker: self.some_marker,
+            trim_strings: self.trim_strings,
+            encoding: self.encoding,
+            max_string_length: self.max_string_length,
+            separator: self.separator,
+            separators: self.separators,
         }
     }
 }
@@ -191,7 +409,7 @@ impl<'a> Values<'a> {
 #[cfg(test)]
 mod tests {
     use super::Values;
-    use crate::de::{error, parse::Value, Error, Position};
+    use crate::de::{error, parse::Value, Error, Limit, Position};
     use claims::{assert_err_eq, assert_ok, assert_ok_eq};
 
     #[test]
@@ -381,4 +599,86 @@ mod tests {
 
         assert_eq!(values.current_position(), Position::new(1, 2));
     }
+
+    #[test]
+    fn borrowed_value() {
+        let mut values = unsafe { Values::new(b"foo:bar", Position::new(0, 0)).assume_borrowed() };
+
+        assert_ok_eq!(values.next(), unsafe {
+            Value::new(b"foo", Position::new(0, 0)).assume_borrowed()
+        });
+        assert_ok_eq!(values.next(), unsafe {
+            Value::new(b"bar", Position::new(0, 4)).assume_borrowed()
+        });
+    }
+
+    #[test]
+    fn stored_borrowed() {
+        let buffer = b"foo";
+        let values = unsafe { Values::new(buffer, Position::new(0, 0)).assume_borrowed() };
+
+        let mut unstored_values = unsafe { values.into_stored().into_values() };
+
+        assert_ok_eq!(unstored_values.next(), unsafe {
+            Value::new(b"foo", Position::new(0, 0)).assume_borrowed()
+        });
+    }
+
+    #[test]
+    fn without_comments() {
+        let mut values = Values::new(b"foo//:bar", Position::new(0, 0)).without_comments();
+
+        assert_ok_eq!(
+            values.next(),
+            Value::new(b"foo//", Position::new(0, 0)).without_comments()
+        );
+        assert_ok_eq!(
+            values.next(),
+            Value::new(b"bar", Position::new(0, 6)).without_comments()
+        );
+    }
+
+    #[test]
+    fn max_string_length() {
+        let mut values =
+            Values::new(b"foo:barbaz", Position::new(0, 0)).with_max_string_length(Some(3));
+
+        assert_ok_eq!(values.next(), Value::new(b"foo", Position::new(0, 0)));
+        assert_err_eq!(
+            values.next(),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::StringLength),
+                Position::new(0, 4)
+            )
+        );
+    }
+
+    #[test]
+    fn skip_whitespace() {
+        let mut values = Values::new(b"\n  foo:bar", Position::new(0, 0));
+
+        values.skip_whitespace();
+
+        assert_ok_eq!(values.next(), Value::new(b"foo", Position::new(1, 2)));
+    }
+
+    #[test]
+    fn skip_whitespace_only_leading() {
+        let mut values = Values::new(b"foo: bar", Position::new(0, 0));
+
+        values.skip_whitespace();
+
+        assert_ok_eq!(values.next(), Value::new(b"foo", Position::new(0, 0)));
+        assert_ok_eq!(values.next(), Value::new(b" bar", Position::new(0, 4)));
+    }
+
+    #[test]
+    fn skip_whitespace_entire_value() {
+        let mut values = Values::new(b" \n ", Position::new(0, 0));
+
+        values.skip_whitespace();
+
+        assert_ok_eq!(values.next(), Value::new(b"", Position::new(1, 1)));
+        assert_ok!(values.assert_exhausted());
+    }
 }
@@ -1,26 +1,57 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub(in crate::de) struct Position {
+use std::hash::{Hash, Hasher};
+
+/// A location within MSD input.
+///
+/// Lines, columns and offsets are all zero-indexed. Columns and offsets are counted in bytes.
+///
+/// Two positions are considered equal if they are on the same line and column. Within a single
+/// input, this also means they are at the same offset.
+#[derive(Clone, Copy, Debug)]
+pub struct Position {
     line: usize,
     column: usize,
+    offset: usize,
 }
 
 impl Position {
+    // The offset is only tracked for positions reached by advancing from the start of the input,
+    // which is the only place this is called outside of tests.
     pub(in crate::de) fn new(line: usize, column: usize) -> Self {
-        Self { line, column }
+        Self {
+            line,
+            column,
+            offset: 0,
+        }
+    }
+
+    pub(in crate::de) fn from_parts(line: usize, column: usize, offset: usize) -> Self {
+        Self {
+            line,
+            column,
+            offset,
+        }
     }
 
-    pub(in crate::de) fn line(&self) -> usize {
+    /// Returns the zero-indexed line.
+    pub fn line(&self) -> usize {
         self.line
     }
 
-    pub(in crate::de) fn column(&self) -> usize {
+    /// Returns the zero-indexed column, measured in bytes from the start of the line.
+    pub fn column(&self) -> usize {
         self.column
     }
 
+    /// Returns the zero-indexed offset, measured in bytes from the start of the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
     pub(in crate::de) fn increment_line(self) -> Self {
         Self {
             line: self.line + 1,
             column: 0,
+            offset: self.offset + 1,
         }
     }
 
@@ -28,6 +59,15 @@ impl Position {
         Self {
             line: self.line,
             column: self.column + 1,
+            offset: self.offset + 1,
+        }
+    }
+
+    pub(in crate::de) fn advance_columns(self, columns: usize) -> Self {
+        Self {
+            line: self.line,
+            column: self.column + columns,
+            offset: self.offset + columns,
         }
     }
 
@@ -35,10 +75,29 @@ impl Position {
         Self {
             line: self.line,
             column: self.column - 1,
+            offset: self.offset.saturating_sub(1),
         }
     }
 }
 
+impl PartialEq for Position {
+    fn eq(&self, other: &Self) -> bool {
+        self.line == other.line && self.column == other.column
+    }
+}
+
+impl Eq for Position {}
+
+impl Hash for Position {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.line.hash(state);
+        self.column.hash(state);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Position;
@@ -57,6 +116,39 @@ mod tests {
         assert_eq!(position.column(), 7);
     }
 
+    #[test]
+    fn offset() {
+        let position = Position::new(0, 0)
+            .advance_columns(3)
+            .increment_line()
+            .increment_column();
+
+        assert_eq!(position.offset(), 5);
+    }
+
+    #[test]
+    fn from_parts() {
+        let position = Position::from_parts(1, 2, 7);
+
+        assert_eq!(position.line(), 1);
+        assert_eq!(position.column(), 2);
+        assert_eq!(position.offset(), 7);
+    }
+
+    #[test]
+    fn decrement_column_offset() {
+        let position = Position::new(0, 0).advance_columns(3).decrement_column();
+
+        assert_eq!(position.offset(), 2);
+    }
+
+    #[test]
+    fn eq_ignores_offset() {
+        let position = Position::new(0, 0).increment_line().increment_column();
+
+        assert_eq!(position, Position::new(1, 1));
+    }
+
     #[test]
     fn increment_line() {
         let position = Position::new(5, 7);
@@ -71,6 +163,13 @@ mod tests {
         assert_eq!(position.increment_column(), Position::new(5, 8));
     }
 
+    #[test]
+    fn advance_columns() {
+        let position = Position::new(5, 7);
+
+        assert_eq!(position.advance_columns(3), Position::new(5, 10));
+    }
+
     #[test]
     fn decrement_column() {
         let position = Position::new(5, 7);
@@ -0,0 +1,584 @@
+//! Low-level reading of MSD tags and parameters.
+//!
+//! While [`Deserializer`] requires a type implementing [`Deserialize`] to describe the input, a
+//! [`Reader`] exposes the structure of the input directly: a sequence of tags, each made up of
+//! one or more parameter lists, which are themselves made up of parameters. This allows walking
+//! arbitrary MSD input tag-by-tag, including tags that are not known ahead of time.
+//!
+//! Input that arrives in pieces, such as from a socket or a decompressor, can instead be pushed
+//! into a [`Parser`] as it becomes available.
+//!
+//! # Example
+//! ```
+//! use msd::de::raw::Reader;
+//!
+//! let mut reader = Reader::new(b"#TITLE:foo;\n#BPMS:0.000=120.000;\n".as_slice());
+//!
+//! let mut names = Vec::new();
+//! while let Some(mut tag) = reader.next_tag().unwrap() {
+//!     let mut parameters = tag.next().unwrap().unwrap();
+//!     names.push(parameters.next().unwrap().unwrap().parse_string().unwrap());
+//! }
+//!
+//! assert_eq!(names, vec!["TITLE", "BPMS"]);
+//! ```
+//!
+//! [`Deserializer`]: crate::Deserializer
+//! [`Deserialize`]: serde::Deserialize
+
+use crate::de::{error, parse, DeserializerOptions, Position, Read, Result};
+
+/// A pull reader over the tags of MSD input.
+///
+/// Tags are returned in the order they appear in the input. Each [`Tag`] borrows from the
+/// `Reader`'s internal buffer, and is therefore only available until the next call to
+/// [`next_tag()`].
+///
+/// [`next_tag()`]: Reader::next_tag()
+#[derive(Debug)]
+pub struct Reader<R> {
+    tags: parse::Tags<R>,
+}
+
+impl<'de, R> Reader<R>
+where
+    R: Read<'de>,
+{
+    /// Creates a new `Reader` reading from the given `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            tags: parse::Tags::new(reader),
+        }
+    }
+
+    /// Creates a new `Reader` interpreting its input according to the given `options`.
+    pub fn with_options(reader: R, options: DeserializerOptions) -> Self {
+        Self {
+            tags: parse::Tags::with_options(reader, options),
+        }
+    }
+
+    /// Returns the next tag in the input, or `None` if the input has been exhausted.
+    ///
+    /// # Errors
+    /// Returns an error if the input is not valid MSD or if the underlying reader fails. Once an
+    /// error has been returned, every subsequent call will return the same error.
+    pub fn next_tag(&mut self) -> Result<Option<Tag<'_>>> {
+        if self.tags.has_next()? {
+            self.tags.next().map(|tag| Some(Tag::new(tag)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A push parser over the tags of MSD input.
+///
+/// Where a [`Reader`] pulls its input, a `Parser` is fed input in chunks using [`feed()`] and
+/// emits each tag once it is complete. A chunk may end anywhere, including in the middle of a tag,
+/// an escape sequence, or a comment. Since a tag may contain multiple parameter lists, a tag is
+/// only known to be complete once the next tag begins, so the final tag is emitted after
+/// [`finish()`] is called.
+///
+/// # Example
+/// ```
+/// use msd::de::raw::{Event, Parser};
+///
+/// let mut parser = Parser::new();
+/// let mut names = Vec::new();
+///
+/// for chunk in [b"#TITLE:f".as_slice(), b"oo;\n#BP", b"MS:0.000=120.000;\n"] {
+///     parser.feed(chunk);
+///     while let Event::Tag(mut tag) = parser.next_event().unwrap() {
+///         let mut parameters = tag.next().unwrap().unwrap();
+///         names.push(parameters.next().unwrap().unwrap().parse_string().unwrap());
+///     }
+/// }
+/// parser.finish();
+/// while let Event::Tag(mut tag) = parser.next_event().unwrap() {
+///     let mut parameters = tag.next().unwrap().unwrap();
+///     names.push(parameters.next().unwrap().unwrap().parse_string().unwrap());
+/// }
+///
+/// assert_eq!(names, vec!["TITLE", "BPMS"]);
+/// ```
+///
+/// [`feed()`]: Parser::feed()
+/// [`finish()`]: Parser::finish()
+#[derive(Debug)]
+pub struct Parser {
+    push: parse::Push,
+}
+
+impl Parser {
+    /// Creates a new `Parser` with no input.
+    pub fn new() -> Self {
+        Self::with_options(DeserializerOptions::default())
+    }
+
+    /// Creates a new `Parser` interpreting its input according to the given `options`.
+    pub fn with_options(options: DeserializerOptions) -> Self {
+        Self {
+            push: parse::Push::new(options),
+        }
+    }
+
+    /// Appends a chunk of bytes to the input.
+    ///
+    /// # Panics
+    /// Panics if called after [`finish()`](Parser::finish()).
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.push.feed(chunk);
+    }
+
+    /// Marks the end of the input.
+    ///
+    /// After this is called, the remaining tags are emitted, followed by [`Event::End`].
+    pub fn finish(&mut self) {
+        self.push.finish();
+    }
+
+    /// Returns the next event from the input fed so far.
+    ///
+    /// A returned [`Tag`] borrows from the `Parser`'s internal buffer, and is therefore only
+    /// available until the parser is next used.
+    ///
+    /// # Errors
+    /// Returns an error if the input is not valid MSD. Once an error has been returned, every
+    /// subsequent call will return the same error.
+    pub fn next_event(&mut self) -> Result<Event<'_>> {
+        self.push.next().map(|step| match step {
+            parse::Step::Tag(tag) => Event::Tag(Tag::new(tag)),
+            parse::Step::Incomplete => Event::NeedMoreInput,
+            parse::Step::End => Event::End,
+        })
+    }
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An event emitted by a [`Parser`].
+#[derive(Debug)]
+pub enum Event<'a> {
+    /// A complete tag.
+    Tag(Tag<'a>),
+    /// No further tag can be emitted until more input is fed, or the input is finished.
+    NeedMoreInput,
+    /// The input has finished, and every tag has been emitted.
+    End,
+}
+
+/// A single tag, such as `#TAG:PARAM0:PARAM1;`.
+///
+/// A tag is an iterator over its parameter lists. Most tags contain a single parameter list,
+/// whose first parameter is the tag's name. Tags with multiple parameter lists, such as those
+/// used by `.dwi` files, separate each list with a `;`.
+///
+/// Reading a parameter list fails if it exceeds the [maximum number of parameters]. The failing
+/// parameter list is skipped, so iteration may continue past the error.
+///
+/// [maximum number of parameters]: crate::de::Limits::max_parameters()
+#[derive(Debug)]
+pub struct Tag<'a> {
+    tag: parse::Tag<'a>,
+}
+
+impl<'a> Tag<'a> {
+    fn new(tag: parse::Tag<'a>) -> Self {
+        Self { tag }
+    }
+
+    /// Returns the position of the tag's leading `#`.
+    pub fn position(&self) -> Position {
+        self.tag.origin_position()
+    }
+}
+
+impl<'a> Iterator for Tag<'a> {
+    type Item = Result<ParameterList<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.tag.next() {
+            Ok(values) => Some(Ok(ParameterList {
+                position: values.current_position(),
+                values,
+            })),
+            Err(error) if matches!(error.kind(), error::Kind::EndOfTag) => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+/// A list of `:`-separated parameters within a [`Tag`].
+///
+/// A parameter list is an iterator over its parameters. Every parameter list contains at least
+/// one parameter, although that parameter may be empty.
+///
+/// Reading a parameter fails if it exceeds the [maximum string length]. The failing parameter is
+/// skipped, so iteration may continue past the error.
+///
+/// [maximum string length]: crate::de::Limits::max_string_length()
+#[derive(Debug)]
+pub struct ParameterList<'a> {
+    values: parse::Values<'a>,
+    position: Position,
+}
+
+impl<'a> ParameterList<'a> {
+    /// Returns the position of the start of the parameter list.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+impl<'a> Iterator for ParameterList<'a> {
+    type Item = Result<Parameter<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.values.next() {
+            Ok(value) => Some(Ok(Parameter { value })),
+            Err(error) if matches!(error.kind(), error::Kind::EndOfValues) => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+/// A single parameter within a [`ParameterList`].
+#[derive(Debug)]
+pub struct Parameter<'a> {
+    value: parse::Value<'a>,
+}
+
+impl<'a> Parameter<'a> {
+    /// Returns the position of the start of the parameter.
+    pub fn position(&self) -> Position {
+        self.value.position()
+    }
+
+    /// Returns the parameter's bytes exactly as they appear in the input.
+    ///
+    /// Escape sequences and comments are left intact.
+    pub fn as_raw_bytes(&self) -> &'a [u8] {
+        self.value.bytes()
+    }
+
+    /// Returns the parameter's bytes with escape sequences interpreted and comments removed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.value.parse_byte_buf()
+    }
+
+    /// Returns the parameter as a string, with escape sequences interpreted and comments removed.
+    ///
+    /// # Errors
+    /// Returns an error if the parameter is not valid UTF-8.
+    pub fn parse_string(&self) -> Result<String> {
+        self.value.parse_string()
+    }
+
+    /// Returns the parameter as an identifier.
+    ///
+    /// This is the same as [`parse_string()`], except that leading and trailing whitespace is
+    /// removed. Tag names are commonly parsed this way.
+    ///
+    /// # Errors
+    /// Returns an error if the parameter is not valid UTF-8.
+    ///
+    /// [`parse_string()`]: Parameter::parse_string()
+    pub fn parse_identifier(&self) -> Result<String> {
+        self.value.parse_identifier()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, Parser, Reader, Tag};
+    use crate::de::{error, DeserializerOptions, Dialect, Error, Limit, Limits, Position};
+    use claims::{
+        assert_err_eq, assert_matches, assert_none, assert_ok, assert_ok_eq, assert_some,
+    };
+
+    fn parameter_lists(tag: Tag) -> Vec<Vec<String>> {
+        tag.map(|parameters| {
+            assert_ok!(parameters)
+                .map(|parameter| assert_ok!(assert_ok!(parameter).parse_string()))
+                .collect()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn empty() {
+        let mut reader = Reader::new(b"".as_slice());
+
+        assert_none!(assert_ok!(reader.next_tag()));
+    }
+
+    #[test]
+    fn only_comments() {
+        let mut reader = Reader::new(b"// foo\n\n// bar\n".as_slice());
+
+        assert_none!(assert_ok!(reader.next_tag()));
+    }
+
+    #[test]
+    fn single_tag() {
+        let mut reader = Reader::new(b"#foo:bar:baz;\n".as_slice());
+
+        let mut tag = assert_some!(assert_ok!(reader.next_tag()));
+        assert_eq!(tag.position(), Position::new(0, 0));
+        let mut parameters = assert_ok!(assert_some!(tag.next()));
+        assert_eq!(parameters.position(), Position::new(0, 1));
+        let parameter = assert_ok!(assert_some!(parameters.next()));
+        assert_ok_eq!(parameter.parse_string(), "foo");
+        assert_eq!(parameter.position(), Position::new(0, 1));
+        let parameter = assert_ok!(assert_some!(parameters.next()));
+        assert_ok_eq!(parameter.parse_string(), "bar");
+        assert_eq!(parameter.position(), Position::new(0, 5));
+        let parameter = assert_ok!(assert_some!(parameters.next()));
+        assert_ok_eq!(parameter.parse_string(), "baz");
+        assert_eq!(parameter.position(), Position::new(0, 9));
+        assert_none!(parameters.next());
+        assert_none!(tag.next());
+
+        assert_none!(assert_ok!(reader.next_tag()));
+    }
+
+    #[test]
+    fn multiple_tags() {
+        let mut reader = Reader::new(b"#foo:1;\n#bar:2;\n#baz:3;\n".as_slice());
+
+        let mut names = Vec::new();
+        while let Some(mut tag) = assert_ok!(reader.next_tag()) {
+            let mut parameters = assert_ok!(assert_some!(tag.next()));
+            names.push(assert_ok!(
+                assert_ok!(assert_some!(parameters.next())).parse_identifier()
+            ));
+        }
+
+        assert_eq!(names, vec!["foo", "bar", "baz"]);
+    }
+
+    #[test]
+    fn multiple_parameter_lists() {
+        let mut reader = Reader::new(b"#foo:a:1;\nb:2;\n".as_slice());
+
+        let tag = assert_some!(assert_ok!(reader.next_tag()));
+        let lists = tag
+            .map(|parameters| {
+                assert_ok!(parameters)
+                    .map(|parameter| assert_ok!(assert_ok!(parameter).parse_identifier()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(lists, vec![vec!["foo", "a", "1"], vec!["b", "2"]]);
+    }
+
+    #[test]
+    fn empty_tag() {
+        let mut reader = Reader::new(b"#;\n".as_slice());
+
+        let mut tag = assert_some!(assert_ok!(reader.next_tag()));
+        let mut parameters = assert_ok!(assert_some!(tag.next()));
+        assert_eq!(
+            assert_ok!(assert_some!(parameters.next())).as_raw_bytes(),
+            b""
+        );
+        assert_none!(parameters.next());
+        assert_none!(tag.next());
+    }
+
+    #[test]
+    fn raw_bytes() {
+        let mut reader = Reader::new(b"#foo:b\\:ar// comment\n;\n".as_slice());
+
+        let mut tag = assert_some!(assert_ok!(reader.next_tag()));
+        let mut parameters = assert_ok!(assert_some!(tag.next()));
+        assert_ok!(assert_some!(parameters.next()));
+        let parameter = assert_ok!(assert_some!(parameters.next()));
+
+        assert_eq!(parameter.as_raw_bytes(), b"b\\:ar// comment\n");
+        assert_eq!(parameter.to_bytes(), b"b:ar\n");
+    }
+
+    #[test]
+    fn parse_string_invalid() {
+        let mut reader = Reader::new(b"#\xF0\x9Ffoo;\n".as_slice());
+
+        let mut tag = assert_some!(assert_ok!(reader.next_tag()));
+        let mut parameters = assert_ok!(assert_some!(tag.next()));
+        let parameter = assert_ok!(assert_some!(parameters.next()));
+
+        assert_err_eq!(
+            parameter.parse_string(),
+            Error::new(error::Kind::ExpectedString, Position::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn max_parameters() {
+        let mut reader = Reader::with_options(
+            b"#foo:bar;\nbaz:qux;\n".as_slice(),
+            DeserializerOptions::new().limits(Limits::new().max_parameters(3)),
+        );
+
+        let mut tag = assert_some!(assert_ok!(reader.next_tag()));
+        assert_ok!(assert_some!(tag.next()));
+        assert_err_eq!(
+            assert_some!(tag.next()),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::Parameters),
+                Position::new(0, 9)
+            )
+        );
+    }
+
+    #[test]
+    fn max_string_length() {
+        let mut reader = Reader::with_options(
+            b"#foo:barbaz:qux;\n".as_slice(),
+            DeserializerOptions::new().limits(Limits::new().max_string_length(3)),
+        );
+
+        let mut tag = assert_some!(assert_ok!(reader.next_tag()));
+        let mut parameters = assert_ok!(assert_some!(tag.next()));
+        assert_ok!(assert_some!(parameters.next()));
+        assert_err_eq!(
+            assert_some!(parameters.next()),
+            Error::new(
+                error::Kind::LimitExceeded(Limit::StringLength),
+                Position::new(0, 5)
+            )
+        );
+        assert_ok_eq!(
+            assert_ok!(assert_some!(parameters.next())).parse_string(),
+            "qux"
+        );
+        assert_none!(parameters.next());
+    }
+
+    #[test]
+    fn character_before_first_tag() {
+        let mut reader = Reader::new(b"foo#bar;\n".as_slice());
+
+        assert_err_eq!(
+            reader.next_tag(),
+            Error::new(error::Kind::ExpectedTag, Position::new(0, 0))
+        );
+        // The error is repeated.
+        assert_err_eq!(
+            reader.next_tag(),
+            Error::new(error::Kind::ExpectedTag, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn with_options() {
+        let mut reader = Reader::with_options(
+            b"#foo:bar\n#baz;\n".as_slice(),
+            DeserializerOptions::new().dialect(Dialect::Strict),
+        );
+
+        assert_err_eq!(
+            reader.next_tag(),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(1, 0))
+        );
+    }
+
+    #[test]
+    fn parser_empty() {
+        let mut parser = Parser::new();
+
+        assert_matches!(assert_ok!(parser.next_event()), Event::NeedMoreInput);
+        parser.finish();
+        assert_matches!(assert_ok!(parser.next_event()), Event::End);
+    }
+
+    #[test]
+    fn parser_single_chunk() {
+        let mut parser = Parser::new();
+
+        parser.feed(b"#foo:bar;\n#baz:qux;\n");
+        match assert_ok!(parser.next_event()) {
+            Event::Tag(tag) => {
+                assert_eq!(tag.position(), Position::new(0, 0));
+                assert_eq!(parameter_lists(tag), vec![vec!["foo", "bar"]]);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert_matches!(assert_ok!(parser.next_event()), Event::NeedMoreInput);
+        parser.finish();
+        match assert_ok!(parser.next_event()) {
+            Event::Tag(tag) => {
+                assert_eq!(tag.position(), Position::new(1, 0));
+                assert_eq!(parameter_lists(tag), vec![vec!["baz", "qux"]]);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert_matches!(assert_ok!(parser.next_event()), Event::End);
+    }
+
+    #[test]
+    fn parser_matches_reader_for_every_chunk_size() {
+        let input = b"// header\n#foo:b\\;ar// comment #x;\n;\n#baz:a:1;\nb:2;\n#qux;#;\n";
+
+        let mut expected = Vec::new();
+        let mut reader = Reader::new(input.as_slice());
+        while let Some(tag) = assert_ok!(reader.next_tag()) {
+            expected.push((tag.position(), parameter_lists(tag)));
+        }
+
+        for chunk_size in 1..=input.len() {
+            let mut parser = Parser::new();
+            let mut tags = Vec::new();
+            for chunk in input.chunks(chunk_size) {
+                parser.feed(chunk);
+                while let Event::Tag(tag) = assert_ok!(parser.next_event()) {
+                    tags.push((tag.position(), parameter_lists(tag)));
+                }
+            }
+            parser.finish();
+            while let Event::Tag(tag) = assert_ok!(parser.next_event()) {
+                tags.push((tag.position(), parameter_lists(tag)));
+            }
+
+            assert_eq!(tags, expected, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn parser_error() {
+        let mut parser = Parser::new();
+
+        parser.feed(b"foo#bar;\n");
+
+        assert_err_eq!(
+            parser.next_event(),
+            Error::new(error::Kind::ExpectedTag, Position::new(0, 0))
+        );
+        // The error is repeated.
+        parser.feed(b"#baz;\n");
+        assert_err_eq!(
+            parser.next_event(),
+            Error::new(error::Kind::ExpectedTag, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn parser_with_options() {
+        let mut parser = Parser::with_options(DeserializerOptions::new().dialect(Dialect::Strict));
+
+        parser.feed(b"#foo:bar\n");
+        assert_matches!(assert_ok!(parser.next_event()), Event::NeedMoreInput);
+        parser.feed(b"#baz;\n");
+
+        assert_err_eq!(
+            parser.next_event(),
+            Error::new(error::Kind::MissingTagTerminator, Position::new(1, 0))
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use std::io;
+
+/// A source of MSD input.
+///
+/// This trait is sealed and cannot be implemented outside of this crate. It is implemented for
+/// every type implementing [`std::io::Read`], in which case every deserialized value is copied out
+/// of an internal buffer, and for [`SliceRead`], which allows deserialized values to borrow
+/// directly from the input.
+pub trait Read<'de>: private::Sealed<'de> {}
+
+impl<'de, R> Read<'de> for R where R: io::Read {}
+
+/// MSD input read from an in-memory slice of bytes.
+///
+/// Values deserialized from a `SliceRead` can borrow from the slice, as long as they contain no
+/// escape sequences or comments. This is used by [`Deserializer::from_slice()`] and
+/// [`from_bytes()`].
+///
+/// [`Deserializer::from_slice()`]: crate::Deserializer::from_slice()
+/// [`from_bytes()`]: crate::from_bytes()
+#[derive(Debug)]
+pub struct SliceRead<'de> {
+    slice: &'de [u8],
+    index: usize,
+
+    tag_start: usize,
+    tag_end: usize,
+}
+
+impl<'de> SliceRead<'de> {
+    /// Creates a new `SliceRead` reading from the given `slice`.
+    pub fn new(slice: &'de [u8]) -> Self {
+        Self {
+            slice,
+            index: 0,
+
+            tag_start: 0,
+            tag_end: 0,
+        }
+    }
+}
+
+impl<'de> Read<'de> for SliceRead<'de> {}
+
+pub(in crate::de) mod private {
+    use std::io;
+
+    /// Buffers owned by the parser on behalf of a [`Sealed`] reader.
+    ///
+    /// Readers that borrow their input directly do not use these buffers, and therefore never
+    /// allocate them.
+    #[derive(Debug, Default)]
+    pub struct Scratch {
+        /// Bytes read from the input that have not yet been consumed.
+        pub(super) read: Vec<u8>,
+        pub(super) read_start: usize,
+        pub(super) read_end: usize,
+
+        /// The contents of the current tag.
+        pub(super) tag: Vec<u8>,
+    }
+
+    pub trait Sealed<'de> {
+        /// Whether the bytes returned by `tag()` are borrowed from input living for `'de`.
+        const BORROWED: bool;
+
+        /// Returns the input that has not yet been consumed, reading more if none is available.
+        ///
+        /// An empty slice indicates the end of the input.
+        fn fill<'s>(&'s mut self, scratch: &'s mut Scratch) -> io::Result<&'s [u8]>;
+
+        /// Consumes the first `amount` bytes returned by `fill()` without including them in the
+        /// current tag.
+        fn consume(&mut self, scratch: &mut Scratch, amount: usize);
+
+        /// Begins a new tag, whose contents start with the next unconsumed byte.
+        fn start_tag(&mut self, scratch: &mut Scratch);
+
+        /// Consumes the first `amount` bytes returned by `fill()`, including them in the current
+        /// tag.
+        fn push_tag(&mut self, scratch: &mut Scratch, amount: usize);
+
+        /// Returns the contents of the current tag.
+        ///
+        /// If `BORROWED` is `true`, the returned slice is guaranteed to live for `'de`.
+        fn tag<'s>(&'s self, scratch: &'s Scratch) -> &'s [u8];
+    }
+}
+
+/// The number of bytes requested from an `io::Read` at a time.
+const READ_CAPACITY: usize = 8 * 1024;
+
+impl<'de, R> private::Sealed<'de> for R
+where
+    R: io::Read,
+{
+    const BORROWED: bool = false;
+
+    fn fill<'s>(&'s mut self, scratch: &'s mut private::Scratch) -> io::Result<&'s [u8]> {
+        if scratch.read_start == scratch.read_end {
+            scratch.read.resize(READ_CAPACITY, 0);
+            loop {
+                match self.read(&mut scratch.read) {
+                    Ok(read) => {
+                        scratch.read_start = 0;
+                        scratch.read_end = read;
+                        break;
+                    }
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+        Ok(&scratch.read[scratch.read_start..scratch.read_end])
+    }
+
+    fn consume(&mut self, scratch: &mut private::Scratch, amount: usize) {
+        scratch.read_start += amount;
+    }
+
+    fn start_tag(&mut self, scratch: &mut private::Scratch) {
+        scratch.tag.clear();
+    }
+
+    fn push_tag(&mut self, scratch: &mut private::Scratch, amount: usize) {
+        let end = scratch.read_start + amount;
+        scratch
+            .tag
+            .extend_from_slice(&scratch.read[scratch.read_start..end]);
+        scratch.read_start = end;
+    }
+
+    fn tag<'s>(&'s self, scratch: &'s private::Scratch) -> &'s [u8] {
+        &scratch.tag
+    }
+}
+
+impl<'de> private::Sealed<'de> for SliceRead<'de> {
+    const BORROWED: bool = true;
+
+    fn fill<'s>(&'s mut self, _scratch: &'s mut private::Scratch) -> io::Result<&'s [u8]> {
+        // SAFETY: `index` is never advanced past the end of the slice.
+        Ok(unsafe { self.slice.get_unchecked(self.index..) })
+    }
+
+    fn consume(&mut self, _scratch: &mut private::Scratch, amount: usize) {
+        self.index += amount;
+    }
+
+    fn start_tag(&mut self, _scratch: &mut private::Scratch) {
+        self.tag_start = self.index;
+        self.tag_end = self.index;
+    }
+
+    fn push_tag(&mut self, _scratch: &mut private::Scratch, amount: usize) {
+        self.index += amount;
+        self.tag_end = self.index;
+    }
+
+    fn tag<'s>(&'s self, _scratch: &'s private::Scratch) -> &'s [u8] {
+        // SAFETY: `tag_start` and `tag_end` are only ever set to indices already read from the
+        // slice, and `tag_start` is never greater than `tag_end`.
+        unsafe { self.slice.get_unchecked(self.tag_start..self.tag_end) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        private::{Scratch, Sealed},
+        SliceRead, READ_CAPACITY,
+    };
+    use claims::{assert_ok, assert_ok_eq};
+    use std::{io, io::Read};
+
+    #[test]
+    fn io_fill() {
+        let mut reader = b"abc".as_slice();
+        let mut scratch = Scratch::default();
+
+        assert_ok_eq!(reader.fill(&mut scratch), b"abc".as_slice());
+        reader.consume(&mut scratch, 1);
+        assert_ok_eq!(reader.fill(&mut scratch), b"bc".as_slice());
+        reader.consume(&mut scratch, 2);
+        assert_ok_eq!(reader.fill(&mut scratch), b"".as_slice());
+    }
+
+    #[test]
+    fn io_fill_in_chunks() {
+        let input = vec![b'a'; READ_CAPACITY + 1];
+        let mut reader = input.as_slice();
+        let mut scratch = Scratch::default();
+
+        assert_eq!(assert_ok!(reader.fill(&mut scratch)).len(), READ_CAPACITY);
+        reader.consume(&mut scratch, READ_CAPACITY);
+        assert_ok_eq!(reader.fill(&mut scratch), b"a".as_slice());
+    }
+
+    #[test]
+    fn io_fill_interrupted() {
+        struct InterruptingReader {
+            interrupted: bool,
+        }
+
+        impl Read for InterruptingReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.interrupted {
+                    buf[0] = b'a';
+                    Ok(1)
+                } else {
+                    self.interrupted = true;
+                    Err(io::Error::from(io::ErrorKind::Interrupted))
+                }
+            }
+        }
+
+        let mut reader = InterruptingReader { interrupted: false };
+        let mut scratch = Scratch::default();
+
+        assert_ok_eq!(reader.fill(&mut scratch), b"a".as_slice());
+    }
+
+    #[test]
+    fn io_fill_error() {
+        struct FailingReader;
+
+        impl Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::from(io::ErrorKind::Other))
+            }
+        }
+
+        let mut scratch = Scratch::default();
+
+        assert_eq!(
+            FailingReader.fill(&mut scratch).unwrap_err().kind(),
+            io::ErrorKind::Other
+        );
+    }
+
+    #[test]
+    fn io_tag() {
+        let mut reader = b"#foo;\n#bar;".as_slice();
+        let mut scratch = Scratch::default();
+
+        assert_ok!(reader.fill(&mut scratch));
+        reader.consume(&mut scratch, 1);
+        reader.start_tag(&mut scratch);
+        reader.push_tag(&mut scratch, 2);
+        reader.push_tag(&mut scratch, 2);
+        // Bytes that are consumed but not pushed are not included in the tag.
+        reader.consume(&mut scratch, 1);
+
+        assert_eq!(reader.tag(&scratch), b"foo;");
+        assert_ok_eq!(reader.fill(&mut scratch), b"#bar;".as_slice());
+    }
+
+    #[test]
+    fn slice_fill() {
+        let mut reader = SliceRead::new(b"abc");
+        let mut scratch = Scratch::default();
+
+        assert_ok_eq!(reader.fill(&mut scratch), b"abc".as_slice());
+        reader.consume(&mut scratch, 1);
+        assert_ok_eq!(reader.fill(&mut scratch), b"bc".as_slice());
+        reader.consume(&mut scratch, 2);
+        assert_ok_eq!(reader.fill(&mut scratch), b"".as_slice());
+    }
+
+    #[test]
+    fn slice_tag() {
+        let mut reader = SliceRead::new(b"#foo;\n#bar;");
+        let mut scratch = Scratch::default();
+
+        reader.consume(&mut scratch, 1);
+        reader.start_tag(&mut scratch);
+        reader.push_tag(&mut scratch, 2);
+        reader.push_tag(&mut scratch, 2);
+        // Bytes that are consumed but not pushed are not included in the tag.
+        reader.consume(&mut scratch, 1);
+
+        assert_eq!(reader.tag(&scratch), b"foo;");
+        assert_ok_eq!(reader.fill(&mut scratch), b"#bar;".as_slice());
+        assert!(scratch.tag.is_empty());
+        assert!(scratch.read.is_empty());
+    }
+}
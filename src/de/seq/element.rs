@@ -1,30 +1,56 @@
-use crate::de::{error, map, parse::Tags, r#enum, r#struct, tuple, Error, Result};
+use crate::{
+    de::{
+        any, delimited, error, map, parse::Tags, r#enum, r#struct, spanned, tuple, Error, Read,
+        Result,
+    },
+    with,
+};
 use serde::{de, de::Visitor};
-use std::io::Read;
+use std::borrow::Cow;
 
 pub(in crate::de) struct Deserializer<'a, R> {
     tags: &'a mut Tags<R>,
+
+    // Set when the element leaves parameter lists within its tag to be read as the next element.
+    continued: Option<&'a mut bool>,
 }
 
 impl<'a, R> Deserializer<'a, R> {
     pub(in crate::de) fn new(tags: &'a mut Tags<R>) -> Self {
-        Self { tags }
+        Self {
+            tags,
+
+            continued: None,
+        }
+    }
+
+    // Allows a sequence of single values to be followed by the next element within the same tag,
+    // recording into `continued` whether it is.
+    pub(in crate::de) fn continuing_into(mut self, continued: &'a mut bool) -> Self {
+        self.continued = Some(continued);
+        self
     }
 }
 
 impl<'de, 'a, R> de::Deserializer<'de> for Deserializer<'a, R>
 where
-    R: Read,
+    R: Read<'de>,
 {
     type Error = Error;
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(self
-            .tags
-            .error_at_current_tag(error::Kind::CannotDeserializeAsSelfDescribing))
+        // Only supported when serde buffers the element, such as for an untagged enum.
+        if !any::is_buffering::<V>() {
+            return Err(self
+                .tags
+                .error_at_current_tag(error::Kind::CannotDeserializeAsSelfDescribing));
+        }
+        let mut tag = self.tags.next()?;
+        let values = tag.next()?;
+        any::content::Deserializer::new(tag, values).deserialize_any(visitor)
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
@@ -275,11 +301,15 @@ where
         let mut values = tag.next()?;
         let value = values.next()?;
         let value_position = value.position();
-        // Parsed string must be owned, since it removes escaping and comments.
-        let parsed = value.parse_string()?;
+        // SAFETY: `'de` is the lifetime of the input.
+        let parsed = unsafe { value.parse_str() }?;
         values.assert_exhausted()?;
         tag.assert_exhausted()?;
-        visitor.visit_str(&parsed).map_err(|mut error: Error| {
+        match parsed {
+            Cow::Borrowed(borrowed) => visitor.visit_borrowed_str(borrowed),
+            Cow::Owned(owned) => visitor.visit_str(&owned),
+        }
+        .map_err(|mut error: Error| {
             error.set_position(value_position);
             error
         })
@@ -310,11 +340,15 @@ where
         let mut values = tag.next()?;
         let value = values.next()?;
         let value_position = value.position();
-        // Parsed bytes must be owned, since it removes escaping and comments.
-        let parsed = value.parse_byte_buf();
+        // SAFETY: `'de` is the lifetime of the input.
+        let parsed = unsafe { value.parse_bytes() };
         values.assert_exhausted()?;
         tag.assert_exhausted()?;
-        visitor.visit_bytes(&parsed).map_err(|mut error: Error| {
+        match parsed {
+            Cow::Borrowed(borrowed) => visitor.visit_borrowed_bytes(borrowed),
+            Cow::Owned(owned) => visitor.visit_bytes(&owned),
+        }
+        .map_err(|mut error: Error| {
             error.set_position(value_position);
             error
         })
@@ -337,13 +371,40 @@ where
         })
     }
 
-    fn deserialize_option<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(self
-            .tags
-            .error_at_current_tag(error::Kind::CannotDeserializeAsOptionInSeq))
+        if !self.tags.empty_as_none() {
+            return Err(self
+                .tags
+                .error_at_current_tag(error::Kind::CannotDeserializeAsOptionInSeq));
+        }
+        // Look ahead at the tag's parameters, only consuming the tag here if it is `None`.
+        let tag = self.tags.next()?;
+        let mut lookahead = tag.clone();
+        let mut values = lookahead.next()?;
+        let value = values.next()?;
+        if value.is_empty()
+            && values.assert_exhausted().is_ok()
+            && lookahead.assert_exhausted().is_ok()
+        {
+            let value_position = value.position();
+            visitor.visit_none().map_err(|mut error: Error| {
+                error.set_position(value_position);
+                error
+            })
+        } else {
+            let mut tag = tag;
+            let mut values = tag.next()?;
+            values.mark_next_as_some();
+            // SAFETY: `values` was created by `tag.next()`.
+            unsafe { tag.revisit(values) };
+            let stored = tag.into_stored();
+            // SAFETY: `stored` points to the current buffer of `self.tags`.
+            unsafe { self.tags.revisit(stored) };
+            visitor.visit_some(self)
+        }
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
@@ -380,20 +441,42 @@ where
         })
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if let Some(separator) = with::separator(name) {
+            let mut tag = self.tags.next()?;
+            let mut values = tag.next()?;
+            let result = visitor
+                .visit_newtype_struct(delimited::Deserializer::new(values.next()?, separator))?;
+            values.assert_exhausted()?;
+            tag.assert_exhausted()?;
+            return Ok(result);
+        }
         visitor.visit_newtype_struct(self)
     }
 
-    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(self
-            .tags
-            .error_at_current_tag(error::Kind::CannotDeserializeNestedSeq))
+        let mut tag = self.tags.next()?;
+        let mut access = super::lists::Access::new(&mut tag);
+        let result = visitor.visit_seq(&mut access)?;
+        if access.read_parameters() {
+            // Each following parameter list is the next element of the enclosing sequence.
+            match self.continued {
+                Some(continued) if tag.assert_exhausted().is_err() => {
+                    let stored = tag.into_stored();
+                    // SAFETY: `stored` references the buffer still active in `self.tags`.
+                    unsafe { self.tags.revisit(stored) };
+                    *continued = true;
+                }
+                _ => tag.assert_exhausted()?,
+            }
+        }
+        Ok(result)
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
@@ -437,13 +520,22 @@ where
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == spanned::NAME {
+            let tag = self.tags.next()?;
+            let start = tag.origin_position();
+            let end = tag.end_position();
+            let stored = tag.into_stored();
+            // SAFETY: `stored` points to the current buffer of `self.tags`.
+            unsafe { self.tags.revisit(stored) };
+            return visitor.visit_map(spanned::Access::new(start, end, self));
+        }
         let mut tag = self.tags.next()?;
         let mut values = tag.next()?;
         let value = values.next()?;
@@ -456,7 +548,7 @@ where
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
@@ -464,7 +556,7 @@ where
     {
         let mut tag = self.tags.next()?;
         let mut values = tag.next()?;
-        let result = visitor.visit_enum(r#enum::Access::new(&mut values))?;
+        let result = visitor.visit_enum(r#enum::Access::new(&mut values, variants))?;
         values.assert_exhausted()?;
         tag.assert_exhausted()?;
         Ok(result)
@@ -501,7 +593,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::Deserializer;
-    use crate::de::{error, parse::Tags, Error, Position};
+    use crate::de::{error, parse::Tags, DeserializerOptions, Error, Position, Segment};
     use claims::{assert_err_eq, assert_ok_eq};
     use serde::{de, de::Visitor, Deserialize};
     use serde_bytes::ByteBuf;
@@ -2623,14 +2715,125 @@ mod tests {
         );
     }
 
+    #[test]
+    fn option_none() {
+        let mut tags = Tags::with_options(
+            b"#;\n#42;".as_slice(),
+            DeserializerOptions::new().empty_as_none(true),
+        );
+
+        assert_ok_eq!(
+            Option::<u64>::deserialize(Deserializer::new(&mut tags)),
+            None
+        );
+        assert_ok_eq!(u64::deserialize(Deserializer::new(&mut tags)), 42);
+    }
+
+    #[test]
+    fn option_some() {
+        let mut tags = Tags::with_options(
+            b"#42;\n#;".as_slice(),
+            DeserializerOptions::new().empty_as_none(true),
+        );
+
+        assert_ok_eq!(
+            Option::<u64>::deserialize(Deserializer::new(&mut tags)),
+            Some(42)
+        );
+        assert_ok_eq!(
+            Option::<u64>::deserialize(Deserializer::new(&mut tags)),
+            None
+        );
+    }
+
+    #[test]
+    fn option_some_empty() {
+        let mut tags = Tags::with_options(
+            b"#\\0;".as_slice(),
+            DeserializerOptions::new().empty_as_none(true),
+        );
+        let deserializer = Deserializer::new(&mut tags);
+
+        assert_ok_eq!(
+            Option::<String>::deserialize(deserializer),
+            Some(String::new())
+        );
+    }
+
+    #[test]
+    fn empty_marker_outside_option() {
+        let mut tags = Tags::with_options(
+            b"#\\0b;\n#\\0;".as_slice(),
+            DeserializerOptions::new().empty_as_none(true),
+        );
+
+        assert_ok_eq!(
+            String::deserialize(Deserializer::new(&mut tags)),
+            "\\0b".to_owned()
+        );
+        assert_ok_eq!(
+            Option::<String>::deserialize(Deserializer::new(&mut tags)),
+            Some(String::new())
+        );
+    }
+
+    #[test]
+    fn option_some_unit() {
+        let mut tags = Tags::with_options(
+            b"#\\0;".as_slice(),
+            DeserializerOptions::new().empty_as_none(true),
+        );
+        let deserializer = Deserializer::new(&mut tags);
+
+        assert_ok_eq!(Option::<()>::deserialize(deserializer), Some(()));
+    }
+
+    #[test]
+    fn option_some_tuple_with_empty_first_value() {
+        let mut tags = Tags::with_options(
+            b"#:42;".as_slice(),
+            DeserializerOptions::new().empty_as_none(true),
+        );
+        let deserializer = Deserializer::new(&mut tags);
+
+        assert_ok_eq!(
+            Option::<(String, u64)>::deserialize(deserializer),
+            Some((String::new(), 42))
+        );
+    }
+
     #[test]
     fn seq() {
-        let mut tags = Tags::new(b"#foo;".as_slice());
+        let mut tags = Tags::new(b"#foo:bar;\nbaz:qux;".as_slice());
         let deserializer = Deserializer::new(&mut tags);
 
-        assert_err_eq!(
-            Vec::<()>::deserialize(deserializer),
-            Error::new(error::Kind::CannotDeserializeNestedSeq, Position::new(0, 1))
+        assert_ok_eq!(
+            Vec::<(String, String)>::deserialize(deserializer),
+            vec![
+                ("foo".to_owned(), "bar".to_owned()),
+                ("baz".to_owned(), "qux".to_owned())
+            ]
         );
     }
+
+    #[test]
+    fn seq_of_seqs() {
+        let mut tags = Tags::new(b"#1:2;\n3;".as_slice());
+        let deserializer = Deserializer::new(&mut tags);
+
+        assert_ok_eq!(
+            Vec::<Vec<u64>>::deserialize(deserializer),
+            vec![vec![1, 2], vec![3]]
+        );
+    }
+
+    #[test]
+    fn seq_of_non_lists() {
+        let mut tags = Tags::new(b"#foo;".as_slice());
+        let deserializer = Deserializer::new(&mut tags);
+
+        let mut expected = Error::new(error::Kind::CannotDeserializeNestedSeq, Position::new(0, 1));
+        expected.push_segment(Segment::Element(0));
+        assert_err_eq!(Vec::<()>::deserialize(deserializer), expected);
+    }
 }
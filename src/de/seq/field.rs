@@ -1,22 +1,46 @@
 use super::element;
-use crate::de::{parse::Tags, Error, Result};
+use crate::de::{error, parse::Tags, Error, Read, Result, Segment};
 use serde::de::{DeserializeSeed, SeqAccess};
-use std::io::Read;
 
 pub(in crate::de) struct Access<'a, R> {
     field: &'a str,
     tags: &'a mut Tags<R>,
+    index: usize,
+
+    // Whether the previous element left parameter lists within its tag to be read as this one.
+    continued: bool,
 }
 
 impl<'a, R> Access<'a, R> {
     pub(in crate::de) fn new(field: &'a str, tags: &'a mut Tags<R>) -> Self {
-        Self { field, tags }
+        Self {
+            field,
+            tags,
+            index: 0,
+
+            continued: false,
+        }
+    }
+
+    fn deserialize_element<'de, T>(&mut self, seed: T) -> Result<T::Value>
+    where
+        R: Read<'de>,
+        T: DeserializeSeed<'de>,
+    {
+        let index = self.index;
+        self.index += 1;
+        self.continued = false;
+        seed.deserialize(element::Deserializer::new(self.tags).continuing_into(&mut self.continued))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Element(index));
+                error
+            })
     }
 }
 
 impl<'a, 'de, R> SeqAccess<'de> for Access<'a, R>
 where
-    R: Read,
+    R: Read<'de>,
 {
     type Error = Error;
 
@@ -24,23 +48,31 @@ where
     where
         T: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
+        if self.continued {
+            // The tag has already been identified as belonging to this field.
+            return self.deserialize_element(seed).map(Some);
+        }
+
+        let mut tag = match self.tags.next_or_end()? {
+            Some(tag) => tag,
+            None => return Ok(None),
         };
 
         // Check that the field name matches.
         let mut values = tag.next()?;
         let value = values.next()?;
-        if value.parse_identifier()? == self.field {
+        let position = value.position();
+        if value.parse_identifier_in(&[self.field])? == self.field {
             // Deserialize the rest of the tag.
             // SAFETY: `values` was created by a call to `tag.next()`.
             unsafe { tag.revisit(values) };
             let stored = tag.into_stored();
             unsafe { self.tags.revisit(stored) };
-            Ok(Some(
-                seed.deserialize(element::Deserializer::new(self.tags))?,
-            ))
+            self.deserialize_element(seed).map(Some)
+        } else if self.index == 0 {
+            // The first tag was put back as belonging to this field, so failing to match it means
+            // nothing would ever consume it.
+            Err(Error::new(error::Kind::ExpectedIdentifier, position))
         } else {
             tag.reset();
             let stored = tag.into_stored();
@@ -54,8 +86,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::Access;
-    use crate::de::parse::Tags;
-    use claims::{assert_none, assert_ok, assert_some_eq};
+    use crate::de::{error, parse::Tags, DeserializerOptions, Error, Position};
+    use claims::{assert_err_eq, assert_none, assert_ok, assert_some_eq};
     use serde::de::SeqAccess;
     use serde_derive::Deserialize;
 
@@ -92,6 +124,52 @@ mod tests {
         let mut tags = Tags::new(b"#bar:42;\n".as_slice());
         let mut access = Access::new("foo", &mut tags);
 
+        assert_err_eq!(
+            access.next_element::<u64>(),
+            Error::new(error::Kind::ExpectedIdentifier, Position::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn incorrect_field_case() {
+        let mut tags = Tags::new(b"#FOO:42;\n".as_slice());
+        let mut access = Access::new("foo", &mut tags);
+
+        assert_err_eq!(
+            access.next_element::<u64>(),
+            Error::new(error::Kind::ExpectedIdentifier, Position::new(0, 1))
+        );
+    }
+
+    #[test]
+    fn incorrect_field_after_element() {
+        let mut tags = Tags::new(b"#foo:1;\n#bar:42;\n".as_slice());
+        let mut access = Access::new("foo", &mut tags);
+
+        assert_some_eq!(assert_ok!(access.next_element::<u64>()), 1);
+        assert_none!(assert_ok!(access.next_element::<u64>()));
+    }
+
+    #[test]
+    fn surrounding_whitespace() {
+        let mut tags = Tags::new(b"# foo :1;\n#foo:2;\n".as_slice());
+        let mut access = Access::new("foo", &mut tags);
+
+        assert_some_eq!(assert_ok!(access.next_element::<u64>()), 1);
+        assert_some_eq!(assert_ok!(access.next_element::<u64>()), 2);
+        assert_none!(assert_ok!(access.next_element::<u64>()));
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let mut tags = Tags::with_options(
+            b"#FOO:1;\n#Foo:2;\n".as_slice(),
+            DeserializerOptions::new().case_insensitive(true),
+        );
+        let mut access = Access::new("foo", &mut tags);
+
+        assert_some_eq!(assert_ok!(access.next_element::<u64>()), 1);
+        assert_some_eq!(assert_ok!(access.next_element::<u64>()), 2);
         assert_none!(assert_ok!(access.next_element::<u64>()));
     }
 
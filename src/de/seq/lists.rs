@@ -0,0 +1,545 @@
+use crate::de::{
+    error,
+    parse::{Tag, Values},
+    tuple, Error, Result, Segment,
+};
+use serde::{
+    de,
+    de::{DeserializeSeed, SeqAccess, Visitor},
+    forward_to_deserialize_any,
+};
+
+// Deserializes the parameter lists of a single tag as a sequence, where each parameter list is a
+// sequence or tuple of its parameters.
+//
+// If the elements are instead single values, they are the parameters of the first parameter list.
+// Any further parameter lists are then left within the tag, to be read by an enclosing sequence.
+pub(in crate::de) struct Access<'a, 'b> {
+    tag: &'a mut Tag<'b>,
+    index: usize,
+
+    // The parameter list containing the elements, once they are known to be single values.
+    parameters: Option<Values<'b>>,
+}
+
+impl<'a, 'b> Access<'a, 'b> {
+    pub(in crate::de) fn new(tag: &'a mut Tag<'b>) -> Self {
+        Self {
+            tag,
+            index: 0,
+
+            parameters: None,
+        }
+    }
+
+    // Whether the elements were read as the parameters of a single parameter list.
+    pub(in crate::de) fn read_parameters(&self) -> bool {
+        self.parameters.is_some()
+    }
+
+    fn next_list(&mut self) -> Result<Option<Values<'b>>> {
+        let continues = self.tag.continues();
+        match self.tag.next() {
+            Ok(mut values) => {
+                if continues {
+                    // Lists after the first usually begin on a new line, which is not part of the
+                    // list's first parameter.
+                    values.skip_whitespace();
+                }
+                Ok(Some(values))
+            }
+            Err(error) if matches!(error.kind(), error::Kind::EndOfTag) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl<'a, 'b, 'de> SeqAccess<'de> for Access<'a, 'b> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some(values) = &mut self.parameters {
+            if values.assert_exhausted().is_ok() {
+                return Ok(None);
+            }
+            let index = self.index;
+            self.index += 1;
+            return seed
+                .deserialize(tuple::element::Deserializer::new(values))
+                .map(Some)
+                .map_err(|mut error: Error| {
+                    error.push_segment(Segment::Value(index));
+                    error
+                });
+        }
+
+        let unread = self.tag.is_unread();
+        let mut values = match self.next_list()? {
+            Some(values) => values,
+            None => return Ok(None),
+        };
+        if self.index == 0 {
+            if unread {
+                // A tag with no parameters at all, such as `#;`, is an empty sequence.
+                if is_empty(&values)? && self.tag.assert_exhausted().is_ok() {
+                    return Ok(None);
+                }
+            } else if values.assert_exhausted().is_ok() {
+                // The first parameter list of a field's tag may contain nothing but the field's
+                // name.
+                values = match self.next_list()? {
+                    Some(values) => values,
+                    None => return Ok(None),
+                };
+            }
+        }
+
+        let index = self.index;
+        self.index += 1;
+        let mut parameters = false;
+        let result = seed.deserialize(Deserializer::new(&mut values, &mut parameters));
+        if parameters {
+            // The remaining elements are the parameters following this one.
+            self.parameters = Some(values);
+            return result.map(Some).map_err(|mut error: Error| {
+                error.push_segment(Segment::Value(index));
+                error
+            });
+        }
+        result
+            .and_then(|value| {
+                values.assert_exhausted()?;
+                Ok(Some(value))
+            })
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Element(index));
+                error
+            })
+    }
+}
+
+// Whether `values` consists of a single empty parameter.
+fn is_empty(values: &Values) -> Result<bool> {
+    let mut values = values.clone();
+    let empty = values.next()?.parse_string()?.is_empty();
+    Ok(empty && values.assert_exhausted().is_ok())
+}
+
+struct Deserializer<'a, 'b> {
+    values: &'a mut Values<'b>,
+
+    // Set when a single value is deserialized, rather than a whole parameter list.
+    parameters: &'a mut bool,
+}
+
+impl<'a, 'b> Deserializer<'a, 'b> {
+    fn new(values: &'a mut Values<'b>, parameters: &'a mut bool) -> Self {
+        Self { values, parameters }
+    }
+
+    fn deserialize_parameter<T, F>(self, deserialize: F) -> Result<T>
+    where
+        F: FnOnce(tuple::element::Deserializer) -> Result<T>,
+    {
+        *self.parameters = true;
+        deserialize(tuple::element::Deserializer::new(self.values))
+    }
+}
+
+impl<'a, 'b, 'de> de::Deserializer<'de> for Deserializer<'a, 'b> {
+    type Error = Error;
+
+    // A parameter list does not describe whether its parameters are a sequence or a single value.
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::new(
+            error::Kind::CannotDeserializeNestedSeq,
+            self.values.current_position(),
+        ))
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_bool(visitor))
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_i8(visitor))
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_i16(visitor))
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_i32(visitor))
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_i64(visitor))
+    }
+
+    #[cfg(has_i128)]
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_i128(visitor))
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_u8(visitor))
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_u16(visitor))
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_u32(visitor))
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_u64(visitor))
+    }
+
+    #[cfg(has_i128)]
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_u128(visitor))
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_f32(visitor))
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_f64(visitor))
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_char(visitor))
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_str(visitor))
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_string(visitor))
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_bytes(visitor))
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_byte_buf(visitor))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_option(visitor))
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(ListAccess::new(self.values))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(tuple::Access::new(self.values, len))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(tuple::Access::new(self.values, len))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| {
+            deserializer.deserialize_enum(name, variants, visitor)
+        })
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_parameter(|deserializer| deserializer.deserialize_identifier(visitor))
+    }
+
+    forward_to_deserialize_any! {
+        unit unit_struct map struct ignored_any
+    }
+}
+
+// Deserializes every remaining parameter within a parameter list as a sequence.
+struct ListAccess<'a, 'b> {
+    values: &'a mut Values<'b>,
+    index: usize,
+}
+
+impl<'a, 'b> ListAccess<'a, 'b> {
+    fn new(values: &'a mut Values<'b>) -> Self {
+        Self { values, index: 0 }
+    }
+}
+
+impl<'a, 'b, 'de> SeqAccess<'de> for ListAccess<'a, 'b> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.values.assert_exhausted().is_ok() {
+            return Ok(None);
+        }
+        let index = self.index;
+        self.index += 1;
+        Ok(Some(
+            seed.deserialize(tuple::element::Deserializer::new(self.values))
+                .map_err(|mut error: Error| {
+                    error.push_segment(Segment::Value(index));
+                    error
+                })?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Access;
+    use crate::de::{error, parse::Tag, Error, Position, Segment};
+    use claims::{assert_err, assert_err_eq, assert_none, assert_ok, assert_some_eq};
+    use serde::de::SeqAccess;
+
+    #[test]
+    fn empty() {
+        let mut tag = Tag::new(b"", Position::new(0, 0));
+        // Consume the tag's only parameter list, as the name of a field would be.
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+        unsafe { tag.revisit(values) };
+        let mut access = Access::new(&mut tag);
+
+        assert_none!(assert_ok!(access.next_element::<Vec<String>>()));
+    }
+
+    #[test]
+    fn single_list() {
+        let mut tag = Tag::new(b"foo:bar", Position::new(0, 0));
+        let mut access = Access::new(&mut tag);
+
+        assert_some_eq!(
+            assert_ok!(access.next_element::<Vec<String>>()),
+            vec!["foo".to_owned(), "bar".to_owned()]
+        );
+        assert_none!(assert_ok!(access.next_element::<Vec<String>>()));
+    }
+
+    #[test]
+    fn multiple_lists() {
+        let mut tag = Tag::new(b"foo:bar;\nbaz:qux", Position::new(0, 0));
+        let mut access = Access::new(&mut tag);
+
+        assert_some_eq!(
+            assert_ok!(access.next_element::<(String, String)>()),
+            ("foo".to_owned(), "bar".to_owned())
+        );
+        assert_some_eq!(
+            assert_ok!(access.next_element::<(String, String)>()),
+            ("baz".to_owned(), "qux".to_owned())
+        );
+        assert_none!(assert_ok!(access.next_element::<(String, String)>()));
+    }
+
+    #[test]
+    fn multiple_lists_of_strings() {
+        let mut tag = Tag::new(b"foo:bar;\nbaz", Position::new(0, 0));
+        let mut access = Access::new(&mut tag);
+
+        assert_some_eq!(
+            assert_ok!(access.next_element::<Vec<String>>()),
+            vec!["foo".to_owned(), "bar".to_owned()]
+        );
+        assert_some_eq!(
+            assert_ok!(access.next_element::<Vec<String>>()),
+            vec!["baz".to_owned()]
+        );
+        assert_none!(assert_ok!(access.next_element::<Vec<String>>()));
+    }
+
+    #[test]
+    fn continued_parameters() {
+        let mut tag = Tag::new(b"foo:bar;\nbaz", Position::new(0, 0));
+        let mut access = Access::new(&mut tag);
+
+        assert_some_eq!(assert_ok!(access.next_element::<String>()), "foo");
+        assert_some_eq!(assert_ok!(access.next_element::<String>()), "bar");
+        assert_none!(assert_ok!(access.next_element::<String>()));
+        // The next element, reading the same tag, does not begin with the line ending.
+        let mut access = Access::new(&mut tag);
+        assert_some_eq!(assert_ok!(access.next_element::<String>()), "baz");
+        assert_none!(assert_ok!(access.next_element::<String>()));
+    }
+
+    #[test]
+    fn no_parameters() {
+        let mut tag = Tag::new(b"", Position::new(0, 0));
+        let mut access = Access::new(&mut tag);
+
+        assert_none!(assert_ok!(access.next_element::<String>()));
+    }
+
+    #[test]
+    fn first_list_after_name() {
+        let mut tag = Tag::new(b"name;\n1:2", Position::new(0, 0));
+        let mut values = assert_ok!(tag.next());
+        assert_ok!(values.next());
+        unsafe { tag.revisit(values) };
+        let mut access = Access::new(&mut tag);
+
+        assert_some_eq!(assert_ok!(access.next_element::<Vec<u64>>()), vec![1, 2]);
+        assert_none!(assert_ok!(access.next_element::<Vec<u64>>()));
+    }
+
+    #[test]
+    fn too_many_values() {
+        let mut tag = Tag::new(b"1:2:3", Position::new(0, 0));
+        let mut access = Access::new(&mut tag);
+
+        let mut expected = Error::new(error::Kind::UnexpectedValue, Position::new(0, 5));
+        expected.push_segment(Segment::Element(0));
+        assert_err_eq!(access.next_element::<(u64, u64)>(), expected);
+    }
+
+    #[test]
+    fn parameters() {
+        let mut tag = Tag::new(b"1:2;\n3", Position::new(0, 0));
+        let mut access = Access::new(&mut tag);
+
+        assert_some_eq!(assert_ok!(access.next_element::<u64>()), 1);
+        assert_some_eq!(assert_ok!(access.next_element::<u64>()), 2);
+        assert_none!(assert_ok!(access.next_element::<u64>()));
+        assert!(access.read_parameters());
+        // The remaining parameter list is left within the tag.
+        assert_err!(tag.assert_exhausted());
+    }
+
+    #[test]
+    fn parameters_invalid_value() {
+        let mut tag = Tag::new(b"1:foo", Position::new(0, 0));
+        let mut access = Access::new(&mut tag);
+
+        assert_some_eq!(assert_ok!(access.next_element::<u64>()), 1);
+        let mut expected = Error::new(error::Kind::ExpectedU64, Position::new(0, 3));
+        expected.push_segment(Segment::Value(1));
+        assert_err_eq!(access.next_element::<u64>(), expected);
+    }
+
+    #[test]
+    fn not_a_list() {
+        let mut tag = Tag::new(b"1", Position::new(0, 0));
+        let mut access = Access::new(&mut tag);
+
+        let mut expected = Error::new(error::Kind::CannotDeserializeNestedSeq, Position::new(0, 1));
+        expected.push_segment(Segment::Element(0));
+        assert_err_eq!(access.next_element::<()>(), expected);
+    }
+
+    #[test]
+    fn invalid_value() {
+        let mut tag = Tag::new(b"1:foo", Position::new(0, 0));
+        let mut access = Access::new(&mut tag);
+
+        let mut expected = Error::new(error::Kind::ExpectedU64, Position::new(0, 3));
+        expected.push_segment(Segment::Value(1));
+        expected.push_segment(Segment::Element(0));
+        assert_err_eq!(access.next_element::<Vec<u64>>(), expected);
+    }
+}
@@ -1,4 +1,5 @@
 pub(in crate::de) mod field;
+pub(in crate::de) mod lists;
 pub(in crate::de) mod root;
 
 mod element;
@@ -1,21 +1,21 @@
 use super::element;
-use crate::de::{parse::Tags, Error, Result};
+use crate::de::{parse::Tags, Error, Read, Result, Segment};
 use serde::de::{DeserializeSeed, SeqAccess};
-use std::io::Read;
 
 pub(in crate::de) struct Access<'a, R> {
     tags: &'a mut Tags<R>,
+    index: usize,
 }
 
 impl<'a, R> Access<'a, R> {
     pub(in crate::de) fn new(tags: &'a mut Tags<R>) -> Self {
-        Self { tags }
+        Self { tags, index: 0 }
     }
 }
 
 impl<'a, 'de, R> SeqAccess<'de> for Access<'a, R>
 where
-    R: Read,
+    R: Read<'de>,
 {
     type Error = Error;
 
@@ -23,14 +23,22 @@ where
     where
         T: DeserializeSeed<'de>,
     {
-        let tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
+        let tag = match self.tags.next_or_end()? {
+            Some(tag) => tag,
+            None => return Ok(None),
         };
         let stored = tag.into_stored();
         unsafe { self.tags.revisit(stored) };
+        let index = self.index;
+        self.index += 1;
+        // An element continued within the same tag is simply read as the next tag.
+        let mut continued = false;
         Ok(Some(
-            seed.deserialize(element::Deserializer::new(self.tags))?,
+            seed.deserialize(element::Deserializer::new(self.tags).continuing_into(&mut continued))
+                .map_err(|mut error: Error| {
+                    error.push_segment(Segment::Element(index));
+                    error
+                })?,
         ))
     }
 }
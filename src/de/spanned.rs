@@ -0,0 +1,355 @@
+use crate::de::{Error, Position, Result};
+use serde::{
+    de,
+    de::{
+        value::{BorrowedStrDeserializer, SeqDeserializer},
+        DeserializeSeed, MapAccess, Visitor,
+    },
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+// These names are used to recognize `Spanned` within this crate's deserializers. Other
+// deserializers will simply see a struct with these fields.
+pub(in crate::de) const NAME: &str = "$__msd_private_Spanned";
+const START: &str = "$__msd_private_start";
+const END: &str = "$__msd_private_end";
+const VALUE: &str = "$__msd_private_value";
+// The end is given last, as it may only be known once the value has been deserialized.
+const FIELDS: &[&str] = &[START, VALUE, END];
+
+/// A value along with the location in the input it was deserialized from.
+///
+/// When a `Spanned<T>` is deserialized from MSD, `T` is deserialized as usual and the start and end
+/// [`Position`]s of its source are recorded. For a struct field or an element of a sequence of
+/// tags, the span covers the entire tag, from the leading `#` to just past its final `;`. A struct
+/// field read from multiple tags, such as a sequence of repeated tags, spans from the first of
+/// them to the last. For a single parameter, such as an element of a list or a map value, the span
+/// covers only that parameter.
+///
+/// Comparisons and hashing only consider the contained value, not its span. `Spanned<T>` is
+/// serialized the same as `T`.
+///
+/// # Example
+/// ```
+/// use msd::Spanned;
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Song {
+///     title: Spanned<String>,
+///     bpm: Spanned<u64>,
+/// }
+///
+/// let song: Song = msd::from_bytes(b"#title:foo;\n#bpm:120;\n".as_slice()).unwrap();
+///
+/// assert_eq!(song.bpm.get_ref(), &120);
+/// assert_eq!(song.bpm.start().line(), 1);
+/// assert_eq!(song.bpm.start().column(), 0);
+/// assert_eq!(song.bpm.end().column(), 9);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Spanned<T> {
+    start: Position,
+    end: Position,
+    value: T,
+}
+
+impl<T> Spanned<T> {
+    /// Returns the position where the value's source begins.
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    /// Returns the position just past the end of the value's source.
+    pub fn end(&self) -> Position {
+        self.end
+    }
+
+    /// Returns a reference to the contained value.
+    pub fn get_ref(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns a mutable reference to the contained value.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    /// Consumes the `Spanned`, returning the contained value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> AsRef<T> for Spanned<T> {
+    fn as_ref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> AsMut<T> for Spanned<T> {
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T> PartialEq for Spanned<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Eq for Spanned<T> where T: Eq {}
+
+impl<T> PartialOrd for Spanned<T>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T> Ord for Spanned<T>
+where
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T> Hash for Spanned<T>
+where
+    T: Hash,
+{
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.value.hash(state);
+    }
+}
+
+impl<T> Serialize for Spanned<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Spanned<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SpannedVisitor<T>(std::marker::PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for SpannedVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = Spanned<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a spanned value")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                fn position<'de, A>(
+                    map: &mut A,
+                    field: &'static str,
+                ) -> std::result::Result<Position, A::Error>
+                where
+                    A: MapAccess<'de>,
+                {
+                    match map.next_key::<&str>()? {
+                        Some(key) if key == field => {
+                            let (line, column, offset) = map.next_value()?;
+                            Ok(Position::from_parts(line, column, offset))
+                        }
+                        _ => Err(de::Error::missing_field(field)),
+                    }
+                }
+
+                let start = position(&mut map, START)?;
+                let value = match map.next_key::<&str>()? {
+                    Some(VALUE) => map.next_value()?,
+                    _ => return Err(de::Error::missing_field(VALUE)),
+                };
+                let end = position(&mut map, END)?;
+                Ok(Spanned { start, end, value })
+            }
+        }
+
+        deserializer.deserialize_struct(NAME, FIELDS, SpannedVisitor(std::marker::PhantomData))
+    }
+}
+
+// The source of a `Spanned` value.
+pub(in crate::de) trait Source<'de> {
+    // Deserializes the value using `seed`, returning it along with the position just past the end
+    // of its source.
+    fn deserialize_spanned<T>(self, seed: T) -> Result<(T::Value, Position)>
+    where
+        T: DeserializeSeed<'de>;
+}
+
+// A source whose end is known before its value is deserialized.
+pub(in crate::de) struct Fixed<D> {
+    deserializer: D,
+    end: Position,
+}
+
+impl<'de, D> Source<'de> for Fixed<D>
+where
+    D: Deserializer<'de, Error = Error>,
+{
+    fn deserialize_spanned<T>(self, seed: T) -> Result<(T::Value, Position)>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        Ok((seed.deserialize(self.deserializer)?, self.end))
+    }
+}
+
+// Provides a span to the `Spanned` visitor, along with the value deserialized from `source`.
+pub(in crate::de) struct Access<S> {
+    start: Position,
+    end: Option<Position>,
+    source: Option<S>,
+    index: usize,
+}
+
+impl<D> Access<Fixed<D>> {
+    pub(in crate::de) fn new(start: Position, end: Position, deserializer: D) -> Self {
+        Self::with_source(start, Fixed { deserializer, end })
+    }
+}
+
+impl<S> Access<S> {
+    pub(in crate::de) fn with_source(start: Position, source: S) -> Self {
+        Self {
+            start,
+            end: None,
+            source: Some(source),
+            index: 0,
+        }
+    }
+}
+
+impl<'de, S> MapAccess<'de> for Access<S>
+where
+    S: Source<'de>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match FIELDS.get(self.index) {
+            Some(field) => seed
+                .deserialize(BorrowedStrDeserializer::new(field))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let index = self.index;
+        self.index += 1;
+        let position = match index {
+            0 => self.start,
+            1 => {
+                let (value, end) = self
+                    .source
+                    .take()
+                    .expect("call to `next_value()` after value was already deserialized")
+                    .deserialize_spanned(seed)?;
+                self.end = Some(end);
+                return Ok(value);
+            }
+            _ => self
+                .end
+                .expect("call to `next_value()` for end before value was deserialized"),
+        };
+        seed.deserialize(SeqDeserializer::new(
+            [position.line(), position.column(), position.offset()].into_iter(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Spanned;
+    use crate::de::Position;
+    use claims::{assert_ok, assert_ok_eq};
+
+    fn spanned<T>(value: T) -> Spanned<T> {
+        Spanned {
+            start: Position::from_parts(0, 1, 1),
+            end: Position::from_parts(0, 4, 4),
+            value,
+        }
+    }
+
+    #[test]
+    fn accessors() {
+        let mut spanned = spanned(42);
+
+        assert_eq!(spanned.start(), Position::new(0, 1));
+        assert_eq!(spanned.end(), Position::new(0, 4));
+        assert_eq!(spanned.get_ref(), &42);
+        *spanned.get_mut() = 43;
+        assert_eq!(spanned.into_inner(), 43);
+    }
+
+    #[test]
+    fn eq_ignores_span() {
+        let mut other = spanned(42);
+        other.start = Position::new(5, 0);
+
+        assert_eq!(spanned(42), other);
+        assert_ne!(spanned(42), spanned(43));
+    }
+
+    #[test]
+    fn serialize() {
+        assert_ok_eq!(
+            crate::to_bytes(&spanned(vec![("foo".to_owned(), 42)])),
+            b"#foo:42;\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn deserialize_nested() {
+        let deserialized: Vec<(String, Spanned<u64>)> =
+            assert_ok!(crate::from_bytes(b"#foo:1;\n".as_slice()));
+
+        assert_eq!(deserialized[0].1.start(), Position::new(0, 5));
+        assert_eq!(deserialized[0].1.end(), Position::new(0, 6));
+    }
+}
@@ -827,6 +827,7 @@ mod tests {
     #[test]
     fn tuple_struct() {
         #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
         struct TupleStruct(String, u64, (), f64);
 
         let deserializer = Deserializer::new("foo", Position::new(1, 2));
@@ -4,15 +4,59 @@ mod field;
 mod value;
 
 use crate::de::{
-    parse::{StoredTag, StoredValues, Tags},
-    Error, Result,
+    error,
+    parse::{StoredTag, StoredValues, Tag, Tags, Values},
+    Error, Position, Read, Result, Segment, UnknownTags,
 };
-use serde::de::{DeserializeSeed, MapAccess};
-use std::{collections::HashSet, io::Read};
+use serde::de::{value::MapDeserializer, DeserializeSeed, MapAccess};
+use std::{borrow::Cow, collections::HashSet};
+
+// Unknown tags captured by `UnknownTags::Capture`, in the order they were found.
+type Extras = Vec<(String, Vec<String>)>;
+
+// Returns whether `field` names a field of the struct, rather than an unknown tag.
+fn is_field(field: &str, fields: &[&str], unknown_tags: Option<UnknownTags>) -> bool {
+    fields.contains(&field)
+        && !matches!(unknown_tags, Some(UnknownTags::Capture(extras)) if extras == field)
+}
+
+// Returns the remaining values of an unknown tag, whose name has already been read from `values`.
+fn capture<'a>(tag: &mut Tag<'a>, mut values: Values<'a>) -> Result<Vec<String>> {
+    let mut captured = Vec::new();
+    loop {
+        while let Ok(value) = values.next() {
+            captured.push(value.parse_string()?);
+        }
+        match tag.next() {
+            Ok(next_values) => values = next_values,
+            Err(_) => return Ok(captured),
+        }
+    }
+}
+
+// Deserializes captured unknown tags as a map.
+fn deserialize_extras<'de, V>(seed: V, extras: Extras) -> Result<V::Value>
+where
+    V: DeserializeSeed<'de>,
+{
+    seed.deserialize(MapDeserializer::new(extras.into_iter()))
+}
+
+enum Key<F> {
+    // The field's raw tag and values are stored on the `Access` itself.
+    Field { field: F, position: Position },
+    Extras(&'static str),
+    End,
+}
 
 pub(in crate::de) struct Access<'a, R> {
     tags: &'a mut Tags<R>,
+    names: &'static [&'static str],
+    // Prepended to each field's name to form its tag name. Only nonempty for nested structs.
+    prefix: String,
+    // The fields that have not yet been deserialized.
     fields: HashSet<&'static str>,
+    extras: Option<Extras>,
 
     // These stored fields contain raw pointers to the internal buffers of the tag and values
     // respectively. Note that the pointed-to buffers are only guaranteed to be valid until another
@@ -24,9 +68,18 @@ pub(in crate::de) struct Access<'a, R> {
 
 impl<'a, R> Access<'a, R> {
     pub(in crate::de) fn new(tags: &'a mut Tags<R>, fields: &'static [&'static str]) -> Self {
+        Self::nested(tags, fields, String::new())
+    }
+
+    // Creates an `Access` for a struct nested within a field, whose tag names all begin with
+    // `prefix`.
+    fn nested(tags: &'a mut Tags<R>, fields: &'static [&'static str], prefix: String) -> Self {
         Self {
             tags,
+            names: fields,
+            prefix,
             fields: fields.iter().copied().collect(),
+            extras: Some(Vec::new()),
 
             tag: None,
             values: None,
@@ -35,9 +88,93 @@ impl<'a, R> Access<'a, R> {
     }
 }
 
+impl<'a, 'de, R> Access<'a, R>
+where
+    R: Read<'de>,
+{
+    fn next_field(&mut self) -> Result<Key<&'static str>> {
+        let unknown_tags = self.tags.unknown_tags();
+        let separator = self.tags.nested_structs();
+        loop {
+            let mut tag = match self.tags.next_or_end()? {
+                Some(tag) => tag,
+                None => return Ok(self.end(unknown_tags)),
+            };
+            let mut values = tag.next()?;
+            let value = values.next()?;
+            // An empty tag name is the start of the next struct, and a tag name without the
+            // prefix is outside of this nested struct.
+            let field = if value.parse_unit().is_ok() {
+                None
+            } else {
+                value.parse_nested_field_in(&self.prefix, self.names, separator)?
+            };
+
+            if let Some(field) = field {
+                if is_field(&field, self.names, unknown_tags) {
+                    // Only return the field if it has not yet been deserialized. Otherwise, it
+                    // belongs to the next struct.
+                    if let Some(static_field) = self.fields.take(field.as_str()) {
+                        let position = value.position();
+                        // Note that these raw values will only live until the next call to
+                        // `next_field()`, at which point they will be overwritten.
+                        self.values = Some(values.into_stored());
+                        self.tag = Some(tag.into_stored());
+                        return Ok(Key::Field {
+                            field: static_field,
+                            position,
+                        });
+                    }
+                } else {
+                    match unknown_tags {
+                        None => {}
+                        Some(UnknownTags::Skip) => continue,
+                        Some(UnknownTags::Error) => {
+                            return Err(Error::new(
+                                error::Kind::UnknownField(field, self.names),
+                                tag.origin_position(),
+                            ))
+                        }
+                        Some(UnknownTags::Capture(_)) => {
+                            let captured = capture(&mut tag, values)?;
+                            if let Some(extras) = &mut self.extras {
+                                extras.push((field, captured));
+                            }
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            tag.reset();
+            let stored_tag = tag.into_stored();
+            // SAFETY: `stored_tag` references the buffer still active in `self.tags`.
+            unsafe { self.tags.revisit(stored_tag) };
+            return Ok(self.end(unknown_tags));
+        }
+    }
+
+    // Captured unknown tags are provided after all other fields.
+    fn end<F>(&mut self, unknown_tags: Option<UnknownTags>) -> Key<F> {
+        match unknown_tags {
+            Some(UnknownTags::Capture(extras)) if self.fields.remove(extras) => Key::Extras(extras),
+            _ => Key::End,
+        }
+    }
+
+    // Returns the full tag name of `field`.
+    fn tag_name(&self, field: &'static str) -> Cow<'static, str> {
+        if self.prefix.is_empty() {
+            Cow::Borrowed(field)
+        } else {
+            Cow::Owned(format!("{}{}", self.prefix, field))
+        }
+    }
+}
+
 impl<'a, 'de, R> MapAccess<'de> for Access<'a, R>
 where
-    R: Read,
+    R: Read<'de>,
 {
     type Error = Error;
 
@@ -45,29 +182,19 @@ where
     where
         K: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
-        };
-        let mut values = tag.next()?;
-        let value = values.next()?;
-        let field = value.parse_identifier()?;
-
-        // Only return the result if the field is in the list of possible fields for the struct.
-        if let Some(static_field) = self.fields.take(field.as_str()) {
-            let result = seed.deserialize(field::Deserializer::new(&field, value.position()))?;
-            // Note that these raw values will only live until the next call to `next_key_seed()`, at
-            // which point they will be overwritten.
-            self.values = Some(values.into_stored());
-            self.tag = Some(tag.into_stored());
-            self.field = Some(static_field);
-            Ok(Some(result))
-        } else {
-            tag.reset();
-            let stored_tag = tag.into_stored();
-            // SAFETY: `stored_tag` references the buffer still active in `self.tags`.
-            unsafe { self.tags.revisit(stored_tag) };
-            Ok(None)
+        match self.next_field()? {
+            Key::Field { field, position } => {
+                let result = seed.deserialize(field::Deserializer::new(field, position))?;
+                self.field = Some(field);
+                Ok(Some(result))
+            }
+            Key::Extras(extras) => {
+                let position = self.tags.current_position();
+                let result = seed.deserialize(field::Deserializer::new(extras, position))?;
+                self.field = Some(extras);
+                Ok(Some(result))
+            }
+            Key::End => Ok(None),
         }
     }
 
@@ -75,22 +202,34 @@ where
     where
         V: DeserializeSeed<'de>,
     {
-        // SAFETY: `self.tags` is not modified here, so this `Tag` will live longer than the
-        // referenced buffer.
-        let tag = self
-            .tag
+        let field = self
+            .field
             .take()
             .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
+        // SAFETY: `self.tags` is not modified here, so this `Tag` will live longer than the
+        // referenced buffer.
+        let tag = match self.tag.take() {
+            Some(tag) => tag,
+            None => {
+                return deserialize_extras(seed, self.extras.take().unwrap_or_default()).map_err(
+                    |mut error: Error| {
+                        error.push_segment(Segment::Tag(field.to_owned()));
+                        error
+                    },
+                )
+            }
+        };
         let values = self
             .values
             .take()
             .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
-        let field = self
-            .field
-            .take()
-            .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
 
-        seed.deserialize(value::Deserializer::new(field, self.tags, tag, values))
+        let tag_name = self.tag_name(field);
+        seed.deserialize(value::Deserializer::new(&tag_name, self.tags, tag, values))
+            .map_err(|mut error: Error| {
+                error.push_segment(Segment::Tag(field.to_owned()));
+                error
+            })
     }
 
     fn next_entry_seed<K, V>(
@@ -102,33 +241,32 @@ where
         K: DeserializeSeed<'de>,
         V: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
+        let (key, field, value) = match self.next_field()? {
+            Key::Field { field, position } => {
+                let key = key_seed.deserialize(field::Deserializer::new(field, position))?;
+                let tag = self.tag.take().expect("field returned without its tag");
+                let values = self
+                    .values
+                    .take()
+                    .expect("field returned without its values");
+                let tag_name = self.tag_name(field);
+                let value = value_seed
+                    .deserialize(value::Deserializer::new(&tag_name, self.tags, tag, values));
+                (key, field, value)
+            }
+            Key::Extras(extras) => {
+                let position = self.tags.current_position();
+                let key = key_seed.deserialize(field::Deserializer::new(extras, position))?;
+                let value = deserialize_extras(value_seed, self.extras.take().unwrap_or_default());
+                (key, extras, value)
+            }
+            Key::End => return Ok(None),
         };
-        let mut values = tag.next()?;
-        let value = values.next()?;
-        let field = value.parse_identifier()?;
-
-        // Only return the result if the field is in the list of possible fields for the struct.
-        if let Some(static_field) = self.fields.take(field.as_str()) {
-            let key = key_seed.deserialize(field::Deserializer::new(&field, value.position()))?;
-            let stored_tag = tag.into_stored();
-            let stored_values = values.into_stored();
-            let value = value_seed.deserialize(value::Deserializer::new(
-                static_field,
-                self.tags,
-                stored_tag,
-                stored_values,
-            ))?;
-            Ok(Some((key, value)))
-        } else {
-            tag.reset();
-            let stored_tag = tag.into_stored();
-            // SAFETY: `stored_tag` references the buffer still active in `self.tags`.
-            unsafe { self.tags.revisit(stored_tag) };
-            Ok(None)
-        }
+        let value = value.map_err(|mut error: Error| {
+            error.push_segment(Segment::Tag(field.to_owned()));
+            error
+        })?;
+        Ok(Some((key, value)))
     }
 }
 
@@ -136,10 +274,11 @@ where
 mod tests {
     use super::Access;
     use crate::de::{
+        error,
         parse::{Tag, Tags},
-        Position,
+        DeserializerOptions, Error, Position, UnknownTags,
     };
-    use claims::{assert_none, assert_ok, assert_ok_eq, assert_some_eq};
+    use claims::{assert_err_eq, assert_none, assert_ok, assert_ok_eq, assert_some_eq};
     use serde::{
         de,
         de::{MapAccess, Visitor},
@@ -257,4 +396,109 @@ mod tests {
         // Should also revisit the tag.
         assert_ok_eq!(tags.next(), Tag::new(b"bar:42;\n", Position::new(0, 0)));
     }
+
+    #[test]
+    fn next_key_case_sensitive() {
+        let mut tags = Tags::new(b"#FOO:42;\n".as_slice());
+        let mut access = Access::new(&mut tags, &["foo"]);
+
+        assert_none!(assert_ok!(access.next_key::<Identifier>()));
+    }
+
+    #[test]
+    fn next_key_skips_unknown_tag() {
+        let mut tags = Tags::with_options(
+            b"#bar:1;\n#foo:42;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Skip),
+        );
+        let mut access = Access::new(&mut tags, &["foo"]);
+
+        assert_some_eq!(
+            assert_ok!(access.next_key::<Identifier>()),
+            Identifier("foo".to_owned())
+        );
+        assert_ok_eq!(access.next_value::<u64>(), 42);
+    }
+
+    #[test]
+    fn next_key_unknown_tag_error() {
+        let mut tags = Tags::with_options(
+            b"#bar:1;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Error),
+        );
+        let mut access = Access::new(&mut tags, &["foo"]);
+
+        assert_err_eq!(
+            access.next_key::<Identifier>(),
+            Error::new(
+                error::Kind::UnknownField("bar".to_owned(), &["foo"]),
+                Position::new(0, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn next_key_unit_tag_ends_struct() {
+        let mut tags = Tags::with_options(
+            b"#;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Skip),
+        );
+        let mut access = Access::new(&mut tags, &["foo"]);
+
+        assert_none!(assert_ok!(access.next_key::<Identifier>()));
+
+        // Should also revisit the tag.
+        assert_ok_eq!(tags.next(), Tag::new(b";\n", Position::new(0, 0)));
+    }
+
+    #[test]
+    fn next_entry_captures_unknown_tags() {
+        let mut tags = Tags::with_options(
+            b"#bar:1:2;\n#foo:42;\n".as_slice(),
+            DeserializerOptions::new().unknown_tags(UnknownTags::Capture("extras")),
+        );
+        let mut access = Access::new(&mut tags, &["foo", "extras"]);
+
+        assert_some_eq!(
+            assert_ok!(access.next_entry::<Identifier, u64>()),
+            (Identifier("foo".to_owned()), 42)
+        );
+        assert_some_eq!(
+            assert_ok!(access.next_entry::<Identifier, Vec<(String, Vec<String>)>>()),
+            (
+                Identifier("extras".to_owned()),
+                vec![("bar".to_owned(), vec!["1".to_owned(), "2".to_owned()])]
+            )
+        );
+        assert_none!(assert_ok!(access.next_entry::<Identifier, u64>()));
+    }
+
+    #[test]
+    fn next_key_case_insensitive() {
+        let mut tags = Tags::with_options(
+            b"#FOO:42;\n".as_slice(),
+            DeserializerOptions::new().case_insensitive(true),
+        );
+        let mut access = Access::new(&mut tags, &["foo"]);
+
+        assert_some_eq!(
+            assert_ok!(access.next_key::<Identifier>()),
+            Identifier("foo".to_owned())
+        );
+        assert_ok_eq!(access.next_value::<u64>(), 42);
+    }
+
+    #[test]
+    fn next_entry_case_insensitive() {
+        let mut tags = Tags::with_options(
+            b"#Foo:42;\n".as_slice(),
+            DeserializerOptions::new().case_insensitive(true),
+        );
+        let mut access = Access::new(&mut tags, &["foo"]);
+
+        assert_some_eq!(
+            assert_ok!(access.next_entry::<Identifier, u64>()),
+            (Identifier("foo".to_owned()), 42)
+        );
+    }
 }
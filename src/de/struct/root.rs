@@ -1,12 +1,22 @@
+use super::{capture, deserialize_extras, is_field, Extras, Key};
 use crate::de::{
-    parse::{StoredTag, StoredValues, Tags},
-    Error, Result,
+    error,
+    parse::{BufferedTag, StoredTag, StoredValues, Tags},
+    DuplicateTags, Error, Position, Read, Result, Segment, UnknownTags,
 };
 use serde::de::{DeserializeSeed, MapAccess};
-use std::io::Read;
+use std::collections::HashMap;
 
 pub(in crate::de) struct Access<'a, R> {
     tags: &'a mut Tags<R>,
+    fields: &'static [&'static str],
+
+    // The position of the first occurrence of each field, tracked only when handling duplicate
+    // tags.
+    seen: HashMap<String, Position>,
+    arranged: bool,
+    extras: Option<Extras>,
+    ended: bool,
 
     // These stored fields contain raw pointers to the internal buffers of the tag and values
     // respectively. Note that the pointed-to buffers are only guaranteed to be valid until another
@@ -17,9 +27,15 @@ pub(in crate::de) struct Access<'a, R> {
 }
 
 impl<'a, R> Access<'a, R> {
-    pub(in crate::de) fn new(tags: &'a mut Tags<R>) -> Self {
+    pub(in crate::de) fn new(tags: &'a mut Tags<R>, fields: &'static [&'static str]) -> Self {
         Self {
+            seen: HashMap::new(),
+            arranged: false,
+            extras: Some(Vec::new()),
+            ended: false,
+
             tags,
+            fields,
 
             tag: None,
             values: None,
@@ -28,9 +44,252 @@ impl<'a, R> Access<'a, R> {
     }
 }
 
+impl<'a, 'de, R> Access<'a, R>
+where
+    R: Read<'de>,
+{
+    // Reorders the remaining tags according to the duplicate tag policy, and so that the tags of
+    // each nested struct directly follow each other, if required.
+    fn arrange(&mut self) -> Result<()> {
+        if self.arranged {
+            return Ok(());
+        }
+        self.arranged = true;
+        let policy = self
+            .tags
+            .duplicate_tags()
+            .filter(|policy| matches!(policy, DuplicateTags::LastWins | DuplicateTags::Collect));
+        let separator = self.tags.nested_structs();
+        if policy.is_none() && separator.is_none() {
+            return Ok(());
+        }
+
+        let fields = self.fields;
+        let unknown_tags = self.tags.unknown_tags();
+        let buffered = self.tags.read_ahead(|mut tag| {
+            let mut values = tag.next().ok()?;
+            let value = values.next().ok()?;
+            let field = value.parse_field_in(fields, separator).ok()?;
+            if !is_field(&field, fields, unknown_tags) {
+                return None;
+            }
+            let path = match separator {
+                Some(separator) => nested_path(&value.parse_identifier().ok()?, &field, separator),
+                None => Vec::new(),
+            };
+            Some((field, path))
+        })?;
+        let buffered = if separator.is_some() {
+            gather(
+                buffered
+                    .into_iter()
+                    .map(|(tag, named)| match named {
+                        Some((field, path)) => ((tag, Some(field)), path),
+                        None => ((tag, None), Vec::new()),
+                    })
+                    .collect(),
+                0,
+            )
+        } else {
+            buffered
+                .into_iter()
+                .map(|(tag, named)| (tag, named.map(|(field, _)| field)))
+                .collect()
+        };
+        match policy {
+            Some(policy) => self.tags.replay(arrange(buffered, policy)),
+            None => self
+                .tags
+                .replay(buffered.into_iter().map(|(tag, _)| tag).collect()),
+        }
+        Ok(())
+    }
+
+    fn next_field(&mut self) -> Result<Key<String>> {
+        self.arrange()?;
+        let unknown_tags = self.tags.unknown_tags();
+        let separator = self.tags.nested_structs();
+        loop {
+            let mut tag = match self.tags.next_or_end()? {
+                Some(tag) => tag,
+                None => return Ok(self.end(unknown_tags)),
+            };
+            let mut values = tag.next()?;
+            let value = values.next()?;
+            let field = value.parse_field_in(self.fields, separator)?;
+            let position = value.position();
+
+            if !is_field(&field, self.fields, unknown_tags) {
+                match unknown_tags {
+                    // Let the struct decide what to do with the unknown tag.
+                    None => {}
+                    Some(UnknownTags::Skip) => continue,
+                    Some(UnknownTags::Error) => {
+                        return Err(Error::new(
+                            error::Kind::UnknownField(field, self.fields),
+                            tag.origin_position(),
+                        ))
+                    }
+                    Some(UnknownTags::Capture(_)) => {
+                        let captured = capture(&mut tag, values)?;
+                        if let Some(extras) = &mut self.extras {
+                            extras.push((field, captured));
+                        }
+                        continue;
+                    }
+                }
+            }
+
+            // Note that these raw values will only live until the next call to `next_field()`, at
+            // which point they will be overwritten.
+            let values = values.into_stored();
+            let tag = tag.into_stored();
+            if self.check_duplicate(&field, tag.origin_position())? {
+                continue;
+            }
+            self.values = Some(values);
+            self.tag = Some(tag);
+            return Ok(Key::Field { field, position });
+        }
+    }
+
+    // Captured unknown tags are provided after all other fields.
+    fn end<F>(&mut self, unknown_tags: Option<UnknownTags>) -> Key<F> {
+        match unknown_tags {
+            Some(UnknownTags::Capture(extras)) if !self.ended && self.fields.contains(&extras) => {
+                self.ended = true;
+                Key::Extras(extras)
+            }
+            _ => Key::End,
+        }
+    }
+
+    // Returns whether the tag at `position` should be skipped as a duplicate of `field`.
+    fn check_duplicate(&mut self, field: &str, position: Position) -> Result<bool> {
+        let policy = match self.tags.duplicate_tags() {
+            Some(policy) if self.fields.contains(&field) => policy,
+            _ => return Ok(false),
+        };
+        match self.seen.get(field) {
+            Some(_) if policy == DuplicateTags::FirstWins => Ok(true),
+            Some(&first) => Err(Error::new(
+                error::Kind::DuplicateTag(field.to_owned(), first),
+                position,
+            )),
+            None => {
+                self.seen.insert(field.to_owned(), position);
+                Ok(false)
+            }
+        }
+    }
+}
+
+// A tag along with the path returned for it by `nested_path()`.
+type Pathed<T> = (T, Vec<String>);
+
+// Returns the path of field names to the field named by `identifier`, starting with `field`.
+//
+// The path is empty if `identifier` names `field` itself rather than a field of a struct nested
+// within it. Names are lowercased, since grouping the tags of distinct fields together does not
+// change the result.
+fn nested_path(identifier: &str, field: &str, separator: char) -> Vec<String> {
+    let rest = match identifier
+        .get(field.len()..)
+        .and_then(|rest| rest.strip_prefix(separator))
+    {
+        Some(rest) => rest,
+        None => return Vec::new(),
+    };
+    let mut path = vec![field.to_owned()];
+    path.extend(rest.split(separator).map(str::to_ascii_lowercase));
+    path
+}
+
+// Moves the tags of each nested struct into the position of its first tag, so that they directly
+// follow each other. Each struct's tags are then grouped by the fields within it in turn, which
+// also keeps the tags of a field containing a sequence together.
+fn gather<T>(tags: Vec<Pathed<T>>, depth: usize) -> Vec<T> {
+    let mut first: HashMap<String, usize> = HashMap::new();
+    // Each group is marked with whether it is the tags of a nested struct or field.
+    let mut groups: Vec<(bool, Vec<Pathed<T>>)> = Vec::new();
+    for (tag, path) in tags {
+        match path.get(depth).cloned() {
+            Some(name) => match first.get(&name) {
+                Some(&index) => groups[index].1.push((tag, path)),
+                None => {
+                    first.insert(name, groups.len());
+                    groups.push((true, vec![(tag, path)]));
+                }
+            },
+            None => groups.push((false, vec![(tag, path)])),
+        }
+    }
+    groups
+        .into_iter()
+        .flat_map(|(nested, group)| {
+            if nested {
+                gather(group, depth + 1)
+            } else {
+                group.into_iter().map(|(tag, _)| tag).collect()
+            }
+        })
+        .collect()
+}
+
+// Consecutive tags for the same field are treated as a single occurrence, which is how a field
+// containing a sequence is written. Tags that are not for a field are left in place.
+fn arrange(
+    buffered: Vec<(BufferedTag, Option<String>)>,
+    policy: DuplicateTags,
+) -> Vec<BufferedTag> {
+    let mut occurrences: Vec<(Option<String>, Vec<BufferedTag>)> = Vec::new();
+    for (tag, field) in buffered {
+        match occurrences.last_mut() {
+            Some((current, tags)) if field.is_some() && field == *current => tags.push(tag),
+            _ => occurrences.push((field, vec![tag])),
+        }
+    }
+
+    match policy {
+        DuplicateTags::LastWins => {
+            let last: HashMap<String, usize> = occurrences
+                .iter()
+                .enumerate()
+                .filter_map(|(index, (field, _))| field.clone().map(|field| (field, index)))
+                .collect();
+            occurrences
+                .into_iter()
+                .enumerate()
+                .filter(|(index, (field, _))| {
+                    field.as_ref().map_or(true, |field| last[field] == *index)
+                })
+                .flat_map(|(_, (_, tags))| tags)
+                .collect()
+        }
+        _ => {
+            // Move every occurrence of a field into the position of its first occurrence.
+            let mut first: HashMap<String, usize> = HashMap::new();
+            let mut combined: Vec<Vec<BufferedTag>> = Vec::new();
+            for (field, tags) in occurrences {
+                match field {
+                    Some(field) => match first.get(&field) {
+                        Some(&index) => combined[index].extend(tags),
+                        None => {
+                            first.insert(field, combined.len());
+                            combined.push(tags);
+                        }
+                    },
+                    None => combined.push(tags),
+                }
+            }
+            combined.into_iter().flatten().collect()
+        }
+    }
+}
+
 impl<'a, 'de, R> MapAccess<'de> for Access<'a, R>
 where
-    R: Read,
+    R: Read<'de>,
 {
     type Error = Error;
 
@@ -38,45 +297,49 @@ where
     where
         K: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
-        };
-        let mut values = tag.next()?;
-        let value = values.next()?;
-        let field = value.parse_identifier()?;
-
-        let result = seed.deserialize(super::field::Deserializer::new(&field, value.position()))?;
-        // Note that these raw values will only live until the next call to `next_key_seed()`, at
-        // which point they will be overwritten.
-        self.values = Some(values.into_stored());
-        self.tag = Some(tag.into_stored());
-        self.field = Some(field);
-        Ok(Some(result))
+        match self.next_field()? {
+            Key::Field { field, position } => {
+                let result = seed.deserialize(super::field::Deserializer::new(&field, position))?;
+                self.field = Some(field);
+                Ok(Some(result))
+            }
+            Key::Extras(extras) => {
+                let position = self.tags.current_position();
+                let result = seed.deserialize(super::field::Deserializer::new(extras, position))?;
+                self.field = Some(extras.to_owned());
+                Ok(Some(result))
+            }
+            Key::End => Ok(None),
+        }
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
     where
         V: DeserializeSeed<'de>,
     {
-        // SAFETY: `self.tags` is not modified here, so this `Tag` will live longer than the
-        // referenced buffer.
-        let tag = self
-            .tag
-            .take()
-            .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
-        let values = self
-            .values
-            .take()
-            .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
         let field = self
             .field
             .take()
             .expect("call to `next_value()` not preceeded by successful call to `next_key()`");
-
-        seed.deserialize(super::value::Deserializer::new(
-            &field, self.tags, tag, values,
-        ))
+        // SAFETY: `self.tags` is not modified here, so this `Tag` will live longer than the
+        // referenced buffer.
+        let result = match self.tag.take() {
+            Some(tag) => {
+                let values = self.values.take().expect(
+                    "call to `next_value()` not preceeded by successful call to `next_key()`",
+                );
+                let keep_last = self.tags.duplicate_tags() == Some(DuplicateTags::LastWins);
+                seed.deserialize(
+                    super::value::Deserializer::new(&field, self.tags, tag, values)
+                        .keeping_last(keep_last),
+                )
+            }
+            None => deserialize_extras(seed, self.extras.take().unwrap_or_default()),
+        };
+        result.map_err(|mut error: Error| {
+            error.push_segment(Segment::Tag(field));
+            error
+        })
     }
 
     fn next_entry_seed<K, V>(
@@ -88,32 +351,43 @@ where
         K: DeserializeSeed<'de>,
         V: DeserializeSeed<'de>,
     {
-        let mut tag = match self.tags.next() {
-            Ok(tag) => tag,
-            Err(_) => return Ok(None),
+        let (key, field, value) = match self.next_field()? {
+            Key::Field { field, position } => {
+                let key =
+                    key_seed.deserialize(super::field::Deserializer::new(&field, position))?;
+                let tag = self.tag.take().expect("field returned without its tag");
+                let values = self
+                    .values
+                    .take()
+                    .expect("field returned without its values");
+                let keep_last = self.tags.duplicate_tags() == Some(DuplicateTags::LastWins);
+                let value = value_seed.deserialize(
+                    super::value::Deserializer::new(&field, self.tags, tag, values)
+                        .keeping_last(keep_last),
+                );
+                (key, field, value)
+            }
+            Key::Extras(extras) => {
+                let position = self.tags.current_position();
+                let key =
+                    key_seed.deserialize(super::field::Deserializer::new(extras, position))?;
+                let value = deserialize_extras(value_seed, self.extras.take().unwrap_or_default());
+                (key, extras.to_owned(), value)
+            }
+            Key::End => return Ok(None),
         };
-        let mut values = tag.next()?;
-        let value = values.next()?;
-        let field = value.parse_identifier()?;
-
-        let key =
-            key_seed.deserialize(super::field::Deserializer::new(&field, value.position()))?;
-        let stored_tag = tag.into_stored();
-        let stored_values = values.into_stored();
-        let value = value_seed.deserialize(super::value::Deserializer::new(
-            &field,
-            self.tags,
-            stored_tag,
-            stored_values,
-        ))?;
+        let value = value.map_err(|mut error: Error| {
+            error.push_segment(Segment::Tag(field));
+            error
+        })?;
         Ok(Some((key, value)))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Access;
-    use crate::de::parse::Tags;
+    use super::{gather, nested_path, Access};
+    use crate::de::{parse::Tags, DeserializerOptions};
     use claims::{assert_none, assert_ok, assert_ok_eq, assert_some_eq};
     use serde::{
         de,
@@ -154,7 +428,7 @@ mod tests {
     #[test]
     fn next_key_and_value() {
         let mut tags = Tags::new(b"#foo:42;\n".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         assert_some_eq!(
             assert_ok!(access.next_key::<Identifier>()),
@@ -168,7 +442,7 @@ mod tests {
     fn next_value_without_next_key() {
         // Should panic if `next_value()` is called before `next_key()`.
         let mut tags = Tags::new(b"#42;\n".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         let _ = access.next_value::<u64>();
     }
@@ -176,7 +450,7 @@ mod tests {
     #[test]
     fn next_key_none() {
         let mut tags = Tags::new(b"".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         assert_none!(assert_ok!(access.next_key::<Identifier>()));
     }
@@ -186,7 +460,7 @@ mod tests {
     fn next_value_after_next_key_none() {
         // Should panic if `next_value()` is called before `next_key()`.
         let mut tags = Tags::new(b"".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         assert_none!(assert_ok!(access.next_key::<Identifier>()));
         let _ = access.next_value::<u64>();
@@ -195,7 +469,7 @@ mod tests {
     #[test]
     fn next_entry() {
         let mut tags = Tags::new(b"#foo:42;\n".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         assert_some_eq!(
             assert_ok!(access.next_entry::<Identifier, u64>()),
@@ -206,8 +480,80 @@ mod tests {
     #[test]
     fn next_entry_none() {
         let mut tags = Tags::new(b"".as_slice());
-        let mut access = Access::new(&mut tags);
+        let mut access = Access::new(&mut tags, &["foo"]);
 
         assert_none!(assert_ok!(access.next_entry::<Identifier, u64>()));
     }
+
+    #[test]
+    fn next_key_case_sensitive() {
+        let mut tags = Tags::new(b"#FOO:42;\n".as_slice());
+        let mut access = Access::new(&mut tags, &["foo"]);
+
+        assert_some_eq!(
+            assert_ok!(access.next_key::<Identifier>()),
+            Identifier("FOO".to_owned())
+        );
+    }
+
+    #[test]
+    fn next_key_case_insensitive() {
+        let mut tags = Tags::with_options(
+            b"#FOO:42;\n".as_slice(),
+            DeserializerOptions::new().case_insensitive(true),
+        );
+        let mut access = Access::new(&mut tags, &["foo"]);
+
+        assert_some_eq!(
+            assert_ok!(access.next_key::<Identifier>()),
+            Identifier("foo".to_owned())
+        );
+        assert_ok_eq!(access.next_value::<u64>(), 42);
+    }
+
+    #[test]
+    fn next_entry_case_insensitive() {
+        let mut tags = Tags::with_options(
+            b"#Foo:42;\n".as_slice(),
+            DeserializerOptions::new().case_insensitive(true),
+        );
+        let mut access = Access::new(&mut tags, &["foo"]);
+
+        assert_some_eq!(
+            assert_ok!(access.next_entry::<Identifier, u64>()),
+            (Identifier("foo".to_owned()), 42)
+        );
+    }
+
+    #[test]
+    fn nested_path_not_nested() {
+        assert!(nested_path("foo", "foo", '.').is_empty());
+    }
+
+    #[test]
+    fn nested_path_nested() {
+        assert_eq!(
+            nested_path("foo.Bar.baz", "foo", '.'),
+            ["foo", "bar", "baz"]
+        );
+    }
+
+    #[test]
+    fn gather_nested() {
+        let path = |names: &[&str]| names.iter().map(|name| (*name).to_owned()).collect();
+
+        assert_eq!(
+            gather(
+                vec![
+                    (1, path(&["foo", "bar", "baz"])),
+                    (2, path(&[])),
+                    (3, path(&["foo", "qux"])),
+                    (4, path(&["foo", "bar", "quux"])),
+                    (5, path(&["foo", "bar", "baz"])),
+                ],
+                0
+            ),
+            [1, 5, 4, 3, 2]
+        );
+    }
 }
@@ -1,10 +1,16 @@
-use crate::de::{
-    error, map,
-    parse::{StoredTag, StoredValues, Tags},
-    r#enum, seq, tuple, Error, Result,
+use crate::{
+    de::{
+        any, delimited, error, map,
+        parse::{StoredTag, StoredValues, Tags},
+        r#enum, seq, spanned, tuple, Error, Position, Read, Result,
+    },
+    with,
 };
-use serde::{de, de::Visitor};
-use std::io::Read;
+use serde::{
+    de,
+    de::{DeserializeSeed, Visitor},
+};
+use std::borrow::Cow;
 
 pub(in super::super) struct Deserializer<'a, R> {
     field: &'a str,
@@ -12,6 +18,10 @@ pub(in super::super) struct Deserializer<'a, R> {
 
     tag: StoredTag,
     values: StoredValues,
+
+    // Whether a value read from a single tag is read from the last of any consecutive tags for
+    // the same field.
+    keep_last: bool,
 }
 
 impl<'a, R> Deserializer<'a, R> {
@@ -26,31 +36,101 @@ impl<'a, R> Deserializer<'a, R> {
             tags,
             tag,
             values,
+
+            keep_last: false,
+        }
+    }
+
+    // Used for `DuplicateTags::LastWins`, under which consecutive tags for a field are a single
+    // occurrence, as they are how a sequence is written. The remaining tags must already be
+    // buffered, so that the current tag outlives reading the next.
+    pub(in super::super) fn keeping_last(mut self, keep_last: bool) -> Self {
+        self.keep_last = keep_last;
+        self
+    }
+}
+
+impl<'a, 'de, R> Deserializer<'a, R>
+where
+    R: Read<'de>,
+{
+    // Moves on to the last consecutive tag for this field, if keeping the last.
+    fn skip_to_last(&mut self) -> Result<()> {
+        if !self.keep_last {
+            return Ok(());
+        }
+        while let Some(tag) = self.tags.next_or_end()? {
+            let field = self.field;
+            let same = tag
+                .clone()
+                .next()
+                .ok()
+                .and_then(|mut values| values.next().ok()?.parse_identifier_in(&[field]).ok())
+                .map_or(false, |name| name == field);
+            let stored = tag.into_stored();
+            if !same {
+                // SAFETY: `stored` references the buffer still active in `self.tags`.
+                unsafe { self.tags.revisit(stored) };
+                break;
+            }
+            // SAFETY: The remaining tags are buffered, so `stored` is still valid.
+            let mut tag = unsafe { stored.into_tag() };
+            let mut values = tag.next()?;
+            values.next()?;
+            self.values = values.into_stored();
+            self.tag = tag.into_stored();
         }
+        Ok(())
+    }
+}
+
+// A field's value may be read from multiple tags, so its span ends after the last tag consumed.
+impl<'a, 'de, R> spanned::Source<'de> for Deserializer<'a, R>
+where
+    R: Read<'de>,
+{
+    fn deserialize_spanned<T>(self, seed: T) -> Result<(T::Value, Position)>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let tag = unsafe { self.tag.into_tag() };
+        self.tags.begin_span(tag.end_position());
+        let result = seed.deserialize(
+            Deserializer::new(self.field, &mut *self.tags, tag.into_stored(), self.values)
+                .keeping_last(self.keep_last),
+        );
+        let end = self.tags.end_span();
+        Ok((result?, end))
     }
 }
 
 impl<'a, 'de, R> de::Deserializer<'de> for Deserializer<'a, R>
 where
-    R: Read,
+    R: Read<'de>,
 {
     type Error = Error;
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    // Buffered values, such as fields of a flattened struct, are guessed at as described in
+    // `any::content`. Otherwise every parameter list is given, as at the root.
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
+        let tag = unsafe { self.tag.into_tag() };
         let values = unsafe { self.values.into_values() };
-        Err(Error::new(
-            error::Kind::CannotDeserializeAsSelfDescribing,
-            values.current_position(),
-        ))
+        if any::is_buffering::<V>() {
+            any::content::Deserializer::new(tag, values).deserialize_any(visitor)
+        } else {
+            any::tag::Deserializer::new(tag, values).deserialize_any(visitor)
+        }
     }
 
-    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_bool<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -63,10 +143,11 @@ where
         })
     }
 
-    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_i8<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -79,10 +160,11 @@ where
         })
     }
 
-    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_i16<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -95,10 +177,11 @@ where
         })
     }
 
-    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_i32<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -111,10 +194,11 @@ where
         })
     }
 
-    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_i64<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -128,10 +212,11 @@ where
     }
 
     #[cfg(has_i128)]
-    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_i128<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -144,10 +229,11 @@ where
         })
     }
 
-    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_u8<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -160,10 +246,11 @@ where
         })
     }
 
-    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_u16<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -176,10 +263,11 @@ where
         })
     }
 
-    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_u32<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -192,10 +280,11 @@ where
         })
     }
 
-    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_u64<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -209,10 +298,11 @@ where
     }
 
     #[cfg(has_i128)]
-    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_u128<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -225,10 +315,11 @@ where
         })
     }
 
-    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_f32<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -241,10 +332,11 @@ where
         })
     }
 
-    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_f64<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -257,10 +349,11 @@ where
         })
     }
 
-    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_char<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -273,26 +366,33 @@ where
         })
     }
 
-    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_str<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
-        let parsed = value.parse_string()?;
+        // SAFETY: `'de` is the lifetime of the input.
+        let parsed = unsafe { value.parse_str() }?;
         values.assert_exhausted()?;
         unsafe { self.tag.into_tag() }.assert_exhausted()?;
-        visitor.visit_str(&parsed).map_err(|mut error: Error| {
+        match parsed {
+            Cow::Borrowed(borrowed) => visitor.visit_borrowed_str(borrowed),
+            Cow::Owned(owned) => visitor.visit_str(&owned),
+        }
+        .map_err(|mut error: Error| {
             error.set_position(value_position);
             error
         })
     }
 
-    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_string<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -305,26 +405,33 @@ where
         })
     }
 
-    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_bytes<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
-        let parsed = value.parse_byte_buf();
+        // SAFETY: `'de` is the lifetime of the input.
+        let parsed = unsafe { value.parse_bytes() };
         values.assert_exhausted()?;
         unsafe { self.tag.into_tag() }.assert_exhausted()?;
-        visitor.visit_bytes(&parsed).map_err(|mut error: Error| {
+        match parsed {
+            Cow::Borrowed(borrowed) => visitor.visit_borrowed_bytes(borrowed),
+            Cow::Owned(owned) => visitor.visit_bytes(&owned),
+        }
+        .map_err(|mut error: Error| {
             error.set_position(value_position);
             error
         })
     }
 
-    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_byte_buf<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -345,10 +452,11 @@ where
         visitor.visit_some(self)
     }
 
-    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_unit<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let values = unsafe { self.values.into_values() };
         values.assert_exhausted()?;
         unsafe { self.tag.into_tag() }.assert_exhausted()?;
@@ -358,10 +466,11 @@ where
         })
     }
 
-    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_unit_struct<V>(mut self, _name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let values = unsafe { self.values.into_values() };
         values.assert_exhausted()?;
         unsafe { self.tag.into_tag() }.assert_exhausted()?;
@@ -371,10 +480,19 @@ where
         })
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(mut self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if let Some(separator) = with::separator(name) {
+            self.skip_to_last()?;
+            let mut values = unsafe { self.values.into_values() };
+            let result = visitor
+                .visit_newtype_struct(delimited::Deserializer::new(values.next()?, separator))?;
+            values.assert_exhausted()?;
+            unsafe { self.tag.into_tag() }.assert_exhausted()?;
+            return Ok(result);
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -389,10 +507,11 @@ where
         visitor.visit_seq(seq::field::Access::new(self.field, self.tags))
     }
 
-    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    fn deserialize_tuple<V>(mut self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let result = visitor.visit_seq(tuple::Access::new(&mut values, len))?;
         values.assert_exhausted()?;
@@ -401,7 +520,7 @@ where
     }
 
     fn deserialize_tuple_struct<V>(
-        self,
+        mut self,
         _name: &'static str,
         len: usize,
         visitor: V,
@@ -409,6 +528,7 @@ where
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let result = visitor.visit_seq(tuple::Access::new(&mut values, len))?;
         values.assert_exhausted()?;
@@ -416,10 +536,11 @@ where
         Ok(result)
     }
 
-    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_map<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut tag = unsafe { self.tag.into_tag() };
         // SAFETY: `self.values` references the same buffer that `self.tag` references.
         unsafe { tag.revisit(self.values.into_values()) };
@@ -430,13 +551,47 @@ where
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
-        _visitor: V,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == spanned::NAME {
+            let tag = unsafe { self.tag.into_tag() };
+            let start = tag.origin_position();
+            return visitor.visit_map(spanned::Access::with_source(
+                start,
+                Self::new(self.field, self.tags, tag.into_stored(), self.values)
+                    .keeping_last(self.keep_last),
+            ));
+        }
+        if let Some(separator) = self.tags.nested_structs() {
+            // The nested struct's first field is this tag, so it must be read again.
+            let mut tag = unsafe { self.tag.into_tag() };
+            tag.reset();
+            let start = tag.origin_position();
+            let stored_tag = tag.into_stored();
+            // SAFETY: `stored_tag` references the buffer still active in `self.tags`.
+            unsafe { self.tags.revisit(stored_tag) };
+            return visitor
+                .visit_map(super::Access::nested(
+                    self.tags,
+                    fields,
+                    format!("{}{}", self.field, separator),
+                ))
+                .map_err(|mut error: Error| {
+                    // Errors raised by the struct itself are reported at its first tag.
+                    if matches!(
+                        error.kind(),
+                        error::Kind::MissingField(_) | error::Kind::DuplicateField(_)
+                    ) {
+                        error.set_position(start);
+                    }
+                    error
+                });
+        }
         let values = unsafe { self.values.into_values() };
         Err(Error::new(
             error::Kind::CannotDeserializeNestedStruct,
@@ -445,25 +600,27 @@ where
     }
 
     fn deserialize_enum<V>(
-        self,
+        mut self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
-        let result = visitor.visit_enum(r#enum::Access::new(&mut values))?;
+        let result = visitor.visit_enum(r#enum::Access::new(&mut values, variants))?;
         values.assert_exhausted()?;
         unsafe { self.tag.into_tag() }.assert_exhausted()?;
         Ok(result)
     }
 
-    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    fn deserialize_identifier<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.skip_to_last()?;
         let mut values = unsafe { self.values.into_values() };
         let value = values.next()?;
         let value_position = value.position();
@@ -493,7 +650,11 @@ mod tests {
     use super::Deserializer;
     use crate::de::{error, parse::Tags, Error, Position};
     use claims::{assert_err_eq, assert_ok, assert_ok_eq};
-    use serde::{de, de::Visitor, Deserialize};
+    use serde::{
+        de,
+        de::{SeqAccess, Visitor},
+        Deserialize,
+    };
     use serde_bytes::ByteBuf;
     use serde_derive::Deserialize;
     use std::{collections::HashMap, fmt};
@@ -3099,8 +3260,8 @@ mod tests {
 
     #[test]
     fn any() {
-        #[derive(Debug)]
-        struct Any;
+        #[derive(Debug, PartialEq)]
+        struct Any(Vec<Vec<String>>);
 
         impl<'de> Deserialize<'de> for Any {
             fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -3115,13 +3276,24 @@ mod tests {
                     fn expecting(&self, _f: &mut fmt::Formatter) -> fmt::Result {
                         unimplemented!()
                     }
+
+                    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: SeqAccess<'de>,
+                    {
+                        let mut elements = Vec::new();
+                        while let Some(element) = seq.next_element()? {
+                            elements.push(element);
+                        }
+                        Ok(Any(elements))
+                    }
                 }
 
                 deserializer.deserialize_any(AnyVisitor)
             }
         }
 
-        let mut tags = Tags::new(b"#foo:foo;\n".as_slice());
+        let mut tags = Tags::new(b"#foo:bar:baz;\nqux;\n".as_slice());
         let mut tag = assert_ok!(tags.next());
         let mut values = assert_ok!(tag.next());
         let _field = assert_ok!(values.next());
@@ -3129,12 +3301,12 @@ mod tests {
         let stored_values = values.into_stored();
         let deserializer = Deserializer::new("foo", &mut tags, stored_tag, stored_values);
 
-        assert_err_eq!(
+        assert_ok_eq!(
             Any::deserialize(deserializer),
-            Error::new(
-                error::Kind::CannotDeserializeAsSelfDescribing,
-                Position::new(0, 5)
-            )
+            Any(vec![
+                vec!["bar".to_owned(), "baz".to_owned()],
+                vec!["qux".to_owned()]
+            ])
         );
     }
 
@@ -1,5 +1,9 @@
-use crate::de::{error, parse::Values, r#enum, Error, Result};
+use crate::{
+    de::{delimited, error, parse::Values, r#enum, spanned, Error, Result},
+    with,
+};
 use serde::de::Visitor;
+use std::borrow::Cow;
 
 pub(in crate::de) struct Deserializer<'a, 'b> {
     values: &'a mut Values<'b>,
@@ -213,12 +217,15 @@ impl<'a, 'b, 'de> serde::Deserializer<'de> for Deserializer<'a, 'b> {
         V: Visitor<'de>,
     {
         let value = self.values.next()?;
-        visitor
-            .visit_str(&value.parse_string()?)
-            .map_err(|mut error: Error| {
-                error.set_position(value.position());
-                error
-            })
+        // SAFETY: `'de` is the lifetime of the input.
+        match unsafe { value.parse_str() }? {
+            Cow::Borrowed(borrowed) => visitor.visit_borrowed_str(borrowed),
+            Cow::Owned(owned) => visitor.visit_str(&owned),
+        }
+        .map_err(|mut error: Error| {
+            error.set_position(value.position());
+            error
+        })
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
@@ -239,12 +246,15 @@ impl<'a, 'b, 'de> serde::Deserializer<'de> for Deserializer<'a, 'b> {
         V: Visitor<'de>,
     {
         let value = self.values.next()?;
-        visitor
-            .visit_bytes(&value.parse_byte_buf())
-            .map_err(|mut error: Error| {
-                error.set_position(value.position());
-                error
-            })
+        // SAFETY: `'de` is the lifetime of the input.
+        match unsafe { value.parse_bytes() } {
+            Cow::Borrowed(borrowed) => visitor.visit_borrowed_bytes(borrowed),
+            Cow::Owned(owned) => visitor.visit_bytes(&owned),
+        }
+        .map_err(|mut error: Error| {
+            error.set_position(value.position());
+            error
+        })
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
@@ -260,14 +270,29 @@ impl<'a, 'b, 'de> serde::Deserializer<'de> for Deserializer<'a, 'b> {
             })
     }
 
-    fn deserialize_option<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(Error::new(
-            error::Kind::CannotDeserializeAsOptionInTuple,
-            self.values.current_position(),
-        ))
+        if !self.values.empty_as_none() {
+            return Err(Error::new(
+                error::Kind::CannotDeserializeAsOptionInTuple,
+                self.values.current_position(),
+            ));
+        }
+        // Look ahead at the next value, only consuming it here if it is `None`.
+        let mut values = self.values.clone();
+        let value = values.next()?;
+        if value.is_empty() {
+            *self.values = values;
+            visitor.visit_none().map_err(|mut error: Error| {
+                error.set_position(value.position());
+                error
+            })
+        } else {
+            self.values.mark_next_as_some();
+            visitor.visit_some(self)
+        }
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
@@ -290,21 +315,26 @@ impl<'a, 'b, 'de> serde::Deserializer<'de> for Deserializer<'a, 'b> {
         })
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if let Some(separator) = with::separator(name) {
+            return visitor.visit_newtype_struct(delimited::Deserializer::new(
+                self.values.next()?,
+                separator,
+            ));
+        }
         visitor.visit_newtype_struct(self)
     }
 
-    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(Error::new(
-            error::Kind::CannotDeserializeAsSeqInTuple,
-            self.values.current_position(),
-        ))
+        // A sequence is a single parameter with its elements separated by `,`, such as the
+        // measures of a chart's note data.
+        delimited::deserialize(&self.values.next()?, b',', visitor)
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
@@ -316,13 +346,16 @@ impl<'a, 'b, 'de> serde::Deserializer<'de> for Deserializer<'a, 'b> {
 
     fn deserialize_tuple_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         len: usize,
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if let Some(separator) = with::separator(name) {
+            return delimited::deserialize(&self.values.next()?, separator, visitor);
+        }
         visitor.visit_seq(super::Access::new(self.values, len))
     }
 
@@ -338,13 +371,21 @@ impl<'a, 'b, 'de> serde::Deserializer<'de> for Deserializer<'a, 'b> {
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         _fields: &'static [&'static str],
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == spanned::NAME {
+            let value = self.values.clone().next()?;
+            return visitor.visit_map(spanned::Access::new(
+                value.position(),
+                value.end_position(),
+                self,
+            ));
+        }
         Err(Error::new(
             error::Kind::CannotDeserializeAsStructInTuple,
             self.values.current_position(),
@@ -354,13 +395,13 @@ impl<'a, 'b, 'de> serde::Deserializer<'de> for Deserializer<'a, 'b> {
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_enum(r#enum::Access::new(self.values))
+        visitor.visit_enum(r#enum::Access::new(self.values, variants))
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
@@ -390,7 +431,11 @@ impl<'a, 'b, 'de> serde::Deserializer<'de> for Deserializer<'a, 'b> {
 #[cfg(test)]
 mod tests {
     use super::Deserializer;
-    use crate::de::{error, parse::Values, Error, Position};
+    use crate::de::{
+        error,
+        parse::{Value, Values},
+        Error, Position, Segment,
+    };
     use claims::{assert_err_eq, assert_ok_eq};
     use serde::{de, de::Visitor, Deserialize};
     use serde_bytes::ByteBuf;
@@ -2118,19 +2163,107 @@ mod tests {
     }
 
     #[test]
-    fn seq() {
-        let mut values = Values::new(b"", Position::new(1, 2));
+    fn option_none() {
+        let mut values = Values::new(b" :42", Position::new(0, 0)).treating_empty_as_none();
+
+        assert_ok_eq!(
+            Option::<u64>::deserialize(Deserializer::new(&mut values)),
+            None
+        );
+        assert_ok_eq!(u64::deserialize(Deserializer::new(&mut values)), 42);
+    }
+
+    #[test]
+    fn option_none_comment() {
+        let mut values = Values::new(b"// comment\n", Position::new(0, 0)).treating_empty_as_none();
+        let deserializer = Deserializer::new(&mut values);
+
+        assert_ok_eq!(Option::<String>::deserialize(deserializer), None);
+    }
+
+    #[test]
+    fn option_some() {
+        let mut values = Values::new(b"42:foo", Position::new(0, 0)).treating_empty_as_none();
+
+        assert_ok_eq!(
+            Option::<u64>::deserialize(Deserializer::new(&mut values)),
+            Some(42)
+        );
+        assert_ok_eq!(
+            String::deserialize(Deserializer::new(&mut values)),
+            "foo".to_owned()
+        );
+    }
+
+    #[test]
+    fn option_some_empty() {
+        let mut values = Values::new(b"\\0", Position::new(0, 0)).treating_empty_as_none();
+        let deserializer = Deserializer::new(&mut values);
+
+        assert_ok_eq!(
+            Option::<String>::deserialize(deserializer),
+            Some(String::new())
+        );
+    }
+
+    #[test]
+    fn empty_marker_outside_option() {
+        let mut values = Values::new(b"\\0b:\\0", Position::new(0, 0)).treating_empty_as_none();
+
+        assert_ok_eq!(
+            String::deserialize(Deserializer::new(&mut values)),
+            "\\0b".to_owned()
+        );
+        assert_ok_eq!(
+            Option::<String>::deserialize(Deserializer::new(&mut values)),
+            Some(String::new())
+        );
+    }
+
+    #[test]
+    fn option_some_invalid() {
+        let mut values = Values::new(b"foo", Position::new(0, 0)).treating_empty_as_none();
         let deserializer = Deserializer::new(&mut values);
 
         assert_err_eq!(
-            Vec::<()>::deserialize(deserializer),
-            Error::new(
-                error::Kind::CannotDeserializeAsSeqInTuple,
-                Position::new(1, 2)
-            )
+            Option::<u64>::deserialize(deserializer),
+            Error::new(error::Kind::ExpectedU64, Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn seq() {
+        let mut values = Values::new(b"0000\n1000,0100\\,:foo", Position::new(0, 0));
+        let deserializer = Deserializer::new(&mut values);
+
+        assert_ok_eq!(
+            Vec::<String>::deserialize(deserializer),
+            vec!["0000\n1000".to_owned(), "0100,".to_owned()]
+        );
+        assert_ok_eq!(
+            values.next(),
+            Value::new(b"foo", Position::from_parts(1, 12, 17))
         );
     }
 
+    #[test]
+    fn seq_empty() {
+        let mut values = Values::new(b"", Position::new(0, 0));
+        let deserializer = Deserializer::new(&mut values);
+
+        assert_ok_eq!(Vec::<u64>::deserialize(deserializer), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn seq_invalid_element() {
+        let mut values = Values::new(b"1,foo", Position::new(0, 0));
+        let deserializer = Deserializer::new(&mut values);
+        let mut expected = Error::new(error::Kind::ExpectedU64, Position::from_parts(0, 2, 2));
+        expected.push_segment(Segment::Value(1));
+
+        assert_err_eq!(Vec::<u64>::deserialize(deserializer), expected);
+    }
+
     #[test]
     fn map() {
         let mut values = Values::new(b"", Position::new(1, 2));
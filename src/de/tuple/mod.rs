@@ -1,16 +1,21 @@
 pub(in crate::de) mod element;
 
-use super::{parse::Values, Error, Result};
+use super::{parse::Values, Error, Result, Segment};
 use serde::de::{DeserializeSeed, SeqAccess};
 
 pub(in crate::de) struct Access<'a, 'b> {
     values: &'a mut Values<'b>,
     len: usize,
+    index: usize,
 }
 
 impl<'a, 'b> Access<'a, 'b> {
     pub(in crate::de) fn new(values: &'a mut Values<'b>, len: usize) -> Self {
-        Self { values, len }
+        Self {
+            values,
+            len,
+            index: 0,
+        }
     }
 }
 
@@ -22,8 +27,14 @@ impl<'a, 'b, 'de> SeqAccess<'de> for Access<'a, 'b> {
         T: DeserializeSeed<'de>,
     {
         self.len = self.len.saturating_sub(1);
+        let index = self.index;
+        self.index += 1;
         Ok(Some(
-            seed.deserialize(element::Deserializer::new(self.values))?,
+            seed.deserialize(element::Deserializer::new(self.values))
+                .map_err(|mut error: Error| {
+                    error.push_segment(Segment::Value(index));
+                    error
+                })?,
         ))
     }
 
@@ -35,7 +46,7 @@ impl<'a, 'b, 'de> SeqAccess<'de> for Access<'a, 'b> {
 #[cfg(test)]
 mod tests {
     use super::Access;
-    use crate::de::{error, parse::Values, Error, Position};
+    use crate::de::{error, parse::Values, Error, Position, Segment};
     use claims::{assert_err_eq, assert_ok, assert_some_eq};
     use serde::de::SeqAccess;
 
@@ -48,10 +59,9 @@ mod tests {
         let mut access = Access::new(&mut values, 0);
 
         assert_some_eq!(access.size_hint(), 0);
-        assert_err_eq!(
-            access.next_element::<bool>(),
-            Error::new(error::Kind::EndOfValues, Position::new(0, 0))
-        );
+        let mut expected = Error::new(error::Kind::EndOfValues, Position::new(0, 0));
+        expected.push_segment(Segment::Value(0));
+        assert_err_eq!(access.next_element::<bool>(), expected);
         assert_some_eq!(access.size_hint(), 0);
     }
 
@@ -63,10 +73,9 @@ mod tests {
         assert_some_eq!(access.size_hint(), 1);
         assert_some_eq!(assert_ok!(access.next_element::<u64>()), 42);
         assert_some_eq!(access.size_hint(), 0);
-        assert_err_eq!(
-            access.next_element::<bool>(),
-            Error::new(error::Kind::EndOfValues, Position::new(0, 2))
-        );
+        let mut expected = Error::new(error::Kind::EndOfValues, Position::new(0, 2));
+        expected.push_segment(Segment::Value(1));
+        assert_err_eq!(access.next_element::<bool>(), expected);
         assert_some_eq!(access.size_hint(), 0);
     }
 
@@ -83,10 +92,9 @@ mod tests {
         assert_some_eq!(access.size_hint(), 1);
         assert_some_eq!(assert_ok!(access.next_element::<u64>()), 42);
         assert_some_eq!(access.size_hint(), 0);
-        assert_err_eq!(
-            access.next_element::<bool>(),
-            Error::new(error::Kind::EndOfValues, Position::new(0, 6))
-        );
+        let mut expected = Error::new(error::Kind::EndOfValues, Position::new(0, 6));
+        expected.push_segment(Segment::Value(2));
+        assert_err_eq!(access.next_element::<bool>(), expected);
         assert_some_eq!(access.size_hint(), 0);
     }
 
@@ -105,10 +113,9 @@ mod tests {
         assert_some_eq!(access.size_hint(), 1);
         assert_some_eq!(assert_ok!(access.next_element::<f64>()), 1.2);
         assert_some_eq!(access.size_hint(), 0);
-        assert_err_eq!(
-            access.next_element::<bool>(),
-            Error::new(error::Kind::EndOfValues, Position::new(0, 10))
-        );
+        let mut expected = Error::new(error::Kind::EndOfValues, Position::new(0, 10));
+        expected.push_segment(Segment::Value(3));
+        assert_err_eq!(access.next_element::<bool>(), expected);
         assert_some_eq!(access.size_hint(), 0);
     }
 }
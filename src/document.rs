@@ -0,0 +1,955 @@
+//! A lossless representation of MSD files.
+//!
+//! [`Document`] keeps every byte of its input, including comments, whitespace and escape
+//! sequences. Tags can be edited in place, and everything that was not edited is written back out
+//! exactly as it was read.
+//!
+//! # Example
+//! ```
+//! use msd::Document;
+//!
+//! let mut document =
+//!     Document::from_bytes(b"// Hand-written notes.\n#OFFSET:0.123;  // synced\n").unwrap();
+//!
+//! let offset = document.get_mut("OFFSET").unwrap();
+//! assert_eq!(offset.parameter(0, 0), Some("0.123".to_owned()));
+//! offset.set_parameter(0, 0, "0.250").unwrap();
+//!
+//! assert_eq!(
+//!     document.to_bytes(),
+//!     b"// Hand-written notes.\n#OFFSET:0.250;  // synced\n"
+//! );
+//! ```
+
+use crate::{
+    de,
+    de::{raw::Reader, DeserializerOptions, Dialect},
+    ser, Encoding,
+};
+use std::{io, ops::Range, slice};
+
+// Fails with the contained error once read, so that an error that cut reading short is reported
+// directly after the bytes that were read.
+struct Failed(Option<io::Error>);
+
+impl io::Read for Failed {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        self.0.take().map_or(Ok(0), Err)
+    }
+}
+
+/// An MSD file, preserving its exact formatting.
+///
+/// Tags are delimited exactly as when deserializing with the same [`DeserializerOptions`]. Any
+/// comments and whitespace before the first tag, or anything at all for [`Dialect::Dwi`], are kept
+/// as-is.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Document {
+    // Everything before the first tag.
+    leading: Vec<u8>,
+    tags: Vec<Tag>,
+
+    encoding: Encoding,
+    dialect: Dialect,
+}
+
+impl Document {
+    /// Creates a new `Document` containing no tags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a `Document` from a slice of bytes.
+    ///
+    /// # Errors
+    /// Returns an error if the input is not valid MSD.
+    pub fn from_bytes(bytes: &[u8]) -> de::Result<Self> {
+        Self::from_bytes_with_options(bytes, DeserializerOptions::new())
+    }
+
+    /// Parses a `Document` from a slice of bytes, interpreting it according to the given
+    /// `options`.
+    ///
+    /// If the [encoding is to be detected], it is guessed from the whole input.
+    ///
+    /// # Errors
+    /// Returns an error if the input is not valid MSD, or if it exceeds the given limits.
+    ///
+    /// [encoding is to be detected]: DeserializerOptions::detect_encoding()
+    pub fn from_bytes_with_options(bytes: &[u8], options: DeserializerOptions) -> de::Result<Self> {
+        Self::parse(bytes, None, options)
+    }
+
+    /// Parses a `Document` from the given `reader`.
+    ///
+    /// # Errors
+    /// Returns an error if the input is not valid MSD or if the underlying reader fails.
+    pub fn from_reader<R>(reader: R) -> de::Result<Self>
+    where
+        R: io::Read,
+    {
+        Self::from_reader_with_options(reader, DeserializerOptions::new())
+    }
+
+    /// Parses a `Document` from the given `reader`, interpreting it according to the given
+    /// `options`.
+    ///
+    /// # Errors
+    /// Returns an error if the input is not valid MSD, if it exceeds the given limits, or if the
+    /// underlying reader fails.
+    pub fn from_reader_with_options<R>(
+        mut reader: R,
+        options: DeserializerOptions,
+    ) -> de::Result<Self>
+    where
+        R: io::Read,
+    {
+        let mut bytes = Vec::new();
+        let error = reader.read_to_end(&mut bytes).err();
+        Self::parse(&bytes, error, options)
+    }
+
+    // Parses `bytes` using the same reader as deserialization, recording where each tag and
+    // parameter lies within them.
+    fn parse(
+        bytes: &[u8],
+        error: Option<io::Error>,
+        options: DeserializerOptions,
+    ) -> de::Result<Self> {
+        // The encoding is decided up front, so that every tag is read and edited in the same one.
+        let encoding = match options.get_encoding() {
+            Some(encoding) => encoding,
+            None if bytes.is_ascii() => Encoding::Utf8,
+            None => Encoding::detect(bytes),
+        };
+        let dialect = options.get_dialect();
+        let mut reader = Reader::with_options(
+            io::Read::chain(bytes, Failed(error)),
+            options.encoding(encoding),
+        );
+
+        // The offset just past each tag's `#`, along with the ranges of its parameters relative to
+        // that offset.
+        let mut parsed = Vec::new();
+        while let Some(tag) = reader.next_tag()? {
+            let start = tag.position().offset() + 1;
+            let mut lists = Vec::new();
+            for list in tag {
+                let mut ranges = Vec::new();
+                for parameter in list? {
+                    let parameter = parameter?;
+                    let offset = parameter.position().offset() - start;
+                    ranges.push(offset..offset + parameter.as_raw_bytes().len());
+                }
+                lists.push(ranges);
+            }
+            parsed.push((start, lists));
+        }
+
+        let leading = parsed
+            .first()
+            .map_or(bytes, |(start, _)| &bytes[..start - 1])
+            .to_vec();
+        let ends = parsed
+            .iter()
+            .skip(1)
+            .map(|(start, _)| start - 1)
+            .chain(Some(bytes.len()));
+        let tags = parsed
+            .iter()
+            .zip(ends)
+            .map(|((start, lists), end)| {
+                Tag::from_parts(
+                    bytes[*start..end].to_vec(),
+                    lists.clone(),
+                    encoding,
+                    dialect,
+                )
+            })
+            .collect();
+        Ok(Self {
+            leading,
+            tags,
+
+            encoding,
+            dialect,
+        })
+    }
+
+    /// Returns the encoding of the document's text.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns an iterator over the document's tags, in the order they appear.
+    pub fn tags(&self) -> slice::Iter<'_, Tag> {
+        self.tags.iter()
+    }
+
+    /// Returns a mutable iterator over the document's tags, in the order they appear.
+    pub fn tags_mut(&mut self) -> slice::IterMut<'_, Tag> {
+        self.tags.iter_mut()
+    }
+
+    /// Returns the first tag with the given `name`, if one exists.
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.tags.iter().find(|tag| tag.name() == name)
+    }
+
+    /// Returns the first tag with the given `name` mutably, if one exists.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Tag> {
+        self.tags.iter_mut().find(|tag| tag.name() == name)
+    }
+
+    /// Appends a tag to the end of the document.
+    ///
+    /// If the document does not already end with a newline, one is inserted before the new tag.
+    ///
+    /// A tag in a different encoding than the document, such as one created by [`Tag::new()`] for
+    /// a document that is not UTF-8, is rewritten in the document's encoding from its name and
+    /// parameter lists. Any comments or formatting within such a tag are not kept.
+    ///
+    /// # Errors
+    /// Returns an error if the tag contains a character that cannot be represented in the
+    /// document's encoding.
+    pub fn push(&mut self, mut tag: Tag) -> ser::Result<()> {
+        if tag.encoding != self.encoding {
+            tag = Tag::build(&tag.name(), &tag.parameter_lists(), self.encoding)?;
+        }
+        tag.dialect = self.dialect;
+
+        let last = self
+            .tags
+            .last_mut()
+            .map_or(&mut self.leading, |tag| &mut tag.bytes);
+        if !last.is_empty() && !last.ends_with(b"\n") {
+            last.push(b'\n');
+        }
+        self.tags.push(tag);
+        Ok(())
+    }
+
+    /// Removes and returns the first tag with the given `name`, if one exists.
+    pub fn remove(&mut self, name: &str) -> Option<Tag> {
+        let index = self.tags.iter().position(|tag| tag.name() == name)?;
+        Some(self.tags.remove(index))
+    }
+
+    /// Writes the document to the given `writer`.
+    pub fn to_writer<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        writer.write_all(&self.leading)?;
+        for tag in &self.tags {
+            writer.write_all(b"#")?;
+            writer.write_all(&tag.bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the document to a byte buffer.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            self.leading.len()
+                + self
+                    .tags
+                    .iter()
+                    .map(|tag| tag.bytes.len() + 1)
+                    .sum::<usize>(),
+        );
+        bytes.extend_from_slice(&self.leading);
+        for tag in &self.tags {
+            bytes.push(b'#');
+            bytes.extend_from_slice(&tag.bytes);
+        }
+        bytes
+    }
+}
+
+/// A single tag within a [`Document`].
+///
+/// A tag holds its raw bytes, from just after its `#` up to the `#` of the next tag. This includes
+/// any whitespace and comments following its final `;`.
+///
+/// As with [`value::Tag`](crate::value::Tag), the first parameter list does not include the tag's
+/// name.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Tag {
+    bytes: Vec<u8>,
+    // The raw bytes of each parameter, grouped into lists. The first parameter of the first list
+    // is the tag's name.
+    lists: Vec<Vec<Range<usize>>>,
+
+    encoding: Encoding,
+    dialect: Dialect,
+}
+
+impl Tag {
+    /// Creates a new UTF-8 `Tag` with the given `name` and `parameter_lists`.
+    ///
+    /// Each parameter list is terminated by a `;` and a newline.
+    pub fn new(name: &str, parameter_lists: &[Vec<String>]) -> Self {
+        Self::build(name, parameter_lists, Encoding::Utf8)
+            .expect("UTF-8 cannot represent every character")
+    }
+
+    fn from_parts(
+        bytes: Vec<u8>,
+        mut lists: Vec<Vec<Range<usize>>>,
+        encoding: Encoding,
+        dialect: Dialect,
+    ) -> Self {
+        // Lists after the first usually begin on a new line. As when deserializing into a `Value`
+        // with trimmed strings, that leading whitespace is not part of the first parameter.
+        for list in lists.iter_mut().skip(1) {
+            if let Some(range) = list.first_mut() {
+                while range.start < range.end && bytes[range.start].is_ascii_whitespace() {
+                    range.start += 1;
+                }
+            }
+        }
+        Self {
+            bytes,
+            lists,
+
+            encoding,
+            dialect,
+        }
+    }
+
+    // Writes a tag in the given `encoding`.
+    fn build(name: &str, parameter_lists: &[Vec<String>], encoding: Encoding) -> ser::Result<Self> {
+        // Appends `parameter` to `bytes`, returning where it was written.
+        fn push(
+            bytes: &mut Vec<u8>,
+            parameter: &str,
+            encoding: Encoding,
+        ) -> ser::Result<Range<usize>> {
+            let start = bytes.len();
+            bytes.extend(ser::escape(parameter, encoding)?);
+            Ok(start..bytes.len())
+        }
+
+        let mut bytes = Vec::new();
+        let mut lists = Vec::new();
+
+        let mut first = vec![push(&mut bytes, name, encoding)?];
+        let mut parameter_lists = parameter_lists.iter();
+        for parameter in parameter_lists.next().into_iter().flatten() {
+            bytes.push(b':');
+            first.push(push(&mut bytes, parameter, encoding)?);
+        }
+        bytes.extend_from_slice(b";\n");
+        lists.push(first);
+        for list in parameter_lists {
+            let mut ranges = Vec::with_capacity(list.len());
+            for (index, parameter) in list.iter().enumerate() {
+                if index > 0 {
+                    bytes.push(b':');
+                }
+                ranges.push(push(&mut bytes, parameter, encoding)?);
+            }
+            if ranges.is_empty() {
+                // An empty list is read back as a single empty parameter.
+                ranges.push(bytes.len()..bytes.len());
+            }
+            bytes.extend_from_slice(b";\n");
+            lists.push(ranges);
+        }
+
+        Ok(Self {
+            bytes,
+            lists,
+
+            encoding,
+            dialect: Dialect::default(),
+        })
+    }
+
+    // Locates the raw bytes of the given parameter, accounting for the tag's name.
+    fn parameter_range(&self, list: usize, index: usize) -> Option<Range<usize>> {
+        let index = if list == 0 { index + 1 } else { index };
+        self.lists.get(list)?.get(index).cloned()
+    }
+
+    fn decode(&self, range: Range<usize>) -> String {
+        self.encoding
+            .decode_lossy(&de::clean(&self.bytes[range], self.encoding, self.dialect))
+            .into_owned()
+    }
+
+    /// Returns the tag's name.
+    ///
+    /// Leading and trailing whitespace is not included.
+    pub fn name(&self) -> String {
+        self.decode(self.lists[0][0].clone())
+            .trim_matches(|c: char| c.is_ascii_whitespace())
+            .to_owned()
+    }
+
+    /// Returns the tag's parameter lists.
+    ///
+    /// Escape sequences are interpreted and comments are removed, exactly as when deserializing a
+    /// string. Bytes that are not valid in the tag's encoding are replaced with
+    /// [`U+FFFD`](std::char::REPLACEMENT_CHARACTER).
+    pub fn parameter_lists(&self) -> Vec<Vec<String>> {
+        self.lists
+            .iter()
+            .enumerate()
+            .map(|(index, list)| {
+                list.iter()
+                    .skip(if index == 0 { 1 } else { 0 })
+                    .map(|range| self.decode(range.clone()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the parameter at `index` within the parameter list at `list`, if it exists.
+    ///
+    /// The parameter is decoded the same way as in [`parameter_lists()`](Tag::parameter_lists()).
+    pub fn parameter(&self, list: usize, index: usize) -> Option<String> {
+        self.parameter_range(list, index)
+            .map(|range| self.decode(range))
+    }
+
+    /// Replaces the parameter at `index` within the parameter list at `list`, returning `None` if
+    /// it does not exist.
+    ///
+    /// `value` is escaped as necessary and written in the tag's encoding. Only the parameter's
+    /// value is replaced: whitespace and comments surrounding it within the parameter are kept, as
+    /// is everything in the tag outside of the parameter, including comments after the tag's final
+    /// `;`.
+    ///
+    /// # Errors
+    /// Returns an error if `value` contains a character that cannot be represented in the tag's
+    /// encoding.
+    pub fn set_parameter(
+        &mut self,
+        list: usize,
+        index: usize,
+        value: &str,
+    ) -> Option<ser::Result<()>> {
+        let parameter = self.parameter_range(list, index)?;
+        let encoded = match ser::escape(value, self.encoding) {
+            Ok(encoded) => encoded,
+            Err(error) => return Some(Err(error)),
+        };
+        let content =
+            de::content_range(&self.bytes[parameter.clone()], self.encoding, self.dialect);
+        let replaced = parameter.start + content.start..parameter.start + content.end;
+        let inserted = encoded.len();
+        self.bytes.splice(replaced.clone(), encoded);
+
+        // Everything following the replaced value is shifted by the change in its length.
+        let shift = |position: usize| position - replaced.len() + inserted;
+        let parameter_index = (list, if list == 0 { index + 1 } else { index });
+        for (list_index, ranges) in self.lists.iter_mut().enumerate() {
+            for (index, range) in ranges.iter_mut().enumerate() {
+                if (list_index, index) == parameter_index {
+                    range.end = shift(range.end);
+                } else if range.start >= parameter.end {
+                    *range = shift(range.start)..shift(range.end);
+                }
+            }
+        }
+        Some(Ok(()))
+    }
+
+    /// Returns the tag's raw bytes, from just after its `#` up to the beginning of the next tag.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Document, Tag};
+    use crate::{
+        de,
+        de::{DeserializerOptions, Dialect, Limit, Limits},
+        ser, Encoding,
+    };
+    use claims::{
+        assert_err, assert_err_eq, assert_none, assert_ok, assert_ok_eq, assert_some,
+        assert_some_eq,
+    };
+    use std::io;
+
+    const SIMFILE: &[u8] = b"// Converted by hand.\n\n#TITLE:Foo\\:Bar;\n#ARTIST:baz; // unsure\n#OFFSET:  0.123 ;\r\n#NOTES:\n     dance-single:\n     :\n     Hard:\n     9:\n     0,0,0,0,0:\n0000\n0100 // jump here\n,\n0010\n;\n";
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(
+            assert_ok!(Document::from_bytes(SIMFILE)).to_bytes(),
+            SIMFILE
+        );
+    }
+
+    #[test]
+    fn round_trip_malformed() {
+        let input = b"garbage\n#FOO:bar\n#BAZ:qux#quux\\";
+
+        assert_eq!(
+            assert_ok!(Document::from_bytes_with_options(
+                input,
+                DeserializerOptions::new().dialect(Dialect::Dwi)
+            ))
+            .to_bytes(),
+            input
+        );
+    }
+
+    #[test]
+    fn leading_garbage() {
+        let error = assert_err!(Document::from_bytes(b"garbage\n#FOO:bar;\n"));
+        assert_eq!(error.kind(), &de::Kind::ExpectedTag);
+        assert_eq!(error.offset(), 0);
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        assert_eq!(assert_ok!(Document::from_bytes(b"")).to_bytes(), b"");
+    }
+
+    #[test]
+    fn tags() {
+        let document = assert_ok!(Document::from_bytes(SIMFILE));
+
+        assert_eq!(
+            document.tags().map(Tag::name).collect::<Vec<_>>(),
+            vec!["TITLE", "ARTIST", "OFFSET", "NOTES"]
+        );
+    }
+
+    #[test]
+    fn leading_comment_with_number_sign() {
+        let document = assert_ok!(Document::from_bytes(b"// #not a tag\n#FOO:bar;\n"));
+
+        assert_eq!(
+            document.tags().map(Tag::name).collect::<Vec<_>>(),
+            vec!["FOO"]
+        );
+    }
+
+    #[test]
+    fn missing_terminator() {
+        let document = assert_ok!(Document::from_bytes(b"#FOO:bar\n#BAZ:qux;\n"));
+
+        assert_some_eq!(
+            document.get("FOO").map(Tag::parameter_lists),
+            vec![vec!["bar".to_owned()]]
+        );
+        assert_some!(document.get("BAZ"));
+    }
+
+    #[test]
+    fn unescaped_number_sign() {
+        let document = assert_ok!(Document::from_bytes(b"#FOO:bar#baz;\n"));
+
+        assert_some_eq!(
+            document.get("FOO").map(Tag::parameter_lists),
+            vec![vec!["bar#baz".to_owned()]]
+        );
+    }
+
+    #[test]
+    fn parameter_lists() {
+        let document = assert_ok!(Document::from_bytes(b"#FOO:a:b\\;c;\n1:2;  // comment\n"));
+
+        assert_some_eq!(
+            document.get("FOO").map(Tag::parameter_lists),
+            vec![
+                vec!["a".to_owned(), "b;c".to_owned()],
+                vec!["1".to_owned(), "2".to_owned()],
+                // As when deserializing, a comment after the final `;` begins another list.
+                vec!["".to_owned()]
+            ]
+        );
+    }
+
+    #[test]
+    fn parameter() {
+        let document = assert_ok!(Document::from_bytes(SIMFILE));
+
+        assert_some_eq!(
+            document.get("TITLE").and_then(|tag| tag.parameter(0, 0)),
+            "Foo:Bar"
+        );
+        assert_some_eq!(
+            document.get("OFFSET").and_then(|tag| tag.parameter(0, 0)),
+            "  0.123 "
+        );
+        assert_none!(document.get("OFFSET").and_then(|tag| tag.parameter(0, 1)));
+        assert_none!(document.get("OFFSET").and_then(|tag| tag.parameter(1, 0)));
+    }
+
+    #[test]
+    fn parameter_with_comment() {
+        let document = assert_ok!(Document::from_bytes(SIMFILE));
+
+        assert_some_eq!(
+            document.get("NOTES").and_then(|tag| tag.parameter(0, 5)),
+            "\n0000\n0100 \n,\n0010\n"
+        );
+    }
+
+    #[test]
+    fn set_parameter() {
+        let mut document = assert_ok!(Document::from_bytes(SIMFILE));
+
+        assert_ok!(assert_some!(document
+            .get_mut("OFFSET")
+            .unwrap()
+            .set_parameter(0, 0, "-0.5")));
+
+        let mut expected = SIMFILE.to_vec();
+        let index = expected
+            .windows(9)
+            .position(|window| window == b"  0.123 ;")
+            .unwrap();
+        // The whitespace around the value is kept.
+        expected.splice(index + 2..index + 7, b"-0.5".iter().copied());
+        assert_eq!(document.to_bytes(), expected);
+    }
+
+    #[test]
+    fn set_parameter_escapes() {
+        let mut document = assert_ok!(Document::from_bytes(b"#TITLE:foo;\n"));
+
+        assert_ok!(assert_some!(document
+            .get_mut("TITLE")
+            .unwrap()
+            .set_parameter(0, 0, "a:b//c")));
+
+        assert_eq!(document.to_bytes(), b"#TITLE:a\\:b\\/\\/c;\n");
+        assert_some_eq!(
+            document.get("TITLE").and_then(|tag| tag.parameter(0, 0)),
+            "a:b//c"
+        );
+    }
+
+    #[test]
+    fn set_parameter_missing_terminator() {
+        let mut document = assert_ok!(Document::from_bytes(b"#FOO:bar\n#BAZ:qux;\n"));
+
+        assert_ok!(assert_some!(document
+            .get_mut("FOO")
+            .unwrap()
+            .set_parameter(0, 0, "quux")));
+
+        assert_eq!(document.to_bytes(), b"#FOO:quux\n#BAZ:qux;\n");
+    }
+
+    #[test]
+    fn set_parameter_out_of_bounds() {
+        let mut document = assert_ok!(Document::from_bytes(b"#FOO:bar;\n"));
+
+        assert_none!(document.get_mut("FOO").unwrap().set_parameter(0, 1, "baz"));
+        assert_none!(document.get_mut("FOO").unwrap().set_parameter(1, 0, "baz"));
+        assert_eq!(document.to_bytes(), b"#FOO:bar;\n");
+    }
+
+    #[test]
+    fn set_parameter_keeps_comment() {
+        let mut document = assert_ok!(Document::from_bytes(b"#OFFSET:0.123 // synced\n;\n"));
+
+        assert_ok!(assert_some!(document
+            .get_mut("OFFSET")
+            .unwrap()
+            .set_parameter(0, 0, "-0.5")));
+
+        assert_eq!(document.to_bytes(), b"#OFFSET:-0.5 // synced\n;\n");
+        assert_some_eq!(
+            document.get("OFFSET").and_then(|tag| tag.parameter(0, 0)),
+            "-0.5 \n"
+        );
+    }
+
+    #[test]
+    fn set_parameter_keeps_trailing_whitespace() {
+        let mut document = assert_ok!(Document::from_bytes(b"#BPMS:0=120  ;\n#OFFSET:0;\n"));
+
+        assert_ok!(assert_some!(document
+            .get_mut("BPMS")
+            .unwrap()
+            .set_parameter(0, 0, "0=140,4=70")));
+
+        assert_eq!(document.to_bytes(), b"#BPMS:0=140,4=70  ;\n#OFFSET:0;\n");
+    }
+
+    #[test]
+    fn set_parameter_empty() {
+        let mut document = assert_ok!(Document::from_bytes(b"#FOO:;\n"));
+
+        assert_ok!(assert_some!(document
+            .get_mut("FOO")
+            .unwrap()
+            .set_parameter(0, 0, "bar")));
+
+        assert_eq!(document.to_bytes(), b"#FOO:bar;\n");
+    }
+
+    #[test]
+    fn set_parameter_keeps_surrounding_whitespace() {
+        let mut document = assert_ok!(Document::from_bytes(b"#FOO: a :b;\nc;\n"));
+        let tag = document.get_mut("FOO").unwrap();
+
+        assert_ok!(assert_some!(tag.set_parameter(0, 0, "xyz")));
+        assert_ok!(assert_some!(tag.set_parameter(1, 0, "d")));
+
+        assert_eq!(document.to_bytes(), b"#FOO: xyz :b;\nd;\n");
+        assert_some_eq!(
+            document.get("FOO").map(|tag| tag.parameter_lists()),
+            vec![
+                vec![" xyz ".to_owned(), "b".to_owned()],
+                vec!["d".to_owned()]
+            ]
+        );
+    }
+
+    #[test]
+    fn set_parameter_later_list() {
+        let mut document = assert_ok!(Document::from_bytes(b"#FOO:bar;\n  1:2;\n"));
+
+        assert_ok!(assert_some!(document
+            .get_mut("FOO")
+            .unwrap()
+            .set_parameter(1, 0, "3")));
+
+        assert_eq!(document.to_bytes(), b"#FOO:bar;\n  3:2;\n");
+    }
+
+    #[test]
+    fn push() {
+        let mut document = assert_ok!(Document::from_bytes(b"#FOO:bar;"));
+
+        assert_ok!(document.push(Tag::new(
+            "BAZ",
+            &[vec!["a:b".to_owned()], vec!["1".to_owned(), "2".to_owned()]],
+        )));
+
+        assert_eq!(document.to_bytes(), b"#FOO:bar;\n#BAZ:a\\:b;\n1:2;\n");
+    }
+
+    #[test]
+    fn push_to_empty() {
+        let mut document = Document::new();
+
+        assert_ok!(document.push(Tag::new("FOO", &[vec!["bar".to_owned()]])));
+
+        assert_eq!(document.to_bytes(), b"#FOO:bar;\n");
+    }
+
+    #[test]
+    fn remove() {
+        let mut document = assert_ok!(Document::from_bytes(b"#FOO:bar;\n#BAZ:qux;\n"));
+
+        assert_some_eq!(
+            document.remove("FOO").map(|tag| tag.parameter_lists()),
+            vec![vec!["bar".to_owned()]]
+        );
+        assert_eq!(document.to_bytes(), b"#BAZ:qux;\n");
+        assert_none!(document.remove("FOO"));
+    }
+
+    #[test]
+    fn new_tag_deserializes() {
+        let tag = Tag::new(
+            "FOO",
+            &[vec!["a;b".to_owned()], vec!["1".to_owned(), "2".to_owned()]],
+        );
+        let mut bytes = b"#".to_vec();
+        bytes.extend_from_slice(tag.as_bytes());
+
+        assert_ok_eq!(
+            crate::from_bytes::<crate::Value>(&bytes),
+            crate::Value::from(vec![crate::value::Tag::new(
+                "FOO",
+                vec![vec!["a;b".to_owned()], vec!["1".to_owned(), "2".to_owned()]]
+            )])
+        );
+    }
+
+    #[test]
+    fn from_reader() {
+        let document = assert_ok!(Document::from_reader(SIMFILE));
+
+        assert_eq!(document, assert_ok!(Document::from_bytes(SIMFILE)));
+    }
+
+    #[test]
+    fn from_reader_error() {
+        struct FailingReader;
+
+        impl io::Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "oops"))
+            }
+        }
+
+        let error = assert_err!(Document::from_reader(FailingReader));
+        assert_eq!(error.kind(), &de::Kind::Io(io::ErrorKind::Other));
+    }
+
+    #[test]
+    fn limits() {
+        let error = assert_err!(Document::from_bytes_with_options(
+            b"#FOO:bar;\n#BAZ:qux;\n",
+            DeserializerOptions::new().limits(Limits::new().max_tags(1))
+        ));
+        assert_eq!(error.kind(), &de::Kind::LimitExceeded(Limit::Tags));
+    }
+
+    #[test]
+    fn dwi_comments() {
+        let document = assert_ok!(Document::from_bytes_with_options(
+            b"#FILE:a//b;\n",
+            DeserializerOptions::new().dialect(Dialect::Dwi)
+        ));
+
+        assert_some_eq!(
+            document.get("FILE").and_then(|tag| tag.parameter(0, 0)),
+            "a//b"
+        );
+    }
+
+    #[test]
+    fn shift_jis() {
+        let input = b"#TITLE:\x93\x8c\x95\xfb;\n#ARTIST:\x95\\;\n";
+        let document = assert_ok!(Document::from_bytes_with_options(
+            input,
+            DeserializerOptions::new().encoding(Encoding::ShiftJis)
+        ));
+
+        assert_eq!(document.encoding(), Encoding::ShiftJis);
+        assert_some_eq!(
+            document.get("TITLE").and_then(|tag| tag.parameter(0, 0)),
+            "東方"
+        );
+        // The second byte of `表` is `\`, which is not an escape.
+        assert_some_eq!(
+            document.get("ARTIST").and_then(|tag| tag.parameter(0, 0)),
+            "表"
+        );
+        assert_eq!(document.to_bytes(), input);
+    }
+
+    #[test]
+    fn detect_encoding() {
+        let document = assert_ok!(Document::from_bytes_with_options(
+            b"#TITLE:\x93\x8c\x95\xfb\x97\x64\x81\x58\x96\xb2;\n",
+            DeserializerOptions::new().detect_encoding()
+        ));
+
+        assert_eq!(document.encoding(), Encoding::ShiftJis);
+        assert_some_eq!(
+            document.get("TITLE").and_then(|tag| tag.parameter(0, 0)),
+            "東方妖々夢"
+        );
+    }
+
+    #[test]
+    fn invalid_encoding_is_replaced() {
+        let document = assert_ok!(Document::from_bytes(b"#TITLE:foo\xff;\n"));
+
+        assert_some_eq!(
+            document.get("TITLE").and_then(|tag| tag.parameter(0, 0)),
+            "foo\u{fffd}"
+        );
+    }
+
+    #[test]
+    fn set_parameter_shift_jis() {
+        let mut document = assert_ok!(Document::from_bytes_with_options(
+            b"#TITLE:foo;\n#ARTIST:bar;\n",
+            DeserializerOptions::new().encoding(Encoding::ShiftJis)
+        ));
+
+        assert_ok!(assert_some!(document
+            .get_mut("TITLE")
+            .unwrap()
+            .set_parameter(0, 0, "東方")));
+
+        assert_eq!(
+            document.to_bytes(),
+            b"#TITLE:\x93\x8c\x95\xfb;\n#ARTIST:bar;\n"
+        );
+        assert_some_eq!(
+            document.get("TITLE").and_then(|tag| tag.parameter(0, 0)),
+            "東方"
+        );
+    }
+
+    #[test]
+    fn set_parameter_unrepresentable() {
+        let mut document = assert_ok!(Document::from_bytes_with_options(
+            b"#TITLE:foo;\n",
+            DeserializerOptions::new().encoding(Encoding::Windows1252)
+        ));
+
+        assert_err_eq!(
+            assert_some!(document
+                .get_mut("TITLE")
+                .unwrap()
+                .set_parameter(0, 0, "東方")),
+            ser::Error::UnrepresentableCharacter('東', Encoding::Windows1252)
+        );
+        assert_eq!(document.to_bytes(), b"#TITLE:foo;\n");
+    }
+
+    #[test]
+    fn set_parameter_shifts_later_parameters() {
+        let mut document = assert_ok!(Document::from_bytes(b"#FOO:a:b;\nc;\n"));
+        let tag = document.get_mut("FOO").unwrap();
+
+        assert_ok!(assert_some!(tag.set_parameter(0, 0, "longer")));
+        assert_ok!(assert_some!(tag.set_parameter(0, 1, "x")));
+        assert_ok!(assert_some!(tag.set_parameter(1, 0, "y")));
+
+        assert_eq!(document.to_bytes(), b"#FOO:longer:x;\ny;\n");
+    }
+
+    #[test]
+    fn push_shift_jis() {
+        let mut document = assert_ok!(Document::from_bytes_with_options(
+            b"#TITLE:foo;\n",
+            DeserializerOptions::new().encoding(Encoding::ShiftJis)
+        ));
+
+        assert_ok!(document.push(Tag::new("ARTIST", &[vec!["東方".to_owned()]])));
+
+        assert_eq!(
+            document.to_bytes(),
+            b"#TITLE:foo;\n#ARTIST:\x93\x8c\x95\xfb;\n"
+        );
+        assert_some_eq!(
+            document.get("ARTIST").and_then(|tag| tag.parameter(0, 0)),
+            "東方"
+        );
+    }
+
+    #[test]
+    fn push_unrepresentable() {
+        let mut document = assert_ok!(Document::from_bytes_with_options(
+            b"#TITLE:foo;\n",
+            DeserializerOptions::new().encoding(Encoding::Windows1252)
+        ));
+
+        assert_err_eq!(
+            document.push(Tag::new("ARTIST", &[vec!["東方".to_owned()]])),
+            ser::Error::UnrepresentableCharacter('東', Encoding::Windows1252)
+        );
+        assert_eq!(document.to_bytes(), b"#TITLE:foo;\n");
+    }
+
+    #[test]
+    fn to_writer() {
+        let document = assert_ok!(Document::from_bytes(SIMFILE));
+        let mut output = Vec::new();
+
+        assert_ok!(document.to_writer(&mut output));
+        assert_eq!(output, SIMFILE);
+    }
+}
@@ -0,0 +1,226 @@
+use chardetng::EncodingDetector;
+use std::{borrow::Cow, str};
+
+/// A character encoding used for the text within MSD files.
+///
+/// MSD files have no way of declaring their own encoding. Most modern files are UTF-8, but files
+/// written by older tools are often encoded in Shift-JIS or Windows-1252.
+///
+/// # Example
+/// ```
+/// use msd::{de::DeserializerOptions, ser::SerializerOptions, Encoding};
+/// use serde::{Deserialize, Serialize};
+///
+/// let mut serialized = Vec::new();
+/// let mut serializer = msd::Serializer::with_options(
+///     &mut serialized,
+///     SerializerOptions::new().encoding(Encoding::ShiftJis),
+/// );
+/// "東方".serialize(&mut serializer).unwrap();
+/// assert_eq!(serialized, b"#\x93\x8c\x95\xfb;\n");
+///
+/// let mut deserializer = msd::Deserializer::with_options(
+///     serialized.as_slice(),
+///     DeserializerOptions::new().encoding(Encoding::ShiftJis),
+/// );
+/// assert_eq!(String::deserialize(&mut deserializer).unwrap(), "東方");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// UTF-8. This is the default.
+    Utf8,
+    /// Shift-JIS, commonly used by Japanese simfiles.
+    ShiftJis,
+    /// Windows-1252, commonly used by Western European simfiles.
+    Windows1252,
+}
+
+impl Encoding {
+    fn as_encoding_rs(self) -> &'static encoding_rs::Encoding {
+        match self {
+            Encoding::Utf8 => encoding_rs::UTF_8,
+            Encoding::ShiftJis => encoding_rs::SHIFT_JIS,
+            Encoding::Windows1252 => encoding_rs::WINDOWS_1252,
+        }
+    }
+
+    // Guesses which encoding `bytes` are in.
+    //
+    // Any guess other than UTF-8 or Shift-JIS falls back to Windows-1252, since it is able to
+    // decode every byte.
+    pub(crate) fn detect(bytes: &[u8]) -> Self {
+        let mut detector = EncodingDetector::new();
+        detector.feed(bytes, true);
+        let guess = detector.guess(None, true);
+        if guess == encoding_rs::UTF_8 {
+            Encoding::Utf8
+        } else if guess == encoding_rs::SHIFT_JIS {
+            Encoding::ShiftJis
+        } else {
+            Encoding::Windows1252
+        }
+    }
+
+    // Returns whether `byte` begins a two-byte character whose second byte may be ASCII.
+    //
+    // In Shift-JIS, the second byte of such a character can be `\`, which must not be mistaken
+    // for an escape.
+    pub(crate) fn is_lead_byte(self, byte: u8) -> bool {
+        self == Encoding::ShiftJis && matches!(byte, 0x81..=0x9f | 0xe0..=0xfc)
+    }
+
+    // Decodes `bytes`, returning `None` if they are malformed.
+    pub(crate) fn decode(self, bytes: &[u8]) -> Option<Cow<'_, str>> {
+        match self {
+            Encoding::Utf8 => str::from_utf8(bytes).ok().map(Cow::Borrowed),
+            _ => self
+                .as_encoding_rs()
+                .decode_without_bom_handling_and_without_replacement(bytes),
+        }
+    }
+
+    // Decodes owned `bytes`, returning `None` if they are malformed.
+    //
+    // UTF-8 input is reused without copying.
+    // Decodes `bytes`, replacing malformed sequences with `U+FFFD`.
+    pub(crate) fn decode_lossy(self, bytes: &[u8]) -> Cow<'_, str> {
+        match self {
+            Encoding::Utf8 => String::from_utf8_lossy(bytes),
+            _ => self.as_encoding_rs().decode_without_bom_handling(bytes).0,
+        }
+    }
+
+    pub(crate) fn decode_owned(self, bytes: Vec<u8>) -> Option<String> {
+        match self {
+            Encoding::Utf8 => String::from_utf8(bytes).ok(),
+            _ => self.decode(&bytes).map(Cow::into_owned),
+        }
+    }
+
+    // Encodes a single character, returning `None` if it cannot be represented.
+    pub(crate) fn encode_char(self, c: char, buffer: &mut [u8; 4]) -> Option<&[u8]> {
+        let mut utf8 = [0; 4];
+        let mut encoder = self.as_encoding_rs().new_encoder();
+        let (result, _, written) =
+            encoder.encode_from_utf8_without_replacement(c.encode_utf8(&mut utf8), buffer, true);
+        match result {
+            encoding_rs::EncoderResult::InputEmpty => Some(&buffer[..written]),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Encoding::Utf8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Encoding;
+    use claims::{assert_none, assert_some_eq};
+
+    #[test]
+    fn default() {
+        assert_eq!(Encoding::default(), Encoding::Utf8);
+    }
+
+    #[test]
+    fn detect_utf8() {
+        assert_eq!(Encoding::detect("東方妖々夢".as_bytes()), Encoding::Utf8);
+    }
+
+    #[test]
+    fn detect_shift_jis() {
+        assert_eq!(
+            Encoding::detect(b"\x93\x8c\x95\xfb\x97\x64\x81\x58\x96\xb2"),
+            Encoding::ShiftJis
+        );
+    }
+
+    #[test]
+    fn detect_windows_1252() {
+        assert_eq!(
+            Encoding::detect(b"Pok\xe9mon Caf\xe9"),
+            Encoding::Windows1252
+        );
+    }
+
+    #[test]
+    fn is_lead_byte_shift_jis() {
+        assert!(Encoding::ShiftJis.is_lead_byte(0x83));
+        assert!(Encoding::ShiftJis.is_lead_byte(0xe0));
+        assert!(!Encoding::ShiftJis.is_lead_byte(b'\\'));
+        assert!(!Encoding::ShiftJis.is_lead_byte(0xb1));
+    }
+
+    #[test]
+    fn is_lead_byte_other_encodings() {
+        assert!(!Encoding::Utf8.is_lead_byte(0x83));
+        assert!(!Encoding::Windows1252.is_lead_byte(0x83));
+    }
+
+    #[test]
+    fn decode_utf8() {
+        assert_some_eq!(Encoding::Utf8.decode("東方".as_bytes()), "東方");
+    }
+
+    #[test]
+    fn decode_utf8_invalid() {
+        assert_none!(Encoding::Utf8.decode(b"\x93\x8c"));
+    }
+
+    #[test]
+    fn decode_shift_jis() {
+        assert_some_eq!(Encoding::ShiftJis.decode(b"\x93\x8c\x95\xfb"), "東方");
+    }
+
+    #[test]
+    fn decode_shift_jis_invalid() {
+        assert_none!(Encoding::ShiftJis.decode(b"\x93"));
+    }
+
+    #[test]
+    fn decode_windows_1252() {
+        assert_some_eq!(Encoding::Windows1252.decode(b"Caf\xe9"), "Café");
+    }
+
+    #[test]
+    fn decode_owned_utf8() {
+        assert_some_eq!(Encoding::Utf8.decode_owned("東方".into()), "東方");
+    }
+
+    #[test]
+    fn decode_owned_shift_jis() {
+        assert_some_eq!(
+            Encoding::ShiftJis.decode_owned(b"\x93\x8c\x95\xfb".to_vec()),
+            "東方"
+        );
+    }
+
+    #[test]
+    fn encode_char_shift_jis() {
+        let mut buffer = [0; 4];
+
+        assert_some_eq!(
+            Encoding::ShiftJis.encode_char('表', &mut buffer),
+            b"\x95\x5c"
+        );
+    }
+
+    #[test]
+    fn encode_char_windows_1252() {
+        let mut buffer = [0; 4];
+
+        assert_some_eq!(Encoding::Windows1252.encode_char('é', &mut buffer), b"\xe9");
+    }
+
+    #[test]
+    fn encode_char_unrepresentable() {
+        let mut buffer = [0; 4];
+
+        assert_none!(Encoding::Windows1252.encode_char('東', &mut buffer));
+    }
+}
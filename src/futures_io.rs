@@ -0,0 +1,454 @@
+//! Asynchronous reading and writing using the [`futures-io`](::futures_io) traits.
+//!
+//! These are the traits used by runtimes such as `async-std` and `smol`. This module is only
+//! available when the `futures-io` feature is enabled.
+//!
+//! # Example
+//! ```
+//! use serde_derive::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Song {
+//!     #[serde(rename = "TITLE")]
+//!     title: String,
+//! }
+//!
+//! # async fn run() {
+//! let mut serialized = Vec::new();
+//! msd::futures_io::to_async_writer(&mut serialized, &Song { title: "foo".to_owned() })
+//!     .await
+//!     .unwrap();
+//!
+//! let song: Song = msd::futures_io::from_async_reader(serialized.as_slice())
+//!     .await
+//!     .unwrap();
+//! assert_eq!(song.title, "foo");
+//! # }
+//! ```
+
+use crate::{
+    de,
+    de::DeserializerOptions,
+    ser,
+    ser::{Serializer, SerializerOptions},
+};
+use ::futures_io::{AsyncRead, AsyncWrite};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+// A future driven by a closure.
+//
+// `std::future::poll_fn()` is not available on the minimum supported Rust version.
+struct PollFn<F>(F);
+
+fn poll_fn<F, T>(f: F) -> PollFn<F>
+where
+    F: FnMut(&mut Context<'_>) -> Poll<T> + Unpin,
+{
+    PollFn(f)
+}
+
+impl<F, T> Future for PollFn<F>
+where
+    F: FnMut(&mut Context<'_>) -> Poll<T> + Unpin,
+{
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        (self.0)(cx)
+    }
+}
+
+/// Deserialize a value of type `T` from the given asynchronous `reader`.
+///
+/// The entire input is read into memory before it is deserialized, without blocking the current
+/// thread. Errors are reported exactly as they would be by [`from_reader`](crate::from_reader).
+pub async fn from_async_reader<R, T>(reader: R) -> de::Result<T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    from_async_reader_with_options(reader, DeserializerOptions::default()).await
+}
+
+/// Deserialize a value of type `T` from the given asynchronous `reader`, interpreting the input
+/// according to the given `options`.
+///
+/// The input is read into memory before it is deserialized, without blocking the current thread.
+/// If a [total bytes limit](crate::de::Limits::max_total_bytes) is set, reading stops once the
+/// input is known to exceed it.
+pub async fn from_async_reader_with_options<R, T>(
+    mut reader: R,
+    options: DeserializerOptions,
+) -> de::Result<T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let limit = de::read_ahead_limit(&options).unwrap_or(usize::MAX);
+    let mut bytes = Vec::new();
+    let mut buffer = [0; 8192];
+    let error = loop {
+        let len = buffer.len().min(limit - bytes.len());
+        if len == 0 {
+            break None;
+        }
+        match poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut buffer[..len])).await {
+            Ok(0) => break None,
+            Ok(read) => bytes.extend_from_slice(&buffer[..read]),
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => break Some(error),
+        }
+    };
+    de::from_read_ahead(&bytes, error, options)
+}
+
+/// Serialize the given `value` into the given asynchronous `writer` in MSD format.
+///
+/// The value is serialized into memory before being written, without blocking the current thread.
+pub async fn to_async_writer<W, T>(writer: W, value: &T) -> ser::Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: ?Sized + Serialize,
+{
+    to_async_writer_with_options(writer, value, SerializerOptions::default()).await
+}
+
+/// Serialize the given `value` into the given asynchronous `writer`, writing the output according
+/// to the given `options`.
+///
+/// The value is serialized into memory before being written, without blocking the current thread.
+pub async fn to_async_writer_with_options<W, T>(
+    mut writer: W,
+    value: &T,
+    options: SerializerOptions,
+) -> ser::Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: ?Sized + Serialize,
+{
+    let mut bytes = Vec::with_capacity(128);
+    value.serialize(&mut Serializer::with_options(&mut bytes, options))?;
+    let mut remaining = bytes.as_slice();
+    while !remaining.is_empty() {
+        match poll_fn(|cx| Pin::new(&mut writer).poll_write(cx, remaining)).await {
+            Ok(0) => return Err(ser::Error::Io(io::Error::from(io::ErrorKind::WriteZero))),
+            Ok(written) => remaining = &remaining[written..],
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(ser::Error::Io(error)),
+        }
+    }
+    poll_fn(|cx| Pin::new(&mut writer).poll_flush(cx))
+        .await
+        .map_err(ser::Error::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        from_async_reader, from_async_reader_with_options, to_async_writer,
+        to_async_writer_with_options,
+    };
+    use crate::{
+        block_on::block_on,
+        de,
+        de::DeserializerOptions,
+        ser,
+        ser::{LineEnding, SerializerOptions},
+    };
+    use ::futures_io::{AsyncRead, AsyncWrite};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use serde_derive::{Deserialize, Serialize};
+    use std::{
+        io, mem,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    // Yields bytes one at a time, interrupting before each one, and then fails.
+    struct FailingReader {
+        bytes: &'static [u8],
+        interrupt: bool,
+    }
+
+    impl FailingReader {
+        fn new(bytes: &'static [u8]) -> Self {
+            Self {
+                bytes,
+                interrupt: true,
+            }
+        }
+    }
+
+    impl AsyncRead for FailingReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            self.interrupt = !self.interrupt;
+            if !self.interrupt {
+                Poll::Ready(Err(io::Error::from(io::ErrorKind::Interrupted)))
+            } else if let Some((&byte, rest)) = self.bytes.split_first() {
+                buf[0] = byte;
+                self.bytes = rest;
+                Poll::Ready(Ok(1))
+            } else {
+                Poll::Ready(Err(io::Error::from(io::ErrorKind::ConnectionReset)))
+            }
+        }
+    }
+
+    // Yields `#` followed by an endless sequence of `a`.
+    struct EndlessReader {
+        started: bool,
+    }
+
+    impl AsyncRead for EndlessReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            buf.fill(b'a');
+            if !self.started {
+                buf[0] = b'#';
+                self.started = true;
+            }
+            Poll::Ready(Ok(buf.len()))
+        }
+    }
+
+    // Accepts a single byte per write.
+    struct SlowWriter(Vec<u8>);
+
+    impl AsyncWrite for SlowWriter {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.0.push(buf[0]);
+            Poll::Ready(Ok(1))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    // Refuses to accept any bytes.
+    struct FullWriter;
+
+    impl AsyncWrite for FullWriter {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            _buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(Ok(0))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    // Holds written bytes until they are flushed.
+    #[derive(Default)]
+    struct BufferedWriter {
+        buffer: Vec<u8>,
+        flushed: Vec<u8>,
+    }
+
+    impl AsyncWrite for BufferedWriter {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.buffer.extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            let buffer = mem::take(&mut self.buffer);
+            self.flushed.extend(buffer);
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    // Accepts writes but fails to flush them.
+    struct FailingFlushWriter;
+
+    impl AsyncWrite for FailingFlushWriter {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Err(io::Error::from(io::ErrorKind::BrokenPipe)))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Song {
+        #[serde(rename = "TITLE")]
+        title: String,
+        #[serde(rename = "BPMS")]
+        bpms: f64,
+    }
+
+    #[test]
+    fn from_async_reader_success() {
+        assert_ok_eq!(
+            block_on(from_async_reader::<_, Song>(
+                b"#TITLE:foo;\n#BPMS:120.0;\n".as_slice()
+            )),
+            Song {
+                title: "foo".to_owned(),
+                bpms: 120.0,
+            }
+        );
+    }
+
+    #[test]
+    fn from_async_reader_parse_error() {
+        assert_eq!(
+            block_on(from_async_reader::<_, Song>(
+                b"#TITLE:foo;\n#BPMS:bar;\n".as_slice()
+            ))
+            .unwrap_err()
+            .kind(),
+            &de::Kind::ExpectedF64
+        );
+    }
+
+    #[test]
+    fn from_async_reader_io_error() {
+        assert_eq!(
+            block_on(from_async_reader::<_, (String, String)>(
+                FailingReader::new(b"#TITLE:fo")
+            ))
+            .unwrap_err()
+            .kind(),
+            &de::Kind::Io(io::ErrorKind::ConnectionReset)
+        );
+    }
+
+    #[test]
+    fn from_async_reader_with_options_success() {
+        assert_ok_eq!(
+            block_on(from_async_reader_with_options::<_, Song>(
+                b"#title:foo;\n#bpms:120.0;\n".as_slice(),
+                DeserializerOptions::new().case_insensitive(true)
+            )),
+            Song {
+                title: "foo".to_owned(),
+                bpms: 120.0,
+            }
+        );
+    }
+
+    #[test]
+    fn from_async_reader_with_options_max_total_bytes() {
+        assert_eq!(
+            block_on(from_async_reader_with_options::<_, (String, String)>(
+                EndlessReader { started: false },
+                DeserializerOptions::new().limits(de::Limits::new().max_total_bytes(1024))
+            ))
+            .unwrap_err()
+            .kind(),
+            &de::Kind::LimitExceeded(de::Limit::TotalBytes)
+        );
+    }
+
+    #[test]
+    fn to_async_writer_success() {
+        let mut output = SlowWriter(Vec::new());
+
+        assert_ok!(block_on(to_async_writer(
+            &mut output,
+            &Song {
+                title: "foo".to_owned(),
+                bpms: 120.0,
+            }
+        )));
+        assert_eq!(output.0, b"#TITLE:foo;\n#BPMS:120.0;\n");
+    }
+
+    #[test]
+    fn to_async_writer_write_zero() {
+        assert_err_eq!(
+            block_on(to_async_writer(FullWriter, &42)),
+            ser::Error::Io(io::Error::from(io::ErrorKind::WriteZero))
+        );
+    }
+
+    #[test]
+    fn to_async_writer_flushes() {
+        let mut output = BufferedWriter::default();
+
+        assert_ok!(block_on(to_async_writer(&mut output, &("TITLE", "foo"))));
+        assert_eq!(output.flushed, b"#TITLE:foo;\n");
+    }
+
+    #[test]
+    fn to_async_writer_flush_error() {
+        assert_err_eq!(
+            block_on(to_async_writer(FailingFlushWriter, &42)),
+            ser::Error::Io(io::Error::from(io::ErrorKind::BrokenPipe))
+        );
+    }
+
+    #[test]
+    fn to_async_writer_with_options_success() {
+        let mut output = Vec::new();
+
+        assert_ok!(block_on(to_async_writer_with_options(
+            &mut output,
+            &Song {
+                title: "foo".to_owned(),
+                bpms: 120.0,
+            },
+            SerializerOptions::new().line_ending(LineEnding::CrLf)
+        )));
+        assert_eq!(output, b"#TITLE:foo;\r\n#BPMS:120.0;\r\n");
+    }
+
+    #[test]
+    fn to_async_writer_serialize_error() {
+        let mut output = SlowWriter(Vec::new());
+
+        assert_err_eq!(
+            block_on(to_async_writer(&mut output, &vec![Some(1)])),
+            ser::Error::UnsupportedType
+        );
+        assert!(output.0.is_empty());
+    }
+}
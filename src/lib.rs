@@ -22,13 +22,37 @@
 //!
 //! assert_eq!(map, deserialized);
 //! ```
+//!
+//! # Features
+//! Asynchronous reading and writing can be enabled using the following optional features:
+//!
+//! - `tokio`: Enables the `tokio` module, supporting `tokio`'s `AsyncRead` and `AsyncWrite`
+//!   traits.
+//! - `futures-io`: Enables the `futures_io` module, supporting the `AsyncRead` and `AsyncWrite`
+//!   traits from the `futures-io` crate.
 
 #![warn(unsafe_op_in_unsafe_fn)]
 
 pub mod de;
+pub mod document;
+#[cfg(feature = "futures-io")]
+pub mod futures_io;
 pub mod ser;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+pub mod value;
+pub mod with;
+
+#[cfg(all(test, any(feature = "futures-io", feature = "tokio")))]
+mod block_on;
+mod encoding;
 
 #[doc(inline)]
-pub use de::{from_bytes, from_reader, Deserializer};
+pub use de::{from_bytes, from_reader, Deserializer, Spanned};
+#[doc(inline)]
+pub use document::Document;
+pub use encoding::Encoding;
 #[doc(inline)]
 pub use ser::{to_bytes, to_writer, Serializer};
+#[doc(inline)]
+pub use value::Value;
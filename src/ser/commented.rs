@@ -0,0 +1,514 @@
+use crate::ser::{Error, Result};
+use serde::{
+    de::{Deserialize, Deserializer},
+    ser,
+    ser::{Impossible, Serialize},
+};
+
+// The name used to identify a `Commented` value to the serializers in this crate.
+pub(super) const NAME: &str = "$msd::Commented";
+
+/// A value preceded by a `//` comment when serialized.
+///
+/// The comment is written on its own line(s) directly before the tag containing the value. This
+/// is supported for values serialized as their own tags: the top-level value, `struct` fields, and
+/// elements of a top-level `seq`. Anywhere else, the comment is omitted and only the value is
+/// serialized. Serializers for other formats also only serialize the value.
+///
+/// When deserialized, the value is deserialized directly and the comment is left empty, since
+/// comments are not retained by the [`Deserializer`](crate::Deserializer).
+///
+/// # Example
+/// ```
+/// use msd::ser::Commented;
+/// use serde_derive::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Song {
+///     #[serde(rename = "TITLE")]
+///     title: String,
+///     #[serde(rename = "OFFSET")]
+///     offset: Commented<f64>,
+/// }
+///
+/// let song = Song {
+///     title: "foo".to_owned(),
+///     offset: Commented::new("synced by ear", -0.5),
+/// };
+///
+/// assert_eq!(
+///     msd::to_bytes(&song).unwrap(),
+///     b"#TITLE:foo;\n// synced by ear\n#OFFSET:-0.5;\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Commented<T> {
+    /// The comment. Each line is written as its own `//` comment.
+    pub comment: String,
+    /// The commented value.
+    pub value: T,
+}
+
+impl<T> Commented<T> {
+    /// Creates a new `Commented` value.
+    pub fn new<S>(comment: S, value: T) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            comment: comment.into(),
+            value,
+        }
+    }
+}
+
+// Passed to the serializer in place of a `Commented` value.
+//
+// Serializers in this crate that support comments serialize this twice: once with
+// `CommentSerializer` to obtain the comment, and once more to serialize the value itself. Any
+// other serializer only ever sees the value.
+struct Payload<'a, T> {
+    comment: &'a str,
+    value: &'a T,
+}
+
+impl<'a, T> Serialize for Payload<'a, T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        if is_comment_serializer::<S>() {
+            serializer.serialize_str(self.comment)
+        } else {
+            self.value.serialize(serializer)
+        }
+    }
+}
+
+impl<T> Serialize for Commented<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(
+            NAME,
+            &Payload {
+                comment: &self.comment,
+                value: &self.value,
+            },
+        )
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Commented<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(|value| Self {
+            comment: String::new(),
+            value,
+        })
+    }
+}
+
+// Whether `S` is `CommentSerializer`.
+//
+// A `Serialize` implementation is given nothing but the serializer's type to tell serializers
+// apart, so the type's name is used as a private marker. Every other serializer, including ones
+// from other crates that are not human readable, is given the value.
+fn is_comment_serializer<S>() -> bool {
+    std::any::type_name::<S>() == std::any::type_name::<CommentSerializer>()
+}
+
+// Extracts the comment from a `Payload`.
+pub(super) struct CommentSerializer;
+
+impl ser::Serializer for CommentSerializer {
+    type Ok = String;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        Ok(v.to_owned())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::UnsupportedType)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommentSerializer, Commented, Payload};
+    use crate::ser::{Error, Result};
+    use claims::{assert_err_eq, assert_ok_eq};
+    use serde::{ser, ser::Impossible, Serialize};
+
+    #[test]
+    fn new() {
+        assert_eq!(
+            Commented::new("foo", 42),
+            Commented {
+                comment: "foo".to_owned(),
+                value: 42
+            }
+        );
+    }
+
+    #[test]
+    fn comment_serializer_extracts_comment() {
+        assert_ok_eq!(
+            Payload {
+                comment: "foo",
+                value: &42
+            }
+            .serialize(CommentSerializer),
+            "foo".to_owned()
+        );
+    }
+
+    #[test]
+    fn comment_serializer_rejects_other_types() {
+        assert_err_eq!(42.serialize(CommentSerializer), Error::UnsupportedType);
+    }
+
+    #[test]
+    fn other_serializers_serialize_value() {
+        // A serializer that is not human readable is still not given the comment.
+        struct Compact;
+
+        impl ser::Serializer for Compact {
+            type Ok = u64;
+            type Error = Error;
+            type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+            type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+            type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+            type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+            type SerializeMap = Impossible<Self::Ok, Self::Error>;
+            type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+            type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+            fn serialize_bool(self, _v: bool) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_i8(self, _v: i8) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_i16(self, _v: i16) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_i32(self, _v: i32) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_i64(self, _v: i64) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_u8(self, _v: u8) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_u16(self, _v: u16) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_u32(self, _v: u32) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+                Ok(v)
+            }
+
+            fn serialize_f32(self, _v: f32) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_f64(self, _v: f64) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_char(self, _v: char) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_str(self, _v: &str) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_none(self) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_some<T>(self, _value: &T) -> Result<Self::Ok>
+            where
+                T: ?Sized + Serialize,
+            {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_unit(self) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_unit_variant(
+                self,
+                _name: &'static str,
+                _variant_index: u32,
+                _variant: &'static str,
+            ) -> Result<Self::Ok> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+            where
+                T: ?Sized + Serialize,
+            {
+                value.serialize(self)
+            }
+
+            fn serialize_newtype_variant<T>(
+                self,
+                _name: &'static str,
+                _variant_index: u32,
+                _variant: &'static str,
+                _value: &T,
+            ) -> Result<Self::Ok>
+            where
+                T: ?Sized + Serialize,
+            {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_tuple_struct(
+                self,
+                _name: &'static str,
+                _len: usize,
+            ) -> Result<Self::SerializeTupleStruct> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_tuple_variant(
+                self,
+                _name: &'static str,
+                _variant_index: u32,
+                _variant: &'static str,
+                _len: usize,
+            ) -> Result<Self::SerializeTupleVariant> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_struct(
+                self,
+                _name: &'static str,
+                _len: usize,
+            ) -> Result<Self::SerializeStruct> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn serialize_struct_variant(
+                self,
+                _name: &'static str,
+                _variant_index: u32,
+                _variant: &'static str,
+                _len: usize,
+            ) -> Result<Self::SerializeStructVariant> {
+                Err(Error::UnsupportedType)
+            }
+
+            fn is_human_readable(&self) -> bool {
+                false
+            }
+        }
+
+        assert_ok_eq!(Commented::new("foo", 42u64).serialize(Compact), 42);
+    }
+
+    #[test]
+    fn deserialize() {
+        assert_ok_eq!(
+            crate::from_bytes::<Commented<u64>>(b"// foo\n#42;\n"),
+            Commented::new("", 42)
+        );
+    }
+}
@@ -0,0 +1,455 @@
+use crate::{
+    ser::{Error, Escaped, Result},
+    with,
+};
+use serde::{
+    ser,
+    ser::{Impossible, SerializeSeq, SerializeTupleStruct},
+    Serialize,
+};
+
+// Serializes the contents of the newtype struct wrapping a list delimited by `separator`,
+// returning the escaped parameter.
+//
+// The adapters in `crate::with` request a delimited list by calling `serialize_newtype_struct()`
+// with a name identifying the separator, wrapping a sequence of the elements.
+pub(super) fn serialize_list<T>(value: &T, separator: u8) -> Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    value.serialize(Serializer {
+        separators: &[],
+        list_separator: Some(separator),
+    })
+}
+
+// Serializes values delimited within a single parameter, as requested by the adapters in
+// `crate::with`.
+//
+// A key and value pair within a list is requested by calling `serialize_tuple_struct()` with a
+// name identifying its delimiter. Along with the usual special characters, the separators of every
+// enclosing sequence are escaped within each element.
+pub(super) struct Serializer<'a> {
+    separators: &'a [u8],
+    // The separator of the list being serialized, if any. Only then is a sequence accepted.
+    list_separator: Option<u8>,
+}
+
+impl<'a> Serializer<'a> {
+    pub(super) fn new(separators: &'a [u8]) -> Self {
+        Self {
+            separators,
+            list_separator: None,
+        }
+    }
+
+    fn escape(&self, bytes: &[u8]) -> Vec<u8> {
+        Escaped::new(bytes)
+            .with_separators(self.separators)
+            .collect()
+    }
+
+    fn delimited(&self, separator: u8) -> Delimited {
+        let mut separators = self.separators.to_vec();
+        separators.push(separator);
+        Delimited::new(separators)
+    }
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = Vec<u8>;
+    type Error = Error;
+    type SerializeSeq = Delimited;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Delimited;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        if v {
+            Ok(self.escape(b"true"))
+        } else {
+            Ok(self.escape(b"false"))
+        }
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    #[cfg(has_i128)]
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    #[cfg(has_i128)]
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        let mut buffer = ryu::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        let mut buffer = ryu::Buffer::new();
+        Ok(self.escape(buffer.format(v).as_bytes()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        let mut buffer = [0; 4];
+        v.encode_utf8(&mut buffer);
+        Ok(self.escape(&buffer[..v.len_utf8()]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        Ok(self.escape(v.as_bytes()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        Ok(self.escape(v))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(Vec::new())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Ok(Vec::new())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Ok(Vec::new())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Ok(self.escape(variant.as_bytes()))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        match self.list_separator {
+            Some(separator) => Ok(self.delimited(separator)),
+            None => Err(Error::UnsupportedType),
+        }
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        match with::separator(name) {
+            Some(separator) => Ok(self.delimited(separator)),
+            None => Err(Error::UnsupportedType),
+        }
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::UnsupportedType)
+    }
+}
+
+// Writes each element separated by the last of `separators`.
+//
+// A final blank value is not read as an element, so a trailing separator is written after a final
+// element that is blank.
+pub(super) struct Delimited {
+    separators: Vec<u8>,
+    output: Vec<u8>,
+
+    written_first: bool,
+    last_blank: bool,
+}
+
+impl Delimited {
+    fn new(separators: Vec<u8>) -> Self {
+        Self {
+            separators,
+            output: Vec::new(),
+
+            written_first: false,
+            last_blank: false,
+        }
+    }
+}
+
+impl Delimited {
+    fn write_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.written_first {
+            self.output.extend(self.separators.last());
+        } else {
+            self.written_first = true;
+        }
+        let element = value.serialize(Serializer::new(&self.separators))?;
+        self.last_blank = element.iter().all(u8::is_ascii_whitespace);
+        self.output.extend(element);
+        Ok(())
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.last_blank {
+            self.output.extend(self.separators.last());
+        }
+        self.output
+    }
+}
+
+impl SerializeSeq for Delimited {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.write_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleStruct for Delimited {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.write_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{serialize_list, Serializer};
+    use crate::{ser::Error, with};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use serde::{ser::SerializeTupleStruct, Serialize, Serializer as _};
+
+    #[test]
+    fn primitive() {
+        assert_ok_eq!(42.serialize(Serializer::new(&[])), b"42".to_vec());
+    }
+
+    #[test]
+    fn str_escapes_separators() {
+        assert_ok_eq!(
+            "foo,bar:baz".serialize(Serializer::new(b",")),
+            b"foo\\,bar\\:baz".to_vec()
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let serializer =
+            assert_ok!(Serializer::new(&[]).serialize_tuple_struct(with::name(','), 0));
+
+        assert_ok_eq!(serializer.end(), b"".to_vec());
+    }
+
+    #[test]
+    fn list() {
+        assert_ok_eq!(serialize_list(&vec![1, 2, 3], b','), b"1,2,3".to_vec());
+    }
+
+    #[test]
+    fn list_escapes_separator() {
+        assert_ok_eq!(serialize_list(&vec!["a|b", "c"], b'|'), b"a\\|b|c".to_vec());
+    }
+
+    #[test]
+    fn list_empty() {
+        assert_ok_eq!(serialize_list(&Vec::<u64>::new(), b','), b"".to_vec());
+    }
+
+    #[test]
+    fn seq_outside_list_unsupported() {
+        assert_err_eq!(
+            [1, 2].serialize(Serializer::new(&[])),
+            Error::UnsupportedType
+        );
+    }
+
+    #[test]
+    fn delimited() {
+        let mut serializer =
+            assert_ok!(Serializer::new(&[]).serialize_tuple_struct(with::name(','), 3));
+        assert_ok!(serializer.serialize_field(&1));
+        assert_ok!(serializer.serialize_field("a,b"));
+        assert_ok!(serializer.serialize_field(&2.5));
+
+        assert_ok_eq!(serializer.end(), b"1,a\\,b,2.5".to_vec());
+    }
+
+    #[test]
+    fn trailing_blank() {
+        let mut serializer =
+            assert_ok!(Serializer::new(&[]).serialize_tuple_struct(with::name(','), 2));
+        assert_ok!(serializer.serialize_field("a"));
+        assert_ok!(serializer.serialize_field(""));
+
+        assert_ok_eq!(serializer.end(), b"a,,".to_vec());
+    }
+
+    #[test]
+    fn single_blank() {
+        let mut serializer =
+            assert_ok!(Serializer::new(&[]).serialize_tuple_struct(with::name(','), 1));
+        assert_ok!(serializer.serialize_field(""));
+
+        assert_ok_eq!(serializer.end(), b",".to_vec());
+    }
+
+    #[test]
+    fn nested() {
+        struct Pair(f64, f64);
+
+        impl Serialize for Pair {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                let mut tuple = serializer.serialize_tuple_struct(with::name('='), 2)?;
+                tuple.serialize_field(&self.0)?;
+                tuple.serialize_field(&self.1)?;
+                tuple.end()
+            }
+        }
+        let mut serializer =
+            assert_ok!(Serializer::new(&[]).serialize_tuple_struct(with::name(','), 2));
+        assert_ok!(serializer.serialize_field(&Pair(0.0, 120.0)));
+        assert_ok!(serializer.serialize_field(&Pair(32.0, 140.0)));
+
+        assert_ok_eq!(serializer.end(), b"0.0=120.0,32.0=140.0".to_vec());
+    }
+
+    #[test]
+    fn nested_escapes_all_separators() {
+        let mut serializer =
+            assert_ok!(Serializer::new(b",").serialize_tuple_struct(with::name('='), 1));
+        assert_ok!(serializer.serialize_field("a=b,c"));
+
+        assert_ok_eq!(serializer.end(), b"a\\=b\\,c".to_vec());
+    }
+
+    #[test]
+    fn other_tuple_struct_unsupported() {
+        assert_err_eq!(
+            Serializer::new(&[])
+                .serialize_tuple_struct("foo", 2)
+                .map(|_| ()),
+            Error::UnsupportedType
+        );
+    }
+}
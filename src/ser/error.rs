@@ -1,14 +1,45 @@
+use super::transcode::Unrepresentable;
+use crate::Encoding;
 use serde::ser;
-use std::{fmt, fmt::Display};
+use std::{fmt, fmt::Display, io};
 
 /// An error that may occur during serialization.
-#[derive(Debug, PartialEq, Eq)]
+///
+/// Two [`Io`] errors are considered equal if their underlying errors have the same
+/// [`io::ErrorKind`].
+///
+/// [`Io`]: Error::Io
+#[derive(Debug)]
 pub enum Error {
     UnsupportedType,
-    Io,
+    /// The underlying writer failed.
+    ///
+    /// This error is also available through [`source()`].
+    ///
+    /// [`source()`]: std::error::Error::source()
+    Io(io::Error),
+    /// A character cannot be represented in the [`Encoding`] being written.
+    ///
+    /// [`Encoding`]: crate::Encoding
+    UnrepresentableCharacter(char, Encoding),
     Custom(String),
 }
 
+impl Error {
+    // Wraps an error from the underlying writer, recovering characters rejected while transcoding.
+    pub(super) fn io(error: io::Error) -> Self {
+        match error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<Unrepresentable>())
+        {
+            Some(unrepresentable) => {
+                Self::UnrepresentableCharacter(unrepresentable.character, unrepresentable.encoding)
+            }
+            None => Self::Io(error),
+        }
+    }
+}
+
 impl ser::Error for Error {
     fn custom<T>(msg: T) -> Self
     where
@@ -22,13 +53,42 @@ impl Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::UnsupportedType => "unsupported Rust type".fmt(formatter),
-            Self::Io => "error during I/O operations".fmt(formatter),
+            Self::Io(_) => "error during I/O operations".fmt(formatter),
+            Self::UnrepresentableCharacter(character, encoding) => write!(
+                formatter,
+                "`{}` cannot be represented in {:?}",
+                character, encoding
+            ),
             Self::Custom(message) => message.fmt(formatter),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::UnsupportedType, Self::UnsupportedType) => true,
+            (Self::Io(error), Self::Io(other_error)) => error.kind() == other_error.kind(),
+            (
+                Self::UnrepresentableCharacter(character, encoding),
+                Self::UnrepresentableCharacter(other_character, other_encoding),
+            ) => character == other_character && encoding == other_encoding,
+            (Self::Custom(message), Self::Custom(other_message)) => message == other_message,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Error {}
 
 /// An alias for a [`Result`] with the error type [`Error`].
 ///
@@ -37,8 +97,10 @@ pub type Result<T> = core::result::Result<T, Error>;
 
 #[cfg(test)]
 mod tests {
-    use super::Error;
+    use super::{Error, Unrepresentable};
+    use crate::Encoding;
     use serde::ser::Error as SerdeError;
+    use std::{error::Error as _, io};
 
     #[test]
     fn display_unsupported_type_error() {
@@ -50,7 +112,45 @@ mod tests {
 
     #[test]
     fn display_io_error() {
-        assert_eq!(format!("{}", Error::Io), "error during I/O operations");
+        assert_eq!(
+            format!("{}", Error::Io(io::Error::from(io::ErrorKind::Other))),
+            "error during I/O operations"
+        );
+    }
+
+    #[test]
+    fn display_unrepresentable_character_error() {
+        assert_eq!(
+            format!(
+                "{}",
+                Error::UnrepresentableCharacter('東', Encoding::Windows1252)
+            ),
+            "`東` cannot be represented in Windows1252"
+        );
+    }
+
+    #[test]
+    fn io_unrepresentable_character() {
+        let error = io::Error::new(
+            io::ErrorKind::InvalidData,
+            Unrepresentable {
+                character: '東',
+                encoding: Encoding::Windows1252,
+            },
+        );
+
+        assert_eq!(
+            Error::io(error),
+            Error::UnrepresentableCharacter('東', Encoding::Windows1252)
+        );
+    }
+
+    #[test]
+    fn io_other() {
+        assert_eq!(
+            Error::io(io::Error::from(io::ErrorKind::WriteZero)),
+            Error::Io(io::Error::from(io::ErrorKind::WriteZero))
+        );
     }
 
     #[test]
@@ -60,4 +160,28 @@ mod tests {
             "custom error message"
         );
     }
+
+    #[test]
+    fn io_source() {
+        let error = Error::Io(io::Error::new(io::ErrorKind::WriteZero, "foo"));
+
+        assert_eq!(format!("{}", error.source().unwrap()), "foo");
+    }
+
+    #[test]
+    fn no_source() {
+        assert!(Error::UnsupportedType.source().is_none());
+    }
+
+    #[test]
+    fn eq_io_compares_kind() {
+        assert_eq!(
+            Error::Io(io::Error::new(io::ErrorKind::WriteZero, "foo")),
+            Error::Io(io::Error::from(io::ErrorKind::WriteZero))
+        );
+        assert_ne!(
+            Error::Io(io::Error::from(io::ErrorKind::WriteZero)),
+            Error::Io(io::Error::from(io::ErrorKind::BrokenPipe))
+        );
+    }
 }
@@ -15,6 +15,8 @@ pub(crate) struct Escaped<'a> {
     current_pointer: *const u8,
     end_pointer: *const u8,
     state: State,
+    // Additional bytes to escape, used for values delimited within a single parameter.
+    separators: &'a [u8],
 
     lifetime: PhantomData<&'a ()>,
 }
@@ -28,10 +30,16 @@ impl<'a> Escaped<'a> {
             // slice points directly past the slice, which is safe.
             end_pointer: unsafe { pointer.add(bytes.len()) },
             state: State::None,
+            separators: &[],
 
             lifetime: PhantomData,
         }
     }
+
+    pub(crate) fn with_separators(mut self, separators: &'a [u8]) -> Self {
+        self.separators = separators;
+        self
+    }
 }
 
 impl<'a> Iterator for Escaped<'a> {
@@ -53,18 +61,14 @@ impl<'a> Iterator for Escaped<'a> {
                         self.state = State::Escaped;
                         Some(b'\\')
                     }
-                    (b'/', true) => {
-                        // SAFETY: The current pointer plus 1 is verified in this branch to be
-                        // valid.
-                        if unsafe { *self.current_pointer.add(1) } == b'/' {
-                            self.state = State::EscapedDouble;
-                            Some(b'\\')
-                        } else {
-                            // SAFETY: We verified above that current pointer was not at the end of its
-                            // slice.
-                            self.current_pointer = unsafe { self.current_pointer.add(1) };
-                            Some(b)
-                        }
+                    (b, _) if self.separators.contains(&b) => {
+                        self.state = State::Escaped;
+                        Some(b'\\')
+                    }
+                    // SAFETY: The current pointer plus 1 is verified in this branch to be valid.
+                    (b'/', true) if unsafe { *self.current_pointer.add(1) } == b'/' => {
+                        self.state = State::EscapedDouble;
+                        Some(b'\\')
                     }
                     _ => {
                         // SAFETY: We verified above that current pointer was not at the end of its
@@ -186,4 +190,18 @@ mod tests {
 
         assert_eq!(escaped.collect::<Vec<_>>(), b"\\#\\:\\;\\\\\\/\\/\\/\\/");
     }
+
+    #[test]
+    fn escapes_separators() {
+        let escaped = Escaped::new(b"foo,bar=baz").with_separators(b",=");
+
+        assert_eq!(escaped.collect::<Vec<_>>(), b"foo\\,bar\\=baz");
+    }
+
+    #[test]
+    fn escapes_separators_and_specials() {
+        let escaped = Escaped::new(b"a/,b:").with_separators(b"/");
+
+        assert_eq!(escaped.collect::<Vec<_>>(), b"a\\/,b\\:");
+    }
 }
@@ -0,0 +1,321 @@
+use crate::ser::{transcode::Transcoder, SerializerOptions};
+use std::{io, io::Write};
+
+// Layout decisions made while writing MSD.
+//
+// The provided methods describe the default layout, which is used as-is by writers that are not a
+// `Formatter`, such as the buffers tag names are built in.
+pub(super) trait Format {
+    fn line_ending(&self) -> &'static [u8] {
+        b"\n"
+    }
+
+    fn indentation(&self) -> &[u8] {
+        b"   "
+    }
+
+    // Whether each parameter list of a tag containing multiple lists is written on its own line.
+    fn multi_line(&self) -> bool {
+        true
+    }
+
+    // Whether closing a parameter list should also end the current line.
+    fn ends_line(&self) -> bool {
+        true
+    }
+
+    // Whether struct field names are written in ASCII uppercase.
+    fn uppercase_field_names(&self) -> bool {
+        false
+    }
+
+    // Whether an `Option` within a tuple or sequence is written, with an empty parameter as `None`.
+    fn empty_as_none(&self) -> bool {
+        false
+    }
+
+    // The separator between the names of a nested struct field and its fields, if nested structs
+    // are written.
+    fn nested_structs(&self) -> Option<char> {
+        None
+    }
+
+    // Records the start of a tag, returning whether a blank line should be written before it.
+    fn start_tag(&mut self) -> bool {
+        false
+    }
+
+    // Records the start of a comment, returning whether a blank line should be written before it.
+    fn start_comment(&mut self) -> bool {
+        false
+    }
+
+    // Whether each parameter is written on its own line.
+    fn pretty(&self) -> bool {
+        false
+    }
+
+    // Records the start of a tag's `len` parameters.
+    fn enter_parameters(&mut self, _len: usize) {}
+
+    // Records the end of a tag's parameters.
+    fn exit_parameters(&mut self) {}
+
+    // Records the start of a tag containing multiple parameter lists.
+    fn enter_lists(&mut self) {}
+
+    // Records the end of a tag containing multiple parameter lists, returning whether the line
+    // must still be ended.
+    fn exit_lists(&mut self) -> bool {
+        false
+    }
+}
+
+impl Format for Vec<u8> {}
+
+/// Writes MSD laid out according to [`SerializerOptions`].
+#[derive(Debug)]
+pub struct Formatter<W> {
+    writer: Transcoder<W>,
+    options: SerializerOptions,
+
+    // Whether a tag has been written since the start of the output or the last comment.
+    written_tag: bool,
+    // How many tags containing multiple parameter lists are currently being written.
+    lists_depth: usize,
+    // Whether the parameters of the current tag are being pretty-printed.
+    pretty: bool,
+}
+
+impl<W> Formatter<W> {
+    pub(super) fn new(writer: W, options: SerializerOptions) -> Self {
+        Self {
+            writer: Transcoder::new(writer, options.get_encoding()),
+            options,
+
+            written_tag: false,
+            lists_depth: 0,
+            pretty: false,
+        }
+    }
+}
+
+impl<W> Format for Formatter<W> {
+    fn line_ending(&self) -> &'static [u8] {
+        self.options.get_line_ending().as_bytes()
+    }
+
+    fn indentation(&self) -> &[u8] {
+        self.options.get_indentation().as_bytes()
+    }
+
+    fn multi_line(&self) -> bool {
+        self.options.get_multi_line()
+    }
+
+    fn ends_line(&self) -> bool {
+        self.lists_depth == 0 || self.options.get_multi_line()
+    }
+
+    fn uppercase_field_names(&self) -> bool {
+        self.options.get_uppercase_field_names()
+    }
+
+    fn empty_as_none(&self) -> bool {
+        self.options.get_empty_as_none()
+    }
+
+    fn nested_structs(&self) -> Option<char> {
+        self.options.get_nested_structs()
+    }
+
+    fn start_tag(&mut self) -> bool {
+        let blank_line = self.written_tag && self.options.get_blank_lines();
+        self.written_tag = true;
+        blank_line
+    }
+
+    fn start_comment(&mut self) -> bool {
+        let blank_line = self.written_tag && self.options.get_blank_lines();
+        self.written_tag = false;
+        blank_line
+    }
+
+    fn pretty(&self) -> bool {
+        self.pretty
+    }
+
+    fn enter_parameters(&mut self, len: usize) {
+        // Values within a tag containing multiple parameter lists are never pretty-printed.
+        if self.lists_depth == 0 {
+            self.pretty = self
+                .options
+                .get_pretty()
+                .map_or(false, |min_parameters| len >= min_parameters);
+        }
+    }
+
+    fn exit_parameters(&mut self) {
+        if self.lists_depth == 0 {
+            self.pretty = false;
+        }
+    }
+
+    fn enter_lists(&mut self) {
+        self.lists_depth += 1;
+    }
+
+    fn exit_lists(&mut self) -> bool {
+        self.lists_depth -= 1;
+        self.lists_depth == 0 && !self.options.get_multi_line()
+    }
+}
+
+impl<W> Write for Formatter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Format, Formatter};
+    use crate::ser::{LineEnding, SerializerOptions};
+    use std::io;
+
+    #[test]
+    fn default_layout() {
+        let formatter = Formatter::new(io::sink(), SerializerOptions::new());
+
+        let default = Vec::new();
+
+        assert_eq!(formatter.line_ending(), default.line_ending());
+        assert_eq!(formatter.indentation(), default.indentation());
+        assert_eq!(formatter.multi_line(), default.multi_line());
+        assert_eq!(
+            formatter.uppercase_field_names(),
+            default.uppercase_field_names()
+        );
+        assert_eq!(formatter.empty_as_none(), default.empty_as_none());
+        assert_eq!(formatter.nested_structs(), default.nested_structs());
+    }
+
+    #[test]
+    fn uppercase_field_names() {
+        let formatter = Formatter::new(
+            io::sink(),
+            SerializerOptions::new().uppercase_field_names(true),
+        );
+
+        assert!(formatter.uppercase_field_names());
+    }
+
+    #[test]
+    fn empty_as_none() {
+        let formatter = Formatter::new(io::sink(), SerializerOptions::new().empty_as_none(true));
+
+        assert!(formatter.empty_as_none());
+    }
+
+    #[test]
+    fn nested_structs() {
+        let formatter = Formatter::new(io::sink(), SerializerOptions::new().nested_structs('.'));
+
+        assert_eq!(formatter.nested_structs(), Some('.'));
+    }
+
+    #[test]
+    fn line_ending() {
+        let formatter = Formatter::new(
+            io::sink(),
+            SerializerOptions::new().line_ending(LineEnding::CrLf),
+        );
+
+        assert_eq!(formatter.line_ending(), b"\r\n");
+    }
+
+    #[test]
+    fn indentation() {
+        let formatter = Formatter::new(io::sink(), SerializerOptions::new().indentation("\t"));
+
+        assert_eq!(formatter.indentation(), b"\t");
+    }
+
+    #[test]
+    fn blank_lines_between_tags() {
+        let mut formatter = Formatter::new(io::sink(), SerializerOptions::new().blank_lines(true));
+
+        assert!(!formatter.start_tag());
+        assert!(formatter.start_tag());
+        assert!(formatter.start_comment());
+        assert!(!formatter.start_comment());
+        assert!(!formatter.start_tag());
+    }
+
+    #[test]
+    fn no_blank_lines() {
+        let mut formatter = Formatter::new(io::sink(), SerializerOptions::new());
+
+        assert!(!formatter.start_tag());
+        assert!(!formatter.start_tag());
+        assert!(!formatter.start_comment());
+    }
+
+    #[test]
+    fn single_line_lists() {
+        let mut formatter = Formatter::new(io::sink(), SerializerOptions::new().multi_line(false));
+
+        assert!(formatter.ends_line());
+        formatter.enter_lists();
+        assert!(!formatter.ends_line());
+        formatter.enter_lists();
+        assert!(!formatter.exit_lists());
+        assert!(formatter.exit_lists());
+        assert!(formatter.ends_line());
+    }
+
+    #[test]
+    fn pretty() {
+        let mut formatter = Formatter::new(io::sink(), SerializerOptions::new().pretty(3));
+
+        formatter.enter_parameters(2);
+        assert!(!formatter.pretty());
+        formatter.enter_parameters(3);
+        assert!(formatter.pretty());
+        formatter.exit_parameters();
+        assert!(!formatter.pretty());
+    }
+
+    #[test]
+    fn pretty_disabled() {
+        let mut formatter = Formatter::new(io::sink(), SerializerOptions::new());
+
+        formatter.enter_parameters(100);
+        assert!(!formatter.pretty());
+    }
+
+    #[test]
+    fn pretty_within_lists() {
+        let mut formatter = Formatter::new(io::sink(), SerializerOptions::new().pretty(1));
+
+        formatter.enter_lists();
+        formatter.enter_parameters(3);
+        assert!(!formatter.pretty());
+    }
+
+    #[test]
+    fn multi_line_lists() {
+        let mut formatter = Formatter::new(io::sink(), SerializerOptions::new());
+
+        formatter.enter_lists();
+        assert!(formatter.ends_line());
+        assert!(!formatter.exit_lists());
+    }
+}
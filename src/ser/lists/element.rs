@@ -0,0 +1,405 @@
+use super::list;
+use crate::ser::{Error, Result, WriteExt};
+use serde::{
+    ser,
+    ser::{Impossible, SerializeSeq},
+    Serialize,
+};
+
+pub(super) struct Serializer<'a, W> {
+    writer: &'a mut W,
+
+    first: bool,
+    parameters: &'a mut Option<bool>,
+    continuing: Option<&'a [u8]>,
+}
+
+impl<'a, W> Serializer<'a, W> {
+    pub(super) fn new(
+        writer: &'a mut W,
+        first: bool,
+        parameters: &'a mut Option<bool>,
+        continuing: Option<&'a [u8]>,
+    ) -> Self {
+        Self {
+            writer,
+
+            first,
+            parameters,
+            continuing,
+        }
+    }
+}
+
+impl<'a, W> Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    fn enter_parameters(&mut self) -> Result<()> {
+        if *self.parameters == Some(false) {
+            return Err(Error::UnsupportedType);
+        }
+        *self.parameters = Some(true);
+        Ok(())
+    }
+
+    // Writes a value as a parameter of the single list containing all of the sequence's elements.
+    //
+    // When continuing a tag, the value begins a new list instead of following the tag's name.
+    fn serialize_parameter<T>(mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.enter_parameters()?;
+        let mut list = list::Serializer::new(self.writer, self.continuing.is_none(), true);
+        list.serialize_element(value)
+    }
+
+    fn open_list(self) -> Result<list::Serializer<'a, W>> {
+        if *self.parameters == Some(true) {
+            return Err(Error::UnsupportedType);
+        }
+        *self.parameters = Some(false);
+        // A sequence of lists can't continue a tag, so it is written within a new one.
+        if let Some(tag_name) = self.continuing {
+            self.writer.write_tag_name_unescaped(tag_name)?;
+            return Ok(list::Serializer::new(self.writer, true, false));
+        }
+        Ok(list::Serializer::new(self.writer, self.first, false))
+    }
+}
+
+impl<'a, W> ser::Serializer for Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = list::Serializer<'a, W>;
+    type SerializeTuple = list::Serializer<'a, W>;
+    type SerializeTupleStruct = list::Serializer<'a, W>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    #[cfg(has_i128)]
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    #[cfg(has_i128)]
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        self.serialize_parameter(&v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        self.serialize_parameter(v)
+    }
+
+    fn serialize_bytes(mut self, v: &[u8]) -> Result<Self::Ok> {
+        self.enter_parameters()?;
+        if self.continuing.is_some() {
+            self.writer.write_list_head_escaped(v)
+        } else {
+            self.writer.write_parameter_escaped(v)
+        }
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_some<T>(self, _v: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        self.serialize_parameter(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.open_list()
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        self.open_list()
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.open_list()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::UnsupportedType)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Serializer;
+    use crate::ser::Error;
+    use claims::{assert_err_eq, assert_ok, assert_some_eq};
+    use serde::Serialize;
+    use serde_derive::Serialize;
+
+    #[test]
+    fn first_seq() {
+        let mut output = Vec::new();
+
+        assert_ok!(vec!["foo", "bar"].serialize(Serializer::new(
+            &mut output,
+            true,
+            &mut None,
+            None
+        )));
+
+        assert_eq!(output, b":foo:bar;\n");
+    }
+
+    #[test]
+    fn seq() {
+        let mut output = Vec::new();
+
+        assert_ok!(vec!["foo", "bar"].serialize(Serializer::new(
+            &mut output,
+            false,
+            &mut None,
+            None
+        )));
+
+        assert_eq!(output, b"foo:bar;\n");
+    }
+
+    #[test]
+    fn tuple() {
+        let mut output = Vec::new();
+
+        assert_ok!((42, "bar", (), 1.0).serialize(Serializer::new(
+            &mut output,
+            false,
+            &mut None,
+            None
+        )));
+
+        assert_eq!(output, b"42:bar:1.0;\n");
+    }
+
+    #[test]
+    fn tuple_struct() {
+        #[derive(Serialize)]
+        struct TupleStruct(usize, &'static str);
+
+        let mut output = Vec::new();
+
+        assert_ok!(TupleStruct(42, "bar").serialize(Serializer::new(
+            &mut output,
+            true,
+            &mut None,
+            None
+        )));
+
+        assert_eq!(output, b":42:bar;\n");
+    }
+
+    #[test]
+    fn newtype_struct() {
+        #[derive(Serialize)]
+        struct Newtype(Vec<u64>);
+
+        let mut output = Vec::new();
+
+        assert_ok!(Newtype(vec![1, 2]).serialize(Serializer::new(
+            &mut output,
+            false,
+            &mut None,
+            None
+        )));
+
+        assert_eq!(output, b"1:2;\n");
+    }
+
+    #[test]
+    fn str() {
+        let mut output = Vec::new();
+        let mut parameters = None;
+
+        assert_ok!("foo".serialize(Serializer::new(&mut output, false, &mut parameters, None)));
+
+        assert_eq!(output, b":foo");
+        assert_some_eq!(parameters, true);
+    }
+
+    #[test]
+    fn str_continuing() {
+        let mut output = Vec::new();
+        let mut parameters = None;
+
+        assert_ok!("foo".serialize(Serializer::new(
+            &mut output,
+            true,
+            &mut parameters,
+            Some(b"bar")
+        )));
+
+        assert_eq!(output, b"foo");
+    }
+
+    #[test]
+    fn str_after_list() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            "foo".serialize(Serializer::new(&mut output, false, &mut Some(false), None)),
+            Error::UnsupportedType
+        );
+    }
+
+    #[test]
+    fn seq_after_parameter() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            vec!["foo"].serialize(Serializer::new(&mut output, false, &mut Some(true), None)),
+            Error::UnsupportedType
+        );
+    }
+
+    #[test]
+    fn seq_continuing() {
+        let mut output = Vec::new();
+        let mut parameters = None;
+
+        assert_ok!(vec!["foo", "bar"].serialize(Serializer::new(
+            &mut output,
+            false,
+            &mut parameters,
+            Some(b"baz")
+        )));
+
+        assert_eq!(output, b"#baz:foo:bar;\n");
+        assert_some_eq!(parameters, false);
+    }
+
+    #[test]
+    fn unit() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            ().serialize(Serializer::new(&mut output, false, &mut None, None)),
+            Error::UnsupportedType
+        );
+    }
+}
@@ -0,0 +1,187 @@
+mod head;
+
+use crate::ser::{tuple, Error, Result, WriteExt};
+use serde::{
+    ser::{SerializeSeq, SerializeTuple, SerializeTupleStruct},
+    Serialize,
+};
+
+pub struct Serializer<'a, W> {
+    writer: &'a mut W,
+
+    // The first parameter list of a tag directly follows the tag's name, so its values are all
+    // prefixed with `:`. Every other list begins with an unprefixed value.
+    written_head: bool,
+    // Nested lists are written within another list, and therefore do not close it.
+    nested: bool,
+}
+
+impl<'a, W> Serializer<'a, W> {
+    pub(in crate::ser) fn new(writer: &'a mut W, written_head: bool, nested: bool) -> Self {
+        Self {
+            writer,
+
+            written_head,
+            nested,
+        }
+    }
+}
+
+impl<'a, W> Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.written_head {
+            value.serialize(tuple::element::Serializer::new(self.writer))
+        } else {
+            self.written_head = true;
+            value.serialize(head::Serializer::new(self.writer))
+        }
+    }
+
+    fn close(self) -> Result<()> {
+        if self.nested {
+            Ok(())
+        } else {
+            self.writer.close_tag()
+        }
+    }
+}
+
+impl<'a, W> SerializeSeq for Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_value(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.close()
+    }
+}
+
+impl<'a, W> SerializeTuple for Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_value(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.close()
+    }
+}
+
+impl<'a, W> SerializeTupleStruct for Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_value(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Serializer;
+    use claims::assert_ok;
+    use serde::ser::{SerializeSeq, SerializeTuple};
+
+    #[test]
+    fn empty() {
+        let mut output = Vec::new();
+        let serializer = Serializer::new(&mut output, false, false);
+
+        assert_ok!(SerializeSeq::end(serializer));
+
+        assert_eq!(output, b";\n");
+    }
+
+    #[test]
+    fn empty_nested() {
+        let mut output = Vec::new();
+        let serializer = Serializer::new(&mut output, false, true);
+
+        assert_ok!(SerializeSeq::end(serializer));
+
+        assert_eq!(output, b"");
+    }
+
+    #[test]
+    fn written_head() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output, true, false);
+
+        assert_ok!(SerializeSeq::serialize_element(&mut serializer, "foo"));
+        assert_ok!(SerializeSeq::serialize_element(&mut serializer, &42));
+        assert_ok!(SerializeSeq::end(serializer));
+
+        assert_eq!(output, b":foo:42;\n");
+    }
+
+    #[test]
+    fn unwritten_head() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output, false, false);
+
+        assert_ok!(SerializeTuple::serialize_element(&mut serializer, "foo"));
+        assert_ok!(SerializeTuple::serialize_element(&mut serializer, &42));
+        assert_ok!(SerializeTuple::end(serializer));
+
+        assert_eq!(output, b"foo:42;\n");
+    }
+
+    #[test]
+    fn nested_head() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output, false, false);
+
+        assert_ok!(SerializeTuple::serialize_element(
+            &mut serializer,
+            &("foo", 1)
+        ));
+        assert_ok!(SerializeTuple::serialize_element(&mut serializer, &42));
+        assert_ok!(SerializeTuple::end(serializer));
+
+        assert_eq!(output, b"foo:1:42;\n");
+    }
+
+    #[test]
+    fn head_escaped() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output, false, false);
+
+        assert_ok!(SerializeSeq::serialize_element(&mut serializer, "f#o:o"));
+        assert_ok!(SerializeSeq::end(serializer));
+
+        assert_eq!(output, b"f\\#o\\:o;\n");
+    }
+}
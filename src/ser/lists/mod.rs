@@ -0,0 +1,220 @@
+pub(super) mod list;
+
+mod element;
+
+use crate::ser::{Error, Result, WriteExt};
+use serde::{ser::SerializeSeq, Serialize};
+use std::mem;
+
+// Serializes a sequence of parameter lists as the remainder of a single tag.
+//
+// The first parameter list continues the list containing the tag's name, while each following
+// list is written on its own line. For example, `[["a", "b"], ["c", "d"]]` within a tag `NAME`
+// is written as `#NAME:a:b;\nc:d;\n`.
+//
+// A sequence of values is instead written as the single parameter list `#NAME:a:b;\n`. The
+// following sequence of values may then continue the same tag as its next list, so that
+// `[["a", "b"], ["c"]]` is written as `#NAME:a:b;\nc;\n`.
+pub struct Serializer<'a, W> {
+    writer: &'a mut W,
+
+    written_first: bool,
+    // Whether the elements are the parameters of a single list rather than parameter lists,
+    // determined by the first element.
+    parameters: Option<bool>,
+    // The escaped name of the tag continued by this sequence. It is only written if the sequence
+    // must open a new tag instead.
+    continuing: Option<&'a [u8]>,
+    // Set once the sequence is written as a single parameter list, allowing the next sequence to
+    // continue its tag.
+    continued: Option<&'a mut bool>,
+}
+
+impl<'a, W> Serializer<'a, W> {
+    pub(super) fn new(writer: &'a mut W) -> Self {
+        Self {
+            writer,
+
+            written_first: false,
+            parameters: None,
+            continuing: None,
+            continued: None,
+        }
+    }
+
+    // Continues the tag named `tag_name` if `continued` is set, setting it again if this sequence
+    // can be continued in turn.
+    //
+    // The tag's name is expected to already be written if `continued` is not set.
+    pub(super) fn continuing(mut self, continued: &'a mut bool, tag_name: &'a [u8]) -> Self {
+        if mem::take(continued) {
+            self.continuing = Some(tag_name);
+        }
+        self.continued = Some(continued);
+        self
+    }
+}
+
+impl<'a, W> SerializeSeq for Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        let first = !self.written_first;
+        self.written_first = true;
+        value.serialize(element::Serializer::new(
+            self.writer,
+            first,
+            &mut self.parameters,
+            self.continuing.take(),
+        ))
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        match self.parameters {
+            Some(true) => {
+                self.writer.close_tag()?;
+                if let Some(continued) = self.continued {
+                    *continued = true;
+                }
+                Ok(())
+            }
+            Some(false) => Ok(()),
+            None => {
+                if let Some(tag_name) = self.continuing {
+                    self.writer.write_tag_name_unescaped(tag_name)?;
+                }
+                self.writer.close_tag()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Serializer;
+    use crate::ser::Error;
+    use claims::{assert_err_eq, assert_ok};
+    use serde::ser::SerializeSeq;
+
+    #[test]
+    fn empty() {
+        let mut output = Vec::new();
+        let serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b";\n");
+    }
+
+    #[test]
+    fn single_list() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.serialize_element(&vec!["foo", "bar"]));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b":foo:bar;\n");
+    }
+
+    #[test]
+    fn multiple_lists() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.serialize_element(&vec!["foo", "bar"]));
+        assert_ok!(serializer.serialize_element(&(1, 2.0)));
+        assert_ok!(serializer.serialize_element(&vec!["baz"]));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b":foo:bar;\n1:2.0;\nbaz;\n");
+    }
+
+    #[test]
+    fn empty_lists() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.serialize_element(&Vec::<&str>::new()));
+        assert_ok!(serializer.serialize_element(&vec!["foo"]));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b";\nfoo;\n");
+    }
+
+    #[test]
+    fn parameters() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.serialize_element(&"foo"));
+        assert_ok!(serializer.serialize_element(&42));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b":foo:42;\n");
+    }
+
+    #[test]
+    fn parameters_continued() {
+        let mut output = Vec::new();
+        let mut continued = false;
+
+        let mut serializer = Serializer::new(&mut output).continuing(&mut continued, b"foo");
+        assert_ok!(serializer.serialize_element(&"bar"));
+        assert_ok!(serializer.end());
+        assert!(continued);
+
+        let mut serializer = Serializer::new(&mut output).continuing(&mut continued, b"foo");
+        assert_ok!(serializer.serialize_element(&"baz"));
+        assert_ok!(serializer.serialize_element(&"qux"));
+        assert_ok!(serializer.end());
+        assert!(continued);
+
+        assert_eq!(output, b":bar;\nbaz:qux;\n");
+    }
+
+    #[test]
+    fn empty_continued() {
+        let mut output = Vec::new();
+        let mut continued = true;
+
+        let serializer = Serializer::new(&mut output).continuing(&mut continued, b"foo");
+        assert_ok!(serializer.end());
+
+        assert!(!continued);
+        assert_eq!(output, b"#foo;\n");
+    }
+
+    #[test]
+    fn lists_continued() {
+        let mut output = Vec::new();
+        let mut continued = true;
+
+        let mut serializer = Serializer::new(&mut output).continuing(&mut continued, b"foo");
+        assert_ok!(serializer.serialize_element(&vec!["bar"]));
+        assert_ok!(serializer.serialize_element(&vec!["baz"]));
+        assert_ok!(serializer.end());
+
+        assert!(!continued);
+        assert_eq!(output, b"#foo:bar;\nbaz;\n");
+    }
+
+    #[test]
+    fn parameters_then_list() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.serialize_element(&"foo"));
+        assert_err_eq!(
+            serializer.serialize_element(&vec!["bar"]),
+            Error::UnsupportedType
+        );
+    }
+}
@@ -5,7 +5,6 @@ mod value;
 
 use crate::ser::{Error, Result, WriteExt};
 use serde::{ser::SerializeMap, Serialize};
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
@@ -23,7 +22,7 @@ impl<'a, W> Serializer<'a, W> {
 
 impl<'a, W> SerializeMap for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -45,10 +44,9 @@ where
 
     fn end(self) -> Result<Self::Ok> {
         if !self.written_field {
-            self.writer.close_tag()
-        } else {
-            Ok(())
+            self.writer.close_tag()?;
         }
+        self.writer.close_lists()
     }
 }
 
@@ -3,7 +3,6 @@ mod key;
 use super::value;
 use crate::ser::{Error, Result, WriteExt};
 use serde::{ser::SerializeMap, Serialize};
-use std::io::Write;
 
 pub struct Serializer<'a, W> {
     writer: &'a mut W,
@@ -21,7 +20,7 @@ impl<'a, W> Serializer<'a, W> {
 
 impl<'a, W> SerializeMap for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -1,6 +1,8 @@
-use crate::ser::{tuple, Error, Result, WriteExt};
+use crate::{
+    ser::{delimited, lists, tuple, Error, Result, WriteExt},
+    with,
+};
 use serde::{ser, ser::Impossible, Serialize};
-use std::io::Write;
 
 pub(super) struct Serializer<'a, W> {
     writer: &'a mut W,
@@ -14,11 +16,11 @@ impl<'a, W> Serializer<'a, W> {
 
 impl<'a, W> ser::Serializer for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
-    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq = lists::Serializer<'a, W>;
     type SerializeTuple = tuple::Serializer<'a, W>;
     type SerializeTupleStruct = tuple::Serializer<'a, W>;
     type SerializeTupleVariant = tuple::Serializer<'a, W>;
@@ -172,10 +174,15 @@ where
         self.writer.close_tag()
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if let Some(separator) = with::separator(name) {
+            let parameter = delimited::serialize_list(value, separator)?;
+            self.writer.write_parameter_unescaped(&parameter)?;
+            return self.writer.close_tag();
+        }
         value.serialize(self)
     }
 
@@ -194,18 +201,20 @@ where
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Err(Error::UnsupportedType)
+        Ok(lists::Serializer::new(self.writer))
     }
 
-    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.writer.open_parameters(len);
         Ok(tuple::Serializer::new(self.writer))
     }
 
     fn serialize_tuple_struct(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
+        self.writer.open_parameters(len);
         Ok(tuple::Serializer::new(self.writer))
     }
 
@@ -214,8 +223,9 @@ where
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
+        self.writer.open_parameters(len + 1);
         self.writer.write_parameter_escaped(variant.as_bytes())?;
         Ok(tuple::Serializer::new(self.writer))
     }
@@ -836,14 +846,31 @@ mod tests {
         assert_eq!(output, b":Variant:1:2:3:4:5:6:7;\n");
     }
 
+    #[test]
+    fn empty_seq() {
+        let mut output = Vec::new();
+
+        assert_ok!(Vec::<Vec<()>>::new().serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b";\n");
+    }
+
     #[test]
     fn seq() {
         let mut output = Vec::new();
 
-        assert_err_eq!(
-            Vec::<()>::new().serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
-        );
+        assert_ok!(vec![vec!["foo", "bar"], vec!["baz"]].serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b":foo:bar;\nbaz;\n");
+    }
+
+    #[test]
+    fn seq_of_values() {
+        let mut output = Vec::new();
+
+        assert_ok!(vec![42].serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b":42;\n");
     }
 
     #[test]
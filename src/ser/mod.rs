@@ -17,19 +17,32 @@
 //!
 //! [`Serialize`]: serde::Serialize
 
+mod commented;
+mod delimited;
 mod error;
 mod escaped;
+mod format;
+mod lists;
 mod map;
+mod options;
 mod seq;
 mod r#struct;
+mod transcode;
 mod tuple;
+mod value;
 mod write;
 
+pub use commented::Commented;
 pub use error::{Error, Result};
+pub use options::{LineEnding, SerializerOptions};
 
-use escaped::Escaped;
+pub(crate) use escaped::Escaped;
+
+use crate::Encoding;
+use format::Formatter;
 use serde::{ser, Serialize};
 use std::io::Write;
+use transcode::Transcoder;
 use write::WriteExt;
 
 /// Serializes data into MSD format.
@@ -55,12 +68,44 @@ use write::WriteExt;
 /// [`Deserializer`]: crate::Deserializer
 #[derive(Debug)]
 pub struct Serializer<W> {
-    writer: W,
+    writer: Formatter<W>,
 }
 
 impl<W> Serializer<W> {
     pub fn new(writer: W) -> Self {
-        Self { writer }
+        Self::with_options(writer, SerializerOptions::default())
+    }
+
+    /// Creates a `Serializer` writing its output according to the given `options`.
+    pub fn with_options(writer: W, options: SerializerOptions) -> Self {
+        Self {
+            writer: Formatter::new(writer, options),
+        }
+    }
+}
+
+impl<W> Serializer<W>
+where
+    W: Write,
+{
+    /// Writes a `//` comment on its own line(s).
+    ///
+    /// This can be used to write header comments before serializing a value, or trailing comments
+    /// after. To attach a comment to a specific tag, use [`Commented`].
+    ///
+    /// # Example
+    /// ```
+    /// use serde::Serialize;
+    ///
+    /// let mut serialized = Vec::new();
+    /// let mut serializer = msd::Serializer::new(&mut serialized);
+    /// serializer.write_comment("converted from DWI").unwrap();
+    /// "foo".serialize(&mut serializer).unwrap();
+    ///
+    /// assert_eq!(serialized, b"// converted from DWI\n#foo;\n");
+    /// ```
+    pub fn write_comment(&mut self, comment: &str) -> Result<()> {
+        self.writer.write_comment(comment.as_bytes())
     }
 }
 
@@ -70,13 +115,13 @@ where
 {
     type Ok = ();
     type Error = Error;
-    type SerializeSeq = seq::tag::Serializer<'a, W>;
-    type SerializeTuple = tuple::tag::Serializer<'a, W>;
-    type SerializeTupleStruct = tuple::tag::Serializer<'a, W>;
-    type SerializeTupleVariant = tuple::Serializer<'a, W>;
-    type SerializeMap = map::tag::Serializer<'a, W>;
-    type SerializeStruct = r#struct::Serializer<'a, W>;
-    type SerializeStructVariant = r#struct::Serializer<'a, W>;
+    type SerializeSeq = seq::tag::Serializer<'a, Formatter<W>>;
+    type SerializeTuple = tuple::tag::Serializer<'a, Formatter<W>>;
+    type SerializeTupleStruct = tuple::tag::Serializer<'a, Formatter<W>>;
+    type SerializeTupleVariant = tuple::Serializer<'a, Formatter<W>>;
+    type SerializeMap = map::tag::Serializer<'a, Formatter<W>>;
+    type SerializeStruct = r#struct::Serializer<'a, Formatter<W>>;
+    type SerializeStructVariant = r#struct::Serializer<'a, Formatter<W>>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
         if v {
@@ -222,10 +267,17 @@ where
         self.writer.close_tag()
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if name == commented::NAME {
+            self.writer
+                .write_comment(value.serialize(commented::CommentSerializer)?.as_bytes())?;
+        }
+        if name == crate::value::NAME {
+            return value.serialize(value::Serializer::new(&mut self.writer));
+        }
         value.serialize(self)
     }
 
@@ -249,15 +301,17 @@ where
         Ok(seq::tag::Serializer::new(&mut self.writer))
     }
 
-    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.writer.open_parameters(len.saturating_sub(1));
         Ok(tuple::tag::Serializer::new(&mut self.writer))
     }
 
     fn serialize_tuple_struct(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
+        self.writer.open_parameters(len.saturating_sub(1));
         Ok(tuple::tag::Serializer::new(&mut self.writer))
     }
 
@@ -266,8 +320,9 @@ where
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
+        self.writer.open_parameters(len);
         self.writer.write_tag_name_escaped(variant.as_bytes())?;
         Ok(tuple::Serializer::new(&mut self.writer))
     }
@@ -314,17 +369,28 @@ where
     Ok(bytes)
 }
 
+// Escapes `value` and encodes it in `encoding`, exactly as is done when serializing a string.
+pub(crate) fn escape(value: &str, encoding: Encoding) -> Result<Vec<u8>> {
+    let escaped: Vec<u8> = Escaped::new(value.as_bytes()).collect();
+    let mut bytes = Vec::with_capacity(escaped.len());
+    Transcoder::new(&mut bytes, encoding)
+        .write_all(&escaped)
+        .map_err(Error::io)?;
+    Ok(bytes)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Serializer;
-    use claims::assert_ok;
+    use super::{Commented, Error, LineEnding, Serializer, SerializerOptions};
+    use crate::{de::DeserializerOptions, Deserializer, Encoding};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
     use serde::{
         ser::{SerializeMap, SerializeTupleStruct, SerializeTupleVariant},
-        Serialize,
+        Deserialize, Serialize,
     };
     use serde_bytes::Bytes;
-    use serde_derive::Serialize;
-    use std::collections::HashMap;
+    use serde_derive::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn r#true() {
@@ -1136,4 +1202,1107 @@ mod tests {
         .serialize(&mut Serializer::new(&mut output)));
         assert_eq!(output, b"#Variant;\n#bar:42;\n#qux:test\\:test;\n");
     }
+
+    #[test]
+    fn encoding_shift_jis() {
+        let mut output = Vec::new();
+
+        assert_ok!(("東方", "ソ").serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().encoding(Encoding::ShiftJis)
+        )));
+        assert_eq!(output, b"#\x93\x8c\x95\xfb:\x83\\;\n");
+    }
+
+    #[test]
+    fn encoding_windows_1252() {
+        let mut output = Vec::new();
+
+        assert_ok!("Café".serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().encoding(Encoding::Windows1252)
+        )));
+        assert_eq!(output, b"#Caf\xe9;\n");
+    }
+
+    #[test]
+    fn encoding_bytes_unchanged() {
+        let mut output = Vec::new();
+
+        assert_ok!(
+            Bytes::new(b"\x93\x8c").serialize(&mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().encoding(Encoding::ShiftJis)
+            ))
+        );
+        assert_eq!(output, b"#\x93\x8c;\n");
+    }
+
+    #[test]
+    fn encoding_unrepresentable() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            "東方".serialize(&mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().encoding(Encoding::Windows1252)
+            )),
+            Error::UnrepresentableCharacter('東', Encoding::Windows1252)
+        );
+    }
+
+    #[test]
+    fn encoding_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(rename = "TITLE")]
+            title: String,
+            #[serde(rename = "ARTIST")]
+            artist: String,
+        }
+        let song = Song {
+            title: "表;ソ:十\\".to_owned(),
+            artist: "ソ".to_owned(),
+        };
+        let mut output = Vec::new();
+
+        assert_ok!(song.serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().encoding(Encoding::ShiftJis)
+        )));
+        assert_ok_eq!(
+            Song::deserialize(&mut Deserializer::with_options(
+                output.as_slice(),
+                DeserializerOptions::new().encoding(Encoding::ShiftJis)
+            )),
+            song
+        );
+    }
+
+    #[test]
+    fn write_comment() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.write_comment("converted from DWI\nby hand"));
+        assert_ok!(42.serialize(&mut serializer));
+        assert_ok!(serializer.write_comment("end"));
+        assert_eq!(output, b"// converted from DWI\n// by hand\n#42;\n// end\n");
+    }
+
+    #[test]
+    fn write_comment_encoded() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().encoding(Encoding::Windows1252),
+        );
+
+        assert_ok!(serializer.write_comment("café"));
+        assert_eq!(output, b"// caf\xe9\n");
+    }
+
+    #[test]
+    fn commented() {
+        let mut output = Vec::new();
+
+        assert_ok!(Commented::new("foo", 42).serialize(&mut Serializer::new(&mut output)));
+        assert_eq!(output, b"// foo\n#42;\n");
+    }
+
+    #[test]
+    fn commented_fields() {
+        #[derive(Serialize)]
+        struct Song {
+            #[serde(rename = "TITLE")]
+            title: Commented<&'static str>,
+            #[serde(rename = "BPMS")]
+            bpms: Vec<Commented<(f64, f64)>>,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Song {
+            title: Commented::new("from the original DWI", "foo"),
+            bpms: vec![Commented::new("ignored", (0.0, 120.0))],
+        }
+        .serialize(&mut Serializer::new(&mut output)));
+        assert_eq!(
+            output,
+            b"// from the original DWI\n#TITLE:foo;\n#BPMS:0.0:120.0;\n"
+        );
+    }
+
+    #[test]
+    fn commented_seq() {
+        let mut output = Vec::new();
+
+        assert_ok!(vec![Commented::new("foo", 1), Commented::new("bar", 2)]
+            .serialize(&mut Serializer::new(&mut output)));
+        assert_eq!(output, b"// foo\n#1;\n// bar\n#2;\n");
+    }
+
+    #[test]
+    fn commented_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(rename = "TITLE")]
+            title: String,
+            #[serde(rename = "OFFSET")]
+            offset: Commented<f64>,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Song {
+            title: "foo".to_owned(),
+            offset: Commented::new("synced by ear", -0.5),
+        }
+        .serialize(&mut Serializer::new(&mut output)));
+        assert_ok_eq!(
+            crate::from_bytes::<Song>(&output),
+            Song {
+                title: "foo".to_owned(),
+                offset: Commented::new("", -0.5),
+            }
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Chart {
+        #[serde(rename = "TITLE")]
+        title: String,
+        #[serde(rename = "BPMS")]
+        bpms: BTreeMap<u64, f64>,
+    }
+
+    fn chart() -> Chart {
+        let mut bpms = BTreeMap::new();
+        bpms.insert(0, 120.0);
+        bpms.insert(16, 140.0);
+        Chart {
+            title: "foo".to_owned(),
+            bpms,
+        }
+    }
+
+    #[test]
+    fn line_ending_crlf() {
+        let mut output = Vec::new();
+
+        assert_ok!(chart().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().line_ending(LineEnding::CrLf)
+        )));
+        assert_eq!(
+            output,
+            b"#TITLE:foo;\r\n#BPMS:\r\n   0:120.0;\r\n   16:140.0;\r\n"
+        );
+    }
+
+    #[test]
+    fn line_ending_crlf_commented() {
+        let mut output = Vec::new();
+
+        assert_ok!(
+            Commented::new("foo\r\nbar", 42).serialize(&mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().line_ending(LineEnding::CrLf)
+            ))
+        );
+        assert_eq!(output, b"// foo\r\n// bar\r\n#42;\r\n");
+    }
+
+    #[test]
+    fn indentation() {
+        let mut output = Vec::new();
+
+        assert_ok!(chart().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().indentation("\t")
+        )));
+        assert_eq!(output, b"#TITLE:foo;\n#BPMS:\n\t0:120.0;\n\t16:140.0;\n");
+    }
+
+    #[test]
+    fn blank_lines() {
+        let mut output = Vec::new();
+        let mut serializer =
+            Serializer::with_options(&mut output, SerializerOptions::new().blank_lines(true));
+
+        assert_ok!(serializer.write_comment("header"));
+        assert_ok!(chart().serialize(&mut serializer));
+        assert_ok!(serializer.write_comment("footer"));
+        assert_eq!(
+            output,
+            b"// header\n#TITLE:foo;\n\n#BPMS:\n   0:120.0;\n   16:140.0;\n\n// footer\n"
+        );
+    }
+
+    #[test]
+    fn blank_lines_commented() {
+        let mut output = Vec::new();
+
+        assert_ok!(
+            vec![Commented::new("foo", 1), Commented::new("bar", 2)].serialize(
+                &mut Serializer::with_options(
+                    &mut output,
+                    SerializerOptions::new().blank_lines(true)
+                )
+            )
+        );
+        assert_eq!(output, b"// foo\n#1;\n\n// bar\n#2;\n");
+    }
+
+    #[test]
+    fn single_line() {
+        let mut output = Vec::new();
+
+        assert_ok!(chart().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().multi_line(false)
+        )));
+        assert_eq!(output, b"#TITLE:foo;\n#BPMS:0:120.0;16:140.0;\n");
+    }
+
+    #[test]
+    fn single_line_empty_map() {
+        let mut output = Vec::new();
+
+        assert_ok!(Chart {
+            title: "foo".to_owned(),
+            bpms: BTreeMap::new(),
+        }
+        .serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().multi_line(false)
+        )));
+        assert_eq!(output, b"#TITLE:foo;\n#BPMS:;\n");
+    }
+
+    #[test]
+    fn formatting_round_trip() {
+        let mut output = Vec::new();
+
+        assert_ok!(chart().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new()
+                .line_ending(LineEnding::CrLf)
+                .indentation("\t")
+                .blank_lines(true)
+                .multi_line(false)
+        )));
+        assert_ok_eq!(crate::from_bytes::<Chart>(&output), chart());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    enum Difficulty {
+        Beginner,
+        Challenge,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Notes(String, Difficulty, u8, Vec<String>);
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Song {
+        #[serde(rename = "TITLE")]
+        title: String,
+        #[serde(rename = "NOTES")]
+        notes: Notes,
+    }
+
+    fn song() -> Song {
+        Song {
+            title: "foo".to_owned(),
+            notes: Notes(
+                "dance-single".to_owned(),
+                Difficulty::Beginner,
+                1,
+                vec!["1000\n0100".to_owned(), "0010\n0001".to_owned()],
+            ),
+        }
+    }
+
+    #[test]
+    fn seq_in_tuple_round_trip() {
+        let notes = Notes(
+            "dance-single".to_owned(),
+            Difficulty::Challenge,
+            12,
+            vec!["1000,0100".to_owned(), "".to_owned()],
+        );
+        let mut output = Vec::new();
+
+        assert_ok!(notes.serialize(&mut Serializer::new(&mut output)));
+        assert_eq!(output, b"#dance-single:Challenge:12:1000\\,0100,,;\n");
+        assert_ok_eq!(crate::from_bytes::<Notes>(&output), notes);
+    }
+
+    #[test]
+    fn pretty() {
+        let mut output = Vec::new();
+
+        assert_ok!(song().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().pretty(4)
+        )));
+        assert_eq!(
+            output,
+            b"#TITLE:foo;\n#NOTES:\n   dance-single:\n   Beginner:\n   1:\n1000\n0100\n,\n0010\n0001\n;\n"
+        );
+    }
+
+    #[test]
+    fn pretty_below_min_parameters() {
+        let mut output = Vec::new();
+
+        assert_ok!(song().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().pretty(5)
+        )));
+        assert_eq!(
+            output,
+            b"#TITLE:foo;\n#NOTES:dance-single:Beginner:1:1000\n0100,0010\n0001;\n"
+        );
+    }
+
+    #[test]
+    fn pretty_crlf() {
+        let mut output = Vec::new();
+
+        assert_ok!(song().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new()
+                .pretty(4)
+                .indentation("     ")
+                .line_ending(LineEnding::CrLf)
+        )));
+        assert_eq!(
+            output,
+            b"#TITLE:foo;\r\n#NOTES:\r\n     dance-single:\r\n     Beginner:\r\n     1:\r\n1000\n0100\r\n,\r\n0010\n0001\r\n;\r\n"
+        );
+    }
+
+    #[test]
+    fn pretty_top_level_tuple() {
+        let mut output = Vec::new();
+
+        assert_ok!(
+            ("NOTES", Difficulty::Challenge, 12).serialize(&mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().pretty(2)
+            ))
+        );
+        assert_eq!(output, b"#NOTES:\n   Challenge:\n   12;\n");
+    }
+
+    #[test]
+    fn pretty_not_applied_within_map() {
+        #[derive(Serialize)]
+        struct Bpms {
+            #[serde(rename = "BPMS")]
+            bpms: BTreeMap<&'static str, (u8, u8)>,
+        }
+        let mut bpms = BTreeMap::new();
+        bpms.insert("foo", (1, 2));
+        let mut output = Vec::new();
+
+        assert_ok!(Bpms { bpms }.serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().pretty(1)
+        )));
+        assert_eq!(output, b"#BPMS:\n   foo:1:2;\n");
+    }
+
+    #[test]
+    fn pretty_round_trip() {
+        let mut output = Vec::new();
+
+        assert_ok!(song().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().pretty(4)
+        )));
+        assert_ok_eq!(
+            Song::deserialize(&mut Deserializer::with_options(
+                output.as_slice(),
+                DeserializerOptions::new().trim_strings(true)
+            )),
+            song()
+        );
+    }
+
+    #[test]
+    fn pretty_strings_keep_whitespace_by_default() {
+        let mut output = Vec::new();
+
+        assert_ok!(
+            ("NOTES", "dance-single", 1).serialize(&mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().pretty(2)
+            ))
+        );
+        assert_ok_eq!(
+            crate::from_bytes::<(String, String, u64)>(&output),
+            ("NOTES".to_owned(), "\n   dance-single".to_owned(), 1)
+        );
+    }
+
+    #[test]
+    fn pretty_trim_strings_loses_surrounding_whitespace() {
+        let mut output = Vec::new();
+
+        assert_ok!(
+            ("NOTES", " dance-single ", 1).serialize(&mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().pretty(2)
+            ))
+        );
+        assert_ok_eq!(
+            <(String, String, u64)>::deserialize(&mut Deserializer::with_options(
+                output.as_slice(),
+                DeserializerOptions::new().trim_strings(true)
+            )),
+            ("NOTES".to_owned(), "dance-single".to_owned(), 1)
+        );
+    }
+
+    #[test]
+    fn struct_containing_seq_of_parameter_lists_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Struct {
+            x: Vec<Vec<String>>,
+        }
+        let value = Struct {
+            x: vec![
+                vec!["a".to_owned(), "b".to_owned()],
+                vec!["c".to_owned()],
+                vec![],
+                vec!["d".to_owned()],
+            ],
+        };
+        let mut output = Vec::new();
+
+        assert_ok!(value.serialize(&mut Serializer::new(&mut output)));
+
+        assert_eq!(output, b"#x:a:b;\nc;\n#x;\n#x:d;\n");
+        assert_ok_eq!(
+            Struct::deserialize(&mut Deserializer::new(output.as_slice())),
+            value
+        );
+    }
+
+    #[test]
+    fn seq_of_parameter_lists_round_trip() {
+        let value = vec![vec![1u32, 2], vec![3]];
+        let mut output = Vec::new();
+
+        assert_ok!(value.serialize(&mut Serializer::new(&mut output)));
+
+        assert_eq!(output, b"#1:2;\n3;\n");
+        assert_ok_eq!(
+            Vec::<Vec<u32>>::deserialize(&mut Deserializer::new(output.as_slice())),
+            value
+        );
+    }
+
+    #[test]
+    fn seq_of_string_parameter_lists_round_trip() {
+        let value = vec![
+            vec!["a".to_owned(), "b".to_owned()],
+            vec!["c".to_owned()],
+            vec![],
+            vec!["".to_owned(), "d".to_owned()],
+        ];
+        let mut output = Vec::new();
+
+        assert_ok!(value.serialize(&mut Serializer::new(&mut output)));
+
+        assert_eq!(output, b"#a:b;\nc;\n#;\n#:d;\n");
+        assert_ok_eq!(
+            Vec::<Vec<String>>::deserialize(&mut Deserializer::new(output.as_slice())),
+            value
+        );
+    }
+
+    #[test]
+    fn seq_of_empty_parameter_list_round_trip() {
+        let value: Vec<Vec<String>> = vec![vec![]];
+        let mut output = Vec::new();
+
+        assert_ok!(value.serialize(&mut Serializer::new(&mut output)));
+
+        assert_eq!(output, b"#;\n");
+        assert_ok_eq!(
+            Vec::<Vec<String>>::deserialize(&mut Deserializer::new(output.as_slice())),
+            value
+        );
+    }
+
+    #[test]
+    fn uppercase_field_names() {
+        #[derive(Serialize)]
+        struct Song {
+            title: &'static str,
+            sample_start: f64,
+            bpms: Vec<(u64, f64)>,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Song {
+            title: "foo",
+            sample_start: 12.5,
+            bpms: vec![(0, 120.0)],
+        }
+        .serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().uppercase_field_names(true)
+        )));
+        assert_eq!(
+            output,
+            b"#TITLE:foo;\n#SAMPLE_START:12.5;\n#BPMS:0:120.0;\n"
+        );
+    }
+
+    #[test]
+    fn uppercase_field_names_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            title: String,
+            sample_start: f64,
+        }
+        let song = Song {
+            title: "foo".to_owned(),
+            sample_start: 12.5,
+        };
+        let mut output = Vec::new();
+
+        assert_ok!(song.serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().uppercase_field_names(true)
+        )));
+        assert_ok_eq!(
+            Song::deserialize(&mut Deserializer::with_options(
+                output.as_slice(),
+                DeserializerOptions::new().case_insensitive(true)
+            )),
+            song
+        );
+    }
+
+    #[test]
+    fn empty_as_none_tuple() {
+        let mut output = Vec::new();
+
+        assert_ok!((
+            "NOTES",
+            None::<String>,
+            Some(""),
+            Some("  "),
+            Some(42),
+            None::<u64>
+        )
+            .serialize(&mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().empty_as_none(true)
+            )));
+        assert_eq!(output, b"#NOTES::\\0:\\0  :42:;\n");
+    }
+
+    #[test]
+    fn empty_as_none_disabled() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            ("NOTES", None::<String>).serialize(&mut Serializer::new(&mut output)),
+            Error::UnsupportedType
+        );
+    }
+
+    #[test]
+    fn empty_as_none_nested_option() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            ("NOTES", Some(None::<String>)).serialize(&mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().empty_as_none(true)
+            )),
+            Error::UnsupportedType
+        );
+    }
+
+    #[test]
+    fn empty_as_none_tuple_some_unit() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            ("NOTES", Some(())).serialize(&mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().empty_as_none(true)
+            )),
+            Error::UnsupportedType
+        );
+    }
+
+    #[test]
+    fn empty_as_none_seq() {
+        #[derive(Serialize)]
+        struct Song {
+            bgchanges: Vec<Option<&'static str>>,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Song {
+            bgchanges: vec![Some("foo"), None, Some("")],
+        }
+        .serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().empty_as_none(true)
+        )));
+        assert_eq!(output, b"#bgchanges:foo;\n#bgchanges:;\n#bgchanges:\\0;\n");
+    }
+
+    #[test]
+    fn empty_as_none_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            notes: (String, Option<String>, Option<String>, Option<u64>),
+            bgchanges: Vec<Option<String>>,
+            attacks: Vec<Option<()>>,
+        }
+        let song = Song {
+            notes: (
+                "dance-single".to_owned(),
+                None,
+                Some(String::new()),
+                Some(1),
+            ),
+            bgchanges: vec![Some("foo".to_owned()), None, Some(" ".to_owned())],
+            attacks: vec![None, Some(())],
+        };
+        let mut output = Vec::new();
+
+        assert_ok!(song.serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().empty_as_none(true)
+        )));
+        assert_ok_eq!(
+            Song::deserialize(&mut Deserializer::with_options(
+                output.as_slice(),
+                DeserializerOptions::new().empty_as_none(true)
+            )),
+            song
+        );
+    }
+
+    #[test]
+    fn empty_as_none_root_seq() {
+        let mut output = Vec::new();
+
+        assert_ok!(vec![Some("foo"), None, Some(""), Some(" ")].serialize(
+            &mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().empty_as_none(true)
+            )
+        ));
+        assert_eq!(output, b"#foo;\n#;\n#\\0;\n#\\0 ;\n");
+    }
+
+    #[test]
+    fn empty_as_none_root_seq_round_trip() {
+        let values = vec![
+            Some("foo".to_owned()),
+            None,
+            Some(String::new()),
+            Some("  ".to_owned()),
+        ];
+        let mut output = Vec::new();
+
+        assert_ok!(values.serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().empty_as_none(true)
+        )));
+        assert_ok_eq!(
+            Vec::<Option<String>>::deserialize(&mut Deserializer::with_options(
+                output.as_slice(),
+                DeserializerOptions::new().empty_as_none(true)
+            )),
+            values
+        );
+    }
+
+    #[test]
+    fn struct_containing_nested_seq() {
+        #[derive(Serialize)]
+        struct Song {
+            title: String,
+            bgchanges: Vec<Vec<(f64, String)>>,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Song {
+            title: "foo".to_owned(),
+            bgchanges: vec![
+                vec![(0.0, "bar".to_owned()), (4.0, "baz".to_owned())],
+                vec![(8.0, "qux".to_owned())],
+            ],
+        }
+        .serialize(&mut Serializer::new(&mut output)));
+        assert_eq!(
+            output,
+            b"#title:foo;\n#bgchanges:0.0:bar;\n4.0:baz;\n#bgchanges:8.0:qux;\n"
+        );
+    }
+
+    #[test]
+    fn nested_seq_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            bgchanges: Vec<Vec<(f64, String)>>,
+            attacks: Vec<Vec<Vec<String>>>,
+        }
+        let song = Song {
+            bgchanges: vec![
+                vec![(0.0, "bar".to_owned()), (4.0, "baz".to_owned())],
+                vec![],
+                vec![(8.0, "qux".to_owned())],
+            ],
+            attacks: vec![vec![
+                vec!["a".to_owned(), "b".to_owned()],
+                vec!["c".to_owned()],
+            ]],
+        };
+
+        for multi_line in [true, false] {
+            let mut output = Vec::new();
+            assert_ok!(song.serialize(&mut Serializer::with_options(
+                &mut output,
+                SerializerOptions::new().multi_line(multi_line)
+            )));
+            assert_ok_eq!(
+                Song::deserialize(&mut Deserializer::new(output.as_slice())),
+                song
+            );
+        }
+    }
+
+    #[test]
+    fn map_seq_value_round_trip() {
+        let mut map = BTreeMap::new();
+        map.insert("bar".to_owned(), vec![(1, 2), (3, 4)]);
+        map.insert("baz".to_owned(), vec![]);
+        map.insert("foo".to_owned(), vec![(5, 6)]);
+        let mut output = Vec::new();
+
+        assert_ok!(map.serialize(&mut Serializer::new(&mut output)));
+        assert_ok_eq!(
+            BTreeMap::<String, Vec<(u64, u64)>>::deserialize(&mut Deserializer::new(
+                output.as_slice()
+            )),
+            map
+        );
+    }
+
+    #[test]
+    fn flatten_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Header {
+            title: String,
+            artist: String,
+        }
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(flatten)]
+            header: Header,
+            offset: f64,
+            bpms: Vec<(f64, f64)>,
+        }
+        let song = Song {
+            header: Header {
+                title: "foo".to_owned(),
+                artist: "bar".to_owned(),
+            },
+            offset: -0.5,
+            bpms: vec![(0.0, 120.0), (4.0, 60.0)],
+        };
+        let mut output = Vec::new();
+
+        assert_ok!(song.serialize(&mut Serializer::new(&mut output)));
+        assert_ok_eq!(
+            Song::deserialize(&mut Deserializer::new(output.as_slice())),
+            song
+        );
+    }
+
+    #[test]
+    fn flatten_map_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            title: String,
+            #[serde(flatten)]
+            other: BTreeMap<String, String>,
+        }
+        let mut other = BTreeMap::new();
+        other.insert("bgchanges".to_owned(), "0.000=bar=1.000".to_owned());
+        other.insert("offset".to_owned(), "a:b".to_owned());
+        let song = Song {
+            title: "foo".to_owned(),
+            other,
+        };
+        let mut output = Vec::new();
+
+        assert_ok!(song.serialize(&mut Serializer::new(&mut output)));
+        assert_eq!(
+            output,
+            b"#title:foo;\n#bgchanges:0.000=bar=1.000;\n#offset:a\\:b;\n"
+        );
+        assert_ok_eq!(
+            Song::deserialize(&mut Deserializer::new(output.as_slice())),
+            song
+        );
+    }
+
+    #[test]
+    fn flatten_non_string_fields_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Header {
+            offset: f64,
+            gap: i64,
+            selectable: bool,
+        }
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            title: String,
+            #[serde(flatten)]
+            header: Header,
+        }
+        let song = Song {
+            title: "foo".to_owned(),
+            header: Header {
+                offset: 1.5,
+                gap: -100,
+                selectable: false,
+            },
+        };
+        let mut output = Vec::new();
+
+        assert_ok!(song.serialize(&mut Serializer::new(&mut output)));
+        assert_ok_eq!(
+            Song::deserialize(&mut Deserializer::new(output.as_slice())),
+            song
+        );
+    }
+
+    #[test]
+    fn internally_tagged_enum_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(tag = "type")]
+        enum Chart {
+            A { x: u32 },
+            B { y: String },
+        }
+
+        for (chart, expected) in [
+            (Chart::A { x: 5 }, b"#type:A;\n#x:5;\n".as_slice()),
+            (
+                Chart::B {
+                    y: "foo".to_owned(),
+                },
+                b"#type:B;\n#y:foo;\n".as_slice(),
+            ),
+        ] {
+            let mut output = Vec::new();
+            assert_ok!(chart.serialize(&mut Serializer::new(&mut output)));
+            assert_eq!(output, expected);
+            assert_ok_eq!(
+                Chart::deserialize(&mut Deserializer::new(output.as_slice())),
+                chart
+            );
+        }
+    }
+
+    #[test]
+    fn untagged_enum_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(untagged)]
+        enum Timing {
+            Bpms { bpms: String },
+            Display { displaybpm: f64 },
+        }
+
+        for timing in [
+            Timing::Bpms {
+                bpms: "0.000=120.000".to_owned(),
+            },
+            Timing::Display { displaybpm: 120.5 },
+        ] {
+            let mut output = Vec::new();
+            assert_ok!(timing.serialize(&mut Serializer::new(&mut output)));
+            assert_ok_eq!(
+                Timing::deserialize(&mut Deserializer::new(output.as_slice())),
+                timing
+            );
+        }
+    }
+
+    #[test]
+    fn seq_of_untagged_enum_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(untagged)]
+        enum Parameter {
+            Number(u32),
+            Text(String),
+        }
+        let value = vec![
+            Parameter::Number(5),
+            Parameter::Text("foo".to_owned()),
+            Parameter::Text("a:b".to_owned()),
+        ];
+        let mut output = Vec::new();
+
+        assert_ok!(value.serialize(&mut Serializer::new(&mut output)));
+        assert_eq!(output, b"#5;\n#foo;\n#a\\:b;\n");
+        assert_ok_eq!(
+            Vec::<Parameter>::deserialize(&mut Deserializer::new(output.as_slice())),
+            value
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Timing {
+        offset: f64,
+        bpms: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct TimedSong {
+        title: String,
+        timing: Timing,
+        artist: String,
+    }
+
+    fn timed_song() -> TimedSong {
+        TimedSong {
+            title: "foo".to_owned(),
+            timing: Timing {
+                offset: -0.5,
+                bpms: vec!["0=120".to_owned(), "4=60".to_owned()],
+            },
+            artist: "bar".to_owned(),
+        }
+    }
+
+    #[test]
+    fn nested_structs() {
+        let mut output = Vec::new();
+
+        assert_ok!(timed_song().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().nested_structs('.')
+        )));
+        assert_eq!(
+            output,
+            b"#title:foo;\n#timing.offset:-0.5;\n#timing.bpms:0=120;\n#timing.bpms:4=60;\n#artist:bar;\n"
+        );
+    }
+
+    #[test]
+    fn nested_structs_disabled() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            timed_song().serialize(&mut Serializer::new(&mut output)),
+            Error::UnsupportedType
+        );
+    }
+
+    #[test]
+    fn nested_structs_uppercase_field_names() {
+        let mut output = Vec::new();
+
+        assert_ok!(timed_song().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new()
+                .uppercase_field_names(true)
+                .nested_structs('.')
+        )));
+        assert_eq!(
+            output,
+            b"#TITLE:foo;\n#TIMING.OFFSET:-0.5;\n#TIMING.BPMS:0=120;\n#TIMING.BPMS:4=60;\n#ARTIST:bar;\n"
+        );
+    }
+
+    #[test]
+    fn nested_structs_escaped_separator() {
+        #[derive(Serialize)]
+        struct Outer {
+            inner: Inner,
+        }
+        #[derive(Serialize)]
+        struct Inner {
+            foo: u64,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Outer {
+            inner: Inner { foo: 1 }
+        }
+        .serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().nested_structs(':')
+        )));
+        assert_eq!(output, b"#inner\\:foo:1;\n");
+    }
+
+    #[test]
+    fn nested_structs_deeply() {
+        #[derive(Serialize)]
+        struct Inner {
+            foo: u64,
+        }
+        #[derive(Serialize)]
+        struct Middle {
+            inner: Inner,
+            bar: u64,
+        }
+        #[derive(Serialize)]
+        struct Outer {
+            middle: Middle,
+            baz: u64,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Outer {
+            middle: Middle {
+                inner: Inner { foo: 1 },
+                bar: 2,
+            },
+            baz: 3,
+        }
+        .serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new().nested_structs('.')
+        )));
+        assert_eq!(output, b"#middle.inner.foo:1;\n#middle.bar:2;\n#baz:3;\n");
+    }
+
+    #[test]
+    fn nested_structs_round_trip() {
+        let mut output = Vec::new();
+
+        assert_ok!(timed_song().serialize(&mut Serializer::with_options(
+            &mut output,
+            SerializerOptions::new()
+                .uppercase_field_names(true)
+                .nested_structs('.')
+        )));
+        assert_ok_eq!(
+            TimedSong::deserialize(&mut Deserializer::with_options(
+                output.as_slice(),
+                DeserializerOptions::new()
+                    .case_insensitive(true)
+                    .nested_structs('.')
+            )),
+            timed_song()
+        );
+    }
 }
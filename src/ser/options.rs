@@ -0,0 +1,511 @@
+use crate::Encoding;
+
+/// The sequence of bytes used to end a line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// A single line feed (`\n`). This is the default.
+    Lf,
+    /// A carriage return followed by a line feed (`\r\n`), as written by Windows tools.
+    CrLf,
+}
+
+impl LineEnding {
+    pub(in crate::ser) fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+impl Default for LineEnding {
+    fn default() -> Self {
+        LineEnding::Lf
+    }
+}
+
+/// Options controlling how a [`Serializer`] writes its output.
+///
+/// # Example
+/// ```
+/// use msd::{
+///     ser::{LineEnding, SerializerOptions},
+///     Encoding,
+/// };
+/// use serde::Serialize;
+///
+/// let options = SerializerOptions::new()
+///     .encoding(Encoding::Windows1252)
+///     .line_ending(LineEnding::CrLf);
+/// let mut serialized = Vec::new();
+/// let mut serializer = msd::Serializer::with_options(&mut serialized, options);
+/// "Café".serialize(&mut serializer).unwrap();
+///
+/// assert_eq!(serialized, b"#Caf\xe9;\r\n");
+/// ```
+///
+/// [`Serializer`]: crate::Serializer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializerOptions {
+    encoding: Encoding,
+    line_ending: LineEnding,
+    indentation: String,
+    blank_lines: bool,
+    multi_line: bool,
+    pretty: Option<usize>,
+    uppercase_field_names: bool,
+    empty_as_none: bool,
+    // `None` indicates nested structs are not supported.
+    nested_structs: Option<char>,
+}
+
+impl SerializerOptions {
+    /// Creates a new set of options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`Encoding`] used to write text.
+    ///
+    /// Defaults to [`Encoding::Utf8`]. Serializing a character that cannot be represented in the
+    /// encoding results in an
+    /// [`UnrepresentableCharacter`](crate::ser::Error::UnrepresentableCharacter) error.
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets the [`LineEnding`] written at the end of each line.
+    ///
+    /// Defaults to [`LineEnding::Lf`].
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Sets the indentation written before each map key.
+    ///
+    /// Defaults to three spaces. Only used when [`multi_line`](Self::multi_line) is enabled.
+    pub fn indentation<S>(mut self, indentation: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.indentation = indentation.into();
+        self
+    }
+
+    /// Sets whether an empty line is written between consecutive tags.
+    ///
+    /// A comment written before a tag is kept directly above it, with the empty line preceding
+    /// the comment instead. Defaults to `false`.
+    pub fn blank_lines(mut self, blank_lines: bool) -> Self {
+        self.blank_lines = blank_lines;
+        self
+    }
+
+    /// Sets whether a tag containing multiple parameter lists, such as a map, writes each list on
+    /// its own line.
+    ///
+    /// When disabled, the entire tag is written on a single line. Defaults to `true`.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::ser::SerializerOptions;
+    /// use serde::Serialize;
+    /// use serde_derive::Serialize;
+    /// use std::collections::BTreeMap;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Song {
+    ///     #[serde(rename = "BPMS")]
+    ///     bpms: BTreeMap<u64, f64>,
+    /// }
+    ///
+    /// let mut bpms = BTreeMap::new();
+    /// bpms.insert(0, 120.0);
+    /// bpms.insert(16, 140.0);
+    /// let mut serialized = Vec::new();
+    /// let mut serializer = msd::Serializer::with_options(
+    ///     &mut serialized,
+    ///     SerializerOptions::new().multi_line(false),
+    /// );
+    /// Song { bpms }.serialize(&mut serializer).unwrap();
+    ///
+    /// assert_eq!(serialized, b"#BPMS:0:120.0;16:140.0;\n");
+    /// ```
+    pub fn multi_line(mut self, multi_line: bool) -> Self {
+        self.multi_line = multi_line;
+        self
+    }
+
+    /// Enables pretty-printing of tags with at least `min_parameters` parameters, such as
+    /// StepMania's `#NOTES` tags.
+    ///
+    /// Each parameter of such a tag is written on its own line, preceded by the
+    /// [`indentation`](Self::indentation). A parameter containing a sequence, such as a chart's
+    /// measures, is written as a block beginning on the following line with each element
+    /// separated by a line containing only a `,`.
+    ///
+    /// Numbers, `bool`s and enum variants ignore the added whitespace when read back, but
+    /// pretty-printed strings do not round-trip. With the default deserializer options, each
+    /// string is read with the preceding line ending and indentation. Enabling
+    /// [`DeserializerOptions::trim_strings()`] removes these, but also removes any whitespace
+    /// surrounding the original string. Disabled by default.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::ser::SerializerOptions;
+    /// use serde::Serialize;
+    ///
+    /// let notes = (
+    ///     "NOTES",
+    ///     "dance-single",
+    ///     "",
+    ///     "Beginner",
+    ///     1,
+    ///     vec!["1000\n0100\n0010\n0001", "0000\n0000\n0000\n0000"],
+    /// );
+    /// let mut serialized = Vec::new();
+    /// let mut serializer = msd::Serializer::with_options(
+    ///     &mut serialized,
+    ///     SerializerOptions::new().indentation("     ").pretty(5),
+    /// );
+    /// notes.serialize(&mut serializer).unwrap();
+    ///
+    /// assert_eq!(
+    ///     serialized,
+    ///     b"#NOTES:
+    ///      dance-single:
+    ///      :
+    ///      Beginner:
+    ///      1:
+    /// 1000
+    /// 0100
+    /// 0010
+    /// 0001
+    /// ,
+    /// 0000
+    /// 0000
+    /// 0000
+    /// 0000
+    /// ;
+    /// "
+    /// );
+    /// ```
+    ///
+    /// [`DeserializerOptions::trim_strings()`]: crate::de::DeserializerOptions::trim_strings()
+    pub fn pretty(mut self, min_parameters: usize) -> Self {
+        self.pretty = Some(min_parameters);
+        self
+    }
+
+    /// Sets whether struct field names are written in ASCII uppercase.
+    ///
+    /// This allows a field named `title` to be written as the tag `#TITLE` without renaming it.
+    /// Defaults to `false`.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::ser::SerializerOptions;
+    /// use serde::Serialize;
+    /// use serde_derive::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Song {
+    ///     title: String,
+    ///     sample_start: f64,
+    /// }
+    ///
+    /// let mut serialized = Vec::new();
+    /// let mut serializer = msd::Serializer::with_options(
+    ///     &mut serialized,
+    ///     SerializerOptions::new().uppercase_field_names(true),
+    /// );
+    /// Song {
+    ///     title: "foo".to_owned(),
+    ///     sample_start: 12.5,
+    /// }
+    /// .serialize(&mut serializer)
+    /// .unwrap();
+    ///
+    /// assert_eq!(serialized, b"#TITLE:foo;\n#SAMPLE_START:12.5;\n");
+    /// ```
+    pub fn uppercase_field_names(mut self, uppercase_field_names: bool) -> Self {
+        self.uppercase_field_names = uppercase_field_names;
+        self
+    }
+
+    /// Sets whether an `Option` within a tuple or sequence is written, with `None` written as an
+    /// empty parameter.
+    ///
+    /// Normally, serializing an `Option` as an element of a tuple or a sequence results in an
+    /// [`UnsupportedType`](crate::ser::Error::UnsupportedType) error. When enabled, a `Some`
+    /// containing an empty value is written with the escape sequence `\0` preceding it to
+    /// distinguish it from `None`. See [`DeserializerOptions::empty_as_none()`] for the matching
+    /// deserializer option.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::ser::SerializerOptions;
+    /// use serde::Serialize;
+    ///
+    /// let notes = (
+    ///     "NOTES",
+    ///     "dance-single",
+    ///     None::<String>,
+    ///     Some("Beginner"),
+    ///     Some(""),
+    /// );
+    /// let mut serialized = Vec::new();
+    /// let mut serializer = msd::Serializer::with_options(
+    ///     &mut serialized,
+    ///     SerializerOptions::new().empty_as_none(true),
+    /// );
+    /// notes.serialize(&mut serializer).unwrap();
+    ///
+    /// assert_eq!(serialized, b"#NOTES:dance-single::Beginner:\\0;\n");
+    /// ```
+    ///
+    /// [`DeserializerOptions::empty_as_none()`]: crate::de::DeserializerOptions::empty_as_none()
+    pub fn empty_as_none(mut self, empty_as_none: bool) -> Self {
+        self.empty_as_none = empty_as_none;
+        self
+    }
+
+    /// Allows structs nested within struct fields, written as tags named by the path to each of
+    /// their fields joined by `separator`.
+    ///
+    /// Normally, serializing a struct as the value of a struct field results in an
+    /// [`UnsupportedType`](crate::ser::Error::UnsupportedType) error. When enabled, each field of
+    /// such a struct is written as its own tag, such as `#timing.offset:...;` (with a `separator`
+    /// of `.`). Structs may be nested to any depth. See
+    /// [`DeserializerOptions::nested_structs()`] for the matching deserializer option.
+    ///
+    /// By default, nested structs are not supported.
+    ///
+    /// # Example
+    /// ```
+    /// use msd::ser::SerializerOptions;
+    /// use serde::Serialize;
+    /// use serde_derive::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Timing {
+    ///     offset: f64,
+    ///     bpms: String,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Song {
+    ///     title: String,
+    ///     timing: Timing,
+    /// }
+    ///
+    /// let mut serialized = Vec::new();
+    /// let mut serializer = msd::Serializer::with_options(
+    ///     &mut serialized,
+    ///     SerializerOptions::new()
+    ///         .uppercase_field_names(true)
+    ///         .nested_structs('.'),
+    /// );
+    /// Song {
+    ///     title: "foo".to_owned(),
+    ///     timing: Timing {
+    ///         offset: -0.5,
+    ///         bpms: "0.000=120.000".to_owned(),
+    ///     },
+    /// }
+    /// .serialize(&mut serializer)
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     serialized,
+    ///     b"#TITLE:foo;\n#TIMING.OFFSET:-0.5;\n#TIMING.BPMS:0.000=120.000;\n"
+    /// );
+    /// ```
+    ///
+    /// [`DeserializerOptions::nested_structs()`]: crate::de::DeserializerOptions::nested_structs()
+    pub fn nested_structs(mut self, separator: char) -> Self {
+        self.nested_structs = Some(separator);
+        self
+    }
+
+    pub(in crate::ser) fn get_encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    pub(in crate::ser) fn get_line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    pub(in crate::ser) fn get_indentation(&self) -> &str {
+        &self.indentation
+    }
+
+    pub(in crate::ser) fn get_blank_lines(&self) -> bool {
+        self.blank_lines
+    }
+
+    pub(in crate::ser) fn get_multi_line(&self) -> bool {
+        self.multi_line
+    }
+
+    pub(in crate::ser) fn get_pretty(&self) -> Option<usize> {
+        self.pretty
+    }
+
+    pub(in crate::ser) fn get_uppercase_field_names(&self) -> bool {
+        self.uppercase_field_names
+    }
+
+    pub(in crate::ser) fn get_empty_as_none(&self) -> bool {
+        self.empty_as_none
+    }
+
+    pub(in crate::ser) fn get_nested_structs(&self) -> Option<char> {
+        self.nested_structs
+    }
+}
+
+impl Default for SerializerOptions {
+    fn default() -> Self {
+        Self {
+            encoding: Encoding::default(),
+            line_ending: LineEnding::default(),
+            indentation: "   ".to_owned(),
+            blank_lines: false,
+            multi_line: true,
+            pretty: None,
+            uppercase_field_names: false,
+            empty_as_none: false,
+            nested_structs: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineEnding, SerializerOptions};
+    use crate::Encoding;
+    use claims::{assert_none, assert_some_eq};
+
+    #[test]
+    fn default_encoding() {
+        assert_eq!(SerializerOptions::new().get_encoding(), Encoding::Utf8);
+    }
+
+    #[test]
+    fn set_encoding() {
+        assert_eq!(
+            SerializerOptions::new()
+                .encoding(Encoding::ShiftJis)
+                .get_encoding(),
+            Encoding::ShiftJis
+        );
+    }
+
+    #[test]
+    fn default_line_ending() {
+        assert_eq!(SerializerOptions::new().get_line_ending(), LineEnding::Lf);
+    }
+
+    #[test]
+    fn set_line_ending() {
+        assert_eq!(
+            SerializerOptions::new()
+                .line_ending(LineEnding::CrLf)
+                .get_line_ending(),
+            LineEnding::CrLf
+        );
+    }
+
+    #[test]
+    fn line_ending_as_bytes() {
+        assert_eq!(LineEnding::Lf.as_bytes(), b"\n");
+        assert_eq!(LineEnding::CrLf.as_bytes(), b"\r\n");
+    }
+
+    #[test]
+    fn default_indentation() {
+        assert_eq!(SerializerOptions::new().get_indentation(), "   ");
+    }
+
+    #[test]
+    fn set_indentation() {
+        assert_eq!(
+            SerializerOptions::new().indentation("\t").get_indentation(),
+            "\t"
+        );
+    }
+
+    #[test]
+    fn default_blank_lines() {
+        assert!(!SerializerOptions::new().get_blank_lines());
+    }
+
+    #[test]
+    fn set_blank_lines() {
+        assert!(SerializerOptions::new().blank_lines(true).get_blank_lines());
+    }
+
+    #[test]
+    fn default_multi_line() {
+        assert!(SerializerOptions::new().get_multi_line());
+    }
+
+    #[test]
+    fn set_multi_line() {
+        assert!(!SerializerOptions::new().multi_line(false).get_multi_line());
+    }
+
+    #[test]
+    fn default_pretty() {
+        assert_none!(SerializerOptions::new().get_pretty());
+    }
+
+    #[test]
+    fn set_pretty() {
+        assert_some_eq!(SerializerOptions::new().pretty(5).get_pretty(), 5);
+    }
+
+    #[test]
+    fn default_uppercase_field_names() {
+        assert!(!SerializerOptions::new().get_uppercase_field_names());
+    }
+
+    #[test]
+    fn set_uppercase_field_names() {
+        assert!(SerializerOptions::new()
+            .uppercase_field_names(true)
+            .get_uppercase_field_names());
+    }
+
+    #[test]
+    fn default_empty_as_none() {
+        assert!(!SerializerOptions::new().get_empty_as_none());
+    }
+
+    #[test]
+    fn set_empty_as_none() {
+        assert!(SerializerOptions::new()
+            .empty_as_none(true)
+            .get_empty_as_none());
+    }
+
+    #[test]
+    fn default_nested_structs() {
+        assert_eq!(SerializerOptions::new().get_nested_structs(), None);
+    }
+
+    #[test]
+    fn set_nested_structs() {
+        assert_eq!(
+            SerializerOptions::new()
+                .nested_structs('.')
+                .get_nested_structs(),
+            Some('.')
+        );
+    }
+}
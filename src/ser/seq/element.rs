@@ -1,24 +1,74 @@
-use crate::ser::{map, r#struct, tuple, Error, Result, WriteExt};
-use serde::{ser, ser::Impossible, Serialize};
-use std::io::Write;
+use crate::{
+    ser::{delimited, lists, map, r#struct, tuple, Error, Result, WriteExt},
+    with,
+};
+use serde::{ser, Serialize};
+use std::mem;
 
 pub(super) struct Serializer<'a, W> {
     writer: &'a mut W,
+
+    // Whether the value is contained within a `Some`, and must therefore be distinguishable from
+    // `None`.
+    some: bool,
+    // Whether the previous element left its tag to be continued, along with that tag's escaped
+    // name. The name is then only written if this value doesn't continue the tag.
+    continuation: Option<(&'a mut bool, &'a [u8])>,
 }
 
 impl<'a, W> Serializer<'a, W> {
     pub(super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self {
+            writer,
+
+            some: false,
+            continuation: None,
+        }
+    }
+
+    pub(super) fn continuing(mut self, continued: &'a mut bool, tag_name: &'a [u8]) -> Self {
+        self.continuation = Some((continued, tag_name));
+        self
+    }
+}
+
+impl<'a, W> Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    fn open_tag(&mut self) -> Result<()> {
+        if let Some((continued, tag_name)) = &mut self.continuation {
+            if mem::take(*continued) {
+                self.writer.write_tag_name_unescaped(tag_name)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_parameter_unescaped(&mut self, parameter: &[u8]) -> Result<()> {
+        if self.some {
+            self.writer.write_some_parameter_unescaped(parameter)
+        } else {
+            self.writer.write_parameter_unescaped(parameter)
+        }
+    }
+
+    fn write_parameter_escaped(&mut self, parameter: &[u8]) -> Result<()> {
+        if self.some {
+            self.writer.write_some_parameter_escaped(parameter)
+        } else {
+            self.writer.write_parameter_escaped(parameter)
+        }
     }
 }
 
 impl<'a, W> ser::Serializer for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
-    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq = lists::Serializer<'a, W>;
     type SerializeTuple = tuple::Serializer<'a, W>;
     type SerializeTupleStruct = tuple::Serializer<'a, W>;
     type SerializeTupleVariant = tuple::Serializer<'a, W>;
@@ -26,7 +76,8 @@ where
     type SerializeStruct = r#struct::Serializer<'a, W>;
     type SerializeStructVariant = r#struct::Serializer<'a, W>;
 
-    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+    fn serialize_bool(mut self, v: bool) -> Result<Self::Ok> {
+        self.open_tag()?;
         if v {
             self.writer.write_parameter_unescaped(b"true")?;
         } else {
@@ -35,28 +86,32 @@ where
         self.writer.close_tag()
     }
 
-    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+    fn serialize_i8(mut self, v: i8) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = itoa::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+    fn serialize_i16(mut self, v: i16) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = itoa::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+    fn serialize_i32(mut self, v: i32) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = itoa::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+    fn serialize_i64(mut self, v: i64) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = itoa::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
@@ -64,35 +119,40 @@ where
     }
 
     #[cfg(has_i128)]
-    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+    fn serialize_i128(mut self, v: i128) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = itoa::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+    fn serialize_u8(mut self, v: u8) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = itoa::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+    fn serialize_u16(mut self, v: u16) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = itoa::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+    fn serialize_u32(mut self, v: u32) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = itoa::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+    fn serialize_u64(mut self, v: u64) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = itoa::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
@@ -100,87 +160,124 @@ where
     }
 
     #[cfg(has_i128)]
-    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+    fn serialize_u128(mut self, v: u128) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = itoa::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+    fn serialize_f32(mut self, v: f32) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = ryu::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+    fn serialize_f64(mut self, v: f64) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = ryu::Buffer::new();
         self.writer
             .write_parameter_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+    fn serialize_char(mut self, v: char) -> Result<Self::Ok> {
+        self.open_tag()?;
         let mut buffer = [0; 4];
         v.encode_utf8(&mut buffer);
-        self.writer
-            .write_parameter_escaped(&buffer[..v.len_utf8()])?;
+        self.write_parameter_escaped(&buffer[..v.len_utf8()])?;
 
         self.writer.close_tag()
     }
 
-    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
-        self.writer.write_parameter_escaped(v.as_bytes())?;
+    fn serialize_str(mut self, v: &str) -> Result<Self::Ok> {
+        self.open_tag()?;
+        self.write_parameter_escaped(v.as_bytes())?;
 
         self.writer.close_tag()
     }
 
-    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
-        self.writer.write_parameter_escaped(v)?;
+    fn serialize_bytes(mut self, v: &[u8]) -> Result<Self::Ok> {
+        self.open_tag()?;
+        self.write_parameter_escaped(v)?;
 
         self.writer.close_tag()
     }
 
-    fn serialize_none(self) -> Result<Self::Ok> {
-        Err(Error::UnsupportedType)
+    fn serialize_none(mut self) -> Result<Self::Ok> {
+        self.open_tag()?;
+        if self.some || !self.writer.option_elements() {
+            return Err(Error::UnsupportedType);
+        }
+        self.writer.write_parameter_unescaped(b"")?;
+
+        self.writer.close_tag()
     }
 
-    fn serialize_some<T>(self, _v: &T) -> Result<Self::Ok>
+    fn serialize_some<T>(mut self, v: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::UnsupportedType)
+        self.open_tag()?;
+        if self.some || !self.writer.option_elements() {
+            return Err(Error::UnsupportedType);
+        }
+        v.serialize(Self {
+            writer: self.writer,
+
+            some: true,
+            continuation: self.continuation,
+        })
     }
 
-    fn serialize_unit(self) -> Result<Self::Ok> {
+    fn serialize_unit(mut self) -> Result<Self::Ok> {
+        self.open_tag()?;
+        if self.some {
+            self.write_parameter_unescaped(b"")?;
+        }
+
         self.writer.close_tag()
     }
 
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+    fn serialize_unit_struct(mut self, _name: &'static str) -> Result<Self::Ok> {
+        self.open_tag()?;
+        if self.some {
+            self.write_parameter_unescaped(b"")?;
+        }
+
         self.writer.close_tag()
     }
 
     fn serialize_unit_variant(
-        self,
+        mut self,
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
     ) -> Result<Self::Ok> {
+        self.open_tag()?;
         self.writer.write_parameter_escaped(variant.as_bytes())?;
 
         self.writer.close_tag()
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(mut self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if let Some(separator) = with::separator(name) {
+            self.open_tag()?;
+            let parameter = delimited::serialize_list(value, separator)?;
+            self.write_parameter_unescaped(&parameter)?;
+            return self.writer.close_tag();
+        }
         value.serialize(self)
     }
 
     fn serialize_newtype_variant<T>(
-        self,
+        mut self,
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
@@ -189,55 +286,77 @@ where
     where
         T: ?Sized + Serialize,
     {
+        self.open_tag()?;
         self.writer.write_parameter_escaped(variant.as_bytes())?;
         value.serialize(self)
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Err(Error::UnsupportedType)
+        // An empty sequence is indistinguishable from `None`.
+        if self.some {
+            return Err(Error::UnsupportedType);
+        }
+        let serializer = lists::Serializer::new(self.writer);
+        Ok(match self.continuation {
+            Some((continued, tag_name)) => serializer.continuing(continued, tag_name),
+            None => serializer,
+        })
     }
 
-    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+    fn serialize_tuple(mut self, len: usize) -> Result<Self::SerializeTuple> {
+        self.open_tag()?;
+        self.writer.open_parameters(len);
         Ok(tuple::Serializer::new(self.writer))
     }
 
     fn serialize_tuple_struct(
-        self,
+        mut self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
+        self.open_tag()?;
+        self.writer.open_parameters(len);
         Ok(tuple::Serializer::new(self.writer))
     }
 
     fn serialize_tuple_variant(
-        self,
+        mut self,
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
+        self.open_tag()?;
+        self.writer.open_parameters(len + 1);
         self.writer.write_parameter_escaped(variant.as_bytes())?;
         Ok(tuple::Serializer::new(self.writer))
     }
 
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        self.writer.write_parameter_unescaped(b"\n")?;
+    fn serialize_map(mut self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.open_tag()?;
+        self.writer.open_lists()?;
         Ok(map::Serializer::new(self.writer))
     }
 
-    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        self.writer.write_parameter_unescaped(b"")?;
+    fn serialize_struct(
+        mut self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct> {
+        self.open_tag()?;
+        self.write_parameter_unescaped(b"")?;
         self.writer.close_tag()?;
         Ok(r#struct::Serializer::new(self.writer))
     }
 
     fn serialize_struct_variant(
-        self,
+        mut self,
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
+        self.open_tag()?;
         self.writer.write_parameter_escaped(variant.as_bytes())?;
         self.writer.close_tag()?;
         Ok(r#struct::Serializer::new(self.writer))
@@ -968,9 +1087,35 @@ mod tests {
     fn seq() {
         let mut output = Vec::new();
 
-        assert_err_eq!(
-            Vec::<()>::new().serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
-        );
+        assert_ok!(vec![("foo", "bar"), ("baz", "qux")].serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b":foo:bar;\nbaz:qux;\n");
+    }
+
+    #[test]
+    fn seq_of_seqs() {
+        let mut output = Vec::new();
+
+        assert_ok!(vec![vec![1, 2], vec![3]].serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b":1:2;\n3;\n");
+    }
+
+    #[test]
+    fn seq_empty() {
+        let mut output = Vec::new();
+
+        assert_ok!(Vec::<(u64, u64)>::new().serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b";\n");
+    }
+
+    #[test]
+    fn seq_of_values() {
+        let mut output = Vec::new();
+
+        assert_ok!(vec![1, 2].serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b":1:2;\n");
     }
 }
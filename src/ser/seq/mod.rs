@@ -4,12 +4,14 @@ mod element;
 
 use crate::ser::{Error, Result, WriteExt};
 use serde::{ser::SerializeSeq, Serialize};
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
 
     escaped_field_name: Vec<u8>,
+    // Whether the previous element was written as a single parameter list, in which case the next
+    // element may continue its tag rather than opening a new one.
+    continued: bool,
 }
 
 impl<'a, W> Serializer<'a, W> {
@@ -18,13 +20,14 @@ impl<'a, W> Serializer<'a, W> {
             writer,
 
             escaped_field_name,
+            continued: false,
         }
     }
 }
 
 impl<'a, W> SerializeSeq for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -33,9 +36,14 @@ where
     where
         T: ?Sized + Serialize,
     {
-        self.writer
-            .write_tag_name_unescaped(&self.escaped_field_name)?;
-        value.serialize(element::Serializer::new(self.writer))
+        if !self.continued {
+            self.writer
+                .write_tag_name_unescaped(&self.escaped_field_name)?;
+        }
+        value.serialize(
+            element::Serializer::new(self.writer)
+                .continuing(&mut self.continued, &self.escaped_field_name),
+        )
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -81,4 +89,53 @@ mod tests {
 
         assert_eq!(output, b"#foo:42;\n#foo:bar;\n#foo;\n");
     }
+
+    #[test]
+    fn parameter_lists() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output, b"foo".to_vec());
+
+        assert_ok!(serializer.serialize_element(&vec!["bar", "baz"]));
+        assert_ok!(serializer.serialize_element(&vec!["qux"]));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b"#foo:bar:baz;\nqux;\n");
+    }
+
+    #[test]
+    fn parameter_lists_empty() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output, b"foo".to_vec());
+
+        assert_ok!(serializer.serialize_element(&vec!["bar"]));
+        assert_ok!(serializer.serialize_element(&Vec::<&str>::new()));
+        assert_ok!(serializer.serialize_element(&vec!["baz"]));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b"#foo:bar;\n#foo;\n#foo:baz;\n");
+    }
+
+    #[test]
+    fn parameter_list_followed_by_value() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output, b"foo".to_vec());
+
+        assert_ok!(serializer.serialize_element(&vec!["bar"]));
+        assert_ok!(serializer.serialize_element(&42));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b"#foo:bar;\n#foo:42;\n");
+    }
+
+    #[test]
+    fn parameter_list_followed_by_lists() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output, b"foo".to_vec());
+
+        assert_ok!(serializer.serialize_element(&vec!["bar"]));
+        assert_ok!(serializer.serialize_element(&vec![vec!["baz"], vec!["qux"]]));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b"#foo:bar;\n#foo:baz;\nqux;\n");
+    }
 }
@@ -1,24 +1,61 @@
-use crate::ser::{r#struct, tuple, Error, Result, WriteExt};
+use crate::ser::{commented, r#struct, tuple, Error, Result, WriteExt};
 use serde::{ser, ser::Impossible, Serialize};
-use std::io::Write;
 
 pub(super) struct Serializer<'a, W> {
     writer: &'a mut W,
+
+    // Whether the value is contained within a `Some`, and must therefore be distinguishable from
+    // `None`.
+    some: bool,
+    // Whether a sequence continues the previous element's tag, along with the flag set if this
+    // value can be continued in turn.
+    continuation: Option<(bool, &'a mut bool)>,
 }
 
 impl<'a, W> Serializer<'a, W> {
     pub(super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self {
+            writer,
+
+            some: false,
+            continuation: None,
+        }
+    }
+
+    pub(super) fn continuing(mut self, continuing: bool, continued: &'a mut bool) -> Self {
+        self.continuation = Some((continuing, continued));
+        self
+    }
+}
+
+impl<'a, W> Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    fn write_tag_name_unescaped(&mut self, tag_name: &[u8]) -> Result<()> {
+        if self.some {
+            self.writer.write_some_tag_name_unescaped(tag_name)
+        } else {
+            self.writer.write_tag_name_unescaped(tag_name)
+        }
+    }
+
+    fn write_tag_name_escaped(&mut self, tag_name: &[u8]) -> Result<()> {
+        if self.some {
+            self.writer.write_some_tag_name_escaped(tag_name)
+        } else {
+            self.writer.write_tag_name_escaped(tag_name)
+        }
     }
 }
 
 impl<'a, W> ser::Serializer for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
-    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq = tuple::tag::Serializer<'a, W>;
     type SerializeTuple = tuple::tag::Serializer<'a, W>;
     type SerializeTupleStruct = tuple::tag::Serializer<'a, W>;
     type SerializeTupleVariant = tuple::Serializer<'a, W>;
@@ -26,141 +63,141 @@ where
     type SerializeStruct = Impossible<Self::Ok, Self::Error>;
     type SerializeStructVariant = r#struct::Serializer<'a, W>;
 
-    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+    fn serialize_bool(mut self, v: bool) -> Result<Self::Ok> {
         if v {
-            self.writer.write_tag_name_unescaped(b"true")?;
+            self.write_tag_name_unescaped(b"true")?;
         } else {
-            self.writer.write_tag_name_unescaped(b"false")?;
+            self.write_tag_name_unescaped(b"false")?;
         }
         self.writer.close_tag()
     }
 
-    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+    fn serialize_i8(mut self, v: i8) -> Result<Self::Ok> {
         let mut buffer = itoa::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+    fn serialize_i16(mut self, v: i16) -> Result<Self::Ok> {
         let mut buffer = itoa::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+    fn serialize_i32(mut self, v: i32) -> Result<Self::Ok> {
         let mut buffer = itoa::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+    fn serialize_i64(mut self, v: i64) -> Result<Self::Ok> {
         let mut buffer = itoa::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     #[cfg(has_i128)]
-    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+    fn serialize_i128(mut self, v: i128) -> Result<Self::Ok> {
         let mut buffer = itoa::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+    fn serialize_u8(mut self, v: u8) -> Result<Self::Ok> {
         let mut buffer = itoa::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+    fn serialize_u16(mut self, v: u16) -> Result<Self::Ok> {
         let mut buffer = itoa::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+    fn serialize_u32(mut self, v: u32) -> Result<Self::Ok> {
         let mut buffer = itoa::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+    fn serialize_u64(mut self, v: u64) -> Result<Self::Ok> {
         let mut buffer = itoa::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
     #[cfg(has_i128)]
-    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+    fn serialize_u128(mut self, v: u128) -> Result<Self::Ok> {
         let mut buffer = itoa::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+    fn serialize_f32(mut self, v: f32) -> Result<Self::Ok> {
         let mut buffer = ryu::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+    fn serialize_f64(mut self, v: f64) -> Result<Self::Ok> {
         let mut buffer = ryu::Buffer::new();
-        self.writer
-            .write_tag_name_unescaped(buffer.format(v).as_bytes())?;
+        self.write_tag_name_unescaped(buffer.format(v).as_bytes())?;
         self.writer.close_tag()
     }
 
-    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+    fn serialize_char(mut self, v: char) -> Result<Self::Ok> {
         let mut buffer = [0; 4];
         v.encode_utf8(&mut buffer);
-        self.writer
-            .write_tag_name_escaped(&buffer[..v.len_utf8()])?;
+        self.write_tag_name_escaped(&buffer[..v.len_utf8()])?;
 
         self.writer.close_tag()
     }
 
-    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
-        self.writer.write_tag_name_escaped(v.as_bytes())?;
+    fn serialize_str(mut self, v: &str) -> Result<Self::Ok> {
+        self.write_tag_name_escaped(v.as_bytes())?;
 
         self.writer.close_tag()
     }
 
-    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
-        self.writer.write_tag_name_escaped(v)?;
+    fn serialize_bytes(mut self, v: &[u8]) -> Result<Self::Ok> {
+        self.write_tag_name_escaped(v)?;
 
         self.writer.close_tag()
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
-        Err(Error::UnsupportedType)
+        if self.some || !self.writer.option_elements() {
+            return Err(Error::UnsupportedType);
+        }
+        self.writer.write_tag_name_unescaped(b"")?;
+
+        self.writer.close_tag()
     }
 
-    fn serialize_some<T>(self, _v: &T) -> Result<Self::Ok>
+    fn serialize_some<T>(self, v: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::UnsupportedType)
+        if self.some || !self.writer.option_elements() {
+            return Err(Error::UnsupportedType);
+        }
+        v.serialize(Self {
+            writer: self.writer,
+
+            some: true,
+            continuation: self.continuation,
+        })
     }
 
-    fn serialize_unit(self) -> Result<Self::Ok> {
-        self.writer.write_tag_name_unescaped(b"")?;
+    fn serialize_unit(mut self) -> Result<Self::Ok> {
+        self.write_tag_name_unescaped(b"")?;
 
         self.writer.close_tag()
     }
 
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
-        self.writer.write_tag_name_unescaped(b"")?;
+    fn serialize_unit_struct(mut self, _name: &'static str) -> Result<Self::Ok> {
+        self.write_tag_name_unescaped(b"")?;
 
         self.writer.close_tag()
     }
@@ -176,10 +213,14 @@ where
         self.writer.close_tag()
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if name == commented::NAME {
+            self.writer
+                .write_comment(value.serialize(commented::CommentSerializer)?.as_bytes())?;
+        }
         value.serialize(self)
     }
 
@@ -198,18 +239,28 @@ where
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Err(Error::UnsupportedType)
+        // An empty sequence is indistinguishable from `None`.
+        if self.some {
+            return Err(Error::UnsupportedType);
+        }
+        let serializer = tuple::tag::Serializer::new(self.writer);
+        Ok(match self.continuation {
+            Some((continuing, continued)) => serializer.continuing(continuing, continued),
+            None => serializer,
+        })
     }
 
-    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.writer.open_parameters(len.saturating_sub(1));
         Ok(tuple::tag::Serializer::new(self.writer))
     }
 
     fn serialize_tuple_struct(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
+        self.writer.open_parameters(len.saturating_sub(1));
         Ok(tuple::tag::Serializer::new(self.writer))
     }
 
@@ -218,8 +269,9 @@ where
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
+        self.writer.open_parameters(len);
         self.writer.write_tag_name_escaped(variant.as_bytes())?;
         Ok(tuple::Serializer::new(self.writer))
     }
@@ -249,7 +301,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::Serializer;
-    use crate::ser::Error;
+    use crate::ser::{Commented, Error};
     use claims::{assert_err_eq, assert_ok};
     use serde::{
         ser::{SerializeTupleStruct, SerializeTupleVariant},
@@ -680,6 +732,15 @@ mod tests {
         assert_eq!(output, b"#42;\n");
     }
 
+    #[test]
+    fn commented() {
+        let mut output = Vec::new();
+
+        assert_ok!(Commented::new("foo", 42).serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"// foo\n#42;\n");
+    }
+
     #[test]
     fn newtype_variant() {
         #[derive(Serialize)]
@@ -872,10 +933,22 @@ mod tests {
     fn seq() {
         let mut output = Vec::new();
 
-        assert_err_eq!(
-            Vec::<()>::new().serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
+        assert_ok!(vec![1, 2].serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"#1:2;\n");
+    }
+
+    #[test]
+    fn seq_continuing() {
+        let mut output = Vec::new();
+        let mut continued = false;
+
+        assert_ok!(
+            vec![1, 2].serialize(Serializer::new(&mut output).continuing(true, &mut continued))
         );
+
+        assert_eq!(output, b"1:2;\n");
+        assert!(continued);
     }
 
     #[test]
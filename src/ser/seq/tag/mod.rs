@@ -1,22 +1,30 @@
 mod element;
 
-use crate::ser::{Error, Result};
+use crate::ser::{Error, Result, WriteExt};
 use serde::{ser::SerializeSeq, Serialize};
-use std::io::Write;
+use std::mem;
 
 pub struct Serializer<'a, W> {
     writer: &'a mut W,
+
+    // Whether the previous element was written as a sequence, in which case a following sequence
+    // continues its tag as another parameter list.
+    continued: bool,
 }
 
 impl<'a, W> Serializer<'a, W> {
     pub(in super::super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self {
+            writer,
+
+            continued: false,
+        }
     }
 }
 
 impl<'a, W> SerializeSeq for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -25,7 +33,10 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(element::Serializer::new(self.writer))
+        let continuing = mem::take(&mut self.continued);
+        value.serialize(
+            element::Serializer::new(self.writer).continuing(continuing, &mut self.continued),
+        )
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -71,4 +82,18 @@ mod tests {
 
         assert_eq!(output, b"#42;\n#bar;\n#;\n");
     }
+
+    #[test]
+    fn parameter_lists() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.serialize_element(&vec![1, 2]));
+        assert_ok!(serializer.serialize_element(&vec![3]));
+        assert_ok!(serializer.serialize_element(&4));
+        assert_ok!(serializer.serialize_element(&vec![5]));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b"#1:2;\n3;\n#4;\n#5;\n");
+    }
 }
@@ -1,6 +1,8 @@
-use crate::ser::{map, seq, tuple, Error, Result, WriteExt};
+use crate::{
+    ser::{commented, delimited, map, seq, tuple, Error, Result, WriteExt},
+    with,
+};
 use serde::{ser, ser::Impossible, Serialize};
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
@@ -19,7 +21,7 @@ impl<'a, W> Serializer<'a, W> {
 
 impl<'a, W> ser::Serializer for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -28,7 +30,7 @@ where
     type SerializeTupleStruct = tuple::Serializer<'a, W>;
     type SerializeTupleVariant = tuple::Serializer<'a, W>;
     type SerializeMap = map::Serializer<'a, W>;
-    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = super::Serializer<'a, W>;
     type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
@@ -213,10 +215,20 @@ where
         self.writer.close_tag()
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if name == commented::NAME {
+            self.writer
+                .write_comment(value.serialize(commented::CommentSerializer)?.as_bytes())?;
+        } else if let Some(separator) = with::separator(name) {
+            let parameter = delimited::serialize_list(value, separator)?;
+            self.writer
+                .write_tag_name_unescaped(&self.escaped_field_name)?;
+            self.writer.write_parameter_unescaped(&parameter)?;
+            return self.writer.close_tag();
+        }
         value.serialize(self)
     }
 
@@ -239,7 +251,8 @@ where
         Ok(seq::Serializer::new(self.writer, self.escaped_field_name))
     }
 
-    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.writer.open_parameters(len);
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
         Ok(tuple::Serializer::new(self.writer))
@@ -248,8 +261,9 @@ where
     fn serialize_tuple_struct(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
+        self.writer.open_parameters(len);
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
         Ok(tuple::Serializer::new(self.writer))
@@ -260,8 +274,9 @@ where
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
+        self.writer.open_parameters(len + 1);
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
         self.writer.write_parameter_escaped(variant.as_bytes())?;
@@ -271,12 +286,16 @@ where
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
         self.writer
             .write_tag_name_unescaped(&self.escaped_field_name)?;
-        self.writer.write_parameter_unescaped(b"\n")?;
+        self.writer.open_lists()?;
         Ok(map::Serializer::new(self.writer))
     }
 
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        Err(Error::UnsupportedType)
+        let prefix = self
+            .writer
+            .nested_prefix(self.escaped_field_name)
+            .ok_or(Error::UnsupportedType)?;
+        Ok(super::Serializer::nested(self.writer, prefix))
     }
 
     fn serialize_struct_variant(
@@ -293,7 +312,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::Serializer;
-    use crate::ser::Error;
+    use crate::ser::{Commented, Error};
     use claims::{assert_err_eq, assert_ok};
     use serde::{
         ser::{SerializeMap, SerializeTupleStruct, SerializeTupleVariant},
@@ -722,6 +741,17 @@ mod tests {
         assert_eq!(output, b"#foo:42;\n");
     }
 
+    #[test]
+    fn commented() {
+        let mut output = Vec::new();
+
+        assert_ok!(
+            Commented::new("bar", 42).serialize(Serializer::new(&mut output, b"foo".to_vec()))
+        );
+
+        assert_eq!(output, b"// bar\n#foo:42;\n");
+    }
+
     #[test]
     fn newtype_variant() {
         #[derive(Serialize)]
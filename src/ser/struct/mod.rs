@@ -1,25 +1,43 @@
 pub(super) mod field;
 
-use crate::ser::{Error, Escaped, Result};
+use crate::ser::{Error, Result, WriteExt};
 use serde::{
     ser::{SerializeStruct, SerializeStructVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub struct Serializer<'a, W> {
     writer: &'a mut W,
+
+    // Prepended to each escaped field name. Only nonempty for nested structs.
+    prefix: Vec<u8>,
 }
 
 impl<'a, W> Serializer<'a, W> {
     pub(super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self::nested(writer, Vec::new())
+    }
+
+    // Creates a `Serializer` for a struct nested within a field, whose tag names all begin with
+    // the already escaped `prefix`.
+    fn nested(writer: &'a mut W, prefix: Vec<u8>) -> Self {
+        Self { writer, prefix }
     }
 }
 
+// Returns the escaped tag name of the field `key`, beginning with `prefix`.
+fn field_name<W>(writer: &W, prefix: &[u8], key: &str) -> Vec<u8>
+where
+    W: WriteExt,
+{
+    let mut field_name = prefix.to_vec();
+    field_name.extend(writer.field_name(key));
+    field_name
+}
+
 impl<'a, W> SerializeStruct for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -28,10 +46,8 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(field::Serializer::new(
-            self.writer,
-            Escaped::new(key.as_bytes()).collect::<Vec<_>>(),
-        ))
+        let field_name = field_name(self.writer, &self.prefix, key);
+        value.serialize(field::Serializer::new(self.writer, field_name))
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -41,7 +57,7 @@ where
 
 impl<'a, W> SerializeStructVariant for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -50,10 +66,8 @@ where
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(field::Serializer::new(
-            self.writer,
-            Escaped::new(key.as_bytes()).collect::<Vec<_>>(),
-        ))
+        let field_name = field_name(self.writer, &self.prefix, key);
+        value.serialize(field::Serializer::new(self.writer, field_name))
     }
 
     fn end(self) -> Result<Self::Ok> {
@@ -0,0 +1,159 @@
+use crate::Encoding;
+use std::{fmt, io, io::Write, str};
+
+// A character that cannot be represented in the encoding being written.
+//
+// This is carried through the `io::Error` returned by `Transcoder`, and recovered as a
+// `ser::Error::UnrepresentableCharacter`.
+#[derive(Debug)]
+pub(super) struct Unrepresentable {
+    pub(super) character: char,
+    pub(super) encoding: Encoding,
+}
+
+impl fmt::Display for Unrepresentable {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "`{}` cannot be represented in {:?}",
+            self.character, self.encoding
+        )
+    }
+}
+
+impl std::error::Error for Unrepresentable {}
+
+/// Converts UTF-8 written to it into another [`Encoding`] before passing it on to the wrapped
+/// writer.
+///
+/// Bytes that are not valid UTF-8 are passed on unchanged, so that byte strings are written
+/// verbatim.
+#[derive(Debug)]
+pub struct Transcoder<W> {
+    writer: W,
+    encoding: Encoding,
+}
+
+impl<W> Transcoder<W> {
+    pub(super) fn new(writer: W, encoding: Encoding) -> Self {
+        Self { writer, encoding }
+    }
+
+    fn transcode(&self, s: &str, transcoded: &mut Vec<u8>) -> io::Result<()> {
+        for c in s.chars() {
+            if c.is_ascii() {
+                transcoded.push(c as u8);
+                continue;
+            }
+            let mut buffer = [0; 4];
+            let encoded = self.encoding.encode_char(c, &mut buffer).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    Unrepresentable {
+                        character: c,
+                        encoding: self.encoding,
+                    },
+                )
+            })?;
+            transcoded.extend_from_slice(encoded);
+        }
+        Ok(())
+    }
+}
+
+impl<W> Write for Transcoder<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.encoding == Encoding::Utf8 {
+            return self.writer.write(buf);
+        }
+
+        let mut transcoded = Vec::with_capacity(buf.len());
+        let mut bytes = buf;
+        loop {
+            match str::from_utf8(bytes) {
+                Ok(valid) => {
+                    self.transcode(valid, &mut transcoded)?;
+                    break;
+                }
+                Err(error) => {
+                    let (valid, invalid) = bytes.split_at(error.valid_up_to());
+                    // SAFETY: `valid_up_to()` guarantees these bytes are valid UTF-8.
+                    self.transcode(unsafe { str::from_utf8_unchecked(valid) }, &mut transcoded)?;
+                    let invalid_len = error.error_len().unwrap_or(invalid.len());
+                    transcoded.extend_from_slice(&invalid[..invalid_len]);
+                    bytes = &invalid[invalid_len..];
+                }
+            }
+        }
+        self.writer.write_all(&transcoded)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transcoder;
+    use crate::Encoding;
+    use claims::{assert_err, assert_ok};
+    use std::{io, io::Write};
+
+    #[test]
+    fn utf8() {
+        let mut output = Vec::new();
+
+        assert_ok!(Transcoder::new(&mut output, Encoding::Utf8).write_all("東方".as_bytes()));
+        assert_eq!(output, "東方".as_bytes());
+    }
+
+    #[test]
+    fn shift_jis() {
+        let mut output = Vec::new();
+
+        assert_ok!(Transcoder::new(&mut output, Encoding::ShiftJis).write_all("#東方;".as_bytes()));
+        assert_eq!(output, b"#\x93\x8c\x95\xfb;");
+    }
+
+    #[test]
+    fn windows_1252() {
+        let mut output = Vec::new();
+
+        assert_ok!(Transcoder::new(&mut output, Encoding::Windows1252).write_all("Café".as_bytes()));
+        assert_eq!(output, b"Caf\xe9");
+    }
+
+    #[test]
+    fn trailing_backslash() {
+        let mut output = Vec::new();
+
+        assert_ok!(Transcoder::new(&mut output, Encoding::ShiftJis).write_all("表\\;".as_bytes()));
+        assert_eq!(output, b"\x95\\\\;");
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let mut output = Vec::new();
+
+        assert_ok!(Transcoder::new(&mut output, Encoding::ShiftJis).write_all(b"a\xffb\xe6"));
+        assert_eq!(output, b"a\xffb\xe6");
+    }
+
+    #[test]
+    fn unrepresentable() {
+        let mut output = Vec::new();
+
+        assert_eq!(
+            assert_err!(
+                Transcoder::new(&mut output, Encoding::Windows1252).write_all("東".as_bytes())
+            )
+            .kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -1,24 +1,55 @@
-use crate::ser::{tuple, Error, Result, WriteExt};
+use crate::{
+    ser::{delimited, tuple, Error, Result, WriteExt},
+    with,
+};
 use serde::{ser, ser::Impossible, Serialize};
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
+
+    // Whether the value is contained within a `Some`, and must therefore be distinguishable from
+    // `None`.
+    some: bool,
 }
 
 impl<'a, W> Serializer<'a, W> {
     pub(in super::super) fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self {
+            writer,
+
+            some: false,
+        }
+    }
+}
+
+impl<'a, W> Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    fn write_parameter_unescaped(&mut self, parameter: &[u8]) -> Result<()> {
+        if self.some {
+            self.writer.write_some_parameter_unescaped(parameter)
+        } else {
+            self.writer.write_parameter_unescaped(parameter)
+        }
+    }
+
+    fn write_parameter_escaped(&mut self, parameter: &[u8]) -> Result<()> {
+        if self.some {
+            self.writer.write_some_parameter_escaped(parameter)
+        } else {
+            self.writer.write_parameter_escaped(parameter)
+        }
     }
 }
 
 impl<'a, W> ser::Serializer for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
-    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq = tuple::separated::Serializer<'a, W>;
     type SerializeTuple = tuple::nested::Serializer<'a, W>;
     type SerializeTupleStruct = tuple::nested::Serializer<'a, W>;
     type SerializeTupleVariant = tuple::nested::Serializer<'a, W>;
@@ -108,36 +139,53 @@ where
             .write_parameter_unescaped(buffer.format(v).as_bytes())
     }
 
-    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+    fn serialize_char(mut self, v: char) -> Result<Self::Ok> {
         let mut buffer = [0; 4];
         v.encode_utf8(&mut buffer);
-        self.writer.write_parameter_escaped(&buffer[..v.len_utf8()])
+        self.write_parameter_escaped(&buffer[..v.len_utf8()])
     }
 
-    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
-        self.writer.write_parameter_escaped(v.as_bytes())
+    fn serialize_str(mut self, v: &str) -> Result<Self::Ok> {
+        self.write_parameter_escaped(v.as_bytes())
     }
 
-    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
-        self.writer.write_parameter_escaped(v)
+    fn serialize_bytes(mut self, v: &[u8]) -> Result<Self::Ok> {
+        self.write_parameter_escaped(v)
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
-        Err(Error::UnsupportedType)
+        if self.some || !self.writer.option_elements() {
+            return Err(Error::UnsupportedType);
+        }
+        self.writer.write_parameter_unescaped(b"")
     }
 
-    fn serialize_some<T>(self, _v: &T) -> Result<Self::Ok>
+    fn serialize_some<T>(self, v: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::UnsupportedType)
+        if self.some || !self.writer.option_elements() {
+            return Err(Error::UnsupportedType);
+        }
+        v.serialize(Self {
+            writer: self.writer,
+
+            some: true,
+        })
     }
 
     fn serialize_unit(self) -> Result<Self::Ok> {
+        // A unit has no parameter, so it cannot be distinguished from a following `None`.
+        if self.some {
+            return Err(Error::UnsupportedType);
+        }
         Ok(())
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        if self.some {
+            return Err(Error::UnsupportedType);
+        }
         Ok(())
     }
 
@@ -150,10 +198,14 @@ where
         self.writer.write_parameter_escaped(variant.as_bytes())
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(mut self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
         T: ?Sized + Serialize,
     {
+        if let Some(separator) = with::separator(name) {
+            let parameter = delimited::serialize_list(value, separator)?;
+            return self.write_parameter_unescaped(&parameter);
+        }
         value.serialize(self)
     }
 
@@ -172,7 +224,8 @@ where
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Err(Error::UnsupportedType)
+        self.writer.open_separated()?;
+        Ok(tuple::separated::Serializer::new(self.writer))
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
@@ -818,10 +871,18 @@ mod tests {
     fn seq() {
         let mut output = Vec::new();
 
-        assert_err_eq!(
-            Vec::<()>::new().serialize(Serializer::new(&mut output)),
-            Error::UnsupportedType
-        );
+        assert_ok!(vec!["0000", "1000"].serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b":0000,1000");
+    }
+
+    #[test]
+    fn seq_empty() {
+        let mut output = Vec::new();
+
+        assert_ok!(Vec::<()>::new().serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b":");
     }
 
     #[test]
@@ -3,12 +3,13 @@ pub(super) mod key;
 pub(super) mod nested;
 pub(super) mod tag;
 
+mod separated;
+
 use crate::ser::{Error, Result, WriteExt};
 use serde::{
     ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub struct Serializer<'a, W> {
     writer: &'a mut W,
@@ -22,7 +23,7 @@ impl<'a, W> Serializer<'a, W> {
 
 impl<'a, W> SerializeTuple for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -41,7 +42,7 @@ where
 
 impl<'a, W> SerializeTupleStruct for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -60,7 +61,7 @@ where
 
 impl<'a, W> SerializeTupleVariant for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -1,10 +1,9 @@
 use super::element;
-use crate::ser::{Error, Result};
+use crate::ser::{Error, Result, WriteExt};
 use serde::{
     ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
@@ -18,7 +17,7 @@ impl<'a, W> Serializer<'a, W> {
 
 impl<'a, W> SerializeTuple for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -37,7 +36,7 @@ where
 
 impl<'a, W> SerializeTupleStruct for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -56,7 +55,7 @@ where
 
 impl<'a, W> SerializeTupleVariant for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
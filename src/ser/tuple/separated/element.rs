@@ -0,0 +1,387 @@
+use crate::ser::{Error, Result, WriteExt};
+use serde::{ser, ser::Impossible, Serialize};
+
+// Serializes a single element of a separated sequence, returning whether it is blank.
+pub(super) struct Serializer<'a, W> {
+    writer: &'a mut W,
+}
+
+impl<'a, W> Serializer<'a, W> {
+    pub(super) fn new(writer: &'a mut W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<'a, W> Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    fn write_unescaped(self, value: &[u8]) -> Result<bool> {
+        self.writer.write_separated_unescaped(value)?;
+        Ok(value.iter().all(u8::is_ascii_whitespace))
+    }
+
+    fn write_escaped(self, value: &[u8]) -> Result<bool> {
+        self.writer.write_separated_escaped(value)?;
+        Ok(value.iter().all(u8::is_ascii_whitespace))
+    }
+}
+
+impl<'a, W> ser::Serializer for Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    type Ok = bool;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        if v {
+            self.write_unescaped(b"true")
+        } else {
+            self.write_unescaped(b"false")
+        }
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    #[cfg(has_i128)]
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    #[cfg(has_i128)]
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+        let mut buffer = itoa::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        let mut buffer = ryu::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        let mut buffer = ryu::Buffer::new();
+        self.write_unescaped(buffer.format(v).as_bytes())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        let mut buffer = [0; 4];
+        v.encode_utf8(&mut buffer);
+        self.write_escaped(&buffer[..v.len_utf8()])
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        self.write_escaped(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        self.write_escaped(v)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_some<T>(self, _v: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Ok(true)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Ok(true)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        self.write_escaped(variant.as_bytes())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::UnsupportedType)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Serializer;
+    use crate::ser::Error;
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use serde::Serialize;
+    use serde_bytes::Bytes;
+    use serde_derive::Serialize;
+
+    #[test]
+    fn bool() {
+        let mut output = Vec::new();
+
+        assert_ok!(true.serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"true");
+    }
+
+    #[test]
+    fn i64() {
+        let mut output = Vec::new();
+
+        assert_ok!((-42i64).serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"-42");
+    }
+
+    #[test]
+    fn u64() {
+        let mut output = Vec::new();
+
+        assert_ok!(42u64.serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"42");
+    }
+
+    #[test]
+    fn f64() {
+        let mut output = Vec::new();
+
+        assert_ok!(0.5f64.serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"0.5");
+    }
+
+    #[test]
+    fn char() {
+        let mut output = Vec::new();
+
+        assert_ok!(':'.serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"\\:");
+    }
+
+    #[test]
+    fn str() {
+        let mut output = Vec::new();
+
+        assert_ok_eq!("0000\n0100;".serialize(Serializer::new(&mut output)), false);
+
+        assert_eq!(output, b"0000\n0100\\;");
+    }
+
+    #[test]
+    fn str_separator() {
+        let mut output = Vec::new();
+
+        assert_ok!("0000,0100".serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"0000\\,0100");
+    }
+
+    #[test]
+    fn str_blank() {
+        let mut output = Vec::new();
+
+        assert_ok_eq!(" ".serialize(Serializer::new(&mut output)), true);
+
+        assert_eq!(output, b" ");
+    }
+
+    #[test]
+    fn bytes() {
+        let mut output = Vec::new();
+
+        assert_ok!(Bytes::new(b"foo#").serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"foo\\#");
+    }
+
+    #[test]
+    fn unit() {
+        let mut output = Vec::new();
+
+        assert_ok_eq!(().serialize(Serializer::new(&mut output)), true);
+
+        assert_eq!(output, b"");
+    }
+
+    #[test]
+    fn unit_variant() {
+        #[derive(Serialize)]
+        enum Unit {
+            Variant,
+        }
+        let mut output = Vec::new();
+
+        assert_ok!(Unit::Variant.serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"Variant");
+    }
+
+    #[test]
+    fn newtype_struct() {
+        #[derive(Serialize)]
+        struct Newtype(u32);
+        let mut output = Vec::new();
+
+        assert_ok!(Newtype(42).serialize(Serializer::new(&mut output)));
+
+        assert_eq!(output, b"42");
+    }
+
+    #[test]
+    fn newtype_variant() {
+        #[derive(Serialize)]
+        enum Newtype {
+            Variant(u32),
+        }
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            Newtype::Variant(42).serialize(Serializer::new(&mut output)),
+            Error::UnsupportedType
+        );
+    }
+
+    #[test]
+    fn seq() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            vec![1, 2].serialize(Serializer::new(&mut output)),
+            Error::UnsupportedType
+        );
+    }
+
+    #[test]
+    fn tuple() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            (1, 2).serialize(Serializer::new(&mut output)),
+            Error::UnsupportedType
+        );
+    }
+
+    #[test]
+    fn option() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            Some(42).serialize(Serializer::new(&mut output)),
+            Error::UnsupportedType
+        );
+    }
+}
@@ -0,0 +1,124 @@
+mod element;
+
+use crate::ser::{Error, Result, WriteExt};
+use serde::{ser::SerializeSeq, Serialize};
+
+// Serializes a sequence as a single parameter, with its elements separated by `,`.
+//
+// This is the layout StepMania uses for the measures of a chart's note data. For example,
+// `["0000", "1000"]` is written as `:0000,1000`, or as `:\n0000\n,\n1000\n` when pretty-printed.
+// A `,` within an element is escaped. As when deserializing delimited values, a final blank value
+// is not an element, so a trailing separator is written after a final element that is blank.
+pub struct Serializer<'a, W> {
+    writer: &'a mut W,
+
+    written_first: bool,
+    last_blank: bool,
+}
+
+impl<'a, W> Serializer<'a, W> {
+    pub(super) fn new(writer: &'a mut W) -> Self {
+        Self {
+            writer,
+
+            written_first: false,
+            last_blank: false,
+        }
+    }
+}
+
+impl<'a, W> SerializeSeq for Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.written_first {
+            self.writer.write_separator()?;
+        } else {
+            self.written_first = true;
+        }
+        self.last_blank = value.serialize(element::Serializer::new(self.writer))?;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        if self.last_blank {
+            self.writer.write_separator()?;
+        }
+        if self.written_first {
+            self.writer.close_separated()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Serializer;
+    use claims::assert_ok;
+    use serde::ser::SerializeSeq;
+
+    #[test]
+    fn empty() {
+        let mut output = Vec::new();
+        let serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b"");
+    }
+
+    #[test]
+    fn single_element() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.serialize_element("0000"));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b"0000");
+    }
+
+    #[test]
+    fn multiple_elements() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.serialize_element("0000"));
+        assert_ok!(serializer.serialize_element("1000"));
+        assert_ok!(serializer.serialize_element(&42));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b"0000,1000,42");
+    }
+
+    #[test]
+    fn trailing_blank() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.serialize_element("0000"));
+        assert_ok!(serializer.serialize_element(""));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b"0000,,");
+    }
+
+    #[test]
+    fn single_blank() {
+        let mut output = Vec::new();
+        let mut serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.serialize_element(""));
+        assert_ok!(serializer.end());
+
+        assert_eq!(output, b",");
+    }
+}
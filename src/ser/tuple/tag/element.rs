@@ -1,6 +1,5 @@
 use crate::ser::{tuple, Error, Result, WriteExt};
 use serde::{ser, ser::Impossible, Serialize};
-use std::io::Write;
 
 pub(in super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
@@ -14,7 +13,7 @@ impl<'a, W> Serializer<'a, W> {
 
 impl<'a, W> ser::Serializer for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
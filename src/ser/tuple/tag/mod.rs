@@ -2,17 +2,21 @@ pub(in super::super) mod nested;
 
 mod element;
 
-use crate::ser::{tuple, Error, Result, WriteExt};
+use crate::ser::{lists, tuple, Error, Result, WriteExt};
 use serde::{
-    ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
+    ser::{SerializeSeq, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub struct Serializer<'a, W> {
     writer: &'a mut W,
 
     written_first: bool,
+    // Whether a sequence continues the tag of the previous sequence, its first value beginning a
+    // new parameter list rather than naming a new tag.
+    continuing: bool,
+    // Set once a sequence is written, allowing the next sequence to continue its tag.
+    continued: Option<&'a mut bool>,
 }
 
 impl<'a, W> Serializer<'a, W> {
@@ -21,13 +25,58 @@ impl<'a, W> Serializer<'a, W> {
             writer,
 
             written_first: false,
+            continuing: false,
+            continued: None,
         }
     }
+
+    pub(in super::super) fn continuing(
+        mut self,
+        continuing: bool,
+        continued: &'a mut bool,
+    ) -> Self {
+        self.continuing = continuing;
+        self.continued = Some(continued);
+        self
+    }
+}
+
+impl<'a, W> SerializeSeq for Serializer<'a, W>
+where
+    W: WriteExt,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.written_first {
+            value.serialize(tuple::element::Serializer::new(self.writer))
+        } else if self.continuing {
+            self.written_first = true;
+            let mut list = lists::list::Serializer::new(self.writer, false, true);
+            SerializeSeq::serialize_element(&mut list, value)
+        } else {
+            self.written_first = true;
+            value.serialize(element::Serializer::new(self.writer))
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        if !self.written_first {
+            self.writer.write_tag_name_unescaped(b"")?;
+        } else if let Some(continued) = self.continued {
+            *continued = true;
+        }
+        self.writer.close_tag()
+    }
 }
 
 impl<'a, W> SerializeTuple for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -54,7 +103,7 @@ where
 
 impl<'a, W> SerializeTupleStruct for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -81,7 +130,7 @@ where
 
 impl<'a, W> SerializeTupleVariant for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -111,6 +160,50 @@ mod tests {
     use super::Serializer;
     use claims::assert_ok;
 
+    #[test]
+    fn serialize_seq_empty() {
+        use serde::ser::SerializeSeq;
+
+        let mut output = Vec::new();
+
+        let serializer = Serializer::new(&mut output);
+
+        assert_ok!(serializer.end());
+        assert_eq!(output, b"#;\n");
+    }
+
+    #[test]
+    fn serialize_seq_multiple() {
+        use serde::ser::SerializeSeq;
+
+        let mut output = Vec::new();
+        let mut continued = false;
+
+        let mut serializer = Serializer::new(&mut output).continuing(false, &mut continued);
+
+        assert_ok!(serializer.serialize_element(&42));
+        assert_ok!(serializer.serialize_element(&"foo"));
+        assert_ok!(serializer.end());
+        assert_eq!(output, b"#42:foo;\n");
+        assert!(continued);
+    }
+
+    #[test]
+    fn serialize_seq_continuing() {
+        use serde::ser::SerializeSeq;
+
+        let mut output = Vec::new();
+        let mut continued = false;
+
+        let mut serializer = Serializer::new(&mut output).continuing(true, &mut continued);
+
+        assert_ok!(serializer.serialize_element(&42));
+        assert_ok!(serializer.serialize_element(&"foo"));
+        assert_ok!(serializer.end());
+        assert_eq!(output, b"42:foo;\n");
+        assert!(continued);
+    }
+
     #[test]
     fn serialize_tuple_empty() {
         use serde::ser::SerializeTuple;
@@ -4,7 +4,6 @@ use serde::{
     ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant},
     Serialize,
 };
-use std::io::Write;
 
 pub(in super::super::super) struct Serializer<'a, W> {
     writer: &'a mut W,
@@ -24,7 +23,7 @@ impl<'a, W> Serializer<'a, W> {
 
 impl<'a, W> SerializeTuple for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -51,7 +50,7 @@ where
 
 impl<'a, W> SerializeTupleStruct for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -78,7 +77,7 @@ where
 
 impl<'a, W> SerializeTupleVariant for Serializer<'a, W>
 where
-    W: Write,
+    W: WriteExt,
 {
     type Ok = ();
     type Error = Error;
@@ -1,56 +1,236 @@
-use crate::ser::{Error, Escaped, Result};
+use crate::ser::{format::Format, Error, Escaped, Result};
 use std::io::Write;
 
 pub(super) trait WriteExt {
     fn write_tag_name_unescaped(&mut self, tag_name: &[u8]) -> Result<()>;
     fn write_tag_name_escaped(&mut self, tag_name: &[u8]) -> Result<()>;
+    fn write_some_tag_name_unescaped(&mut self, tag_name: &[u8]) -> Result<()>;
+    fn write_some_tag_name_escaped(&mut self, tag_name: &[u8]) -> Result<()>;
+    fn field_name(&self, key: &str) -> Vec<u8>;
+    fn nested_prefix(&self, field_name: Vec<u8>) -> Option<Vec<u8>>;
     fn write_parameter_unescaped(&mut self, parameter: &[u8]) -> Result<()>;
     fn write_parameter_escaped(&mut self, parameter: &[u8]) -> Result<()>;
+    fn option_elements(&self) -> bool;
+    fn write_some_parameter_unescaped(&mut self, parameter: &[u8]) -> Result<()>;
+    fn write_some_parameter_escaped(&mut self, parameter: &[u8]) -> Result<()>;
     fn write_key_unescaped(&mut self, value: &[u8]) -> Result<()>;
     fn write_key_escaped(&mut self, value: &[u8]) -> Result<()>;
+    fn write_list_head_unescaped(&mut self, value: &[u8]) -> Result<()>;
+    fn write_list_head_escaped(&mut self, value: &[u8]) -> Result<()>;
+    fn write_separated_unescaped(&mut self, value: &[u8]) -> Result<()>;
+    fn write_separated_escaped(&mut self, value: &[u8]) -> Result<()>;
+    fn write_separator(&mut self) -> Result<()>;
+    fn open_separated(&mut self) -> Result<()>;
+    fn close_separated(&mut self) -> Result<()>;
+    fn open_parameters(&mut self, len: usize);
     fn close_tag(&mut self) -> Result<()>;
+    fn open_lists(&mut self) -> Result<()>;
+    fn close_lists(&mut self) -> Result<()>;
+    fn write_comment(&mut self, comment: &[u8]) -> Result<()>;
+    fn write_line_ending(&mut self) -> Result<()>;
+    fn write_indentation(&mut self) -> Result<()>;
 }
 
 impl<W> WriteExt for W
 where
-    W: Write,
+    W: Write + Format,
 {
     fn write_tag_name_unescaped(&mut self, tag_name: &[u8]) -> Result<()> {
-        self.write_all(b"#").or(Err(Error::Io))?;
-        self.write_all(tag_name).or(Err(Error::Io))
+        if self.start_tag() {
+            self.write_line_ending()?;
+        }
+        self.write_all(b"#").map_err(Error::io)?;
+        self.write_all(tag_name).map_err(Error::io)
     }
 
     fn write_tag_name_escaped(&mut self, tag_name: &[u8]) -> Result<()> {
         self.write_tag_name_unescaped(&Escaped::new(tag_name).collect::<Vec<_>>())
     }
 
+    fn write_some_tag_name_unescaped(&mut self, tag_name: &[u8]) -> Result<()> {
+        if tag_name.iter().all(u8::is_ascii_whitespace) {
+            // Distinguish this value from `None` by marking it with `\0`.
+            self.write_tag_name_unescaped(&[b"\\0".as_slice(), tag_name].concat())
+        } else {
+            self.write_tag_name_unescaped(tag_name)
+        }
+    }
+
+    fn write_some_tag_name_escaped(&mut self, tag_name: &[u8]) -> Result<()> {
+        self.write_some_tag_name_unescaped(&Escaped::new(tag_name).collect::<Vec<_>>())
+    }
+
+    fn field_name(&self, key: &str) -> Vec<u8> {
+        if self.uppercase_field_names() {
+            Escaped::new(key.to_ascii_uppercase().as_bytes()).collect()
+        } else {
+            Escaped::new(key.as_bytes()).collect()
+        }
+    }
+
+    fn nested_prefix(&self, mut field_name: Vec<u8>) -> Option<Vec<u8>> {
+        let separator = self.nested_structs()?;
+        field_name.extend(Escaped::new(separator.encode_utf8(&mut [0; 4]).as_bytes()));
+        Some(field_name)
+    }
+
     fn write_parameter_unescaped(&mut self, parameter: &[u8]) -> Result<()> {
-        self.write_all(b":").or(Err(Error::Io))?;
-        self.write_all(parameter).or(Err(Error::Io))
+        self.write_all(b":").map_err(Error::io)?;
+        if self.pretty() {
+            self.write_line_ending()?;
+            self.write_indentation()?;
+        }
+        self.write_all(parameter).map_err(Error::io)
     }
 
     fn write_parameter_escaped(&mut self, parameter: &[u8]) -> Result<()> {
         self.write_parameter_unescaped(&Escaped::new(parameter).collect::<Vec<_>>())
     }
 
+    fn option_elements(&self) -> bool {
+        self.empty_as_none()
+    }
+
+    fn write_some_parameter_unescaped(&mut self, parameter: &[u8]) -> Result<()> {
+        if parameter.iter().all(u8::is_ascii_whitespace) {
+            // Distinguish this value from `None` by marking it with `\0`.
+            self.write_parameter_unescaped(&[b"\\0".as_slice(), parameter].concat())
+        } else {
+            self.write_parameter_unescaped(parameter)
+        }
+    }
+
+    fn write_some_parameter_escaped(&mut self, parameter: &[u8]) -> Result<()> {
+        self.write_some_parameter_unescaped(&Escaped::new(parameter).collect::<Vec<_>>())
+    }
+
     fn write_key_unescaped(&mut self, value: &[u8]) -> Result<()> {
-        self.write_all(b"   ").or(Err(Error::Io))?;
-        self.write_all(value).or(Err(Error::Io))
+        if self.multi_line() {
+            self.write_indentation()?;
+        }
+        self.write_all(value).map_err(Error::io)
     }
 
     fn write_key_escaped(&mut self, value: &[u8]) -> Result<()> {
         self.write_key_unescaped(&Escaped::new(value).collect::<Vec<_>>())
     }
 
+    fn write_list_head_unescaped(&mut self, value: &[u8]) -> Result<()> {
+        self.write_all(value).map_err(Error::io)
+    }
+
+    fn write_list_head_escaped(&mut self, value: &[u8]) -> Result<()> {
+        self.write_list_head_unescaped(&Escaped::new(value).collect::<Vec<_>>())
+    }
+
+    fn write_separated_unescaped(&mut self, value: &[u8]) -> Result<()> {
+        self.write_all(value).map_err(Error::io)
+    }
+
+    fn write_separated_escaped(&mut self, value: &[u8]) -> Result<()> {
+        self.write_separated_unescaped(
+            &Escaped::new(value)
+                .with_separators(b",")
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn write_separator(&mut self) -> Result<()> {
+        if self.pretty() {
+            self.write_line_ending()?;
+            self.write_all(b",").map_err(Error::io)?;
+            self.write_line_ending()
+        } else {
+            self.write_all(b",").map_err(Error::io)
+        }
+    }
+
+    fn open_separated(&mut self) -> Result<()> {
+        self.write_all(b":").map_err(Error::io)?;
+        if self.pretty() {
+            self.write_line_ending()?;
+        }
+        Ok(())
+    }
+
+    fn close_separated(&mut self) -> Result<()> {
+        if self.pretty() {
+            self.write_line_ending()?;
+        }
+        Ok(())
+    }
+
+    fn open_parameters(&mut self, len: usize) {
+        self.enter_parameters(len);
+    }
+
     fn close_tag(&mut self) -> Result<()> {
-        self.write_all(b";\n").or(Err(Error::Io))
+        self.exit_parameters();
+        self.write_all(b";").map_err(Error::io)?;
+        if self.ends_line() {
+            self.write_line_ending()?;
+        }
+        Ok(())
+    }
+
+    fn open_lists(&mut self) -> Result<()> {
+        self.enter_lists();
+        self.write_all(b":").map_err(Error::io)?;
+        if self.multi_line() {
+            self.write_line_ending()?;
+        }
+        Ok(())
+    }
+
+    fn close_lists(&mut self) -> Result<()> {
+        if self.exit_lists() {
+            self.write_line_ending()?;
+        }
+        Ok(())
+    }
+
+    fn write_comment(&mut self, comment: &[u8]) -> Result<()> {
+        if self.start_comment() {
+            self.write_line_ending()?;
+        }
+        // Lines may end in `\r\n`, `\r` or `\n`, each of which is replaced by the line ending in use.
+        let mut remaining = comment;
+        loop {
+            let end = remaining
+                .iter()
+                .position(|&byte| matches!(byte, b'\r' | b'\n'))
+                .unwrap_or(remaining.len());
+            let line = &remaining[..end];
+            self.write_all(b"//").map_err(Error::io)?;
+            if !line.is_empty() {
+                self.write_all(b" ").map_err(Error::io)?;
+                self.write_all(line).map_err(Error::io)?;
+            }
+            self.write_line_ending()?;
+            remaining = match &remaining[end..] {
+                [b'\r', b'\n', rest @ ..] | [_, rest @ ..] => rest,
+                [] => return Ok(()),
+            };
+        }
+    }
+
+    fn write_line_ending(&mut self) -> Result<()> {
+        let line_ending = self.line_ending();
+        self.write_all(line_ending).map_err(Error::io)
+    }
+
+    fn write_indentation(&mut self) -> Result<()> {
+        let indentation = self.indentation().to_vec();
+        self.write_all(&indentation).map_err(Error::io)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::WriteExt;
-    use claims::{assert_err, assert_ok};
+    use crate::ser::format::{Format, Formatter};
+    use crate::ser::{Error, SerializerOptions};
+    use claims::{assert_err, assert_none, assert_ok, assert_some_eq};
     use std::{io, io::Write};
 
     #[test]
@@ -89,6 +269,79 @@ mod tests {
         assert_eq!(output, b"#fo\\#o");
     }
 
+    #[test]
+    fn write_some_tag_name_unescaped_regular() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_some_tag_name_unescaped(b"foo"));
+
+        assert_eq!(output, b"#foo");
+    }
+
+    #[test]
+    fn write_some_tag_name_unescaped_empty() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_some_tag_name_unescaped(b""));
+
+        assert_eq!(output, b"#\\0");
+    }
+
+    #[test]
+    fn write_some_tag_name_unescaped_whitespace() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_some_tag_name_unescaped(b" \n"));
+
+        assert_eq!(output, b"#\\0 \n");
+    }
+
+    #[test]
+    fn write_some_tag_name_escaped_escapes() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_some_tag_name_escaped(b"fo#o"));
+
+        assert_eq!(output, b"#fo\\#o");
+    }
+
+    #[test]
+    fn write_some_tag_name_escaped_empty() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_some_tag_name_escaped(b""));
+
+        assert_eq!(output, b"#\\0");
+    }
+
+    #[test]
+    fn field_name_regular() {
+        let output = Vec::new();
+
+        assert_eq!(output.field_name("foo"), b"foo");
+    }
+
+    #[test]
+    fn field_name_escapes() {
+        let output = Vec::new();
+
+        assert_eq!(output.field_name("fo#o"), b"fo\\#o");
+    }
+
+    #[test]
+    fn nested_prefix_disabled() {
+        let output = Vec::new();
+
+        assert_none!(output.nested_prefix(b"foo".to_vec()));
+    }
+
+    #[test]
+    fn nested_prefix_escapes_separator() {
+        let output = Formatter::new(Vec::new(), SerializerOptions::new().nested_structs(':'));
+
+        assert_some_eq!(output.nested_prefix(b"foo".to_vec()), b"foo\\:");
+    }
+
     #[test]
     fn write_parameter_unescaped_regular() {
         let mut output = Vec::new();
@@ -125,6 +378,56 @@ mod tests {
         assert_eq!(output, b":fo\\#o");
     }
 
+    #[test]
+    fn option_elements_disabled() {
+        assert!(!Vec::new().option_elements());
+    }
+
+    #[test]
+    fn write_some_parameter_unescaped_regular() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_some_parameter_unescaped(b"foo"));
+
+        assert_eq!(output, b":foo");
+    }
+
+    #[test]
+    fn write_some_parameter_unescaped_empty() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_some_parameter_unescaped(b""));
+
+        assert_eq!(output, b":\\0");
+    }
+
+    #[test]
+    fn write_some_parameter_unescaped_whitespace() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_some_parameter_unescaped(b" \n"));
+
+        assert_eq!(output, b":\\0 \n");
+    }
+
+    #[test]
+    fn write_some_parameter_escaped_escapes() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_some_parameter_escaped(b"fo#o"));
+
+        assert_eq!(output, b":fo\\#o");
+    }
+
+    #[test]
+    fn write_some_parameter_escaped_empty() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_some_parameter_escaped(b""));
+
+        assert_eq!(output, b":\\0");
+    }
+
     #[test]
     fn write_key_unescaped_escapes() {
         let mut output = Vec::new();
@@ -152,6 +455,42 @@ mod tests {
         assert_eq!(output, b"   fo\\#o");
     }
 
+    #[test]
+    fn write_list_head_unescaped_regular() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_list_head_unescaped(b"foo"));
+
+        assert_eq!(output, b"foo");
+    }
+
+    #[test]
+    fn write_list_head_unescaped_escapes() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_list_head_unescaped(b"fo#o"));
+
+        assert_eq!(output, b"fo#o");
+    }
+
+    #[test]
+    fn write_list_head_escaped_regular() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_list_head_escaped(b"foo"));
+
+        assert_eq!(output, b"foo");
+    }
+
+    #[test]
+    fn write_list_head_escaped_escapes() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_list_head_escaped(b"fo#o"));
+
+        assert_eq!(output, b"fo\\#o");
+    }
+
     #[test]
     fn close_tag() {
         let mut output = Vec::new();
@@ -161,6 +500,78 @@ mod tests {
         assert_eq!(output, b";\n");
     }
 
+    #[test]
+    fn write_comment() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_comment(b"foo"));
+
+        assert_eq!(output, b"// foo\n");
+    }
+
+    #[test]
+    fn write_comment_multiple_lines() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_comment(b"foo\n\nbar"));
+
+        assert_eq!(output, b"// foo\n//\n// bar\n");
+    }
+
+    #[test]
+    fn write_comment_carriage_returns() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_comment(b"foo\r\nbar\rbaz\n\r\nqux"));
+
+        assert_eq!(output, b"// foo\n// bar\n// baz\n//\n// qux\n");
+    }
+
+    #[test]
+    fn write_separated_escaped() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_separated_escaped(b"fo#o,"));
+
+        assert_eq!(output, b"fo\\#o\\,");
+    }
+
+    #[test]
+    fn write_separator() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.write_separator());
+
+        assert_eq!(output, b",");
+    }
+
+    #[test]
+    fn open_separated() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.open_separated());
+
+        assert_eq!(output, b":");
+    }
+
+    #[test]
+    fn open_lists() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.open_lists());
+
+        assert_eq!(output, b":\n");
+    }
+
+    #[test]
+    fn close_lists() {
+        let mut output = Vec::new();
+
+        assert_ok!(output.close_lists());
+
+        assert_eq!(output, b"");
+    }
+
     struct FailingWriter;
 
     impl Write for FailingWriter {
@@ -173,6 +584,8 @@ mod tests {
         }
     }
 
+    impl Format for FailingWriter {}
+
     #[test]
     fn write_tag_name_unescaped_failure() {
         let mut output = FailingWriter;
@@ -215,10 +628,41 @@ mod tests {
         assert_err!(output.write_key_escaped(b"foo"));
     }
 
+    #[test]
+    fn write_list_head_unescaped_failure() {
+        let mut output = FailingWriter;
+
+        assert_err!(output.write_list_head_unescaped(b"foo"));
+    }
+
+    #[test]
+    fn write_list_head_escaped_failure() {
+        let mut output = FailingWriter;
+
+        assert_err!(output.write_list_head_escaped(b"foo"));
+    }
+
     #[test]
     fn close_tag_failure() {
         let mut output = FailingWriter;
 
         assert_err!(output.close_tag());
     }
+
+    #[test]
+    fn write_comment_failure() {
+        let mut output = FailingWriter;
+
+        assert_err!(output.write_comment(b"foo"));
+    }
+
+    #[test]
+    fn failure_preserves_io_error() {
+        let mut output = FailingWriter;
+
+        match assert_err!(output.close_tag()) {
+            Error::Io(error) => assert_eq!(error.to_string(), "failed"),
+            error => panic!("unexpected error {:?}", error),
+        }
+    }
 }
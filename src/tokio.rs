@@ -0,0 +1,343 @@
+//! Asynchronous reading and writing using [`tokio`].
+//!
+//! This module is only available when the `tokio` feature is enabled.
+//!
+//! # Example
+//! ```
+//! use serde_derive::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Song {
+//!     #[serde(rename = "TITLE")]
+//!     title: String,
+//! }
+//!
+//! # async fn run() {
+//! let mut serialized = Vec::new();
+//! msd::tokio::to_async_writer(&mut serialized, &Song { title: "foo".to_owned() })
+//!     .await
+//!     .unwrap();
+//!
+//! let song: Song = msd::tokio::from_async_reader(serialized.as_slice())
+//!     .await
+//!     .unwrap();
+//! assert_eq!(song.title, "foo");
+//! # }
+//! ```
+
+use crate::{
+    de,
+    de::DeserializerOptions,
+    ser,
+    ser::{Serializer, SerializerOptions},
+};
+use ::tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Deserialize a value of type `T` from the given asynchronous `reader`.
+///
+/// The entire input is read into memory before it is deserialized, without blocking the current
+/// thread. Errors are reported exactly as they would be by [`from_reader`](crate::from_reader).
+pub async fn from_async_reader<R, T>(reader: R) -> de::Result<T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    from_async_reader_with_options(reader, DeserializerOptions::default()).await
+}
+
+/// Deserialize a value of type `T` from the given asynchronous `reader`, interpreting the input
+/// according to the given `options`.
+///
+/// The input is read into memory before it is deserialized, without blocking the current thread.
+/// If a [total bytes limit](crate::de::Limits::max_total_bytes) is set, reading stops once the
+/// input is known to exceed it.
+pub async fn from_async_reader_with_options<R, T>(
+    mut reader: R,
+    options: DeserializerOptions,
+) -> de::Result<T>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let mut bytes = Vec::new();
+    let error = match de::read_ahead_limit(&options) {
+        Some(limit) => (&mut reader)
+            .take(limit as u64)
+            .read_to_end(&mut bytes)
+            .await
+            .err(),
+        None => reader.read_to_end(&mut bytes).await.err(),
+    };
+    de::from_read_ahead(&bytes, error, options)
+}
+
+/// Serialize the given `value` into the given asynchronous `writer` in MSD format.
+///
+/// The value is serialized into memory before being written, without blocking the current thread.
+pub async fn to_async_writer<W, T>(writer: W, value: &T) -> ser::Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: ?Sized + Serialize,
+{
+    to_async_writer_with_options(writer, value, SerializerOptions::default()).await
+}
+
+/// Serialize the given `value` into the given asynchronous `writer`, writing the output according
+/// to the given `options`.
+///
+/// The value is serialized into memory before being written, without blocking the current thread.
+pub async fn to_async_writer_with_options<W, T>(
+    mut writer: W,
+    value: &T,
+    options: SerializerOptions,
+) -> ser::Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: ?Sized + Serialize,
+{
+    let mut bytes = Vec::with_capacity(128);
+    value.serialize(&mut Serializer::with_options(&mut bytes, options))?;
+    writer.write_all(&bytes).await.map_err(ser::Error::Io)?;
+    writer.flush().await.map_err(ser::Error::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        from_async_reader, from_async_reader_with_options, to_async_writer,
+        to_async_writer_with_options,
+    };
+    use crate::{
+        block_on::block_on,
+        de,
+        de::DeserializerOptions,
+        ser,
+        ser::{LineEnding, SerializerOptions},
+    };
+    use ::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+    use claims::{assert_err_eq, assert_ok, assert_ok_eq};
+    use serde_derive::{Deserialize, Serialize};
+    use std::{
+        io, mem,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    // Yields some bytes and then fails.
+    struct FailingReader(&'static [u8]);
+
+    impl AsyncRead for FailingReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            if self.0.is_empty() {
+                Poll::Ready(Err(io::Error::from(io::ErrorKind::ConnectionReset)))
+            } else {
+                buf.put_slice(self.0);
+                self.0 = &[];
+                Poll::Ready(Ok(()))
+            }
+        }
+    }
+
+    // Yields `#` followed by an endless sequence of `a`.
+    struct EndlessReader {
+        started: bool,
+    }
+
+    impl AsyncRead for EndlessReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            if !self.started {
+                buf.put_slice(b"#");
+                self.started = true;
+            }
+            let remaining = buf.remaining();
+            buf.put_slice(&vec![b'a'; remaining]);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    // Holds written bytes until they are flushed.
+    #[derive(Default)]
+    struct BufferedWriter {
+        buffer: Vec<u8>,
+        flushed: Vec<u8>,
+    }
+
+    impl AsyncWrite for BufferedWriter {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.buffer.extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            let buffer = mem::take(&mut self.buffer);
+            self.flushed.extend(buffer);
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    // Accepts writes but fails to flush them.
+    struct FailingFlushWriter;
+
+    impl AsyncWrite for FailingFlushWriter {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Err(io::Error::from(io::ErrorKind::BrokenPipe)))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Song {
+        #[serde(rename = "TITLE")]
+        title: String,
+        #[serde(rename = "BPMS")]
+        bpms: f64,
+    }
+
+    #[test]
+    fn from_async_reader_success() {
+        assert_ok_eq!(
+            block_on(from_async_reader::<_, Song>(
+                b"#TITLE:foo;\n#BPMS:120.0;\n".as_slice()
+            )),
+            Song {
+                title: "foo".to_owned(),
+                bpms: 120.0,
+            }
+        );
+    }
+
+    #[test]
+    fn from_async_reader_parse_error() {
+        assert_eq!(
+            block_on(from_async_reader::<_, Song>(
+                b"#TITLE:foo;\n#BPMS:bar;\n".as_slice()
+            ))
+            .unwrap_err()
+            .kind(),
+            &de::Kind::ExpectedF64
+        );
+    }
+
+    #[test]
+    fn from_async_reader_io_error() {
+        assert_eq!(
+            block_on(from_async_reader::<_, (String, String)>(FailingReader(
+                b"#TITLE:foo;\n"
+            )))
+            .unwrap_err()
+            .kind(),
+            &de::Kind::Io(io::ErrorKind::ConnectionReset)
+        );
+    }
+
+    #[test]
+    fn from_async_reader_with_options_success() {
+        assert_ok_eq!(
+            block_on(from_async_reader_with_options::<_, Song>(
+                b"#title:foo;\n#bpms:120.0;\n".as_slice(),
+                DeserializerOptions::new().case_insensitive(true)
+            )),
+            Song {
+                title: "foo".to_owned(),
+                bpms: 120.0,
+            }
+        );
+    }
+
+    #[test]
+    fn from_async_reader_with_options_max_total_bytes() {
+        assert_eq!(
+            block_on(from_async_reader_with_options::<_, (String, String)>(
+                EndlessReader { started: false },
+                DeserializerOptions::new().limits(de::Limits::new().max_total_bytes(1024))
+            ))
+            .unwrap_err()
+            .kind(),
+            &de::Kind::LimitExceeded(de::Limit::TotalBytes)
+        );
+    }
+
+    #[test]
+    fn to_async_writer_success() {
+        let mut output = Vec::new();
+
+        assert_ok!(block_on(to_async_writer(
+            &mut output,
+            &Song {
+                title: "foo".to_owned(),
+                bpms: 120.0,
+            }
+        )));
+        assert_eq!(output, b"#TITLE:foo;\n#BPMS:120.0;\n");
+    }
+
+    #[test]
+    fn to_async_writer_flushes() {
+        let mut output = BufferedWriter::default();
+
+        assert_ok!(block_on(to_async_writer(&mut output, &("TITLE", "foo"))));
+        assert_eq!(output.flushed, b"#TITLE:foo;\n");
+    }
+
+    #[test]
+    fn to_async_writer_flush_error() {
+        assert_err_eq!(
+            block_on(to_async_writer(FailingFlushWriter, &42)),
+            ser::Error::Io(io::Error::from(io::ErrorKind::BrokenPipe))
+        );
+    }
+
+    #[test]
+    fn to_async_writer_with_options_success() {
+        let mut output = Vec::new();
+
+        assert_ok!(block_on(to_async_writer_with_options(
+            &mut output,
+            &Song {
+                title: "foo".to_owned(),
+                bpms: 120.0,
+            },
+            SerializerOptions::new().line_ending(LineEnding::CrLf)
+        )));
+        assert_eq!(output, b"#TITLE:foo;\r\n#BPMS:120.0;\r\n");
+    }
+
+    #[test]
+    fn to_async_writer_serialize_error() {
+        let mut output = Vec::new();
+
+        assert_err_eq!(
+            block_on(to_async_writer(&mut output, &vec![Some(1)])),
+            ser::Error::UnsupportedType
+        );
+        assert!(output.is_empty());
+    }
+}
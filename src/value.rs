@@ -0,0 +1,257 @@
+//! An untyped representation of MSD data.
+//!
+//! [`Value`] can hold any valid MSD input without knowing its structure ahead of time, which is
+//! useful for inspecting unknown files or transcoding them into other formats.
+//!
+//! # Example
+//! ```
+//! use msd::value::{Tag, Value};
+//!
+//! let value: Value = msd::from_bytes(b"#TITLE:foo;\n#BPMS:0.000=120.000;\n").unwrap();
+//!
+//! assert_eq!(
+//!     value.get("TITLE"),
+//!     Some(&Tag::new("TITLE", vec![vec!["foo".to_owned()]]))
+//! );
+//! ```
+
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    ser::SerializeSeq,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::fmt;
+
+// The name used to identify a `Value` to the serializers in this crate, which write its entries as
+// tags.
+pub(crate) const NAME: &str = "$msd::Value";
+
+/// Any valid MSD data, represented as an ordered list of tags.
+///
+/// In other formats, a `Value` is serialized as a sequence of entries, each a pair of a tag name and
+/// its sequence of parameter lists, where each parameter list is a sequence of strings. This
+/// retains tags that share a name. A `Value` can be deserialized from either this shape or a map of
+/// tag names to sequences of parameter lists.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Value {
+    /// The tags, in the order they appear in the input.
+    pub tags: Vec<Tag>,
+}
+
+impl Value {
+    /// Creates a new `Value` containing no tags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the first tag with the given `name`, if one exists.
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+}
+
+impl From<Vec<Tag>> for Value {
+    fn from(tags: Vec<Tag>) -> Self {
+        Self { tags }
+    }
+}
+
+/// A single tag within a [`Value`].
+///
+/// The tag `#NAME:a:b;` has the name `NAME` and a single parameter list `["a", "b"]`. Note that
+/// the first parameter list does not include the tag's name.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Tag {
+    /// The tag's name.
+    pub name: String,
+    /// The tag's parameter lists. Every tag has at least one parameter list.
+    ///
+    /// A tag without any parameter lists is written the same as a tag with a single empty
+    /// parameter list, and is read back as the latter.
+    pub parameter_lists: Vec<Vec<String>>,
+}
+
+impl Tag {
+    /// Creates a new `Tag` with the given `name` and `parameter_lists`.
+    ///
+    /// If `parameter_lists` is empty, the tag is given a single empty parameter list instead.
+    pub fn new<S>(name: S, mut parameter_lists: Vec<Vec<String>>) -> Self
+    where
+        S: Into<String>,
+    {
+        if parameter_lists.is_empty() {
+            parameter_lists.push(Vec::new());
+        }
+        Self {
+            name: name.into(),
+            parameter_lists,
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(NAME, &Entries(&self.tags))
+    }
+}
+
+// Serializes tags as a sequence of pairs of names and parameter lists.
+struct Entries<'a>(&'a [Tag]);
+
+impl<'a> Serialize for Entries<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for tag in self.0 {
+            seq.serialize_element(&(&tag.name, &tag.parameter_lists))?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a sequence of tag names and parameter lists")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut tags = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some((name, parameter_lists)) = seq.next_element()? {
+                    tags.push(Tag {
+                        name,
+                        parameter_lists,
+                    });
+                }
+                Ok(Value { tags })
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut tags = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some((name, parameter_lists)) = map.next_entry()? {
+                    tags.push(Tag {
+                        name,
+                        parameter_lists,
+                    });
+                }
+                Ok(Value { tags })
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tag, Value};
+    use claims::{assert_none, assert_ok, assert_ok_eq, assert_some_eq};
+
+    #[test]
+    fn get() {
+        let value = Value::from(vec![
+            Tag::new("foo", vec![vec!["1".to_owned()]]),
+            Tag::new("bar", vec![vec!["2".to_owned()]]),
+            Tag::new("foo", vec![vec!["3".to_owned()]]),
+        ]);
+
+        assert_some_eq!(
+            value.get("foo"),
+            &Tag::new("foo", vec![vec!["1".to_owned()]])
+        );
+        assert_none!(value.get("baz"));
+    }
+
+    #[test]
+    fn deserialize() {
+        assert_ok_eq!(
+            crate::from_bytes::<Value>(b"#foo:bar:baz;\n#qux;\n1:2;\n#foo:;\n"),
+            Value::from(vec![
+                Tag::new("foo", vec![vec!["bar".to_owned(), "baz".to_owned()]]),
+                Tag::new("qux", vec![vec![], vec!["1".to_owned(), "2".to_owned()]]),
+                Tag::new("foo", vec![vec!["".to_owned()]]),
+            ])
+        );
+    }
+
+    #[test]
+    fn deserialize_empty() {
+        assert_ok_eq!(crate::from_bytes::<Value>(b""), Value::new());
+    }
+
+    #[test]
+    fn serialize() {
+        let value = Value::from(vec![
+            Tag::new("foo", vec![vec!["bar".to_owned(), "b:az".to_owned()]]),
+            Tag::new("qux", vec![vec![], vec!["1".to_owned(), "2".to_owned()]]),
+        ]);
+
+        assert_ok_eq!(
+            crate::to_bytes(&value),
+            b"#foo:bar:b\\:az;\n#qux;\n1:2;\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn serialize_empty() {
+        assert_ok_eq!(crate::to_bytes(&Value::new()), b"".to_vec());
+    }
+
+    #[test]
+    fn tag_without_parameter_lists() {
+        let value = Value::from(vec![Tag::new("foo", vec![])]);
+
+        assert_eq!(value.tags[0].parameter_lists, vec![Vec::<String>::new()]);
+        let serialized = assert_ok!(crate::to_bytes(&value));
+        assert_eq!(serialized, b"#foo;\n");
+        assert_ok_eq!(crate::from_bytes::<Value>(&serialized), value);
+    }
+
+    #[test]
+    fn json_retains_repeated_tags() {
+        let value = assert_ok!(crate::from_bytes::<Value>(b"#A:b:c;\n#A:d;\n"));
+
+        let json = assert_ok!(serde_json::to_string(&value));
+        assert_eq!(json, r#"[["A",[["b","c"]]],["A",[["d"]]]]"#);
+        assert_ok_eq!(serde_json::from_str::<Value>(&json), value);
+    }
+
+    #[test]
+    fn json_map() {
+        assert_ok_eq!(
+            serde_json::from_str::<Value>(r#"{"A":[["b","c"]],"D":[[]]}"#),
+            Value::from(vec![
+                Tag::new("A", vec![vec!["b".to_owned(), "c".to_owned()]]),
+                Tag::new("D", vec![vec![]]),
+            ])
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let input = b"#foo:bar:baz;\n#qux;\n1:2;\n#foo:;\n";
+
+        let value = assert_ok!(crate::from_bytes::<Value>(input));
+
+        assert_ok_eq!(crate::to_bytes(&value), input.to_vec());
+    }
+}
@@ -0,0 +1,785 @@
+//! Adapters for parameters containing their own delimited values.
+//!
+//! Many tags pack more structure into a single parameter than MSD itself describes. For example,
+//! the `#BPMS` tag of a `.sm` file is a `,`-separated list of `=`-separated beat and BPM pairs:
+//! `#BPMS:0.000=120.000,32.000=140.000;`. The modules here are intended to be used with serde's
+//! `with` attribute to split such parameters into their parts during deserialization, and to join
+//! them again during serialization. Within each part, the delimiters are escaped with a `\`.
+//! A trailing separator is ignored, so a final empty part is written followed by one.
+//!
+//! [`comma_list`] and [`key_eq_value_pairs`] cover the most common layouts. Any other ASCII
+//! punctuation can be used with [`separated_list`] and [`separated_pairs`].
+//!
+//! Other formats see these values as sequences of elements, with each pair being a sequence of two
+//! elements.
+//!
+//! # Example
+//! ```
+//! use serde_derive::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Deserialize, PartialEq, Serialize)]
+//! struct Song {
+//!     #[serde(rename = "BPMS", with = "msd::with::key_eq_value_pairs")]
+//!     bpms: Vec<(f64, f64)>,
+//! }
+//!
+//! let song: Song = msd::from_bytes(b"#BPMS:0.000=120.000,32.000=140.000;\n").unwrap();
+//! assert_eq!(
+//!     song,
+//!     Song {
+//!         bpms: vec![(0.0, 120.0), (32.0, 140.0)]
+//!     }
+//! );
+//!
+//! assert_eq!(msd::to_bytes(&song).unwrap(), b"#BPMS:0.0=120.0,32.0=140.0;\n");
+//! ```
+
+use serde::{
+    de,
+    de::{DeserializeSeed, SeqAccess, Visitor},
+    ser::SerializeTupleStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{fmt, marker::PhantomData};
+
+// The prefix of the names used to identify delimited values to the serializers and deserializers
+// in this crate. Each name is this prefix followed by the separator, as returned by `name()`.
+//
+// A delimited list is a newtype struct with the name of its separator, wrapping a sequence of the
+// elements. A key and value pair is a tuple struct of two fields with the name of its delimiter.
+const NAME: &str = "$msd::Delimited";
+
+// Every separator, in the same order as `NAMES`.
+const SEPARATORS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+// The name for each separator. Names must be `'static`, so they cannot be built on demand.
+const NAMES: [&str; 32] = [
+    "$msd::Delimited!",
+    "$msd::Delimited\"",
+    "$msd::Delimited#",
+    "$msd::Delimited$",
+    "$msd::Delimited%",
+    "$msd::Delimited&",
+    "$msd::Delimited'",
+    "$msd::Delimited(",
+    "$msd::Delimited)",
+    "$msd::Delimited*",
+    "$msd::Delimited+",
+    "$msd::Delimited,",
+    "$msd::Delimited-",
+    "$msd::Delimited.",
+    "$msd::Delimited/",
+    "$msd::Delimited:",
+    "$msd::Delimited;",
+    "$msd::Delimited<",
+    "$msd::Delimited=",
+    "$msd::Delimited>",
+    "$msd::Delimited?",
+    "$msd::Delimited@",
+    "$msd::Delimited[",
+    "$msd::Delimited\\",
+    "$msd::Delimited]",
+    "$msd::Delimited^",
+    "$msd::Delimited_",
+    "$msd::Delimited`",
+    "$msd::Delimited{",
+    "$msd::Delimited|",
+    "$msd::Delimited}",
+    "$msd::Delimited~",
+];
+
+// Returns the name identifying values delimited by `separator`.
+//
+// Separators are checked by `check_separator()` first, so `NAME` itself is only returned for an
+// invalid separator. It is not recognized by `separator()`.
+pub(crate) fn name(separator: char) -> &'static str {
+    SEPARATORS
+        .find(separator)
+        .map_or(NAME, |index| NAMES[index])
+}
+
+// Returns the separator of delimited values identified by `name`, or `None` if `name` does not
+// identify delimited values.
+pub(crate) fn separator(name: &str) -> Option<u8> {
+    match name.strip_prefix(NAME)?.as_bytes() {
+        [separator] if separator.is_ascii_punctuation() => Some(*separator),
+        _ => None,
+    }
+}
+
+/// Serializes and deserializes a `Vec<T>` as a `,`-separated parameter.
+///
+/// # Example
+/// ```
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Song {
+///     #[serde(rename = "BGCHANGES", with = "msd::with::comma_list")]
+///     background_changes: Vec<String>,
+/// }
+///
+/// let song: Song = msd::from_bytes(b"#BGCHANGES:foo,bar\\,baz;\n").unwrap();
+///
+/// assert_eq!(song.background_changes, vec!["foo", "bar,baz"]);
+/// ```
+pub mod comma_list {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serializes the elements of `value` separated by `,`.
+    pub fn serialize<T, S>(value: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        super::separated_list::serialize::<T, S, ','>(value, serializer)
+    }
+
+    /// Deserializes `,`-separated elements.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        super::separated_list::deserialize::<T, D, ','>(deserializer)
+    }
+}
+
+/// Serializes and deserializes a `Vec<(K, V)>` as `,`-separated pairs of `=`-separated values.
+///
+/// See the [module documentation](self) for an example.
+pub mod key_eq_value_pairs {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serializes the pairs of `value` separated by `,`, with each key and value separated by `=`.
+    pub fn serialize<K, V, S>(value: &[(K, V)], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        super::separated_pairs::serialize::<K, V, S, ',', '='>(value, serializer)
+    }
+
+    /// Deserializes `,`-separated pairs, with each key and value separated by `=`.
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<Vec<(K, V)>, D::Error>
+    where
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        super::separated_pairs::deserialize::<K, V, D, ',', '='>(deserializer)
+    }
+}
+
+/// Serializes and deserializes a `Vec<T>` as a parameter separated by an ASCII punctuation
+/// `SEPARATOR`.
+///
+/// As the separator is provided as a const generic parameter, these functions are used with
+/// serde's `serialize_with` and `deserialize_with` attributes.
+///
+/// # Example
+/// ```
+/// use serde_derive::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Song {
+///     #[serde(
+///         serialize_with = "msd::with::separated_list::serialize::<_, _, '|'>",
+///         deserialize_with = "msd::with::separated_list::deserialize::<_, _, '|'>"
+///     )]
+///     credits: Vec<String>,
+/// }
+///
+/// let song: Song = msd::from_bytes(b"#credits:foo|bar;\n").unwrap();
+/// assert_eq!(song.credits, vec!["foo", "bar"]);
+///
+/// assert_eq!(msd::to_bytes(&song).unwrap(), b"#credits:foo|bar;\n");
+/// ```
+pub mod separated_list {
+    use super::{check_separator, Elements, List};
+    use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+    use std::marker::PhantomData;
+
+    /// Serializes the elements of `value` separated by `SEPARATOR`.
+    ///
+    /// Returns an error if `SEPARATOR` is not ASCII punctuation.
+    pub fn serialize<T, S, const SEPARATOR: char>(
+        value: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        check_separator(SEPARATOR).map_err(ser::Error::custom)?;
+        serializer.serialize_newtype_struct(super::name(SEPARATOR), value)
+    }
+
+    /// Deserializes elements separated by `SEPARATOR`.
+    ///
+    /// Returns an error if `SEPARATOR` is not ASCII punctuation.
+    pub fn deserialize<'de, T, D, const SEPARATOR: char>(
+        deserializer: D,
+    ) -> Result<Vec<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        check_separator(SEPARATOR).map_err(de::Error::custom)?;
+        deserializer.deserialize_newtype_struct(
+            super::name(SEPARATOR),
+            List {
+                elements: Elements {
+                    seed: PhantomData::<T>,
+                },
+            },
+        )
+    }
+}
+
+/// Serializes and deserializes a `Vec<(K, V)>` as pairs separated by an ASCII punctuation
+/// `SEPARATOR`, with each key and value separated by an ASCII punctuation `DELIMITER`.
+///
+/// As the separators are provided as const generic parameters, these functions are used with
+/// serde's `serialize_with` and `deserialize_with` attributes.
+///
+/// # Example
+/// ```
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Song {
+///     #[serde(deserialize_with = "msd::with::separated_pairs::deserialize::<_, _, _, '|', '@'>")]
+///     mods: Vec<(String, f64)>,
+/// }
+///
+/// let song: Song = msd::from_bytes(b"#mods:drunk@1.5|mini@2;\n").unwrap();
+///
+/// assert_eq!(
+///     song.mods,
+///     vec![("drunk".to_owned(), 1.5), ("mini".to_owned(), 2.0)]
+/// );
+/// ```
+pub mod separated_pairs {
+    use super::{check_separator, Elements, List, Pair, PairSeed};
+    use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+    use std::marker::PhantomData;
+
+    /// Serializes the pairs of `value` separated by `SEPARATOR`, with each key and value
+    /// separated by `DELIMITER`.
+    ///
+    /// Returns an error if either separator is not ASCII punctuation.
+    pub fn serialize<K, V, S, const SEPARATOR: char, const DELIMITER: char>(
+        value: &[(K, V)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        check_separator(SEPARATOR).map_err(ser::Error::custom)?;
+        check_separator(DELIMITER).map_err(ser::Error::custom)?;
+        let pairs = value
+            .iter()
+            .map(|(key, value)| Pair {
+                key,
+                value,
+                delimiter: DELIMITER,
+            })
+            .collect::<Vec<_>>();
+        serializer.serialize_newtype_struct(super::name(SEPARATOR), &pairs)
+    }
+
+    /// Deserializes pairs separated by `SEPARATOR`, with each key and value separated by
+    /// `DELIMITER`.
+    ///
+    /// Returns an error if either separator is not ASCII punctuation.
+    pub fn deserialize<'de, K, V, D, const SEPARATOR: char, const DELIMITER: char>(
+        deserializer: D,
+    ) -> Result<Vec<(K, V)>, D::Error>
+    where
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        check_separator(SEPARATOR).map_err(de::Error::custom)?;
+        check_separator(DELIMITER).map_err(de::Error::custom)?;
+        deserializer.deserialize_newtype_struct(
+            super::name(SEPARATOR),
+            List {
+                elements: Elements {
+                    seed: PairSeed {
+                        delimiter: DELIMITER,
+                        pair: PhantomData,
+                    },
+                },
+            },
+        )
+    }
+}
+
+// Values are split on single bytes, and only ASCII punctuation can be escaped within them.
+fn check_separator(separator: char) -> Result<(), String> {
+    if separator.is_ascii_punctuation() {
+        Ok(())
+    } else {
+        Err(format!(
+            "separator `{}` is not ASCII punctuation",
+            separator
+        ))
+    }
+}
+
+// Serializes a key and value as a delimited sequence.
+struct Pair<'a, K, V> {
+    key: &'a K,
+    value: &'a V,
+    delimiter: char,
+}
+
+impl<'a, K, V> Serialize for Pair<'a, K, V>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut tuple = serializer.serialize_tuple_struct(name(self.delimiter), 2)?;
+        tuple.serialize_field(self.key)?;
+        tuple.serialize_field(self.value)?;
+        tuple.end()
+    }
+}
+
+// Deserializes the newtype struct wrapping a delimited sequence.
+struct List<T> {
+    elements: Elements<T>,
+}
+
+impl<'de, T> Visitor<'de> for List<T>
+where
+    T: DeserializeSeed<'de> + Clone,
+{
+    type Value = Vec<T::Value>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.elements.expecting(formatter)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self.elements)
+    }
+}
+
+// Deserializes each element of a delimited sequence using `seed`.
+struct Elements<T> {
+    seed: T,
+}
+
+impl<'de, T> Visitor<'de> for Elements<T>
+where
+    T: DeserializeSeed<'de> + Clone,
+{
+    type Value = Vec<T::Value>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("delimited values")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element_seed(self.seed.clone())? {
+            elements.push(element);
+        }
+        Ok(elements)
+    }
+}
+
+// Deserializes a key and value delimited by `delimiter`.
+struct PairSeed<K, V> {
+    delimiter: char,
+    pair: PhantomData<(K, V)>,
+}
+
+impl<K, V> Clone for PairSeed<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            delimiter: self.delimiter,
+            pair: PhantomData,
+        }
+    }
+}
+
+impl<'de, K, V> DeserializeSeed<'de> for PairSeed<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = (K, V);
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple_struct(name(self.delimiter), 2, self)
+    }
+}
+
+impl<'de, K, V> Visitor<'de> for PairSeed<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = (K, V);
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "a key and value separated by `{}`",
+            self.delimiter
+        )
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let key = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let value = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok((key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::de::{Kind, Segment};
+    use claims::{assert_err, assert_none, assert_ok, assert_ok_eq, assert_some_eq};
+    use serde_derive::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Bpms(#[serde(with = "super::key_eq_value_pairs")] Vec<(f64, f64)>);
+
+    #[test]
+    fn comma_list_struct_field() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(with = "super::comma_list")]
+            stops: Vec<u64>,
+        }
+        let song = Song {
+            stops: vec![1, 2, 3],
+        };
+
+        let serialized = assert_ok!(crate::to_bytes(&song));
+        assert_eq!(serialized, b"#stops:1,2,3;\n");
+        assert_ok_eq!(crate::from_bytes::<Song>(&serialized), song);
+    }
+
+    #[test]
+    fn comma_list_empty() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(with = "super::comma_list")]
+            stops: Vec<u64>,
+        }
+        let song = Song { stops: vec![] };
+
+        let serialized = assert_ok!(crate::to_bytes(&song));
+        assert_eq!(serialized, b"#stops:;\n");
+        assert_ok_eq!(crate::from_bytes::<Song>(&serialized), song);
+    }
+
+    #[test]
+    fn comma_list_trailing_separator() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Song {
+            #[serde(with = "super::comma_list")]
+            stops: Vec<u64>,
+        }
+
+        assert_ok_eq!(
+            crate::from_bytes::<Song>(b"#stops:\n1,\n2,\n;\n"),
+            Song { stops: vec![1, 2] }
+        );
+    }
+
+    #[test]
+    fn comma_list_trailing_empty_element() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(with = "super::comma_list")]
+            changes: Vec<String>,
+        }
+        let song = Song {
+            changes: vec!["a,b".to_owned(), "c=d".to_owned(), String::new()],
+        };
+
+        let serialized = assert_ok!(crate::to_bytes(&song));
+        assert_eq!(serialized, b"#changes:a\\,b,c=d,,;\n");
+        assert_ok_eq!(crate::from_bytes::<Song>(&serialized), song);
+    }
+
+    #[test]
+    fn comma_list_single_empty_element() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(with = "super::comma_list")]
+            changes: Vec<String>,
+        }
+        let song = Song {
+            changes: vec![String::new()],
+        };
+
+        let serialized = assert_ok!(crate::to_bytes(&song));
+        assert_eq!(serialized, b"#changes:,;\n");
+        assert_ok_eq!(crate::from_bytes::<Song>(&serialized), song);
+    }
+
+    #[test]
+    fn key_eq_value_pairs_empty_value_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(with = "super::key_eq_value_pairs")]
+            changes: Vec<(String, String)>,
+        }
+        let song = Song {
+            changes: vec![("a".to_owned(), String::new())],
+        };
+
+        assert_ok_eq!(
+            crate::from_bytes::<Song>(&assert_ok!(crate::to_bytes(&song))),
+            song
+        );
+    }
+
+    #[test]
+    fn key_eq_value_pairs_struct_field() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(rename = "BPMS", with = "super::key_eq_value_pairs")]
+            bpms: Vec<(f64, f64)>,
+        }
+
+        assert_ok_eq!(
+            crate::from_bytes::<Song>(b"#BPMS:0.000=120.000,\n32.000=140.000 // end\n;\n"),
+            Song {
+                bpms: vec![(0.0, 120.0), (32.0, 140.0)]
+            }
+        );
+    }
+
+    #[test]
+    fn key_eq_value_pairs_escaped_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(with = "super::key_eq_value_pairs")]
+            changes: Vec<(String, String)>,
+        }
+        let song = Song {
+            changes: vec![
+                ("a=b".to_owned(), "c,d".to_owned()),
+                ("e:f".to_owned(), "g\\h".to_owned()),
+            ],
+        };
+
+        let serialized = assert_ok!(crate::to_bytes(&song));
+        assert_eq!(serialized, b"#changes:a\\=b=c\\,d,e\\:f=g\\\\h;\n".to_vec());
+        assert_ok_eq!(crate::from_bytes::<Song>(&serialized), song);
+    }
+
+    #[test]
+    fn key_eq_value_pairs_missing_value() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            #[allow(dead_code)]
+            #[serde(with = "super::key_eq_value_pairs")]
+            bpms: Vec<(f64, f64)>,
+        }
+
+        let error = assert_err!(crate::from_bytes::<Song>(b"#bpms:0=120,32;\n"));
+        assert_eq!(
+            error.path(),
+            &[Segment::Tag("bpms".to_owned()), Segment::Value(1)]
+        );
+    }
+
+    #[test]
+    fn key_eq_value_pairs_invalid_value() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            #[allow(dead_code)]
+            #[serde(with = "super::key_eq_value_pairs")]
+            bpms: Vec<(f64, f64)>,
+        }
+
+        let error = assert_err!(crate::from_bytes::<Song>(b"#bpms:0=120,32=foo;\n"));
+        assert_eq!(error.kind(), &Kind::ExpectedF64);
+        assert_eq!(error.position().line(), 0);
+        assert_eq!(error.position().column(), 15);
+        assert_eq!(
+            error.path(),
+            &[
+                Segment::Tag("bpms".to_owned()),
+                Segment::Value(1),
+                Segment::Value(1)
+            ]
+        );
+    }
+
+    #[test]
+    fn key_eq_value_pairs_too_many_values() {
+        #[derive(Debug, Deserialize)]
+        struct Song {
+            #[allow(dead_code)]
+            #[serde(with = "super::key_eq_value_pairs")]
+            bpms: Vec<(f64, f64)>,
+        }
+
+        let error = assert_err!(crate::from_bytes::<Song>(b"#bpms:0=120=1;\n"));
+        assert_eq!(error.kind(), &Kind::UnexpectedValue);
+    }
+
+    #[test]
+    fn separated_list_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(
+                serialize_with = "super::separated_list::serialize::<_, _, '|'>",
+                deserialize_with = "super::separated_list::deserialize::<_, _, '|'>"
+            )]
+            credits: Vec<String>,
+        }
+        let song = Song {
+            credits: vec!["foo|bar".to_owned(), "baz,qux".to_owned()],
+        };
+
+        let serialized = assert_ok!(crate::to_bytes(&song));
+        assert_eq!(serialized, b"#credits:foo\\|bar|baz,qux;\n");
+        assert_ok_eq!(crate::from_bytes::<Song>(&serialized), song);
+    }
+
+    #[test]
+    fn separated_pairs_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(
+                serialize_with = "super::separated_pairs::serialize::<_, _, _, '|', '@'>",
+                deserialize_with = "super::separated_pairs::deserialize::<_, _, _, '|', '@'>"
+            )]
+            mods: Vec<(String, u64)>,
+        }
+        let song = Song {
+            mods: vec![("drunk".to_owned(), 1), ("mini".to_owned(), 2)],
+        };
+
+        let serialized = assert_ok!(crate::to_bytes(&song));
+        assert_eq!(serialized, b"#mods:drunk@1|mini@2;\n");
+        assert_ok_eq!(crate::from_bytes::<Song>(&serialized), song);
+    }
+
+    #[test]
+    fn separator_not_punctuation() {
+        #[derive(Debug, Deserialize, Serialize)]
+        struct Song {
+            #[serde(
+                serialize_with = "super::separated_list::serialize::<_, _, 'é'>",
+                deserialize_with = "super::separated_list::deserialize::<_, _, 'é'>"
+            )]
+            credits: Vec<String>,
+        }
+
+        assert_err!(crate::to_bytes(&Song { credits: vec![] }));
+        assert_err!(crate::from_bytes::<Song>(b"#credits:foo;\n"));
+    }
+
+    #[test]
+    fn map_values() {
+        let mut map = BTreeMap::new();
+        map.insert("BPMS".to_owned(), Bpms(vec![(0.0, 120.0), (32.0, 140.0)]));
+
+        let serialized = assert_ok!(crate::to_bytes(&map));
+        assert_eq!(serialized, b"#BPMS:0.0=120.0,32.0=140.0;\n");
+        assert_ok_eq!(
+            crate::from_bytes::<BTreeMap<String, Bpms>>(&serialized),
+            map
+        );
+    }
+
+    #[test]
+    fn sequence_elements() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            bpms: Vec<Bpms>,
+        }
+        let song = Song {
+            bpms: vec![Bpms(vec![(0.0, 120.0)]), Bpms(vec![(1.0, 60.0)])],
+        };
+
+        let serialized = assert_ok!(crate::to_bytes(&song));
+        assert_eq!(serialized, b"#bpms:0.0=120.0;\n#bpms:1.0=60.0;\n");
+        assert_ok_eq!(crate::from_bytes::<Song>(&serialized), song);
+    }
+
+    #[test]
+    fn tuple_elements() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            timing: (u64, Bpms, u64),
+        }
+        let song = Song {
+            timing: (1, Bpms(vec![(0.0, 120.0), (4.0, 60.0)]), 2),
+        };
+
+        let serialized = assert_ok!(crate::to_bytes(&song));
+        assert_eq!(serialized, b"#timing:1:0.0=120.0,4.0=60.0:2;\n");
+        assert_ok_eq!(crate::from_bytes::<Song>(&serialized), song);
+    }
+
+    #[test]
+    fn names_identify_separators() {
+        for separator in super::SEPARATORS.chars() {
+            assert_some_eq!(super::separator(super::name(separator)), separator as u8);
+        }
+    }
+
+    #[test]
+    fn name_prefix_alone_not_recognized() {
+        assert_none!(super::separator(super::NAME));
+    }
+
+    #[test]
+    fn other_format_round_trip() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Song {
+            #[serde(with = "super::comma_list")]
+            stops: Vec<u64>,
+            bpms: Bpms,
+        }
+        let song = Song {
+            stops: vec![1, 2],
+            bpms: Bpms(vec![(0.0, 120.0), (32.0, 140.0)]),
+        };
+
+        let serialized = assert_ok!(serde_json::to_string(&song));
+        assert_eq!(
+            serialized,
+            r#"{"stops":[1,2],"bpms":[[0.0,120.0],[32.0,140.0]]}"#
+        );
+        assert_ok_eq!(serde_json::from_str::<Song>(&serialized), song);
+    }
+}